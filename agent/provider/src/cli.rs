@@ -6,6 +6,7 @@ pub mod keystore;
 pub mod pre_install;
 pub mod preset;
 pub mod profile;
+pub mod reputation;
 pub mod rule;
 pub mod whitelist;
 
@@ -5,6 +5,7 @@ use dialoguer::{Input, Select};
 use structopt::StructOpt;
 
 use crate::market::{Preset, PresetManager};
+use crate::payments::{pricing_offer, PRICING_MODELS};
 use crate::startup_config::{PresetNoInteractive, ProviderConfig, UpdateNames};
 
 #[derive(StructOpt, Clone, Debug)]
@@ -184,7 +185,7 @@ pub fn create_interactive(config: ProviderConfig) -> anyhow::Result<()> {
     let registry = config.registry()?;
 
     let exeunits = registry.list().into_iter().map(|desc| desc.name).collect();
-    let pricing_models = vec!["linear".to_string()];
+    let pricing_models = PRICING_MODELS.iter().map(ToString::to_string).collect();
 
     let preset =
         PresetUpdater::new(Preset::default(), exeunits, pricing_models).interact(&config)?;
@@ -351,9 +352,7 @@ fn validate_preset(config: &ProviderConfig, preset: &Preset) -> anyhow::Result<(
     let registry = config.registry()?;
     registry.find_exeunit(&preset.exeunit_name)?;
 
-    if preset.pricing_model != "linear" {
-        bail!("Not supported pricing model.")
-    }
+    pricing_offer(preset)?;
 
     Ok(())
 }
@@ -367,7 +366,7 @@ fn update_preset_interactive(config: ProviderConfig, name: String) -> anyhow::Re
     let registry = config.registry()?;
 
     let exeunits = registry.list().into_iter().map(|desc| desc.name).collect();
-    let pricing_models = vec!["linear".to_string()];
+    let pricing_models = PRICING_MODELS.iter().map(ToString::to_string).collect();
 
     let preset =
         PresetUpdater::new(presets.get(&name)?, exeunits, pricing_models).interact(&config)?;
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use ya_client_model::NodeId;
+use ya_utils_cli::{CommandOutput, ResponseTable};
+
+use crate::reputation::{ReputationStore, RequestorHistory};
+use crate::startup_config::ProviderConfig;
+
+#[derive(StructOpt, Clone, Debug)]
+pub enum ReputationCommand {
+    /// List scores of all known Requestors, starting from the lowest
+    List,
+    /// Show score of a single Requestor
+    Show {
+        /// Requestor node id
+        node_id: NodeId,
+    },
+}
+
+impl ReputationCommand {
+    pub fn run(self, config: ProviderConfig) -> Result<()> {
+        let store = ReputationStore::load_read_only(&config.reputation_file)?;
+        let requestors = match self {
+            ReputationCommand::List => store.list(),
+            ReputationCommand::Show { node_id } => store
+                .get(&node_id)
+                .map(|history| vec![(node_id, history)])
+                .unwrap_or_default(),
+        };
+
+        let table = ResponseTable {
+            columns: vec![
+                "requestor".to_string(),
+                "score".to_string(),
+                "paid on time".to_string(),
+                "paid late".to_string(),
+                "debit notes accepted".to_string(),
+                "agreements broken".to_string(),
+                "payments overdue".to_string(),
+                "last event".to_string(),
+            ],
+            values: requestors.into_iter().map(row).collect(),
+        };
+        CommandOutput::from(table).print(config.json)?;
+        Ok(())
+    }
+}
+
+fn row((node_id, history): (NodeId, RequestorHistory)) -> serde_json::Value {
+    serde_json::json! {[
+        node_id,
+        format!("{:.2}", history.score()),
+        history.invoices_paid_on_time,
+        history.invoices_paid_late,
+        history.debit_notes_accepted,
+        history.agreements_broken,
+        history.payments_overdue,
+        history.last_event.map(|ts| ts.to_rfc3339()),
+    ]}
+}
@@ -151,6 +151,7 @@ impl From<PresetV0> for Preset {
                     _ => None,
                 })
                 .collect(),
+            pricing_params: Default::default(),
         }
     }
 }
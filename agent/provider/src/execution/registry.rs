@@ -46,6 +46,54 @@ pub fn default_counter_config() -> HashMap<String, CounterDefinition> {
             price: false,
         },
     );
+    counters.insert(
+        "golem.usage.disk_io_gib".into(),
+        CounterDefinition {
+            name: "disk_io_gib".into(),
+            description: "Disk I/O".into(),
+            price: false,
+        },
+    );
+    counters.insert(
+        "golem.usage.network_gib".into(),
+        CounterDefinition {
+            name: "network_gib".into(),
+            description: "Network traffic".into(),
+            price: false,
+        },
+    );
+    counters.insert(
+        "golem.usage.dns_lookups".into(),
+        CounterDefinition {
+            name: "dns_lookups".into(),
+            description: "DNS lookups".into(),
+            price: false,
+        },
+    );
+    counters.insert(
+        "golem.usage.dns_failures".into(),
+        CounterDefinition {
+            name: "dns_failures".into(),
+            description: "DNS failures".into(),
+            price: false,
+        },
+    );
+    counters.insert(
+        "golem.usage.inet_egress_gib".into(),
+        CounterDefinition {
+            name: "inet_egress_gib".into(),
+            description: "Outbound network traffic sent".into(),
+            price: true,
+        },
+    );
+    counters.insert(
+        "golem.usage.inet_ingress_gib".into(),
+        CounterDefinition {
+            name: "inet_ingress_gib".into(),
+            description: "Outbound network traffic received".into(),
+            price: true,
+        },
+    );
 
     counters
 }
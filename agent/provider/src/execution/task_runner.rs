@@ -29,6 +29,7 @@ use super::registry::{ExeUnitDesc, ExeUnitsRegistry};
 use super::task::Task;
 use crate::market::provider_market::NewAgreement;
 use crate::market::Preset;
+use crate::startup_config::DNS_JSON;
 use crate::tasks::{AgreementBroken, AgreementClosed};
 
 const EXE_UNIT_DIR: &str = "exe-unit";
@@ -162,6 +163,7 @@ pub struct TaskRunner {
     event_ts: DateTime<Utc>,
     tasks_dir: PathBuf,
     cache_dir: PathBuf,
+    dns_config_file: PathBuf,
 }
 
 impl TaskRunner {
@@ -174,6 +176,7 @@ impl TaskRunner {
         let data_dir = data_dir.as_ref();
         let tasks_dir = exe_unit_work_dir(data_dir);
         let cache_dir = exe_unit_cache_dir(data_dir);
+        let dns_config_file = data_dir.join(DNS_JSON);
 
         log::debug!("TaskRunner config: {:?}", config);
 
@@ -213,6 +216,7 @@ impl TaskRunner {
             event_ts: Utc::now(),
             tasks_dir,
             cache_dir,
+            dns_config_file,
         })
     }
 
@@ -484,6 +488,19 @@ impl TaskRunner {
             args.extend(["--requestor-pub-key", req_pub_key].iter());
         }
 
+        // Checked on each activity, so the configuration can be changed without restart.
+        if self.dns_config_file.exists() {
+            args.extend(
+                [
+                    "--dns-config",
+                    self.dns_config_file
+                        .to_str()
+                        .ok_or_else(|| anyhow!("None"))?,
+                ]
+                .iter(),
+            );
+        }
+
         let args = args.iter().map(ToString::to_string).collect();
 
         log::info!(
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::io;
 use std::ops::{Add, Not, Sub};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 #[cfg(windows)]
 use std::ptr;
 use std::sync::{Arc, Mutex};
@@ -322,6 +322,7 @@ impl Profiles {
 #[derive(Debug)]
 pub struct Manager {
     state: Arc<Mutex<ManagerState>>,
+    storage_path: PathBuf,
     monitor: Option<FileMonitor>,
     sender: Option<watch::Sender<Event>>,
     receiver: watch::Receiver<Event>,
@@ -383,6 +384,7 @@ impl Manager {
         let (tx, rx) = watch::channel(Event::Initialized);
         Ok(Manager {
             state: Arc::new(Mutex::new(state)),
+            storage_path: conf.hardware_file.clone(),
             monitor: None,
             sender: Some(tx),
             receiver: rx,
@@ -424,8 +426,61 @@ impl Manager {
         state.res_cap
     }
 
-    #[allow(dead_code)]
-    pub fn allocate(&mut self, id: String, res: Resources) -> Result<(), Error> {
+    /// Handle for reserving resources of the active profile.
+    pub fn allocator(&self) -> Allocator {
+        Allocator {
+            state: self.state.clone(),
+            storage_path: self.storage_path.clone(),
+        }
+    }
+}
+
+/// Reserves hardware resources, shared with `Manager` it was created by.
+#[derive(Clone, Debug)]
+pub struct Allocator {
+    state: Arc<Mutex<ManagerState>>,
+    /// Path on the partition, which profiles' storage was measured on.
+    storage_path: PathBuf,
+}
+
+impl Allocator {
+    /// Creates allocator of given resources, not bound to hardware profiles.
+    pub fn new(res: Resources) -> Self {
+        let active = DEFAULT_PROFILE_NAME.to_string();
+        let mut state = ManagerState {
+            profiles: Profiles {
+                active: active.clone(),
+                profiles: vec![(active.clone(), res)].into_iter().collect(),
+            },
+            res_available: res,
+            res_cap: Resources::new_empty(),
+            res_remaining: Resources::new_empty(),
+            res_alloc: HashMap::new(),
+        };
+        state
+            .change_profile(active)
+            .expect("default profile exists");
+
+        Allocator {
+            state: Arc::new(Mutex::new(state)),
+            storage_path: PathBuf::from("."),
+        }
+    }
+
+    /// Resources of the host, which allocated resources are taken from.
+    pub fn host(&self) -> SystemResources {
+        SystemResources {
+            storage_path: self.storage_path.clone(),
+        }
+    }
+
+    #[inline]
+    pub fn remaining(&self) -> Resources {
+        let state = self.state.lock().unwrap();
+        state.res_remaining
+    }
+
+    pub fn allocate(&self, id: String, res: Resources) -> Result<(), Error> {
         let mut state = self.state.lock().unwrap();
         if state.res_alloc.contains_key(&id) {
             return Err(Error::AlreadyAllocated(id));
@@ -438,8 +493,7 @@ impl Manager {
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn release(&mut self, id: String) -> Result<(), Error> {
+    pub fn release(&self, id: String) -> Result<(), Error> {
         let mut state = self.state.lock().unwrap();
         match state.res_alloc.remove(&id) {
             Some(res) => state.res_remaining = state.res_remaining + res,
@@ -449,6 +503,44 @@ impl Manager {
     }
 }
 
+impl Default for Allocator {
+    fn default() -> Self {
+        Allocator::new(MIN_CAPS)
+    }
+}
+
+/// Resources currently free on the host, regardless of profiles and allocations.
+pub trait HostResources {
+    fn available_mem_gib(&self) -> Result<f64, Error>;
+    fn available_storage_gib(&self) -> Result<f64, Error>;
+    /// Average load of a single CPU thread during the last minute.
+    fn cpu_load(&self) -> Result<f64, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct SystemResources {
+    storage_path: PathBuf,
+}
+
+impl HostResources for SystemResources {
+    fn available_mem_gib(&self) -> Result<f64, Error> {
+        Ok(1000. * sys_info::mem_info()?.avail as f64 / (1024. * 1024. * 1024.))
+    }
+
+    fn available_storage_gib(&self) -> Result<f64, Error> {
+        Ok(partition_space(&self.storage_path)? as f64 / (1024. * 1024. * 1024.))
+    }
+
+    fn cpu_load(&self) -> Result<f64, Error> {
+        match sys_info::loadavg() {
+            Ok(load) => Ok(load.one / num_cpus::get() as f64),
+            // Load average is not available on Windows.
+            Err(sys_info::Error::UnsupportedSystem) => Ok(0.),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
 #[cfg(windows)]
 fn to_wstring(value: impl AsRef<OsStr>) -> Vec<u16> {
     use std::os::windows::ffi::OsStrExt;
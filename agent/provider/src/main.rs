@@ -27,6 +27,7 @@ async fn main() -> anyhow::Result<()> {
     config.presets_file = data_dir.join(config.presets_file);
     config.hardware_file = data_dir.join(config.hardware_file);
     config.rules_file = data_dir.join(config.rules_file);
+    config.reputation_file = data_dir.join(config.reputation_file);
 
     match cli_args.commands {
         Commands::Run(args) => {
@@ -51,5 +52,6 @@ async fn main() -> anyhow::Result<()> {
         Commands::Whitelist(whitelist_cmd) => whitelist_cmd.run(config),
         Commands::Clean(clean_cmd) => clean_cmd.run(config),
         Commands::Rule(outbound_cmd) => outbound_cmd.run(config),
+        Commands::Reputation(reputation_cmd) => reputation_cmd.run(config),
     }
 }
@@ -4,5 +4,5 @@ pub mod presets;
 pub mod provider_market;
 pub mod termination_reason;
 
-pub use presets::{Preset, PresetManager, Presets};
+pub use presets::{Preset, PresetManager, Presets, PricingParams};
 pub use provider_market::{CreateOffer, ProviderMarket};
@@ -3,6 +3,7 @@ pub mod builtin;
 mod common;
 mod component;
 mod composite;
+pub mod external;
 pub mod factory;
 
 pub use accept_all::AcceptAllNegotiator;
@@ -12,4 +13,6 @@ pub use common::{
     AgreementResponse, AgreementResult, Negotiator, NegotiatorAddr, ProposalResponse,
 };
 
-pub use component::{NegotiationResult, NegotiatorComponent, NegotiatorsPack, ProposalView};
+pub use component::{
+    AgreementView, NegotiationResult, NegotiatorComponent, NegotiatorsPack, ProposalView,
+};
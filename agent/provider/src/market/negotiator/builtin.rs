@@ -2,15 +2,19 @@ pub mod allow_only;
 pub mod blacklist;
 pub mod demand_validation;
 pub mod expiration;
+pub mod limit_resources;
 pub mod manifest;
 pub mod max_agreements;
 pub mod note_interval;
 pub mod payment_timeout;
 pub mod price;
+pub mod reputation;
 
 pub use expiration::LimitExpiration;
+pub use limit_resources::LimitResources;
 pub use manifest::ManifestSignature;
 pub use max_agreements::MaxAgreements;
 pub use note_interval::DebitNoteInterval;
 pub use payment_timeout::PaymentTimeout;
 pub use price::PriceNego;
+pub use reputation::Reputation;
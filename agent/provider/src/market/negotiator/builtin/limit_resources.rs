@@ -0,0 +1,343 @@
+use crate::hardware::{Allocator, HostResources, Resources};
+use crate::market::negotiator::composite::to_proposal_views;
+use crate::market::negotiator::factory::LimitResourcesConfig;
+use crate::market::negotiator::{
+    AgreementResult, AgreementView, NegotiationResult, NegotiatorComponent, ProposalView,
+};
+
+const CPU_THREADS_PROPERTY: &str = "golem.inf.cpu.threads";
+const MEM_GIB_PROPERTY: &str = "golem.inf.mem.gib";
+const STORAGE_GIB_PROPERTY: &str = "golem.inf.storage.gib";
+
+/// Negotiator reserving hardware resources for each Agreement. Rejects Proposals,
+/// which would require more resources than remaining in the active hardware profile
+/// or currently free on the host.
+pub struct LimitResources {
+    allocator: Allocator,
+    host: Box<dyn HostResources>,
+    max_cpu_load: f64,
+    default_reservation: Resources,
+}
+
+impl LimitResources {
+    pub fn new(config: &LimitResourcesConfig, allocator: &Allocator) -> LimitResources {
+        LimitResources {
+            allocator: allocator.clone(),
+            host: Box::new(allocator.host()),
+            max_cpu_load: config.max_cpu_load,
+            default_reservation: Resources {
+                cpu_threads: config.reserve_cpu_threads,
+                mem_gib: config.reserve_mem_gib,
+                storage_gib: config.reserve_storage_gib,
+            },
+        }
+    }
+
+    /// Resources needed by the Agreement: `golem.inf.*` hints of the Demand, or
+    /// configured defaults when the Demand has none. Our Offer advertises the whole
+    /// profile, so its values only cap the reservation.
+    fn required(&self, demand: &ProposalView, offer: &ProposalView) -> Resources {
+        Resources {
+            cpu_threads: required_value(
+                demand,
+                offer,
+                CPU_THREADS_PROPERTY,
+                self.default_reservation.cpu_threads,
+            ),
+            mem_gib: required_value(
+                demand,
+                offer,
+                MEM_GIB_PROPERTY,
+                self.default_reservation.mem_gib,
+            ),
+            storage_gib: required_value(
+                demand,
+                offer,
+                STORAGE_GIB_PROPERTY,
+                self.default_reservation.storage_gib,
+            ),
+        }
+    }
+
+    /// Reason to reject Proposal, if host can't handle it at the moment.
+    fn host_overloaded(&self, required: &Resources) -> anyhow::Result<Option<String>> {
+        let cpu_load = self.host.cpu_load()?;
+        if cpu_load > self.max_cpu_load {
+            return Ok(Some(format!("CPU load {cpu_load:.2}")));
+        }
+        let available_mem = self.host.available_mem_gib()?;
+        if available_mem < required.mem_gib {
+            return Ok(Some(format!(
+                "host memory available {available_mem:.2} GiB"
+            )));
+        }
+        let available_storage = self.host.available_storage_gib()?;
+        if available_storage < required.storage_gib {
+            return Ok(Some(format!(
+                "host storage available {available_storage:.2} GiB"
+            )));
+        }
+        Ok(None)
+    }
+}
+
+fn required_value<T>(demand: &ProposalView, offer: &ProposalView, property: &str, default: T) -> T
+where
+    T: PartialOrd + serde::de::DeserializeOwned,
+{
+    let requested = demand.get_property::<T>(property).unwrap_or(default);
+    match offer.get_property::<T>(property) {
+        Ok(offered) if offered < requested => offered,
+        _ => requested,
+    }
+}
+
+impl NegotiatorComponent for LimitResources {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        let required = self.required(demand, &offer);
+        let remaining = self.allocator.remaining();
+
+        let reason = match remaining < required {
+            true => Some(format!("remaining {remaining:?}")),
+            false => self.host_overloaded(&required)?,
+        };
+        if let Some(reason) = reason {
+            log::info!(
+                "'LimitResources' negotiator: Reject proposal [{}], required {:?}, {}.",
+                demand.id,
+                required,
+                reason,
+            );
+            return Ok(NegotiationResult::Reject {
+                message: "Not enough free resources".to_string(),
+                is_final: false,
+            });
+        }
+        Ok(NegotiationResult::Ready { offer })
+    }
+
+    fn on_agreement_terminated(
+        &mut self,
+        agreement_id: &str,
+        _result: &AgreementResult,
+    ) -> anyhow::Result<()> {
+        self.allocator.release(agreement_id.to_string())?;
+        log::info!(
+            "Negotiator: released resources of Agreement [{}], remaining {:?}.",
+            agreement_id,
+            self.allocator.remaining()
+        );
+        Ok(())
+    }
+
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
+        let (demand, offer) = to_proposal_views(agreement.clone())?;
+        let required = self.required(&demand, &offer);
+        self.allocator.allocate(agreement.id.clone(), required)?;
+        log::info!(
+            "Negotiator: reserved {:?} for Agreement [{}].",
+            required,
+            agreement.id
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use ya_agreement_utils::agreement::expand;
+    use ya_agreement_utils::OfferTemplate;
+    use ya_client_model::market::proposal::State;
+    use ya_client_model::NodeId;
+
+    use crate::hardware::Error;
+
+    /// Host with resources set by test.
+    #[derive(Clone)]
+    struct TestHost(Arc<Mutex<(f64, Resources)>>);
+
+    impl HostResources for TestHost {
+        fn available_mem_gib(&self) -> Result<f64, Error> {
+            Ok(self.0.lock().unwrap().1.mem_gib)
+        }
+
+        fn available_storage_gib(&self) -> Result<f64, Error> {
+            Ok(self.0.lock().unwrap().1.storage_gib)
+        }
+
+        fn cpu_load(&self) -> Result<f64, Error> {
+            Ok(self.0.lock().unwrap().0)
+        }
+    }
+
+    fn proposal(properties: serde_json::Value) -> ProposalView {
+        ProposalView {
+            content: OfferTemplate {
+                properties: expand(properties),
+                constraints: "()".to_string(),
+            },
+            id: "proposal".to_string(),
+            issuer: Default::default(),
+            state: State::Initial,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn negotiator(capacity: Resources, host: &TestHost) -> LimitResources {
+        LimitResources {
+            allocator: Allocator::new(capacity),
+            host: Box::new(host.clone()),
+            max_cpu_load: 0.9,
+            default_reservation: Resources {
+                cpu_threads: 1,
+                mem_gib: 0.5,
+                storage_gib: 1.,
+            },
+        }
+    }
+
+    fn host(cpu_load: f64, mem_gib: f64, storage_gib: f64) -> TestHost {
+        let free = Resources {
+            cpu_threads: 0,
+            mem_gib,
+            storage_gib,
+        };
+        TestHost(Arc::new(Mutex::new((cpu_load, free))))
+    }
+
+    fn agreement(id: &str, demand: serde_json::Value, offer: serde_json::Value) -> AgreementView {
+        AgreementView {
+            json: serde_json::json!({
+                "agreementId": id,
+                "timestamp": chrono::Utc::now(),
+                "demand": {
+                    "demandId": "demand",
+                    "requestorId": NodeId::default(),
+                    "properties": expand(demand),
+                    "constraints": "()",
+                },
+                "offer": {
+                    "offerId": "offer",
+                    "providerId": NodeId::default(),
+                    "properties": expand(offer),
+                    "constraints": "()",
+                },
+            }),
+            id: id.to_string(),
+        }
+    }
+
+    fn profile_offer() -> serde_json::Value {
+        serde_json::json!({
+            "golem.inf.cpu.threads": 4,
+            "golem.inf.mem.gib": 8.0,
+            "golem.inf.storage.gib": 20.0,
+        })
+    }
+
+    #[test]
+    fn required_from_demand() {
+        let negotiator = negotiator(
+            Resources {
+                cpu_threads: 8,
+                mem_gib: 16.,
+                storage_gib: 100.,
+            },
+            &host(0., 16., 100.),
+        );
+        let offer = proposal(profile_offer());
+
+        // Demand hints are reserved, defaults fill the rest.
+        let required = negotiator.required(
+            &proposal(serde_json::json!({
+                "golem.inf.cpu.threads": 2,
+                "golem.inf.mem.gib": 4.0,
+            })),
+            &offer,
+        );
+        assert_eq!(required.cpu_threads, 2);
+        assert_eq!(required.mem_gib, 4.);
+        assert_eq!(required.storage_gib, 1.);
+
+        // Offer caps the hints.
+        let required = negotiator.required(
+            &proposal(serde_json::json!({
+                "golem.inf.cpu.threads": 16,
+                "golem.inf.storage.gib": 50.0,
+            })),
+            &offer,
+        );
+        assert_eq!(required.cpu_threads, 4);
+        assert_eq!(required.mem_gib, 0.5);
+        assert_eq!(required.storage_gib, 20.);
+    }
+
+    #[test]
+    fn reserve_and_release() {
+        let host = host(0., 16., 100.);
+        let mut negotiator = negotiator(
+            Resources {
+                cpu_threads: 2,
+                mem_gib: 4.,
+                storage_gib: 10.,
+            },
+            &host,
+        );
+        let hints = serde_json::json!({
+            "golem.inf.cpu.threads": 1,
+            "golem.inf.mem.gib": 2.0,
+            "golem.inf.storage.gib": 5.0,
+        });
+        let demand = proposal(hints.clone());
+        let offer = proposal(profile_offer());
+
+        // Each Agreement reserves its hints, not the whole advertised profile.
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Ready { .. }));
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Ready { .. }));
+        negotiator
+            .on_agreement_approved(&agreement("agreement-2", hints.clone(), profile_offer()))
+            .unwrap();
+        negotiator
+            .on_agreement_approved(&agreement("agreement-1", hints.clone(), profile_offer()))
+            .unwrap();
+
+        // Profile exhausted.
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Reject { .. }));
+
+        negotiator
+            .on_agreement_terminated("agreement-1", &AgreementResult::ClosedByUs)
+            .unwrap();
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Ready { .. }));
+
+        // Host resources are used by someone else.
+        host.0.lock().unwrap().1.mem_gib = 1.;
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Reject { .. }));
+
+        host.0.lock().unwrap().1.mem_gib = 16.;
+        host.0.lock().unwrap().1.storage_gib = 4.;
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Reject { .. }));
+
+        host.0.lock().unwrap().1.storage_gib = 100.;
+        host.0.lock().unwrap().0 = 0.95;
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert!(matches!(result, NegotiationResult::Reject { .. }));
+
+        host.0.lock().unwrap().0 = 0.5;
+        let result = negotiator.negotiate_step(&demand, offer).unwrap();
+        assert!(matches!(result, NegotiationResult::Ready { .. }));
+    }
+}
@@ -145,6 +145,8 @@ mod tests {
                         &cert_dir,
                     )
                     .unwrap(),
+                    reputation: Default::default(),
+                    hardware: Default::default(),
                 },
             ),
             tempdir,
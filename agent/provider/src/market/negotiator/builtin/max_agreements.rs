@@ -3,7 +3,7 @@ use std::collections::HashSet;
 
 use crate::market::negotiator::factory::LimitAgreementsNegotiatorConfig;
 use crate::market::negotiator::{
-    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+    AgreementResult, AgreementView, NegotiationResult, NegotiatorComponent, ProposalView,
 };
 
 /// Negotiator that can limit number of running agreements.
@@ -60,15 +60,15 @@ impl NegotiatorComponent for MaxAgreements {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
         if self.has_free_slot() {
-            self.active_agreements.insert(agreement_id.to_string());
+            self.active_agreements.insert(agreement.id.clone());
             Ok(())
         } else {
-            self.active_agreements.insert(agreement_id.to_string());
+            self.active_agreements.insert(agreement.id.clone());
             bail!(
                 "Agreement [{}] approved despite not available capacity.",
-                agreement_id
+                agreement.id
             )
         }
     }
@@ -0,0 +1,199 @@
+use serde_json::Value;
+
+use crate::market::negotiator::factory::{ReputationAction, ReputationConfig};
+use crate::market::negotiator::{NegotiationResult, NegotiatorComponent, ProposalView};
+use crate::provider_agent::AgentNegotiatorsConfig;
+use crate::reputation::ReputationStore;
+
+static PRICING_MODEL_PROPERTY: &str = "/golem/com/pricing/model";
+static MARKUP_PROPERTY: &str = "/golem/com/pricing/reputation-markup";
+/// Pricing parameters, which are scaled by markup. Either lists of coefficients
+/// or a single price: `flat` model's fee and `minimum` model's charge.
+static PRICE_PARAMS: &[&str] = &["coeffs", "tier-coeffs", "fee", "charge"];
+
+/// Rejects or reprices Proposals from Requestors with poor payment history.
+pub struct Reputation {
+    store: ReputationStore,
+    config: ReputationConfig,
+}
+
+impl Reputation {
+    pub fn new(config: &ReputationConfig, agent_negotiators_cfg: AgentNegotiatorsConfig) -> Self {
+        Self {
+            store: agent_negotiators_cfg.reputation,
+            config: config.clone(),
+        }
+    }
+}
+
+impl NegotiatorComponent for Reputation {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        mut offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        let history = match self.store.get(&demand.issuer) {
+            Some(history) if history.outcomes() >= self.config.reputation_min_history => history,
+            _ => return Ok(NegotiationResult::Ready { offer }),
+        };
+        let score = history.score();
+        if score >= self.config.reputation_threshold {
+            return Ok(NegotiationResult::Ready { offer });
+        }
+
+        match self.config.reputation_action {
+            ReputationAction::Reject => {
+                log::info!(
+                    "'Reputation' negotiator: Reject proposal [{}] from Requestor [{}] with score {score:.2}.",
+                    demand.id,
+                    demand.issuer,
+                );
+                Ok(NegotiationResult::Reject {
+                    message: format!("Requestor reputation score too low: {score:.2}"),
+                    is_final: true,
+                })
+            }
+            ReputationAction::Reprice => {
+                // Offer may already contain our previous counter-proposal.
+                if offer.pointer(MARKUP_PROPERTY).is_some() {
+                    return Ok(NegotiationResult::Ready { offer });
+                }
+
+                let markup = self.config.reputation_markup;
+                log::info!(
+                    "'Reputation' negotiator: Increasing prices by {:.0}% for Requestor [{}] with score {score:.2}.",
+                    markup * 100.,
+                    demand.issuer,
+                );
+                if let Some(Value::Object(models)) = offer.pointer_mut(PRICING_MODEL_PROPERTY) {
+                    let prices = models
+                        .values_mut()
+                        .filter_map(Value::as_object_mut)
+                        .flat_map(|params| {
+                            params
+                                .iter_mut()
+                                .filter(|(name, _)| PRICE_PARAMS.contains(&name.as_str()))
+                                .map(|(_, price)| price)
+                        })
+                        .flat_map(|price| match price {
+                            Value::Array(coeffs) => coeffs.iter_mut().collect(),
+                            price => vec![price],
+                        });
+                    for price in prices {
+                        if let Some(value) = price.as_f64() {
+                            *price = Value::from(value * (1. + markup));
+                        }
+                    }
+                }
+                if let Some(Value::Object(pricing)) = offer.pointer_mut("/golem/com/pricing") {
+                    pricing.insert("reputation-markup".to_string(), Value::from(markup));
+                }
+                Ok(NegotiationResult::Negotiating { offer })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+    use ya_agreement_utils::agreement::expand;
+    use ya_agreement_utils::OfferTemplate;
+    use ya_client_model::market::proposal::State;
+    use ya_client_model::NodeId;
+
+    use crate::reputation::ReputationEvent;
+
+    fn requestor() -> NodeId {
+        "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap()
+    }
+
+    fn negotiator(action: ReputationAction) -> Reputation {
+        let store = ReputationStore::default();
+        for _ in 0..3 {
+            store.record(requestor(), ReputationEvent::PaymentOverdue);
+        }
+        Reputation {
+            store,
+            config: ReputationConfig {
+                reputation_threshold: 0.5,
+                reputation_action: action,
+                reputation_markup: 0.5,
+                reputation_min_history: 3,
+            },
+        }
+    }
+
+    fn proposal(issuer: NodeId, properties: serde_json::Value) -> ProposalView {
+        ProposalView {
+            content: OfferTemplate {
+                properties: expand(properties),
+                constraints: "()".to_string(),
+            },
+            id: "proposalId".to_string(),
+            issuer,
+            state: State::Initial,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn offer() -> ProposalView {
+        proposal(
+            Default::default(),
+            json!({
+                "golem.com.pricing.model.linear.coeffs": [0.25, 0.5, 1.0],
+                "golem.com.pricing.model.flat.fee": 2.0,
+                "golem.com.pricing.model.minimum.charge": 4.0,
+            }),
+        )
+    }
+
+    /// Requestors without enough history are not affected.
+    #[test]
+    fn test_unknown_requestor_ready() {
+        let mut negotiator = negotiator(ReputationAction::Reject);
+        let demand = proposal(Default::default(), json!({}));
+
+        let result = negotiator.negotiate_step(&demand, offer()).unwrap();
+        assert_eq!(result, NegotiationResult::Ready { offer: offer() });
+    }
+
+    #[test]
+    fn test_low_score_rejected() {
+        let mut negotiator = negotiator(ReputationAction::Reject);
+        let demand = proposal(requestor(), json!({}));
+
+        match negotiator.negotiate_step(&demand, offer()).unwrap() {
+            NegotiationResult::Reject { is_final, .. } => assert!(is_final),
+            result => panic!("Expected rejection, got: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_low_score_repriced() {
+        let mut negotiator = negotiator(ReputationAction::Reprice);
+        let demand = proposal(requestor(), json!({}));
+
+        let offer = match negotiator.negotiate_step(&demand, offer()).unwrap() {
+            NegotiationResult::Negotiating { offer } => offer,
+            result => panic!("Expected counter-proposal, got: {result:?}"),
+        };
+        assert_eq!(
+            offer.pointer(PRICING_MODEL_PROPERTY).unwrap(),
+            &json!({
+                "linear": { "coeffs": [0.375, 0.75, 1.5] },
+                "flat": { "fee": 3.0 },
+                "minimum": { "charge": 6.0 },
+            })
+        );
+        assert_eq!(offer.pointer(MARKUP_PROPERTY).unwrap(), &json!(0.5));
+
+        // Our previous counter-proposal is not repriced again.
+        let result = negotiator.negotiate_step(&demand, offer.clone()).unwrap();
+        assert_eq!(result, NegotiationResult::Ready { offer });
+    }
+}
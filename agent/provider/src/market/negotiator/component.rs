@@ -2,7 +2,7 @@ use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-pub use ya_agreement_utils::{OfferDefinition, ProposalView};
+pub use ya_agreement_utils::{AgreementView, OfferDefinition, ProposalView};
 
 use crate::market::negotiator::AgreementResult;
 
@@ -60,7 +60,7 @@ pub trait NegotiatorComponent {
 
     /// Called when Negotiator decided to approve Agreement. It's only notification,
     /// `NegotiatorComponent` can't reject Agreement anymore.
-    fn on_agreement_approved(&mut self, _agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> anyhow::Result<()> {
         Ok(())
     }
 }
@@ -151,15 +151,15 @@ impl NegotiatorComponent for NegotiatorsPack {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
         for (name, component) in &mut self.components {
             component
-                .on_agreement_approved(agreement_id)
+                .on_agreement_approved(agreement)
                 .map_err(|e| {
                     log::warn!(
                         "Negotiator component '{}' failed handling Agreement [{}] approval. {}",
                         name,
-                        agreement_id,
+                        agreement.id,
                         e
                     )
                 })
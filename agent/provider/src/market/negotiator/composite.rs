@@ -1,4 +1,4 @@
-use actix::{Actor, Addr, Context, Handler};
+use actix::{Actor, Context, Handler};
 use anyhow::anyhow;
 use serde_json::Value;
 use std::convert::TryFrom;
@@ -9,9 +9,11 @@ use ya_client::model::market::NewOffer;
 use ya_client_model::market::proposal::State;
 
 use super::builtin::{
-    DebitNoteInterval, LimitExpiration, ManifestSignature, MaxAgreements, PaymentTimeout,
+    DebitNoteInterval, LimitExpiration, LimitResources, ManifestSignature, MaxAgreements,
+    PaymentTimeout, Reputation,
 };
 use super::common::{offer_definition_to_offer, AgreementResponse, Negotiator, ProposalResponse};
+use super::external::ExternalNegotiator;
 use super::{NegotiationResult, NegotiatorsPack};
 use crate::market::negotiator::builtin::allow_only::AllowOnly;
 use crate::market::negotiator::builtin::blacklist::Blacklist;
@@ -22,7 +24,6 @@ use crate::market::negotiator::common::{
 };
 use crate::market::negotiator::factory::CompositeNegotiatorConfig;
 use crate::market::negotiator::{NegotiatorComponent, ProposalView};
-use crate::market::ProviderMarket;
 use crate::provider_agent::AgentNegotiatorsConfig;
 
 /// Negotiator that can limit number of running agreements.
@@ -32,11 +33,10 @@ pub struct CompositeNegotiator {
 
 impl CompositeNegotiator {
     pub fn new(
-        _market: Addr<ProviderMarket>,
         config: &CompositeNegotiatorConfig,
         agent_negotiators_cfg: AgentNegotiatorsConfig,
     ) -> anyhow::Result<CompositeNegotiator> {
-        let components = NegotiatorsPack::default()
+        let mut components = NegotiatorsPack::default()
             .add_component(
                 "Validation",
                 Box::new(DemandValidation::new(&config.validation_config)),
@@ -69,7 +69,14 @@ impl CompositeNegotiator {
                 "ManifestSignature",
                 Box::new(ManifestSignature::new(
                     &config.policy_config.clone(),
-                    agent_negotiators_cfg,
+                    agent_negotiators_cfg.clone(),
+                )),
+            )
+            .add_component(
+                "Reputation",
+                Box::new(Reputation::new(
+                    &config.reputation_config,
+                    agent_negotiators_cfg.clone(),
                 )),
             )
             .add_component(
@@ -77,6 +84,26 @@ impl CompositeNegotiator {
                 Box::new(PriceNego::new(&config.expire_agreements_config)?),
             );
 
+        if config.limit_resources_config.limit_resources {
+            components = components.add_component(
+                "LimitResources",
+                Box::new(LimitResources::new(
+                    &config.limit_resources_config,
+                    &agent_negotiators_cfg.hardware,
+                )),
+            );
+        }
+
+        if let Some(path) = &config.external_config.negotiator_plugin {
+            components = components.add_component(
+                "External",
+                Box::new(ExternalNegotiator::new(
+                    path.clone(),
+                    &config.external_config,
+                )?),
+            );
+        }
+
         Ok(CompositeNegotiator { components })
     }
 }
@@ -178,7 +205,7 @@ impl Handler<ReactToAgreement> for CompositeNegotiator {
     type Result = anyhow::Result<AgreementResponse>;
 
     fn handle(&mut self, msg: ReactToAgreement, _: &mut Context<Self>) -> Self::Result {
-        let agreement_id = msg.agreement.id.clone();
+        let agreement = msg.agreement.clone();
         let (demand_proposal, offer_proposal) = to_proposal_views(msg.agreement).map_err(|e| {
             anyhow!(
                 "Negotiator failed to extract Proposals from Agreement. {}",
@@ -193,7 +220,7 @@ impl Handler<ReactToAgreement> for CompositeNegotiator {
             .negotiate_step(&demand_proposal, offer_proposal)?
         {
             NegotiationResult::Ready { .. } => {
-                self.components.on_agreement_approved(&agreement_id)?;
+                self.components.on_agreement_approved(&agreement)?;
                 Ok(AgreementResponse::ApproveAgreement)
             }
             NegotiationResult::Reject { message, is_final } => {
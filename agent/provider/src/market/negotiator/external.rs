@@ -0,0 +1,282 @@
+//! Negotiator component forwarding calls to external executable.
+//!
+//! Plugin is spawned as a child process and communicates using JSON-RPC 2.0
+//! over stdio. Each request and response is a single line of JSON.
+//! Pipes are handled by separate threads, so unresponsive plugin can't block
+//! the caller longer than configured timeout. `CompositeNegotiator` runs on its own
+//! arbiter anyway, so waiting for the plugin doesn't stall other Provider actors.
+use anyhow::{anyhow, bail, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+use ya_agreement_utils::{AgreementView, OfferDefinition, OfferTemplate};
+
+use crate::market::negotiator::factory::ExternalNegotiatorConfig;
+use crate::market::negotiator::{
+    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+};
+
+#[derive(Serialize)]
+struct Request<'a, P: Serialize> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: Option<u64>,
+    #[serde(default)]
+    result: Value,
+    error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct NegotiateStepParams<'a> {
+    demand: &'a ProposalView,
+    offer: &'a ProposalView,
+}
+
+#[derive(Serialize)]
+struct AgreementApprovedParams<'a> {
+    agreement_id: &'a str,
+}
+
+#[derive(Serialize)]
+struct AgreementTerminatedParams<'a> {
+    agreement_id: &'a str,
+    result: AgreementOutcome,
+}
+
+/// Serializable counterpart of `AgreementResult`.
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum AgreementOutcome {
+    ApprovalFailed,
+    ClosedByUs,
+    ClosedByRequestor,
+    Broken {
+        reason: String,
+        requestor_fault: bool,
+    },
+}
+
+impl From<&AgreementResult> for AgreementOutcome {
+    fn from(result: &AgreementResult) -> Self {
+        match result {
+            AgreementResult::ApprovalFailed => AgreementOutcome::ApprovalFailed,
+            AgreementResult::ClosedByUs => AgreementOutcome::ClosedByUs,
+            AgreementResult::ClosedByRequestor => AgreementOutcome::ClosedByRequestor,
+            AgreementResult::Broken { reason } => AgreementOutcome::Broken {
+                reason: reason.to_string(),
+                requestor_fault: reason.is_requestor_fault(),
+            },
+        }
+    }
+}
+
+struct Plugin {
+    child: Child,
+    requests: Sender<String>,
+    responses: Receiver<String>,
+}
+
+impl Plugin {
+    fn spawn(path: &Path) -> anyhow::Result<Plugin> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to spawn negotiator plugin {}", path.display()))?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("No plugin stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("No plugin stdout"))?;
+
+        let (requests, rx) = mpsc::channel::<String>();
+        std::thread::spawn(move || {
+            for request in rx {
+                if stdin
+                    .write_all(request.as_bytes())
+                    .and_then(|_| stdin.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let (tx, responses) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        log::info!("Started negotiator plugin {}.", path.display());
+        Ok(Plugin {
+            child,
+            requests,
+            responses,
+        })
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+/// Forwards `NegotiatorComponent` calls to external process.
+/// Plugin is restarted on next call, if it exited or failed to respond.
+pub struct ExternalNegotiator {
+    path: PathBuf,
+    timeout: Duration,
+    plugin: Option<Plugin>,
+    next_id: u64,
+}
+
+impl ExternalNegotiator {
+    pub fn new(path: PathBuf, config: &ExternalNegotiatorConfig) -> anyhow::Result<Self> {
+        Ok(ExternalNegotiator {
+            plugin: Some(Plugin::spawn(&path)?),
+            path,
+            timeout: config.negotiator_plugin_timeout,
+            next_id: 0,
+        })
+    }
+
+    fn call<P: Serialize>(&mut self, method: &str, params: P) -> anyhow::Result<Value> {
+        let result = self.try_call(method, params);
+        if result.is_err() {
+            // Plugin state is unknown, so we start from scratch next time.
+            self.plugin = None;
+        }
+        result.map_err(|e| anyhow!("Negotiator plugin '{}' failed: {}", method, e))
+    }
+
+    fn try_call<P: Serialize>(&mut self, method: &str, params: P) -> anyhow::Result<Value> {
+        if self.plugin.is_none() {
+            self.plugin = Some(Plugin::spawn(&self.path)?);
+        }
+        let plugin = self.plugin.as_mut().unwrap();
+
+        self.next_id += 1;
+        let id = self.next_id;
+        let mut request = serde_json::to_string(&Request {
+            jsonrpc: "2.0",
+            id,
+            method,
+            params,
+        })?;
+        request.push('\n');
+        plugin
+            .requests
+            .send(request)
+            .map_err(|_| anyhow!("plugin exited"))?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let line = match plugin.responses.recv_timeout(timeout) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => bail!("no response within {:?}", self.timeout),
+                Err(RecvTimeoutError::Disconnected) => bail!("plugin exited"),
+            };
+
+            let response: Response = serde_json::from_str(&line)
+                .with_context(|| format!("invalid response: {}", line))?;
+            if response.id != Some(id) {
+                log::debug!("Negotiator plugin: ignoring response: {}", line);
+                continue;
+            }
+
+            return match response.error {
+                Some(error) => Err(anyhow!("{} (code: {})", error.message, error.code)),
+                None => Ok(response.result),
+            };
+        }
+    }
+}
+
+impl NegotiatorComponent for ExternalNegotiator {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        let result = self.call(
+            "negotiate_step",
+            NegotiateStepParams {
+                demand,
+                offer: &offer,
+            },
+        )?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    fn fill_template(
+        &mut self,
+        mut offer_template: OfferDefinition,
+    ) -> anyhow::Result<OfferDefinition> {
+        let result = self.call("fill_template", &offer_template.offer)?;
+        // Null result leaves template unchanged.
+        if !result.is_null() {
+            offer_template.offer = serde_json::from_value::<OfferTemplate>(result)?;
+        }
+        Ok(offer_template)
+    }
+
+    fn on_agreement_terminated(
+        &mut self,
+        agreement_id: &str,
+        result: &AgreementResult,
+    ) -> anyhow::Result<()> {
+        self.call(
+            "on_agreement_terminated",
+            AgreementTerminatedParams {
+                agreement_id,
+                result: result.into(),
+            },
+        )?;
+        Ok(())
+    }
+
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
+        self.call(
+            "on_agreement_approved",
+            AgreementApprovedParams {
+                agreement_id: &agreement.id,
+            },
+        )?;
+        Ok(())
+    }
+}
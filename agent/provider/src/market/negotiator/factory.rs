@@ -1,5 +1,7 @@
-use actix::Addr;
+use actix::Arbiter;
+use futures::channel::oneshot;
 use humantime;
+use std::path::PathBuf;
 use std::sync::Arc;
 use structopt::StructOpt;
 
@@ -8,7 +10,6 @@ use ya_manifest_utils::PolicyConfig;
 use super::common::NegotiatorAddr;
 use crate::market::config::MarketConfig;
 use crate::market::negotiator::{AcceptAllNegotiator, CompositeNegotiator};
-use crate::market::ProviderMarket;
 use crate::provider_agent::AgentNegotiatorsConfig;
 
 /// Configuration for Demand Validation Negotiator.
@@ -25,6 +26,26 @@ pub struct LimitAgreementsNegotiatorConfig {
     pub max_simultaneous_agreements: u32,
 }
 
+/// Configuration for LimitResources Negotiator.
+#[derive(StructOpt, Clone, Debug)]
+pub struct LimitResourcesConfig {
+    /// Reserve hardware resources for each Agreement and reject Proposals exceeding them
+    #[structopt(long, env)]
+    pub limit_resources: bool,
+    /// Maximal average load of a single CPU thread, above which Proposals are rejected
+    #[structopt(long, env, default_value = "0.9")]
+    pub max_cpu_load: f64,
+    /// Number of CPU threads reserved for an Agreement, whose Demand does not specify it
+    #[structopt(long, env, default_value = "1")]
+    pub reserve_cpu_threads: i32,
+    /// Amount of memory (GiB) reserved for an Agreement, whose Demand does not specify it
+    #[structopt(long, env, default_value = "0.5")]
+    pub reserve_mem_gib: f64,
+    /// Amount of storage (GiB) reserved for an Agreement, whose Demand does not specify it
+    #[structopt(long, env, default_value = "1.0")]
+    pub reserve_storage_gib: f64,
+}
+
 /// Configuration for LimitAgreements Negotiator.
 #[derive(StructOpt, Clone, Debug)]
 pub struct AgreementExpirationNegotiatorConfig {
@@ -62,6 +83,42 @@ pub struct PaymentTimeoutConfig {
     pub payment_timeout_required_duration: std::time::Duration,
 }
 
+/// Action taken on Proposals from Requestors with reputation below threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::EnumString, strum_macros::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ReputationAction {
+    Reject,
+    Reprice,
+}
+
+/// Configuration for Reputation negotiator
+#[derive(StructOpt, Clone, Debug)]
+pub struct ReputationConfig {
+    /// Minimal Requestor reputation score (0.0 - 1.0). Disabled by default.
+    #[structopt(long, env, default_value = "0")]
+    pub reputation_threshold: f64,
+    /// What to do with Requestors below threshold: `reject` or `reprice`
+    #[structopt(long, env, default_value = "reject")]
+    pub reputation_action: ReputationAction,
+    /// Relative price increase for Requestors below threshold, when repricing
+    #[structopt(long, env, default_value = "0.5")]
+    pub reputation_markup: f64,
+    /// Number of paid invoices and broken agreements needed to judge Requestor
+    #[structopt(long, env, default_value = "3")]
+    pub reputation_min_history: u64,
+}
+
+/// Configuration for external negotiator plugin
+#[derive(StructOpt, Clone, Debug)]
+pub struct ExternalNegotiatorConfig {
+    /// Executable implementing negotiator plugin protocol (JSON-RPC over stdio)
+    #[structopt(long, env)]
+    pub negotiator_plugin: Option<PathBuf>,
+    /// Maximum time to wait for negotiator plugin response
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration), default_value = "5s")]
+    pub negotiator_plugin_timeout: std::time::Duration,
+}
+
 /// Configuration for LimitAgreements Negotiator.
 #[derive(StructOpt, Clone, Debug)]
 pub struct CompositeNegotiatorConfig {
@@ -70,6 +127,8 @@ pub struct CompositeNegotiatorConfig {
     #[structopt(flatten)]
     pub limit_agreements_config: LimitAgreementsNegotiatorConfig,
     #[structopt(flatten)]
+    pub limit_resources_config: LimitResourcesConfig,
+    #[structopt(flatten)]
     pub expire_agreements_config: AgreementExpirationNegotiatorConfig,
     #[structopt(flatten)]
     pub debit_note_interval_config: DebitNoteIntervalConfig,
@@ -77,6 +136,10 @@ pub struct CompositeNegotiatorConfig {
     pub payment_timeout_config: PaymentTimeoutConfig,
     #[structopt(flatten)]
     pub policy_config: PolicyConfig,
+    #[structopt(flatten)]
+    pub reputation_config: ReputationConfig,
+    #[structopt(flatten)]
+    pub external_config: ExternalNegotiatorConfig,
 }
 
 #[derive(StructOpt, Clone, Debug)]
@@ -85,24 +148,29 @@ pub struct NegotiatorsConfig {
     pub composite_config: CompositeNegotiatorConfig,
 }
 
-pub fn create_negotiator(
-    market: Addr<ProviderMarket>,
+pub async fn create_negotiator(
     config: &MarketConfig,
     agent_negotiators_cfg: &AgentNegotiatorsConfig,
-) -> Arc<NegotiatorAddr> {
+) -> anyhow::Result<Arc<NegotiatorAddr>> {
     let negotiator = match &config.negotiator_type[..] {
-        "Composite" => NegotiatorAddr::from(
-            CompositeNegotiator::new(
-                market,
-                &config.negotiator_config.composite_config,
-                agent_negotiators_cfg.clone(),
-            )
-            .unwrap(),
-        ),
+        "Composite" => {
+            // Negotiator components are synchronous and some of them (external plugin)
+            // wait for other processes, so Composite negotiator gets its own thread.
+            let config = config.negotiator_config.composite_config.clone();
+            let agent_negotiators_cfg = agent_negotiators_cfg.clone();
+            let (tx, rx) = oneshot::channel();
+            Arbiter::new().spawn_fn(move || {
+                let negotiator = CompositeNegotiator::new(&config, agent_negotiators_cfg)
+                    .map(NegotiatorAddr::from);
+                tx.send(negotiator).ok();
+            });
+            rx.await
+                .map_err(|_| anyhow::anyhow!("Composite negotiator thread stopped"))??
+        }
         "AcceptAll" => NegotiatorAddr::from(AcceptAllNegotiator),
         _ => Default::default(),
     };
-    Arc::new(negotiator)
+    Ok(Arc::new(negotiator))
 }
 
 impl Default for NegotiatorAddr {
@@ -23,6 +23,36 @@ pub struct Preset {
     pub initial_price: f64,
     // It's important that all values are sorted, so that other tools can easily detect changes.
     pub usage_coeffs: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "PricingParams::is_empty")]
+    pub pricing_params: PricingParams,
+}
+
+/// Additional parameters of non-linear pricing models.
+/// Each model uses only its own parameters and ignores the rest.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct PricingParams {
+    /// `tiered`: usage, above which `tier-coeffs` are charged instead of `usage-coeffs`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tier_thresholds: BTreeMap<String, f64>,
+    /// `tiered`: prices of usage above `tier-thresholds`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tier_coeffs: BTreeMap<String, f64>,
+    /// `minimum`: minimal amount charged for a single activity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_charge: Option<f64>,
+    /// `time-of-day`: range of UTC hours `[from, to)`, in which peak prices apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_hours: Option<(u32, u32)>,
+    /// `time-of-day`: multiplier of all prices in peak hours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_multiplier: Option<f64>,
+}
+
+impl PricingParams {
+    pub fn is_empty(&self) -> bool {
+        self == &PricingParams::default()
+    }
 }
 
 impl Preset {
@@ -226,6 +256,7 @@ impl Default for Preset {
             exeunit_name: "wasmtime".to_string(),
             pricing_model: "linear".to_string(),
             usage_coeffs,
+            pricing_params: Default::default(),
         }
     }
 }
@@ -236,6 +267,7 @@ impl PartialEq for Preset {
             && self.exeunit_name == other.exeunit_name
             && self.pricing_model == other.pricing_model
             && self.usage_coeffs == other.usage_coeffs
+            && self.pricing_params == other.pricing_params
     }
 }
 
@@ -291,5 +323,54 @@ fn display_preset(
         )?;
     }
 
+    display_pricing_params(f, &preset.pricing_params, align_coeff)
+}
+
+fn display_pricing_params(
+    f: &mut fmt::Formatter,
+    params: &PricingParams,
+    align: usize,
+) -> fmt::Result {
+    if params.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "Pricing parameters:")?;
+
+    for (name, threshold) in params.tier_thresholds.iter() {
+        let price = params
+            .tier_coeffs
+            .get(name)
+            .map(|coeff| format!("{} GLM", coeff))
+            .unwrap_or_else(|| "-".to_string());
+        writeln!(
+            f,
+            "    {:width$}above {}: {}",
+            name,
+            threshold,
+            price,
+            width = align
+        )?;
+    }
+    if let Some(charge) = params.minimum_charge {
+        writeln!(
+            f,
+            "    {:width$}{} GLM",
+            "Minimum charge",
+            charge,
+            width = align
+        )?;
+    }
+    if let Some((from, to)) = params.peak_hours {
+        let multiplier = params.peak_multiplier.unwrap_or(1.0);
+        writeln!(
+            f,
+            "    {:width$}{}:00-{}:00 UTC x{}",
+            "Peak hours",
+            from,
+            to,
+            multiplier,
+            width = align
+        )?;
+    }
     Ok(())
 }
@@ -116,7 +116,6 @@ pub struct ProviderMarket {
     subscriptions: HashMap<String, Subscription>,
     postponed_demands: Vec<SubscriptionProposal>,
     config: Arc<MarketConfig>,
-    agent_negotiators_cfg: Arc<AgentNegotiatorsConfig>,
 
     /// External actors can listen on this signal.
     pub agreement_signed_signal: SignalSlot<NewAgreement>,
@@ -139,22 +138,21 @@ impl ProviderMarket {
     // Initialization
     // =========================================== //
 
-    pub fn new(
+    pub async fn new(
         api: MarketProviderApi,
         config: MarketConfig,
         agent_negotiators_cfg: AgentNegotiatorsConfig,
-    ) -> ProviderMarket {
-        ProviderMarket {
-            negotiator: Arc::new(NegotiatorAddr::default()),
+    ) -> Result<ProviderMarket> {
+        Ok(ProviderMarket {
+            negotiator: factory::create_negotiator(&config, &agent_negotiators_cfg).await?,
             api: Arc::new(api),
             subscriptions: HashMap::new(),
             postponed_demands: Vec::new(),
             config: Arc::new(config),
-            agent_negotiators_cfg: Arc::new(agent_negotiators_cfg),
             agreement_signed_signal: SignalSlot::<NewAgreement>::default(),
             agreement_terminated_signal: SignalSlot::<CloseAgreement>::default(),
             handles: HashMap::new(),
-        }
+        })
     }
 
     fn async_context(&self, ctx: &mut Context<Self>) -> AsyncCtx {
@@ -593,9 +591,6 @@ impl Actor for ProviderMarket {
             "collect-agreement-events".to_string(),
             ctx.spawn(collect_agreement_events(actx).into_actor(self)),
         );
-
-        self.negotiator =
-            factory::create_negotiator(ctx.address(), &self.config, &self.agent_negotiators_cfg);
     }
 }
 
@@ -51,6 +51,19 @@ pub enum BreakReason {
     Shutdown,
 }
 
+impl BreakReason {
+    /// Agreement was broken because Requestor didn't fulfill its obligations.
+    pub fn is_requestor_fault(&self) -> bool {
+        matches!(
+            self,
+            Self::DebitNotesDeadline(_)
+                | Self::DebitNoteRejected(_)
+                | Self::DebitNoteNotPaid(_)
+                | Self::RequestorUnreachable(_)
+        )
+    }
+}
+
 impl TryFrom<DebitNoteEventType> for BreakReason {
     type Error = ();
 
@@ -11,6 +11,7 @@ use crate::display::EnableDisplay;
 
 use ya_agreement_utils::AgreementView;
 use ya_client::activity::ActivityProviderApi;
+use ya_client::model::NodeId;
 
 const PAYMENT_PRECISION: i64 = 18; // decimal places
 
@@ -48,6 +49,7 @@ pub enum ActivityPayment {
 /// We must wait until agreement will be closed, before we send invoice.
 pub struct AgreementPayment {
     pub agreement_id: String,
+    pub requestor_id: NodeId,
     pub approved_ts: DateTime<Utc>,
     pub payment_model: Arc<dyn PaymentModel>,
     pub activities: HashMap<String, ActivityPayment>,
@@ -105,6 +107,7 @@ impl AgreementPayment {
 
         Ok(AgreementPayment {
             agreement_id: agreement.id.clone(),
+            requestor_id: agreement.requestor_id()?,
             approved_ts,
             activities: HashMap::new(),
             payment_model,
@@ -249,7 +252,7 @@ pub async fn compute_cost(
         );
     }
 
-    let cost = payment_model.compute_cost(&usage)?;
+    let cost = payment_model.compute_activity_cost(&activity_id, &usage)?;
 
     Ok(CostInfo::new(usage, cost))
 }
@@ -7,4 +7,6 @@ mod pricing;
 
 pub use factory::PaymentModelFactory;
 pub use payments::{Payments, PaymentsConfig};
-pub use pricing::{AccountView, LinearPricing, LinearPricingOffer, PricingOffer};
+pub use pricing::{
+    pricing_offer, AccountView, LinearPricing, LinearPricingOffer, PricingOffer, PRICING_MODELS,
+};
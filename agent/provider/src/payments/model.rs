@@ -1,7 +1,9 @@
 use anyhow::Result;
 use bigdecimal::BigDecimal;
+use serde::de::DeserializeOwned;
 use std::time::Duration;
 
+use ya_agreement_utils::agreement::PROPERTY_TAG;
 use ya_agreement_utils::{AgreementView, Error};
 
 use crate::market::negotiator::builtin::expiration::DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY;
@@ -14,6 +16,13 @@ use crate::market::negotiator::builtin::payment_timeout::PAYMENT_TIMEOUT_PROPERT
 /// of money, that requestor should pay for computations.
 pub trait PaymentModel {
     fn compute_cost(&self, usage: &[f64]) -> Result<BigDecimal>;
+    /// Cost of a single activity. Models depending on the time of usage
+    /// keep track of usage already charged for each activity.
+    fn compute_activity_cost(&self, _activity_id: &str, usage: &[f64]) -> Result<BigDecimal> {
+        self.compute_cost(usage)
+    }
+    /// Called once the final cost of the activity was computed.
+    fn activity_finished(&self, _activity_id: &str) {}
     fn expected_usage_len(&self) -> usize;
 }
 
@@ -32,6 +41,29 @@ impl<'a> PaymentDescription<'a> {
         Ok(self.agreement.pointer_typed::<Vec<f64>>(coeffs_addr)?)
     }
 
+    /// Name of pricing model from `golem.com.pricing.model` property.
+    pub fn get_pricing_model(&self) -> Result<String> {
+        let model_addr = "/offer/properties/golem/com/pricing/model";
+        // Model name is merged with model parameters under the same key.
+        match self.agreement.pointer_typed::<String>(model_addr) {
+            Err(Error::UnexpectedType(..)) => Ok(self
+                .agreement
+                .pointer_typed::<String>(&format!("{}/{}", model_addr, PROPERTY_TAG))?),
+            result => Ok(result?),
+        }
+    }
+
+    pub fn get_usage_vector(&self) -> Result<Vec<String>> {
+        let vector_addr = "/offer/properties/golem/com/usage/vector";
+        Ok(self.agreement.pointer_typed::<Vec<String>>(vector_addr)?)
+    }
+
+    /// Pricing model parameter stored under `golem.com.pricing.model.<model>.<name>`.
+    pub fn get_pricing_param<T: DeserializeOwned>(&self, model: &str, name: &str) -> Result<T> {
+        let param_addr = format!("/offer/properties/golem/com/pricing/model/{model}/{name}");
+        Ok(self.agreement.pointer_typed::<T>(&param_addr)?)
+    }
+
     pub fn get_update_interval(&self) -> Result<Duration> {
         let interval = match self.agreement.pointer_typed::<u32>(&format!(
             "/offer/properties{}",
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::sync::Arc;
 use std::time::Duration;
@@ -30,6 +30,7 @@ use crate::execution::{ActivityDestroyed, CreateActivity};
 use crate::interval::RelativeInterval;
 use crate::market::provider_market::NewAgreement;
 use crate::market::termination_reason::BreakReason;
+use crate::reputation::{ReputationEvent, ReputationStore};
 use crate::tasks::{AgreementBroken, AgreementClosed, BreakAgreement};
 
 use super::agreement::{compute_cost, ActivityPayment, AgreementPayment, CostInfo};
@@ -132,6 +133,8 @@ struct ProviderCtx {
     payment_api: Arc<PaymentApi>,
     debit_checker: Addr<DeadlineChecker>,
     payment_checker: Addr<DeadlineChecker>,
+    invoice_checker: Addr<DeadlineChecker>,
+    reputation: ReputationStore,
     config: PaymentsConfig,
 }
 
@@ -142,6 +145,8 @@ pub struct Payments {
     agreements: HashMap<String, AgreementPayment>,
 
     invoices_to_pay: Vec<Invoice>,
+    /// Invoices already recorded as overdue, so paying them late isn't penalized twice.
+    overdue_invoices: HashSet<String>,
     earnings: BigDecimal,
 
     break_agreement_signal: SignalSlot<BreakAgreement>,
@@ -154,12 +159,15 @@ impl Payments {
         activity_api: ActivityProviderApi,
         payment_api: PaymentApi,
         config: PaymentsConfig,
+        reputation: ReputationStore,
     ) -> Payments {
         let provider_ctx = ProviderCtx {
             activity_api: Arc::new(activity_api),
             payment_api: Arc::new(payment_api),
             debit_checker: DeadlineChecker::default().start(),
             payment_checker: DeadlineChecker::default().start(),
+            invoice_checker: DeadlineChecker::default().start(),
+            reputation,
             config,
         };
 
@@ -167,6 +175,7 @@ impl Payments {
             agreements: HashMap::new(),
             context: Arc::new(provider_ctx),
             invoices_to_pay: vec![],
+            overdue_invoices: HashSet::new(),
             earnings: BigDecimal::zero(),
             break_agreement_signal: SignalSlot::<BreakAgreement>::default(),
         }
@@ -391,21 +400,38 @@ async fn handle_debit_note_event(
     provider_signal: &SignalSlot<BreakAgreement>,
 ) {
     match &event.event_type {
-        DebitNoteEventType::DebitNoteAcceptedEvent => provider_ctx
-            .debit_checker
-            .send(StopTracking {
-                id: note_accept_id(&event.debit_note_id),
-                category: None,
-            })
-            .await
-            .map(|_| log::debug!("DebitNote [{}] accepted.", event.debit_note_id))
-            .map_err(|_| {
-                log::warn!(
-                    "Failed to notify about accepted DebitNote {}",
-                    event.debit_note_id
-                )
-            })
-            .ok(),
+        DebitNoteEventType::DebitNoteAcceptedEvent => {
+            match provider_ctx
+                .payment_api
+                .get_debit_note(&event.debit_note_id)
+                .await
+            {
+                Ok(note) => provider_ctx
+                    .reputation
+                    .record(note.recipient_id, ReputationEvent::DebitNoteAccepted),
+                Err(e) => log::debug!(
+                    "Failed to get accepted DebitNote [{}]: {}",
+                    event.debit_note_id,
+                    e
+                ),
+            }
+
+            provider_ctx
+                .debit_checker
+                .send(StopTracking {
+                    id: note_accept_id(&event.debit_note_id),
+                    category: None,
+                })
+                .await
+                .map(|_| log::debug!("DebitNote [{}] accepted.", event.debit_note_id))
+                .map_err(|_| {
+                    log::warn!(
+                        "Failed to notify about accepted DebitNote {}",
+                        event.debit_note_id
+                    )
+                })
+                .ok()
+        }
         DebitNoteEventType::DebitNoteSettledEvent => provider_ctx
             .payment_checker
             .send(StopTracking {
@@ -603,6 +629,7 @@ impl Handler<ActivityDestroyed> for Payments {
                 &debit_note_info.activity_id,
                 &debit_note.total_amount_due
             );
+            payment_model.activity_finished(&debit_note_info.activity_id);
 
             let msg = FinalizeActivity {
                 cost_summary: cost_info,
@@ -827,6 +854,20 @@ impl Handler<IssueInvoice> for Payments {
                 match provider_ctx.payment_api.issue_invoice(&invoice).await {
                     Ok(invoice) => {
                         log::info!("Invoice [{}] issued.", invoice.invoice_id);
+                        let _ = provider_ctx
+                            .invoice_checker
+                            .send(TrackDeadline {
+                                category: invoice.agreement_id.clone(),
+                                deadline: invoice.payment_due_date,
+                                id: invoice_payment_id(&invoice.invoice_id),
+                            })
+                            .await
+                            .map_err(|_| {
+                                log::warn!(
+                                    "Failed to track payment deadline of Invoice [{}].",
+                                    invoice.invoice_id
+                                )
+                            });
                         return Ok(invoice);
                     }
                     Err(e) => {
@@ -881,6 +922,15 @@ impl Handler<AgreementBroken> for Payments {
             return ActorResponse::reply(Ok(()));
         }
 
+        if let (Some(event), Some(agreement)) = (
+            reputation_event(&msg.reason),
+            self.agreements.get(&msg.agreement_id),
+        ) {
+            self.context
+                .reputation
+                .record(agreement.requestor_id, event);
+        }
+
         let address = ctx.address();
         let future = async move {
             let msg = AgreementClosed {
@@ -930,6 +980,22 @@ impl Handler<InvoiceSettled> for Payments {
                         invoice.agreement_id,
                         invoice.amount
                     );
+                    myself.context.invoice_checker.do_send(StopTracking {
+                        id: invoice_payment_id(&invoice.invoice_id),
+                        category: Some(invoice.agreement_id.clone()),
+                    });
+                    // Overdue payment was already recorded when the deadline elapsed.
+                    if !myself.overdue_invoices.remove(&invoice.invoice_id) {
+                        let event = match Utc::now() <= invoice.payment_due_date {
+                            true => ReputationEvent::InvoicePaidOnTime,
+                            false => ReputationEvent::InvoicePaidLate,
+                        };
+                        myself
+                            .context
+                            .reputation
+                            .record(invoice.recipient_id, event);
+                    }
+
                     myself.agreements.remove(&invoice.agreement_id);
                     myself
                         .invoices_to_pay
@@ -949,6 +1015,31 @@ impl Handler<DeadlineElapsed> for Payments {
     type Result = ();
 
     fn handle(&mut self, msg: DeadlineElapsed, _ctx: &mut Context<Self>) -> Self::Result {
+        // Unpaid Invoice doesn't break anything, since the Agreement is already
+        // terminated. It only affects Requestor's reputation.
+        if let Some(invoice_id) = msg.id.strip_prefix(INVOICE_PREFIX) {
+            log::warn!(
+                "Deadline {} elapsed for Invoice [{}] payment for Agreement [{}]",
+                msg.deadline,
+                invoice_id,
+                msg.category,
+            );
+            match self.agreements.get(&msg.category) {
+                Some(agreement) => {
+                    self.context
+                        .reputation
+                        .record(agreement.requestor_id, ReputationEvent::PaymentOverdue);
+                    self.overdue_invoices.insert(invoice_id.to_string());
+                }
+                None => log::error!(
+                    "Invoice [{}] payment deadline elapsed for not existing Agreement [{}].",
+                    invoice_id,
+                    msg.category
+                ),
+            }
+            return;
+        }
+
         let agreement = match self.agreements.get_mut(&msg.category) {
             Some(agreement) => {
                 // If at least one deadline elapses, we don't want to generate any
@@ -1048,7 +1139,11 @@ impl Actor for Payments {
             payment_addr.clone(),
         ));
         tokio::task::spawn_local(async move {
-            for checker in &[&provider_ctx.debit_checker, &provider_ctx.payment_checker] {
+            for checker in &[
+                &provider_ctx.debit_checker,
+                &provider_ctx.payment_checker,
+                &provider_ctx.invoice_checker,
+            ] {
                 let _ = checker
                     .send(Subscribe(payment_addr.clone().recipient()))
                     .await
@@ -1072,6 +1167,7 @@ fn get_backoff() -> backoff::ExponentialBackoff {
 
 const ACCEPT_PREFIX: &str = "debit-";
 const PAYMENT_PREFIX: &str = "payment-";
+const INVOICE_PREFIX: &str = "invoice-";
 
 #[inline(always)]
 fn note_accept_id(id: impl AsRef<str>) -> String {
@@ -1082,3 +1178,17 @@ fn note_accept_id(id: impl AsRef<str>) -> String {
 fn note_payment_id(id: impl AsRef<str>) -> String {
     format!("{}{}", PAYMENT_PREFIX, id.as_ref())
 }
+
+#[inline(always)]
+fn invoice_payment_id(id: impl AsRef<str>) -> String {
+    format!("{}{}", INVOICE_PREFIX, id.as_ref())
+}
+
+/// Single penalty recorded for Agreement broken because of Requestor's fault.
+fn reputation_event(reason: &BreakReason) -> Option<ReputationEvent> {
+    match reason {
+        BreakReason::DebitNoteNotPaid(_) => Some(ReputationEvent::PaymentOverdue),
+        reason if reason.is_requestor_fault() => Some(ReputationEvent::AgreementBroken),
+        _ => None,
+    }
+}
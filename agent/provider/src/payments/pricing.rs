@@ -1,19 +1,24 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use bigdecimal::BigDecimal;
+use chrono::{DateTime, Timelike, Utc};
 use serde_json::json;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::sync::Mutex;
 
 use ya_agreement_utils::ComInfo;
 use ya_client::model::{payment::Account, NodeId};
-use ya_core_model::payment::local::NetworkName;
 
 use super::model::{PaymentDescription, PaymentModel};
 use crate::market::presets::Preset;
 
+/// Names of pricing models, that can be selected in presets.
+pub const PRICING_MODELS: &[&str] = &["linear", "tiered", "minimum", "time-of-day", "flat"];
+
 #[derive(Clone, Debug)]
 pub struct AccountView {
     pub address: NodeId,
-    pub network: NetworkName,
+    pub network: String,
     pub platform: String,
 }
 
@@ -21,14 +26,17 @@ impl From<Account> for AccountView {
     fn from(account: Account) -> Self {
         Self {
             address: account.address.parse().unwrap(), // TODO: use TryFrom
-            network: account.network.parse().unwrap(), // TODO: use TryFrom
+            network: account.network,
             platform: account.platform,
         }
     }
 }
 
 pub trait PricingOffer {
-    fn prices(&self, preset: &Preset) -> Vec<(String, f64)>;
+    /// Prices of usage counters. All models take them from preset coefficients.
+    fn prices(&self, preset: &Preset) -> Vec<(String, f64)> {
+        preset.usage_coeffs.clone().into_iter().collect()
+    }
     fn build(
         &self,
         accounts: &[AccountView],
@@ -37,6 +45,87 @@ pub trait PricingOffer {
     ) -> Result<ComInfo>;
 }
 
+/// Creates offer builder for pricing model selected in preset.
+pub fn pricing_offer(preset: &Preset) -> Result<Box<dyn PricingOffer>> {
+    let params = &preset.pricing_params;
+    Ok(match preset.pricing_model.as_str() {
+        "linear" => Box::<LinearPricingOffer>::default(),
+        "tiered" => {
+            if params.tier_thresholds.is_empty() {
+                bail!("Pricing model 'tiered' requires 'tier-thresholds'")
+            }
+            if let Some(name) = params
+                .tier_thresholds
+                .keys()
+                .find(|name| !params.tier_coeffs.contains_key(*name))
+            {
+                bail!("Pricing model 'tiered' is missing tier coefficient for [{name}]")
+            }
+            Box::new(TieredPricingOffer {
+                thresholds: params.tier_thresholds.clone(),
+                tier_coeffs: params.tier_coeffs.clone(),
+            })
+        }
+        "minimum" => Box::new(MinimumPricingOffer {
+            charge: params
+                .minimum_charge
+                .ok_or_else(|| anyhow!("Pricing model 'minimum' requires 'minimum-charge'"))?,
+        }),
+        "time-of-day" => {
+            let (from, to) = params
+                .peak_hours
+                .ok_or_else(|| anyhow!("Pricing model 'time-of-day' requires 'peak-hours'"))?;
+            if from > 23 || to > 24 {
+                bail!("Invalid peak hours: {from}-{to}")
+            }
+            Box::new(TimeOfDayPricingOffer {
+                peak_hours: (from, to),
+                peak_multiplier: params.peak_multiplier.ok_or_else(|| {
+                    anyhow!("Pricing model 'time-of-day' requires 'peak-multiplier'")
+                })?,
+            })
+        }
+        "flat" => Box::new(FlatPricingOffer {}),
+        other => bail!("Unsupported pricing model: {}", other),
+    })
+}
+
+fn to_decimals(values: Vec<f64>, what: &str) -> Result<Vec<BigDecimal>> {
+    values
+        .into_iter()
+        .map(BigDecimal::try_from)
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| anyhow!("Failed to convert {what} to BigDecimal: {e}"))
+}
+
+fn to_decimal(value: f64, what: &str) -> Result<BigDecimal> {
+    BigDecimal::try_from(value).map_err(|e| anyhow!("Failed to convert {what} to BigDecimal: {e}"))
+}
+
+/// Cost of linear model, where last coefficient is constant initial price.
+fn linear_cost(usage_coeffs: &[BigDecimal], usage: &[BigDecimal]) -> BigDecimal {
+    // Note: last element of usage_coeffs contains constant initial cost
+    // of computing task, so we don't multiply it.
+    let const_coeff_idx = usage_coeffs.len() - 1;
+    usage_coeffs[const_coeff_idx].clone()
+        + usage_coeffs[0..const_coeff_idx]
+            .iter()
+            .zip(usage.iter())
+            .map(|(coeff, usage_value)| coeff * usage_value)
+            .sum::<BigDecimal>()
+}
+
+fn model_coefficients(commercials: &PaymentDescription, model: &str) -> Result<Vec<BigDecimal>> {
+    let coeffs = to_decimals(
+        commercials.get_pricing_param::<Vec<f64>>(model, "coeffs")?,
+        "usage coefficients",
+    )?;
+    if coeffs.is_empty() {
+        bail!("Empty coefficients vector for pricing model '{model}'");
+    }
+    Ok(coeffs)
+}
+
 /// Computes computations costs.
 pub struct LinearPricing {
     usage_coeffs: Vec<BigDecimal>,
@@ -44,21 +133,50 @@ pub struct LinearPricing {
 
 impl PaymentModel for LinearPricing {
     fn compute_cost(&self, usage: &[f64]) -> Result<BigDecimal> {
-        let usage: Vec<BigDecimal> = usage
-            .iter()
-            .cloned()
-            .map(BigDecimal::try_from)
-            .collect::<std::result::Result<_, _>>()
-            .map_err(|e| anyhow!("Failed to convert usage to BigDecimal: {e}"))?;
+        let usage = to_decimals(usage.to_vec(), "usage")?;
+        Ok(linear_cost(&self.usage_coeffs, &usage))
+    }
+
+    fn expected_usage_len(&self) -> usize {
+        self.usage_coeffs.len() - 1
+    }
+}
+
+impl LinearPricing {
+    pub fn new<'a>(commercials: &'a PaymentDescription<'a>) -> Result<LinearPricing> {
+        let usage = to_decimals(commercials.get_usage_coefficients()?, "usage coefficients")?;
+
+        log::info!("Creating LinearPricing payment model. Usage coefficients vector: {usage:?}.");
+
+        Ok(LinearPricing {
+            usage_coeffs: usage,
+        })
+    }
+}
+
+/// Charges `tier_coeffs` instead of `usage_coeffs` for usage above `thresholds`.
+pub struct TieredPricing {
+    usage_coeffs: Vec<BigDecimal>,
+    thresholds: Vec<BigDecimal>,
+    tier_coeffs: Vec<BigDecimal>,
+}
 
-        // Note: last element of usage_coeffs contains constant initial cost
-        // of computing task, so we don't multiply it.
+impl PaymentModel for TieredPricing {
+    fn compute_cost(&self, usage: &[f64]) -> Result<BigDecimal> {
+        let usage = to_decimals(usage.to_vec(), "usage")?;
         let const_coeff_idx = self.usage_coeffs.len() - 1;
         Ok(self.usage_coeffs[const_coeff_idx].clone()
-            + self.usage_coeffs[0..const_coeff_idx]
+            + usage
                 .iter()
-                .zip(usage.iter())
-                .map(|(coeff, usage_value)| coeff * usage_value)
+                .zip(self.usage_coeffs.iter())
+                .zip(self.thresholds.iter().zip(self.tier_coeffs.iter()))
+                .map(|((usage_value, coeff), (threshold, tier_coeff))| {
+                    if usage_value > threshold {
+                        coeff * threshold + tier_coeff * (usage_value - threshold)
+                    } else {
+                        coeff * usage_value
+                    }
+                })
                 .sum::<BigDecimal>())
     }
 
@@ -67,21 +185,263 @@ impl PaymentModel for LinearPricing {
     }
 }
 
-impl LinearPricing {
-    pub fn new<'a>(commercials: &'a PaymentDescription<'a>) -> Result<LinearPricing> {
-        let usage: Vec<BigDecimal> = commercials
-            .get_usage_coefficients()?
+impl TieredPricing {
+    pub fn new<'a>(commercials: &'a PaymentDescription<'a>) -> Result<TieredPricing> {
+        let usage_coeffs = model_coefficients(commercials, "tiered")?;
+        let thresholds = to_decimals(
+            commercials.get_pricing_param::<Vec<f64>>("tiered", "thresholds")?,
+            "tier thresholds",
+        )?;
+        let tier_coeffs = to_decimals(
+            commercials.get_pricing_param::<Vec<f64>>("tiered", "tier-coeffs")?,
+            "tier coefficients",
+        )?;
+
+        let usage_len = usage_coeffs.len() - 1;
+        if thresholds.len() != usage_len || tier_coeffs.len() != usage_len {
+            bail!(
+                "Tiered pricing expects {usage_len} thresholds and tier coefficients, got {} and {}",
+                thresholds.len(),
+                tier_coeffs.len()
+            );
+        }
+
+        log::info!(
+            "Creating TieredPricing payment model. Usage coefficients vector: {usage_coeffs:?}, \
+            thresholds: {thresholds:?}, tier coefficients: {tier_coeffs:?}."
+        );
+
+        Ok(TieredPricing {
+            usage_coeffs,
+            thresholds,
+            tier_coeffs,
+        })
+    }
+}
+
+/// Linear pricing, which never charges less than `charge` for a single activity.
+pub struct MinimumPricing {
+    usage_coeffs: Vec<BigDecimal>,
+    charge: BigDecimal,
+}
+
+impl PaymentModel for MinimumPricing {
+    fn compute_cost(&self, usage: &[f64]) -> Result<BigDecimal> {
+        let usage = to_decimals(usage.to_vec(), "usage")?;
+        let cost = linear_cost(&self.usage_coeffs, &usage);
+        Ok(if cost < self.charge {
+            self.charge.clone()
+        } else {
+            cost
+        })
+    }
+
+    fn expected_usage_len(&self) -> usize {
+        self.usage_coeffs.len() - 1
+    }
+}
+
+impl MinimumPricing {
+    pub fn new<'a>(commercials: &'a PaymentDescription<'a>) -> Result<MinimumPricing> {
+        let usage_coeffs = model_coefficients(commercials, "minimum")?;
+        let charge = to_decimal(
+            commercials.get_pricing_param::<f64>("minimum", "charge")?,
+            "minimum charge",
+        )?;
+
+        log::info!(
+            "Creating MinimumPricing payment model. Usage coefficients vector: {usage_coeffs:?}, \
+            minimum charge: {charge}."
+        );
+
+        Ok(MinimumPricing {
+            usage_coeffs,
+            charge,
+        })
+    }
+}
+
+/// Linear pricing with prices multiplied by `peak_multiplier` in peak hours.
+/// Usage is charged with the multiplier of the time window, in which it was reported,
+/// so each Debit Note can be verified by Requestor against its timestamp.
+pub struct TimeOfDayPricing {
+    usage_coeffs: Vec<BigDecimal>,
+    peak_hours: (u32, u32),
+    peak_multiplier: BigDecimal,
+    activities: Mutex<HashMap<String, ActivityCharge>>,
+}
+
+/// Usage already charged for an activity and its cost.
+struct ActivityCharge {
+    usage: Vec<BigDecimal>,
+    cost: BigDecimal,
+}
+
+impl PaymentModel for TimeOfDayPricing {
+    fn compute_cost(&self, usage: &[f64]) -> Result<BigDecimal> {
+        let usage = to_decimals(usage.to_vec(), "usage")?;
+        Ok(linear_cost(&self.usage_coeffs, &usage) * self.multiplier(Utc::now()))
+    }
+
+    fn compute_activity_cost(&self, activity_id: &str, usage: &[f64]) -> Result<BigDecimal> {
+        self.charge(activity_id, usage, Utc::now())
+    }
+
+    fn activity_finished(&self, activity_id: &str) {
+        self.activities.lock().unwrap().remove(activity_id);
+    }
+
+    fn expected_usage_len(&self) -> usize {
+        self.usage_coeffs.len() - 1
+    }
+}
+
+impl TimeOfDayPricing {
+    pub fn new<'a>(commercials: &'a PaymentDescription<'a>) -> Result<TimeOfDayPricing> {
+        let usage_coeffs = model_coefficients(commercials, "time-of-day")?;
+        let (from, to) =
+            commercials.get_pricing_param::<(u32, u32)>("time-of-day", "peak-hours")?;
+        let peak_multiplier = to_decimal(
+            commercials.get_pricing_param::<f64>("time-of-day", "peak-multiplier")?,
+            "peak multiplier",
+        )?;
+
+        log::info!(
+            "Creating TimeOfDayPricing payment model. Usage coefficients vector: {usage_coeffs:?}, \
+            peak hours: {from}-{to} UTC, peak multiplier: {peak_multiplier}."
+        );
+
+        Ok(TimeOfDayPricing {
+            usage_coeffs,
+            peak_hours: (from, to),
+            peak_multiplier,
+            activities: Default::default(),
+        })
+    }
+
+    fn multiplier(&self, at: DateTime<Utc>) -> BigDecimal {
+        match is_peak_hour(at.hour(), self.peak_hours.0, self.peak_hours.1) {
+            true => self.peak_multiplier.clone(),
+            false => BigDecimal::from(1),
+        }
+    }
+
+    /// Charges usage reported since previous call with the multiplier of the window `now`
+    /// belongs to. Initial price is charged in the window, in which activity was first reported.
+    fn charge(&self, activity_id: &str, usage: &[f64], now: DateTime<Utc>) -> Result<BigDecimal> {
+        let usage = to_decimals(usage.to_vec(), "usage")?;
+        let multiplier = self.multiplier(now);
+        let const_coeff_idx = self.usage_coeffs.len() - 1;
+
+        let mut activities = self.activities.lock().unwrap();
+        let charged = activities
+            .entry(activity_id.to_string())
+            .or_insert_with(|| ActivityCharge {
+                usage: vec![BigDecimal::from(0); const_coeff_idx],
+                cost: &self.usage_coeffs[const_coeff_idx] * &multiplier,
+            });
+
+        // Usage counters are cumulative, so only increase since previous call is charged.
+        let increment = usage
+            .iter()
+            .zip(charged.usage.iter())
+            .zip(self.usage_coeffs.iter())
+            .filter(|((usage_value, last), _)| usage_value > last)
+            .map(|((usage_value, last), coeff)| coeff * (usage_value - last))
+            .sum::<BigDecimal>();
+
+        charged.cost += increment * multiplier;
+        charged.usage = usage
             .into_iter()
-            .map(BigDecimal::try_from)
-            .collect::<std::result::Result<_, _>>()
-            .map_err(|e| anyhow!("Failed to convert usage coefficients to BigDecimal: {e}"))?;
+            .zip(charged.usage.iter())
+            .map(|(usage_value, last)| usage_value.max(last.clone()))
+            .collect();
+        Ok(charged.cost.clone())
+    }
+}
 
-        log::info!("Creating LinearPricing payment model. Usage coefficients vector: {usage:?}.");
+/// Checks if `hour` is in `[from, to)` range, which can wrap around midnight.
+fn is_peak_hour(hour: u32, from: u32, to: u32) -> bool {
+    if from <= to {
+        from <= hour && hour < to
+    } else {
+        hour >= from || hour < to
+    }
+}
 
-        Ok(LinearPricing {
-            usage_coeffs: usage,
+/// Charges the same fee for each activity regardless of usage.
+pub struct FlatPricing {
+    fee: BigDecimal,
+    usage_len: usize,
+}
+
+impl PaymentModel for FlatPricing {
+    fn compute_cost(&self, _usage: &[f64]) -> Result<BigDecimal> {
+        Ok(self.fee.clone())
+    }
+
+    fn expected_usage_len(&self) -> usize {
+        self.usage_len
+    }
+}
+
+impl FlatPricing {
+    pub fn new<'a>(commercials: &'a PaymentDescription<'a>) -> Result<FlatPricing> {
+        let fee = to_decimal(
+            commercials.get_pricing_param::<f64>("flat", "fee")?,
+            "flat fee",
+        )?;
+        let usage_len = commercials.get_usage_vector()?.len();
+
+        log::info!("Creating FlatPricing payment model. Fee: {fee}.");
+
+        Ok(FlatPricing { fee, usage_len })
+    }
+}
+
+/// Builds commercial part of the Offer common for all pricing models.
+fn build_com_info(
+    accounts: &[AccountView],
+    usage_vector: Vec<String>,
+    model: &str,
+    model_params: serde_json::Value,
+) -> ComInfo {
+    let mut params = json!({
+        "scheme": "payu".to_string(),
+        "scheme.payu": json!({}),
+        "pricing": json!({
+            "model": model.to_string(),
+            format!("model.{model}"): model_params,
+        }),
+        "usage": json!({
+            "vector": usage_vector
         })
+    });
+
+    for account in accounts {
+        params.as_object_mut().unwrap().insert(
+            format!("payment.platform.{}", account.platform),
+            json!({
+                "address".to_string(): account.address,
+            }),
+        );
     }
+
+    ComInfo { params }
+}
+
+/// Splits prices into usage vector and coefficients with initial price at the end.
+fn split_prices(initial_price: f64, prices: Vec<(String, f64)>) -> (Vec<String>, Vec<f64>) {
+    let mut usage_vector = Vec::new();
+    let coefficients = prices
+        .into_iter()
+        .map(|(p, v)| {
+            usage_vector.push(p);
+            v
+        })
+        .chain(std::iter::once(initial_price))
+        .collect::<Vec<_>>();
+    (usage_vector, coefficients)
 }
 
 /// Helper for building offer.
@@ -104,62 +464,138 @@ impl LinearPricingOffer {
 }
 
 impl PricingOffer for LinearPricingOffer {
-    fn prices(&self, preset: &Preset) -> Vec<(String, f64)> {
-        preset.usage_coeffs.clone().into_iter().collect()
+    fn build(
+        &self,
+        accounts: &[AccountView],
+        initial_price: f64,
+        prices: Vec<(String, f64)>,
+    ) -> Result<ComInfo> {
+        let (usage_vector, coefficients) = split_prices(initial_price, prices);
+        Ok(build_com_info(
+            accounts,
+            usage_vector,
+            "linear",
+            json!({ "coeffs": coefficients }),
+        ))
     }
+}
+
+pub struct TieredPricingOffer {
+    thresholds: std::collections::BTreeMap<String, f64>,
+    tier_coeffs: std::collections::BTreeMap<String, f64>,
+}
 
+impl PricingOffer for TieredPricingOffer {
     fn build(
         &self,
         accounts: &[AccountView],
         initial_price: f64,
         prices: Vec<(String, f64)>,
     ) -> Result<ComInfo> {
-        let mut usage_vector = Vec::new();
-        let coefficients = prices
-            .into_iter()
-            .map(|(p, v)| {
-                usage_vector.push(p);
-                v
+        // Usage counters without threshold are charged with the same price from the start.
+        let (thresholds, tier_coeffs): (Vec<f64>, Vec<f64>) = prices
+            .iter()
+            .map(|(name, price)| match self.thresholds.get(name) {
+                Some(threshold) => (*threshold, self.tier_coeffs[name]),
+                None => (0.0, *price),
             })
-            .chain(std::iter::once(initial_price))
-            .collect::<Vec<_>>();
-
-        let mut params = json!({
-            "scheme": "payu".to_string(),
-            "scheme.payu": json!({}),
-            "pricing": json!({
-                "model": "linear".to_string(),
-                "model.linear": json!({
-                    "coeffs": coefficients
-                })
+            .unzip();
+        let (usage_vector, coefficients) = split_prices(initial_price, prices);
+        Ok(build_com_info(
+            accounts,
+            usage_vector,
+            "tiered",
+            json!({
+                "coeffs": coefficients,
+                "thresholds": thresholds,
+                "tier-coeffs": tier_coeffs,
             }),
-            "usage": json!({
-                "vector": usage_vector
-            })
-        });
-
-        for account in accounts {
-            params.as_object_mut().unwrap().insert(
-                format!("payment.platform.{}", account.platform),
-                json!({
-                    "address".to_string(): account.address,
-                }),
-            );
-        }
+        ))
+    }
+}
+
+pub struct MinimumPricingOffer {
+    charge: f64,
+}
+
+impl PricingOffer for MinimumPricingOffer {
+    fn build(
+        &self,
+        accounts: &[AccountView],
+        initial_price: f64,
+        prices: Vec<(String, f64)>,
+    ) -> Result<ComInfo> {
+        let (usage_vector, coefficients) = split_prices(initial_price, prices);
+        Ok(build_com_info(
+            accounts,
+            usage_vector,
+            "minimum",
+            json!({
+                "coeffs": coefficients,
+                "charge": self.charge,
+            }),
+        ))
+    }
+}
 
-        Ok(ComInfo { params })
+pub struct TimeOfDayPricingOffer {
+    peak_hours: (u32, u32),
+    peak_multiplier: f64,
+}
+
+impl PricingOffer for TimeOfDayPricingOffer {
+    fn build(
+        &self,
+        accounts: &[AccountView],
+        initial_price: f64,
+        prices: Vec<(String, f64)>,
+    ) -> Result<ComInfo> {
+        let (usage_vector, coefficients) = split_prices(initial_price, prices);
+        Ok(build_com_info(
+            accounts,
+            usage_vector,
+            "time-of-day",
+            json!({
+                "coeffs": coefficients,
+                "peak-hours": [self.peak_hours.0, self.peak_hours.1],
+                "peak-multiplier": self.peak_multiplier,
+            }),
+        ))
+    }
+}
+
+/// Preset coefficients only determine usage vector, initial price is the fee.
+pub struct FlatPricingOffer {}
+
+impl PricingOffer for FlatPricingOffer {
+    fn build(
+        &self,
+        accounts: &[AccountView],
+        initial_price: f64,
+        prices: Vec<(String, f64)>,
+    ) -> Result<ComInfo> {
+        let (usage_vector, _) = split_prices(initial_price, prices);
+        Ok(build_com_info(
+            accounts,
+            usage_vector,
+            "flat",
+            json!({ "fee": initial_price }),
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use bigdecimal::BigDecimal;
+    use chrono::{TimeZone, Utc};
     use std::convert::TryFrom;
     use std::str::FromStr;
     use test_case::test_case;
 
+    use crate::market::{Preset, PricingParams};
     use crate::payments::model::{PaymentDescription, PaymentModel};
-    use crate::payments::LinearPricing;
+    use crate::payments::pricing::{is_peak_hour, pricing_offer, TimeOfDayPricing};
+    use crate::payments::{LinearPricing, PaymentModelFactory};
 
     use ya_agreement_utils::agreement::try_from_json;
     use ya_agreement_utils::AgreementView;
@@ -235,4 +671,181 @@ mod tests {
 
         assert_eq!(pricing.compute_cost(usage).unwrap(), expected);
     }
+
+    const MODEL_AGREEMENT_TEMPLATE: &str = r#"
+{
+  "agreementId": "0ec929f5acc8f98a47ab72d61a2c2f343d45d8438d3aa4ccdc84e717c219e185",
+  "proposedSignature": "NoSignature",
+  "state": "Pending",
+  "timestamp": "2022-05-22T10:41:42.564784259Z",
+  "validTo": "2022-05-22T11:41:42.562457Z",
+
+  "offer": {
+    "properties": {
+      "golem.com.pricing.model": "${model}",
+      ${params},
+      "golem.com.scheme": "payu",
+      "golem.com.usage.vector": [
+        "golem.usage.cpu_sec",
+        "golem.usage.duration_sec"
+      ]
+    },
+    "constraints": "()",
+    "offerId": "afce49b1ea5b45db91bdd6e5481479f9-9095fca9dea0a91ce95cf994125b33cdd838fcc963a1106f2be9e4b5b65a52f0",
+    "providerId": "0x86a269498fb5270f20bdc6fdcf6039122b0d3b23",
+    "timestamp": "2022-05-22T10:41:42.564784259Z"
+  },
+
+  "demand": {
+    "constraints": "()",
+    "demandId": "773035fc685c46da8e61473ac2a2568e-3f3eb86d6ef9a01708d0f57d0b19cc69fd74422150c120e33cc1b5f4a1a12b96",
+    "properties": {},
+    "requestorId": "0xa5ad3f81e283983b8e9705b2e31d0c138bb2b1b7",
+    "timestamp": "2022-05-22T10:41:42.564784259Z"
+  }
+}
+"#;
+
+    #[test_case(
+        "tiered",
+        r#""golem.com.pricing.model.tiered.coeffs": [0.1, 0.01, 1.0],
+           "golem.com.pricing.model.tiered.thresholds": [10.0, 0.0],
+           "golem.com.pricing.model.tiered.tier-coeffs": [0.05, 0.01]"#,
+        &[20.0, 100.0],
+        BigDecimal::from_str("3.5").unwrap();
+        "Tiered pricing above threshold"
+    )]
+    #[test_case(
+        "tiered",
+        r#""golem.com.pricing.model.tiered.coeffs": [0.1, 0.01, 1.0],
+           "golem.com.pricing.model.tiered.thresholds": [10.0, 0.0],
+           "golem.com.pricing.model.tiered.tier-coeffs": [0.05, 0.01]"#,
+        &[5.0, 100.0],
+        BigDecimal::from_str("2.5").unwrap();
+        "Tiered pricing below threshold"
+    )]
+    #[test_case(
+        "minimum",
+        r#""golem.com.pricing.model.minimum.coeffs": [0.1, 0.01, 1.0],
+           "golem.com.pricing.model.minimum.charge": 5.0"#,
+        &[10.0, 100.0],
+        BigDecimal::from_str("5").unwrap();
+        "Minimum charge applies"
+    )]
+    #[test_case(
+        "minimum",
+        r#""golem.com.pricing.model.minimum.coeffs": [0.1, 0.01, 1.0],
+           "golem.com.pricing.model.minimum.charge": 5.0"#,
+        &[40.0, 100.0],
+        BigDecimal::from_str("6").unwrap();
+        "Minimum charge exceeded"
+    )]
+    #[test_case(
+        "flat",
+        r#""golem.com.pricing.model.flat.fee": 2.5"#,
+        &[10.0, 100.0],
+        BigDecimal::from_str("2.5").unwrap();
+        "Flat fee ignores usage"
+    )]
+    fn test_payment_model_cost(model: &str, params: &str, usage: &[f64], expected: BigDecimal) {
+        let agreement = AgreementView::try_from(
+            try_from_json(template(
+                MODEL_AGREEMENT_TEMPLATE,
+                &[("model", model.to_string()), ("params", params.to_string())],
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        let payment = PaymentDescription::new(&agreement).unwrap();
+        let pricing = PaymentModelFactory::create(&payment).unwrap();
+
+        assert_eq!(pricing.expected_usage_len(), 2);
+        assert_eq!(pricing.compute_cost(usage).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_time_of_day_windows() {
+        let agreement = AgreementView::try_from(
+            try_from_json(template(
+                MODEL_AGREEMENT_TEMPLATE,
+                &[
+                    ("model", "time-of-day".to_string()),
+                    (
+                        "params",
+                        r#""golem.com.pricing.model.time-of-day.coeffs": [0.1, 0.01, 1.0],
+                           "golem.com.pricing.model.time-of-day.peak-hours": [8, 20],
+                           "golem.com.pricing.model.time-of-day.peak-multiplier": 2.0"#
+                            .to_string(),
+                    ),
+                ],
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        let payment = PaymentDescription::new(&agreement).unwrap();
+        let pricing = TimeOfDayPricing::new(&payment).unwrap();
+        let at = |hour: u32| Utc.with_ymd_and_hms(2022, 5, 22, hour, 30, 0).unwrap();
+
+        // Initial price and usage reported in peak hours are doubled.
+        assert_eq!(
+            pricing.charge("peak", &[10.0, 100.0], at(10)).unwrap(),
+            BigDecimal::from_str("6").unwrap()
+        );
+        // Only usage increase is charged off-peak with regular prices.
+        assert_eq!(
+            pricing.charge("peak", &[20.0, 200.0], at(21)).unwrap(),
+            BigDecimal::from_str("8").unwrap()
+        );
+        // Unchanged usage doesn't change the cost.
+        assert_eq!(
+            pricing.charge("peak", &[20.0, 200.0], at(9)).unwrap(),
+            BigDecimal::from_str("8").unwrap()
+        );
+
+        // Activities are charged separately.
+        assert_eq!(
+            pricing.charge("off-peak", &[10.0, 100.0], at(22)).unwrap(),
+            BigDecimal::from_str("3").unwrap()
+        );
+        assert_eq!(
+            pricing.charge("off-peak", &[20.0, 100.0], at(12)).unwrap(),
+            BigDecimal::from_str("5").unwrap()
+        );
+
+        // Finished activities are forgotten.
+        pricing.activity_finished("peak");
+        pricing.activity_finished("off-peak");
+        assert!(pricing.activities.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_peak_hours() {
+        assert!(is_peak_hour(8, 8, 20));
+        assert!(!is_peak_hour(20, 8, 20));
+        assert!(is_peak_hour(23, 22, 6));
+        assert!(is_peak_hour(0, 22, 6));
+        assert!(!is_peak_hour(12, 22, 6));
+    }
+
+    #[test]
+    fn test_pricing_offer_requires_params() {
+        let mut preset = Preset {
+            pricing_model: "tiered".to_string(),
+            ..Default::default()
+        };
+        assert!(pricing_offer(&preset).is_err());
+
+        preset.pricing_params = PricingParams {
+            tier_thresholds: [("golem.usage.cpu_sec".to_string(), 3600.0)].into(),
+            tier_coeffs: [("golem.usage.cpu_sec".to_string(), 0.5)].into(),
+            ..Default::default()
+        };
+        assert!(pricing_offer(&preset).is_ok());
+
+        preset.pricing_model = "minimum".to_string();
+        assert!(pricing_offer(&preset).is_err());
+
+        preset.pricing_model = "unknown".to_string();
+        assert!(pricing_offer(&preset).is_err());
+    }
 }
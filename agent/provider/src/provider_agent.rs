@@ -6,6 +6,7 @@ use ya_core_model::NodeId;
 
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 use tokio_stream::wrappers::WatchStream;
@@ -24,13 +25,16 @@ use crate::execution::{ExeUnitDesc, GetExeUnit, GetOfferTemplates, TaskRunner, U
 use crate::hardware;
 use crate::market::provider_market::{OfferKind, Shutdown as MarketShutdown, Unsubscribe};
 use crate::market::{CreateOffer, Preset, PresetManager, ProviderMarket};
-use crate::payments::{AccountView, LinearPricingOffer, Payments, PricingOffer};
+use crate::payments::{pricing_offer, AccountView, Payments, PricingOffer};
+use crate::reputation::ReputationStore;
 use crate::rules::RulesManager;
 use crate::startup_config::{FileMonitor, NodeConfig, PaymentPlatform, ProviderConfig, RunConfig};
 use crate::tasks::task_manager::{
     InitializeTaskManager, Shutdown as TaskManagerShutdown, TaskManager,
 };
 
+const REPUTATION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
 struct GlobalsManager {
     state: Arc<Mutex<GlobalsState>>,
     monitor: Option<FileMonitor>,
@@ -68,6 +72,8 @@ impl GlobalsManager {
 #[derive(Clone)]
 pub struct AgentNegotiatorsConfig {
     pub rules_manager: RulesManager,
+    pub reputation: ReputationStore,
+    pub hardware: hardware::Allocator,
 }
 
 pub struct ProviderAgent {
@@ -84,6 +90,7 @@ pub struct ProviderAgent {
     keystore_monitor: FileMonitor,
     whitelist_monitor: FileMonitor,
     net_api: NetApi,
+    reputation: ReputationStore,
 }
 
 impl ProviderAgent {
@@ -146,18 +153,32 @@ impl ProviderAgent {
 
         let networks = args.node.account.networks.clone();
         for n in networks.iter() {
-            let net_color = match n.network {
-                NetworkName::Mainnet => yansi::Color::Magenta,
-                NetworkName::Polygon => yansi::Color::Magenta,
-                NetworkName::Rinkeby => yansi::Color::Cyan,
-                NetworkName::Mumbai => yansi::Color::Cyan,
-                NetworkName::Goerli => yansi::Color::Cyan,
-                NetworkName::Holesky => yansi::Color::Cyan,
+            let net_color = match NetworkName::from_str(&n.network) {
+                Ok(NetworkName::Mainnet) => yansi::Color::Magenta,
+                Ok(NetworkName::Polygon) => yansi::Color::Magenta,
+                Ok(NetworkName::Rinkeby) => yansi::Color::Cyan,
+                Ok(NetworkName::Mumbai) => yansi::Color::Cyan,
+                Ok(NetworkName::Goerli) => yansi::Color::Cyan,
+                Ok(NetworkName::Holesky) => yansi::Color::Cyan,
+                Ok(NetworkName::Dev) => yansi::Color::Cyan,
                 _ => yansi::Color::Red,
             };
             log::info!("Using payment network: {}", net_color.paint(&n.network));
         }
 
+        // Only yagna knows which networks were added to payment driver config.
+        let provider_accounts = api.payment.get_provider_accounts().await?;
+        for n in networks.iter().filter(|n| n.is_custom()) {
+            let platform = n.platform();
+            if !provider_accounts.iter().any(|a| a.platform == platform) {
+                anyhow::bail!(
+                    "Payment platform {platform} is not served by any payment driver. \
+                    Add network {} to payment driver config and initialize receiver account for it",
+                    n.network
+                );
+            }
+        }
+
         let mut globals = GlobalsManager::try_new(&config.globals_file, args.node)?;
         globals.spawn_monitor(&config.globals_file)?;
         let mut presets = PresetManager::load_or_create(&config.presets_file)?;
@@ -167,10 +188,25 @@ impl ProviderAgent {
         let (rulestore_monitor, keystore_monitor, whitelist_monitor) =
             rules_manager.spawn_file_monitors()?;
 
-        let agent_negotiators_cfg = AgentNegotiatorsConfig { rules_manager };
+        let reputation = ReputationStore::load_or_create(&config.reputation_file)?;
+        reputation.spawn_saver(REPUTATION_SAVE_INTERVAL);
 
-        let market = ProviderMarket::new(api.market, args.market, agent_negotiators_cfg).start();
-        let payments = Payments::new(api.activity.clone(), api.payment, args.payment).start();
+        let agent_negotiators_cfg = AgentNegotiatorsConfig {
+            rules_manager,
+            reputation: reputation.clone(),
+            hardware: hardware.allocator(),
+        };
+
+        let market = ProviderMarket::new(api.market, args.market, agent_negotiators_cfg)
+            .await?
+            .start();
+        let payments = Payments::new(
+            api.activity.clone(),
+            api.payment,
+            args.payment,
+            reputation.clone(),
+        )
+        .start();
         let runner = TaskRunner::new(api.activity, args.runner, registry, data_dir)?.start();
         let task_manager =
             TaskManager::new(market.clone(), runner.clone(), payments, args.tasks)?.start();
@@ -190,6 +226,7 @@ impl ProviderAgent {
             keystore_monitor,
             whitelist_monitor,
             net_api,
+            reputation,
         })
     }
 
@@ -248,10 +285,7 @@ impl ProviderAgent {
         mut offer: OfferTemplate,
         exeunit_desc: ExeUnitDesc,
     ) -> anyhow::Result<CreateOffer> {
-        let pricing_model: Box<dyn PricingOffer> = match preset.pricing_model.as_str() {
-            "linear" => Box::<LinearPricingOffer>::default(),
-            other => return Err(anyhow!("Unsupported pricing model: {}", other)),
-        };
+        let pricing_model = pricing_offer(&preset)?;
         let (initial_price, prices) = get_prices(pricing_model.as_ref(), &preset, &offer)?;
         offer.set_property("golem.com.usage.vector", get_usage_vector_value(&prices));
         offer.set_property(
@@ -484,10 +518,14 @@ impl Handler<Shutdown> for ProviderAgent {
         self.keystore_monitor.stop();
         self.rulestore_monitor.stop();
         self.whitelist_monitor.stop();
+        let reputation = self.reputation.clone();
 
         async move {
             market.send(MarketShutdown).await??;
             tasks.send(TaskManagerShutdown {}).await??;
+            if let Err(e) = reputation.flush() {
+                log::warn!("Failed to save reputation history: {e}");
+            }
             log_handler.shutdown();
             Ok(())
         }
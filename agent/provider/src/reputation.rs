@@ -0,0 +1,282 @@
+//! Requestor reputation built from local payment and agreement history.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use ya_client_model::NodeId;
+
+const INVOICE_PAID_ON_TIME_WEIGHT: f64 = 1.0;
+const INVOICE_PAID_LATE_WEIGHT: f64 = 0.5;
+const DEBIT_NOTE_ACCEPTED_WEIGHT: f64 = 0.1;
+const AGREEMENT_BROKEN_WEIGHT: f64 = 2.0;
+const PAYMENT_OVERDUE_WEIGHT: f64 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReputationEvent {
+    InvoicePaidOnTime,
+    InvoicePaidLate,
+    DebitNoteAccepted,
+    /// Agreement broken because of Requestor's fault.
+    AgreementBroken,
+    PaymentOverdue,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RequestorHistory {
+    pub invoices_paid_on_time: u64,
+    pub invoices_paid_late: u64,
+    pub debit_notes_accepted: u64,
+    pub agreements_broken: u64,
+    pub payments_overdue: u64,
+    pub last_event: Option<DateTime<Utc>>,
+}
+
+impl RequestorHistory {
+    /// Score in range (0, 1). Requestors without history are scored 0.5.
+    pub fn score(&self) -> f64 {
+        let positive = INVOICE_PAID_ON_TIME_WEIGHT * self.invoices_paid_on_time as f64
+            + DEBIT_NOTE_ACCEPTED_WEIGHT * self.debit_notes_accepted as f64;
+        let negative = INVOICE_PAID_LATE_WEIGHT * self.invoices_paid_late as f64
+            + AGREEMENT_BROKEN_WEIGHT * self.agreements_broken as f64
+            + PAYMENT_OVERDUE_WEIGHT * self.payments_overdue as f64;
+        (positive + 1.) / (positive + negative + 2.)
+    }
+
+    /// Number of finished payments and agreements. Debit notes are not included,
+    /// since a single agreement produces many of them.
+    pub fn outcomes(&self) -> u64 {
+        self.invoices_paid_on_time
+            + self.invoices_paid_late
+            + self.agreements_broken
+            + self.payments_overdue
+    }
+
+    fn record(&mut self, event: ReputationEvent) {
+        match event {
+            ReputationEvent::InvoicePaidOnTime => self.invoices_paid_on_time += 1,
+            ReputationEvent::InvoicePaidLate => self.invoices_paid_late += 1,
+            ReputationEvent::DebitNoteAccepted => self.debit_notes_accepted += 1,
+            ReputationEvent::AgreementBroken => self.agreements_broken += 1,
+            ReputationEvent::PaymentOverdue => self.payments_overdue += 1,
+        }
+        self.last_event = Some(Utc::now());
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReputationHistory {
+    pub requestors: HashMap<NodeId, RequestorHistory>,
+}
+
+/// Requestors history shared between Payments and negotiators.
+/// Store without a path is kept in memory only.
+///
+/// Recording only marks history as changed. It is written to disk by the task
+/// started with `spawn_saver` and by an explicit `flush` on shutdown.
+#[derive(Clone, Debug, Default)]
+pub struct ReputationStore {
+    path: Option<PathBuf>,
+    history: Arc<RwLock<ReputationHistory>>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl ReputationStore {
+    pub fn load_or_create(path: &Path) -> anyhow::Result<Self> {
+        let store = Self {
+            path: Some(path.to_path_buf()),
+            history: Arc::new(RwLock::new(read_history(path)?)),
+            dirty: Default::default(),
+        };
+        store.save()?;
+        Ok(store)
+    }
+
+    /// Loads history without ever writing it back.
+    pub fn load_read_only(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            path: None,
+            history: Arc::new(RwLock::new(read_history(path)?)),
+            dirty: Default::default(),
+        })
+    }
+
+    /// Writes history to a temporary file first, so an interrupted save
+    /// never leaves a truncated history behind.
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(path) = &self.path {
+            let tmp_path = path.with_extension("json.tmp");
+            {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(&tmp_path)?;
+                let mut writer = BufWriter::new(file);
+                serde_json::to_writer_pretty(&mut writer, &*self.history.read().unwrap())?;
+                writer.flush()?;
+                writer.get_ref().sync_all()?;
+            }
+            std::fs::rename(&tmp_path, path)?;
+        }
+        Ok(())
+    }
+
+    /// Saves history if anything was recorded since the last save.
+    pub fn flush(&self) -> anyhow::Result<()> {
+        if self.dirty.swap(false, Ordering::AcqRel) {
+            if let Err(e) = self.save() {
+                self.dirty.store(true, Ordering::Release);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Periodically flushes history on the blocking thread pool, so recording
+    /// events never waits for the disk.
+    pub fn spawn_saver(&self, interval: Duration) {
+        if self.path.is_none() {
+            return;
+        }
+
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                let store = store.clone();
+                match tokio::task::spawn_blocking(move || store.flush()).await {
+                    Ok(Err(e)) => log::warn!("Failed to save reputation history: {e}"),
+                    Err(e) => log::warn!("Reputation history saver failed: {e}"),
+                    Ok(Ok(())) => (),
+                }
+            }
+        });
+    }
+
+    pub fn record(&self, requestor_id: NodeId, event: ReputationEvent) {
+        log::debug!("Reputation of Requestor [{requestor_id}]: {event:?}");
+        self.history
+            .write()
+            .unwrap()
+            .requestors
+            .entry(requestor_id)
+            .or_default()
+            .record(event);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    pub fn get(&self, requestor_id: &NodeId) -> Option<RequestorHistory> {
+        self.history
+            .read()
+            .unwrap()
+            .requestors
+            .get(requestor_id)
+            .cloned()
+    }
+
+    pub fn list(&self) -> Vec<(NodeId, RequestorHistory)> {
+        let history = self.history.read().unwrap();
+        let mut requestors = history
+            .requestors
+            .iter()
+            .map(|(id, history)| (*id, history.clone()))
+            .collect::<Vec<_>>();
+        requestors.sort_by(|(_, a), (_, b)| a.score().total_cmp(&b.score()));
+        requestors
+    }
+}
+
+/// Corrupted history is moved aside and replaced with an empty one, since
+/// losing reputation is better than not starting the Provider at all.
+fn read_history(path: &Path) -> anyhow::Result<ReputationHistory> {
+    if !path.exists() {
+        return Ok(Default::default());
+    }
+
+    log::debug!("Loading reputation history from: {}", path.display());
+    let file = OpenOptions::new().read(true).open(path)?;
+    match serde_json::from_reader(BufReader::new(file)) {
+        Ok(history) => Ok(history),
+        Err(e) => {
+            let corrupted_path = path.with_extension("json.corrupted");
+            log::warn!(
+                "Reputation history {} is corrupted ({e}). Starting with empty history, previous one moved to {}",
+                path.display(),
+                corrupted_path.display()
+            );
+            std::fs::rename(path, &corrupted_path)?;
+            Ok(Default::default())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_follows_history() {
+        let store = ReputationStore::default();
+        let good: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let bad: NodeId = "0x0000000000000000000000000000000000000002"
+            .parse()
+            .unwrap();
+
+        assert!(store.get(&good).is_none());
+        assert_eq!(RequestorHistory::default().score(), 0.5);
+
+        for _ in 0..3 {
+            store.record(good, ReputationEvent::InvoicePaidOnTime);
+            store.record(bad, ReputationEvent::InvoicePaidOnTime);
+        }
+        store.record(bad, ReputationEvent::PaymentOverdue);
+        store.record(bad, ReputationEvent::AgreementBroken);
+
+        let good_score = store.get(&good).unwrap().score();
+        let bad_score = store.get(&bad).unwrap().score();
+        assert!(good_score > 0.75);
+        assert!(bad_score < 0.5);
+        assert_eq!(store.list()[0].0, bad);
+    }
+
+    #[test]
+    fn history_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reputation.json");
+        let requestor: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+
+        let store = ReputationStore::load_or_create(&path).unwrap();
+        store.record(requestor, ReputationEvent::InvoicePaidOnTime);
+        assert_eq!(
+            ReputationStore::load_read_only(&path).unwrap().list().len(),
+            0
+        );
+        store.flush().unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        // Read only store doesn't write its records.
+        let read_only = ReputationStore::load_read_only(&path).unwrap();
+        assert_eq!(read_only.get(&requestor).unwrap().invoices_paid_on_time, 1);
+        read_only.record(requestor, ReputationEvent::InvoicePaidOnTime);
+        read_only.flush().unwrap();
+        let reloaded = ReputationStore::load_or_create(&path).unwrap();
+        assert_eq!(reloaded.get(&requestor).unwrap().invoices_paid_on_time, 1);
+
+        std::fs::write(&path, "{ \"requestors\": ").unwrap();
+        let store = ReputationStore::load_or_create(&path).unwrap();
+        assert!(store.list().is_empty());
+        assert!(path.with_extension("json.corrupted").exists());
+    }
+}
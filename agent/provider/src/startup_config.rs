@@ -23,6 +23,7 @@ use crate::cli::keystore::KeystoreConfig;
 use crate::cli::pre_install::PreInstallConfig;
 pub use crate::cli::preset::PresetsConfig;
 use crate::cli::profile::ProfileConfig;
+use crate::cli::reputation::ReputationCommand;
 use crate::cli::rule::RuleCommand;
 use crate::cli::whitelist::WhitelistConfig;
 pub(crate) use crate::config::globals::GLOBALS_JSON;
@@ -40,6 +41,8 @@ pub(crate) const RULES_JSON: &str = "rules.json";
 pub(crate) const PRESETS_JSON: &str = "presets.json";
 pub(crate) const HARDWARE_JSON: &str = "hardware.json";
 pub(crate) const CERT_DIR: &str = "cert-dir";
+pub(crate) const DNS_JSON: &str = "dns.json";
+pub(crate) const REPUTATION_JSON: &str = "reputation.json";
 
 const DATA_DIR_ENV: &str = "DATA_DIR";
 
@@ -88,6 +91,8 @@ pub struct ProviderConfig {
     pub hardware_file: PathBuf,
     #[structopt(skip = RULES_JSON)]
     pub rules_file: PathBuf,
+    #[structopt(skip = REPUTATION_JSON)]
+    pub reputation_file: PathBuf,
     /// Max number of available CPU cores
     #[structopt(
         long,
@@ -146,18 +151,18 @@ impl ProviderConfig {
 #[derive(Clone, Debug)]
 pub struct PaymentPlatform {
     pub driver: String,
-    pub network: NetworkName,
+    pub network: String,
+    pub token: String,
 }
 
 impl PaymentPlatform {
     pub fn platform(&self) -> String {
-        format!(
-            "{}-{}-{}",
-            self.driver,
-            self.network,
-            self.network.get_token()
-        )
-        .to_lowercase()
+        format!("{}-{}-{}", self.driver, self.network, self.token).to_lowercase()
+    }
+
+    /// Network unknown to yagna, which payment driver can serve when it's added to driver config.
+    pub fn is_custom(&self) -> bool {
+        NetworkName::from_str(&self.network).is_err()
     }
 }
 
@@ -168,7 +173,8 @@ impl FromStr for PaymentPlatform {
         let value = if let Ok(network_name) = NetworkName::from_str(arg) {
             PaymentPlatform {
                 driver: DEFAULT_PAYMENT_DRIVER.to_string(),
-                network: network_name,
+                network: network_name.to_string(),
+                token: network_name.get_token().to_string(),
             }
         } else {
             let err = "Not a valid network or a platform";
@@ -182,14 +188,20 @@ impl FromStr for PaymentPlatform {
             if !DriverName::VARIANTS.contains(&parts[0]) {
                 return Err(err.to_string());
             }
-            let network_name = NetworkName::from_str(parts[1]).map_err(|_| err.to_string())?;
-            if parts[2] != network_name.get_token() {
-                return Err(err.to_string());
+            match NetworkName::from_str(parts[1]) {
+                Ok(network_name) if !parts[2].eq_ignore_ascii_case(network_name.get_token()) => {
+                    return Err(err.to_string());
+                }
+                Ok(_) => (),
+                // Custom networks are checked against networks served by payment drivers on startup.
+                Err(_) if is_valid_name(parts[1]) && is_valid_name(parts[2]) => (),
+                Err(_) => return Err(err.to_string()),
             }
 
             PaymentPlatform {
                 driver: parts[0].to_string(),
-                network: network_name,
+                network: parts[1].to_string(),
+                token: parts[2].to_string(),
             }
         };
 
@@ -197,6 +209,13 @@ impl FromStr for PaymentPlatform {
     }
 }
 
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
 #[derive(StructOpt, Clone, Debug, derive_more::Display)]
 #[display(
     fmt = "{}Networks: {:?}",
@@ -212,7 +231,7 @@ pub struct ReceiverAccount {
         long = "payment-network",
         env = "YA_PAYMENT_NETWORK",
         default_value = NetworkName::Mainnet.into(),
-        help = "Specify platforms to collect funds, e.g. erc20-mainnet-glm. Network name can be passed as well, in which case the default driver will be used"
+        help = "Specify platforms to collect funds, e.g. erc20-mainnet-glm. Network name can be passed as well, in which case the default driver will be used. Networks added to payment driver config have to be passed as a platform"
     )]
     pub networks: Vec<PaymentPlatform>,
 }
@@ -308,6 +327,8 @@ pub enum Commands {
     Clean(CleanConfig),
     /// Manage Rule config
     Rule(RuleCommand),
+    /// Show Requestors reputation scores
+    Reputation(ReputationCommand),
 }
 
 #[derive(Debug)]
@@ -473,3 +494,46 @@ fn default_plugins() -> PathBuf {
         .map(|p| p.join("ya-*.json"))
         .unwrap_or_else(|| "/usr/lib/yagna/plugins/ya-*.json".into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_payment_platform() {
+        let platform = PaymentPlatform::from_str("holesky").unwrap();
+        assert_eq!(platform.platform(), "erc20-holesky-tglm");
+        assert!(!platform.is_custom());
+
+        let platform = PaymentPlatform::from_str("erc20-polygon-glm").unwrap();
+        assert_eq!(platform.network, "polygon");
+        assert_eq!(platform.platform(), "erc20-polygon-glm");
+
+        assert!(PaymentPlatform::from_str("erc20-polygon-tglm").is_err());
+    }
+
+    #[test]
+    fn custom_payment_platform() {
+        let platform = PaymentPlatform::from_str("erc20-arbitrum_one-glm").unwrap();
+        assert_eq!(platform.driver, "erc20");
+        assert_eq!(platform.network, "arbitrum_one");
+        assert_eq!(platform.token, "glm");
+        assert_eq!(platform.platform(), "erc20-arbitrum_one-glm");
+        assert!(platform.is_custom());
+
+        for invalid in [
+            "arbitrum",
+            "erc20-arbitrum",
+            "erc20-Arbitrum-glm",
+            "erc20-arbitrum-GLM",
+            "erc20-arbitrum-one-glm",
+            "erc20--glm",
+            "zksync-arbitrum-glm",
+        ] {
+            assert!(
+                PaymentPlatform::from_str(invalid).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+}
@@ -66,7 +66,11 @@ fn allowonly_negotiator_rule_disabled(
     setup_certificates_rules(rules_manager.allow_only(), allow_certs);
     setup_identity_rules(rules_manager.allow_only(), allow_ids);
 
-    let mut negotiator = AllowOnly::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = AllowOnly::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
@@ -158,7 +162,11 @@ fn allowonly_negotiator_rule_rejections(
     setup_certificates_rules(rules_manager.allow_only(), allow_certs);
     setup_identity_rules(rules_manager.allow_only(), allow_ids);
 
-    let mut negotiator = AllowOnly::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = AllowOnly::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
@@ -233,7 +241,11 @@ fn allowonly_negotiator_rule_accepts(
     setup_certificates_rules(rules_manager.allow_only(), allow_certs);
     setup_identity_rules(rules_manager.allow_only(), allow_ids);
 
-    let mut negotiator = AllowOnly::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = AllowOnly::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
@@ -57,7 +57,11 @@ fn blacklist_negotiator_rule_disabled(
     setup_certificates_rules(rules_manager.blacklist(), blacklist_certs);
     setup_identity_rules(rules_manager.blacklist(), blacklist_ids);
 
-    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
@@ -95,7 +99,11 @@ fn blacklist_negotiator_id_blacklisted(node_descriptor: Option<&str>, expected_e
         .add_identity_rule(NodeId::from_str("0x0000000000000000000000000000000000000000").unwrap())
         .unwrap();
 
-    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
@@ -157,7 +165,11 @@ fn blacklist_negotiator_certificate_blacklisted(
     rules_manager.blacklist().enable().unwrap();
     setup_certificates_rules(rules_manager.blacklist(), blacklist_certs);
 
-    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
@@ -202,7 +214,11 @@ fn blacklist_negotiator_pass_node(
     setup_certificates_rules(rules_manager.blacklist(), blacklist_certs);
     setup_identity_rules(rules_manager.blacklist(), blacklist_ids);
 
-    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig { rules_manager });
+    let mut negotiator = Blacklist::new(AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    });
     let demand = create_demand(load_node_descriptor(node_descriptor));
 
     let result = negotiator
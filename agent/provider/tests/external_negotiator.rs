@@ -0,0 +1,87 @@
+#![cfg(unix)]
+mod utils;
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ya_provider::market::negotiator::external::ExternalNegotiator;
+use ya_provider::market::negotiator::factory::ExternalNegotiatorConfig;
+use ya_provider::market::negotiator::{
+    AgreementResult, AgreementView, NegotiationResult, NegotiatorComponent,
+};
+
+use crate::utils::rules::{create_demand, create_offer};
+
+/// Rejects all Proposals and acknowledges all notifications.
+static REJECTING_PLUGIN: &str = r#"#!/bin/sh
+while read -r line; do
+    id=$(echo "$line" | sed 's/^{"jsonrpc":"2.0","id":\([0-9]*\).*/\1/')
+    case "$line" in
+        *'"method":"negotiate_step"'*)
+            echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"Reject\":{\"message\":\"Not today\",\"is_final\":false}}}" ;;
+        *)
+            echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":null}" ;;
+    esac
+done
+"#;
+
+/// Exits after reading the first request.
+static EXITING_PLUGIN: &str = r#"#!/bin/sh
+read -r line
+exit 1
+"#;
+
+fn write_plugin(dir: &Path, script: &str) -> PathBuf {
+    let path = dir.join("plugin.sh");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn config(path: &Path) -> ExternalNegotiatorConfig {
+    ExternalNegotiatorConfig {
+        negotiator_plugin: Some(path.to_path_buf()),
+        negotiator_plugin_timeout: Duration::from_secs(5),
+    }
+}
+
+#[test]
+fn external_negotiator_forwards_calls() {
+    let dir = tempdir::TempDir::new("external-negotiator").unwrap();
+    let path = write_plugin(dir.path(), REJECTING_PLUGIN);
+    let mut negotiator = ExternalNegotiator::new(path.clone(), &config(&path)).unwrap();
+
+    let demand = create_demand(serde_json::json!({ "golem.srv.comp.expiration": 0 }));
+    let result = negotiator.negotiate_step(&demand, create_offer()).unwrap();
+    assert_eq!(
+        result,
+        NegotiationResult::Reject {
+            message: "Not today".to_string(),
+            is_final: false,
+        }
+    );
+
+    let agreement = AgreementView {
+        json: serde_json::json!({}),
+        id: "agreement".to_string(),
+    };
+    negotiator.on_agreement_approved(&agreement).unwrap();
+    negotiator
+        .on_agreement_terminated("agreement", &AgreementResult::ClosedByRequestor)
+        .unwrap();
+}
+
+#[test]
+fn external_negotiator_restarts_plugin() {
+    let dir = tempdir::TempDir::new("external-negotiator").unwrap();
+    let path = write_plugin(dir.path(), EXITING_PLUGIN);
+    let mut negotiator = ExternalNegotiator::new(path.clone(), &config(&path)).unwrap();
+
+    let demand = create_demand(serde_json::json!({}));
+    assert!(negotiator.negotiate_step(&demand, create_offer()).is_err());
+
+    write_plugin(dir.path(), REJECTING_PLUGIN);
+    let result = negotiator.negotiate_step(&demand, create_offer()).unwrap();
+    assert!(matches!(result, NegotiationResult::Reject { .. }));
+}
@@ -70,7 +70,11 @@ fn manifest_negotiator_test_accepted_because_of_no_payload() {
             .expect("Can't load RulesManager");
 
     let config = create_manifest_signature_validating_policy_config();
-    let negotiator_cfg = AgentNegotiatorsConfig { rules_manager };
+    let negotiator_cfg = AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    };
     let mut manifest_negotiator = ManifestSignature::new(&config, negotiator_cfg);
     // Current implementation does not verify content of certificate permissions incoming in demand.
 
@@ -571,7 +575,11 @@ fn manifest_negotiator_test_encoded_manifest_sign_and_cert_and_cert_dir_files(
             .expect("Can't load RulesManager");
 
     let config = create_manifest_signature_validating_policy_config();
-    let negotiator_cfg = AgentNegotiatorsConfig { rules_manager };
+    let negotiator_cfg = AgentNegotiatorsConfig {
+        rules_manager,
+        reputation: Default::default(),
+        hardware: Default::default(),
+    };
     let mut manifest_negotiator = ManifestSignature::new(&config, negotiator_cfg);
     // Current implementation does not verify content of certificate permissions incoming in demand.
 
@@ -139,7 +139,7 @@ mod common {
             .timeout(timeout_margin(query.timeout))
             .await???;
 
-        set_persisted_state(&db, &path.activity_id, state)
+        set_persisted_state(&db, &path.activity_id, id.identity, state)
             .await
             .map(web::Json)
     }
@@ -11,7 +11,8 @@ use ya_client_model::{
     market::{Agreement, AgreementListEntry, Role},
     NodeId,
 };
-use ya_core_model::{activity, market};
+use ya_core_model::event::{Event, EventCategory};
+use ya_core_model::{activity, event, market};
 use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
@@ -101,15 +102,34 @@ pub(crate) async fn get_persisted_state(
     Ok(db.as_dao::<ActivityStateDao>().get(activity_id).await?)
 }
 
+/// Persists activity state and publishes it to identity `owner_id`, which is the Provider
+/// or the Requestor of the activity, depending on the side state is persisted on.
 pub(crate) async fn set_persisted_state(
     db: &DbExecutor,
     activity_id: &str,
+    owner_id: NodeId,
     activity_state: ActivityState,
 ) -> Result<ActivityState, Error> {
-    Ok(db
+    let state = db
         .as_dao::<ActivityStateDao>()
         .set(activity_id, activity_state)
-        .await?)
+        .await?;
+
+    let state_event = Event::new(
+        EventCategory::Activity,
+        "ActivityStateChangedEvent",
+        Some(owner_id),
+        serde_json::json!({
+            "activityId": activity_id,
+            "state": state,
+        }),
+    );
+    tokio::task::spawn_local(async move {
+        if let Err(e) = bus::service(event::BUS_ID).send(state_event).await {
+            log::trace!("Failed to publish activity state event: {e}");
+        }
+    });
+    Ok(state)
 }
 
 pub(crate) fn agreement_provider_service(
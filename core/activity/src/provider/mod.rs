@@ -28,7 +28,7 @@ async fn set_activity_state(
     log::trace!("set_activity_state_web {:?}", state);
     authorize_activity_executor(&db, id.identity, &path.activity_id, Role::Provider).await?;
 
-    set_persisted_state(&db, &path.activity_id, state.into_inner())
+    set_persisted_state(&db, &path.activity_id, id.identity, state.into_inner())
         .await
         .map(|_| web::Json(()))
 }
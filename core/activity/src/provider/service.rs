@@ -434,7 +434,9 @@ async fn monitor_activity(
                     let _ = tracker
                         .update_state(activity_id.clone(), State::Unresponsive)
                         .await;
-                    if let Err(e) = set_persisted_state(&db, &activity_id, new_state).await {
+                    if let Err(e) =
+                        set_persisted_state(&db, &activity_id, provider_id, new_state).await
+                    {
                         log::error!("cannot update activity {} state: {}", activity_id, e);
                     }
                     counter!("activity.provider.unresponsive", 1);
@@ -468,7 +470,7 @@ async fn monitor_activity(
                 let _ = tracker
                     .update_state(activity_id.clone(), state.state.0)
                     .await;
-                if let Err(e) = set_persisted_state(&db, &activity_id, state).await {
+                if let Err(e) = set_persisted_state(&db, &activity_id, provider_id, state).await {
                     log::error!("cannot update activity {} state: {}", activity_id, e);
                 }
                 counter!("activity.provider.responsive-again", 1);
@@ -543,7 +545,8 @@ mod local {
         let _ = tracker
             .update_state(msg.activity_id.clone(), msg.state.state.0)
             .await;
-        set_persisted_state(&db, &msg.activity_id, msg.state).await?;
+        let agreement = get_activity_agreement(&db, &msg.activity_id, Role::Provider).await?;
+        set_persisted_state(&db, &msg.activity_id, *agreement.provider_id(), msg.state).await?;
         Ok(())
     }
 
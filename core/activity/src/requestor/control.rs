@@ -145,6 +145,7 @@ async fn destroy_activity(
     set_persisted_state(
         &db,
         &path.activity_id,
+        id.identity,
         ActivityState {
             state: State::Terminated.into(),
             reason: None,
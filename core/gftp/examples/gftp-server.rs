@@ -70,7 +70,15 @@ async fn send(
     let msg = RpcMessage::request(Some(&RpcId::Int(id)), req);
     stdin.write_message(msg).await?;
 
-    let res = reader.read_message().await?;
+    let res = loop {
+        let res = reader.read_message().await?;
+        match res.body {
+            RpcBody::Notification { notification } => {
+                log::info!("notification: {:?}", notification)
+            }
+            _ => break res,
+        }
+    };
     match res.id {
         Some(RpcId::Int(v)) => {
             if v != id {
@@ -82,7 +90,9 @@ async fn send(
 
     match res.body {
         RpcBody::Error { error } => Err(anyhow!("Request {:?} failed: {:?}", id, error)),
-        RpcBody::Request { .. } => Err(anyhow!("Unexpected message: {:?}", res)),
+        RpcBody::Request { .. } | RpcBody::Notification { .. } => {
+            Err(anyhow!("Unexpected message: {:?}", res))
+        }
         RpcBody::Result { result } => Ok(result),
     }
 }
@@ -190,6 +200,11 @@ async fn main() -> Result<()> {
     let req = RpcRequest::Download {
         url,
         output_file: output_file.clone(),
+        recursive: false,
+        concurrency: None,
+        resume: true,
+        verify: true,
+        progress: true,
     };
     send(&mut stdin, &mut reader, req).await?;
 
@@ -213,6 +228,8 @@ async fn main() -> Result<()> {
     let req = RpcRequest::Upload {
         url,
         file: args.share,
+        resume: true,
+        progress: true,
     };
     send(&mut stdin, &mut reader, req).await?;
 
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use env_logger::{Builder, Env, Target};
 use gftp::rpc::{RpcBody, RpcId, RpcMessage, RpcRequest, RpcResult, RpcStatusResult};
+use gftp::TransferOptions;
 
 use structopt::{clap, StructOpt};
 use tokio::io;
@@ -81,8 +82,37 @@ async fn execute_inner(id: Option<&RpcId>, request: RpcRequest, verbose: bool) -
             .print(verbose);
             ExecMode::OneShot
         }
-        RpcRequest::Download { url, output_file } => {
-            gftp::download_from_url(&url, &output_file).await?;
+        RpcRequest::Download {
+            url,
+            output_file,
+            recursive,
+            concurrency,
+            resume,
+            verify,
+            progress,
+        } => {
+            let options = TransferOptions { resume, verify };
+            let on_progress = |p| {
+                if progress {
+                    RpcMessage::progress(id, output_file.clone(), url.clone(), p).print(verbose);
+                }
+            };
+            match recursive {
+                true => {
+                    let concurrency = concurrency.unwrap_or(1);
+                    gftp::download_dir_from_url_with(
+                        &url,
+                        &output_file,
+                        &options,
+                        concurrency,
+                        on_progress,
+                    )
+                    .await?
+                }
+                false => {
+                    gftp::download_from_url_with(&url, &output_file, &options, on_progress).await?
+                }
+            }
             RpcMessage::file_response(id, output_file, url).print(verbose);
             ExecMode::OneShot
         }
@@ -91,8 +121,22 @@ async fn execute_inner(id: Option<&RpcId>, request: RpcRequest, verbose: bool) -
             RpcMessage::file_response(id, output_file, url).print(verbose);
             ExecMode::Service
         }
-        RpcRequest::Upload { file, url } => {
-            gftp::upload_file(&file, &url).await?;
+        RpcRequest::Upload {
+            file,
+            url,
+            resume,
+            progress,
+        } => {
+            let options = TransferOptions {
+                resume,
+                ..Default::default()
+            };
+            gftp::upload_file_with(&file, &url, &options, |p| {
+                if progress {
+                    RpcMessage::progress(id, file.clone(), url.clone(), p).print(verbose);
+                }
+            })
+            .await?;
             RpcMessage::file_response(id, file, url).print(verbose);
             ExecMode::OneShot
         }
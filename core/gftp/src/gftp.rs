@@ -1,12 +1,15 @@
-use anyhow::{anyhow, Context, Error, Result};
+use anyhow::{anyhow, bail, Context, Error, Result};
 use futures::lock::Mutex;
 use futures::prelude::*;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use sha3::{Digest, Sha3_256};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{fs, io};
@@ -20,33 +23,55 @@ use ya_service_bus::{typed as bus, RpcEndpoint};
 
 pub const DEFAULT_CHUNK_SIZE: u64 = 40 * 1024;
 
+/// Download and upload options
+#[derive(Clone, Debug, Default)]
+pub struct TransferOptions {
+    /// Continue an interrupted transfer from the last completed chunk
+    pub resume: bool,
+    /// Verify each downloaded chunk and the whole file against published hashes
+    pub verify: bool,
+}
+
+/// Transfer progress, reported after each completed chunk
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    pub transferred: u64,
+    pub total: u64,
+}
+
 // =========================================== //
 // File download - publisher side ("requestor")
 // =========================================== //
 
 struct FileDesc {
     hash: String,
+    path: PathBuf,
     file: Mutex<fs::File>,
     meta: model::GftpMetadata,
+    /// Chunk hashes by chunk size. Hashes for `DEFAULT_CHUNK_SIZE` are computed on publish,
+    /// other sizes on the first request.
+    chunk_hashes: std::sync::Mutex<HashMap<u64, Vec<String>>>,
 }
 
 impl FileDesc {
-    fn new(file: fs::File, hash: String, meta: model::GftpMetadata) -> Arc<Self> {
-        let file = Mutex::new(file);
-
-        Arc::new(FileDesc { hash, file, meta })
-    }
-
     pub fn open(path: &Path) -> Result<Arc<FileDesc>> {
         let mut file =
             fs::File::open(path).with_context(|| format!("Can't open file {}.", path.display()))?;
 
-        let hash = hash_file_sha256(&mut file)?;
+        let (hash, chunk_hashes) = hash_file_chunks(&mut file, DEFAULT_CHUNK_SIZE)?;
         let meta = model::GftpMetadata {
             file_size: file.metadata()?.len(),
         };
 
-        Ok(FileDesc::new(file, hash, meta))
+        Ok(Arc::new(FileDesc {
+            hash,
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            meta,
+            chunk_hashes: std::sync::Mutex::new(
+                std::iter::once((DEFAULT_CHUNK_SIZE, chunk_hashes)).collect(),
+            ),
+        }))
     }
 
     pub fn bind_handlers(self: &Arc<Self>) {
@@ -61,6 +86,49 @@ impl FileDesc {
             let desc = desc.clone();
             async move { desc.get_chunk(msg.offset, msg.size).await }
         });
+
+        let desc = self.clone();
+        let _ = bus::bind(&gsb_address, move |msg: model::GetChunkHashes| {
+            let desc = desc.clone();
+            async move { desc.get_chunk_hashes(msg.chunk_size).await }
+        });
+    }
+
+    async fn get_chunk_hashes(
+        &self,
+        chunk_size: u64,
+    ) -> Result<model::GftpChunkHashes, model::Error> {
+        if chunk_size == 0 {
+            return Err(model::Error::InternalError(
+                "Chunk size must be greater than 0".to_string(),
+            ));
+        }
+
+        let cached = self.chunk_hashes.lock().unwrap().get(&chunk_size).cloned();
+        let hashes = match cached {
+            Some(hashes) => hashes,
+            None => {
+                // Hashed from a separate handle, so chunk downloads are not blocked meanwhile
+                log::debug!("Computing chunk hashes, chunk size: {}", chunk_size);
+                let (_, hashes) = fs::File::open(&self.path)
+                    .map_err(Error::from)
+                    .and_then(|mut file| hash_file_chunks(&mut file, chunk_size))
+                    .map_err(|error| {
+                        model::Error::ReadError(format!(
+                            "Can't hash {}: {}",
+                            self.path.display(),
+                            error
+                        ))
+                    })?;
+                self.chunk_hashes
+                    .lock()
+                    .unwrap()
+                    .insert(chunk_size, hashes.clone());
+                hashes
+            }
+        };
+
+        Ok(model::GftpChunkHashes { chunk_size, hashes })
     }
 
     async fn get_chunk(
@@ -98,13 +166,95 @@ impl FileDesc {
     }
 }
 
+thread_local! {
+    /// Hashes of files published as a part of a directory, by manifest hash
+    static PUBLISHED_DIRS: RefCell<HashMap<String, Vec<String>>> = Default::default();
+    /// Number of publications sharing the binding of a file or manifest hash, e.g. the same
+    /// file published on its own and within a directory
+    static BINDINGS: RefCell<HashMap<String, usize>> = Default::default();
+}
+
+/// Registers a publication of `hash`. Returns `true` if it has to be bound.
+fn acquire(hash: &str) -> bool {
+    BINDINGS.with(|bindings| {
+        let mut bindings = bindings.borrow_mut();
+        let count = bindings.entry(hash.to_string()).or_insert(0);
+        *count += 1;
+        *count == 1
+    })
+}
+
+/// Releases a publication of `hash`. Returns `Some(true)` if it is no longer used and
+/// has to be unbound, or `None` if it was not published.
+fn release(hash: &str) -> Option<bool> {
+    BINDINGS.with(|bindings| {
+        let mut bindings = bindings.borrow_mut();
+        let count = bindings.get_mut(hash)?;
+        *count -= 1;
+        if *count > 0 {
+            return Some(false);
+        }
+        bindings.remove(hash);
+        Some(true)
+    })
+}
+
+/// Publishes a file, or a directory tree. Each file in the tree is published
+/// separately, while the returned URL points to the directory manifest.
 pub async fn publish(path: &Path) -> Result<Url> {
+    if path.is_dir() {
+        return publish_dir(path).await;
+    }
+
     let filedesc = FileDesc::open(path)?;
-    filedesc.bind_handlers();
+    if acquire(&filedesc.hash) {
+        filedesc.bind_handlers();
+    }
 
     gftp_url(&filedesc.hash).await
 }
 
+async fn publish_dir(path: &Path) -> Result<Url> {
+    let (file_paths, dir_paths) = list_tree(path)?;
+
+    let mut descs = Vec::new();
+    let mut entries = Vec::new();
+    for file_path in file_paths {
+        let filedesc = FileDesc::open(&file_path)?;
+        entries.push(model::GftpManifestEntry {
+            path: relative_path(path, &file_path)?,
+            file_size: filedesc.meta.file_size,
+            hash: filedesc.hash.clone(),
+        });
+        descs.push(filedesc);
+    }
+    let dirs = dir_paths
+        .iter()
+        .map(|dir_path| relative_path(path, dir_path))
+        .collect::<Result<_>>()?;
+
+    // Files are bound only once the whole tree has been read successfully
+    for filedesc in descs {
+        if acquire(&filedesc.hash) {
+            filedesc.bind_handlers();
+        }
+    }
+
+    let files = entries.iter().map(|e| e.hash.clone()).collect();
+    let manifest = model::GftpManifest { entries, dirs };
+    let hash = hash_bytes_sha256(&serde_json::to_vec(&manifest)?);
+
+    if acquire(&hash) {
+        let _ = bus::bind(
+            &model::file_bus_id(&hash),
+            move |_msg: model::GetManifest| future::ok(manifest.clone()),
+        );
+        PUBLISHED_DIRS.with(|dirs| dirs.borrow_mut().insert(hash.clone(), files));
+    }
+
+    gftp_url(&hash).await
+}
+
 pub async fn close(url: &Url) -> Result<bool> {
     let hash_name = match url.path_segments() {
         Some(segments) => match segments.last() {
@@ -114,6 +264,23 @@ pub async fn close(url: &Url) -> Result<bool> {
         _ => return Err(anyhow!("Invalid URL: {:?}", url)),
     };
 
+    match release(hash_name) {
+        // Still published by someone else
+        Some(false) => return Ok(true),
+        Some(true) => {
+            let files = PUBLISHED_DIRS
+                .with(|dirs| dirs.borrow_mut().remove(hash_name))
+                .unwrap_or_default();
+            for file_hash in files {
+                if release(&file_hash) == Some(true) {
+                    let _ = bus::unbind(model::file_bus_id(&file_hash).as_str()).await;
+                }
+            }
+        }
+        // Not published, e.g. opened for upload
+        None => (),
+    }
+
     bus::unbind(model::file_bus_id(hash_name).as_str())
         .await
         .map_err(|e| anyhow!(e))
@@ -129,10 +296,32 @@ pub async fn download_from_url(url: &Url, dst_path: &Path) -> Result<()> {
 }
 
 pub async fn download_file(node_id: NodeId, hash: &str, dst_path: &Path) -> Result<()> {
-    let remote = node_id.service_transfer(&model::file_bus_id(hash));
-    log::debug!("Creating target file {}", dst_path.display());
+    download_file_with(node_id, hash, dst_path, &Default::default(), |_| ()).await
+}
+
+pub async fn download_from_url_with<F: FnMut(Progress)>(
+    url: &Url,
+    dst_path: &Path,
+    options: &TransferOptions,
+    progress: F,
+) -> Result<()> {
+    let (node_id, hash) = extract_url(url)?;
+    download_file_with(node_id, &hash, dst_path, options, progress).await
+}
 
-    let mut file = create_dest_file(dst_path)?;
+/// Downloads a file chunk by chunk. With `resume` enabled, completed chunks
+/// are recorded in a state file next to the destination, which is used
+/// to continue an interrupted download. Downloads are resumed only when
+/// the publisher provides chunk hashes, so the last completed chunk can be
+/// checked before continuing.
+pub async fn download_file_with<F: FnMut(Progress)>(
+    node_id: NodeId,
+    hash: &str,
+    dst_path: &Path,
+    options: &TransferOptions,
+    mut progress: F,
+) -> Result<()> {
+    let remote = node_id.service_transfer(&model::file_bus_id(hash));
 
     log::debug!("Loading file {} metadata.", dst_path.display());
     let metadata = remote.send(model::GetMetadata {}).await??;
@@ -142,33 +331,254 @@ pub async fn download_file(node_id: NodeId, hash: &str, dst_path: &Path) -> Resu
     let chunk_size = DEFAULT_CHUNK_SIZE;
     let num_chunks = (metadata.file_size + (chunk_size - 1)) / chunk_size; // Divide and round up.
 
+    let hashes = match (options.verify, options.resume) {
+        (true, _) => Some(load_chunk_hashes(&remote, dst_path, chunk_size, num_chunks).await?),
+        (false, true) => load_chunk_hashes(&remote, dst_path, chunk_size, num_chunks)
+            .await
+            .map_err(|e| log::debug!("Download of {} can't be resumed: {}", dst_path.display(), e))
+            .ok(),
+        (false, false) => None,
+    };
+
+    let state_path = DownloadState::path(dst_path);
+    let mut state = DownloadState {
+        hash: hash.to_string(),
+        file_size: metadata.file_size,
+        chunk_size,
+        completed: 0,
+    };
+
+    let mut file = match (options.resume, &hashes) {
+        (true, Some(hashes)) => match DownloadState::load(&state_path) {
+            Some(saved) if saved.resumes(&state) => {
+                state = saved;
+                let mut file = open_dest_file(dst_path, false)?;
+                if state.completed > 0 {
+                    let last = state.completed - 1;
+                    if hash_file_chunk(&mut file, last, chunk_size)? != hashes[last as usize] {
+                        log::debug!("Last completed chunk {} is corrupted.", last);
+                        state.completed = last;
+                    }
+                }
+                log::debug!(
+                    "Resuming download of {} from chunk {}.",
+                    dst_path.display(),
+                    state.completed
+                );
+                file
+            }
+            _ => create_dest_file(dst_path)?,
+        },
+        _ => create_dest_file(dst_path)?,
+    };
+
     file.set_len(metadata.file_size)?;
+    file.seek(SeekFrom::Start(state.completed * chunk_size))?;
 
-    futures::stream::iter(0..num_chunks)
+    let mut transferred = (state.completed * chunk_size).min(metadata.file_size);
+    progress(Progress {
+        transferred,
+        total: metadata.file_size,
+    });
+
+    let chunks = futures::stream::iter(state.completed..num_chunks)
         .map(|chunk_number| {
-            remote.call(model::GetChunk {
-                offset: chunk_number * chunk_size,
-                size: chunk_size,
-            })
-        })
-        .buffered(12)
-        .map_err(anyhow::Error::from)
-        .try_for_each(move |result| {
-            future::ready((|| {
-                let chunk = result?;
-                file.write_all(&chunk.content[..])?;
-                Ok(())
-            })())
+            remote
+                .call(model::GetChunk {
+                    offset: chunk_number * chunk_size,
+                    size: chunk_size,
+                })
+                .map(move |result| (chunk_number, result))
         })
-        .await?;
+        .buffered(12);
+    futures::pin_mut!(chunks);
+
+    while let Some((chunk_number, result)) = chunks.next().await {
+        let chunk = result??;
+        if let Some(hashes) = &hashes {
+            if hash_bytes_sha256(&chunk.content) != hashes[chunk_number as usize] {
+                log::debug!("Chunk {} hash verification failed.", chunk_number);
+                return Err(model::Error::IntegrityError.into());
+            }
+        }
+
+        file.write_all(&chunk.content[..])?;
+        transferred += chunk.content.len() as u64;
+        if options.resume && hashes.is_some() {
+            file.sync_data()?;
+            state.completed = chunk_number + 1;
+            state.save(&state_path)?;
+        }
+
+        progress(Progress {
+            transferred,
+            total: metadata.file_size,
+        });
+    }
+
+    if options.verify {
+        log::debug!("Download finished. Verifying hash...");
+        if hash_file_sha256(&mut file)? != hash {
+            return Err(model::Error::IntegrityError.into());
+        }
+    }
+    if options.resume {
+        let _ = fs::remove_file(&state_path);
+    }
 
     Ok(())
 }
 
+async fn load_chunk_hashes(
+    remote: &bus::Endpoint,
+    dst_path: &Path,
+    chunk_size: u64,
+    num_chunks: u64,
+) -> Result<Vec<String>> {
+    log::debug!("Loading file {} chunk hashes.", dst_path.display());
+    let hashes = remote.send(model::GetChunkHashes { chunk_size }).await??;
+    if hashes.chunk_size != chunk_size || hashes.hashes.len() as u64 != num_chunks {
+        bail!(
+            "Invalid chunk hashes: expected {} chunks of size {}, got {} chunks of size {}.",
+            num_chunks,
+            chunk_size,
+            hashes.hashes.len(),
+            hashes.chunk_size
+        );
+    }
+    Ok(hashes.hashes)
+}
+
+pub async fn download_dir_from_url_with<F: FnMut(Progress)>(
+    url: &Url,
+    dst_dir: &Path,
+    options: &TransferOptions,
+    concurrency: usize,
+    progress: F,
+) -> Result<()> {
+    let (node_id, hash) = extract_url(url)?;
+    download_dir_with(node_id, &hash, dst_dir, options, concurrency, progress).await
+}
+
+/// Downloads a published directory tree, `concurrency` files at a time.
+/// Reported progress is aggregated over all files.
+pub async fn download_dir_with<F: FnMut(Progress)>(
+    node_id: NodeId,
+    hash: &str,
+    dst_dir: &Path,
+    options: &TransferOptions,
+    concurrency: usize,
+    progress: F,
+) -> Result<()> {
+    let remote = node_id.service_transfer(&model::file_bus_id(hash));
+
+    log::debug!("Loading directory {} manifest.", dst_dir.display());
+    let manifest = remote.send(model::GetManifest {}).await??;
+
+    let total = manifest.entries.iter().map(|e| e.file_size).sum();
+    let transferred = Cell::new(0u64);
+    let progress = RefCell::new(progress);
+
+    fs::create_dir_all(dst_dir)?;
+    for dir in manifest.dirs.iter() {
+        fs::create_dir_all(dst_dir.join(manifest_path(dir)?))?;
+    }
+
+    futures::stream::iter(manifest.entries.iter())
+        .map(|entry| {
+            let transferred = &transferred;
+            let progress = &progress;
+            async move {
+                let dst_path = dst_dir.join(manifest_path(&entry.path)?);
+                let mut last = 0;
+                download_file_with(node_id, &entry.hash, &dst_path, options, |p| {
+                    transferred.set(transferred.get() + p.transferred - last);
+                    last = p.transferred;
+                    (progress.borrow_mut())(Progress {
+                        transferred: transferred.get(),
+                        total,
+                    });
+                })
+                .await
+                .with_context(|| format!("Can't download [{}].", entry.path))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_for_each(|_| future::ok(()))
+        .await
+}
+
+/// Converts a manifest path to a relative path, rejecting paths
+/// which would escape the destination directory.
+fn manifest_path(path: &str) -> Result<PathBuf> {
+    let segments = path.split('/').collect::<Vec<_>>();
+    if segments
+        .iter()
+        .any(|s| s.is_empty() || *s == "." || *s == ".." || s.contains('\\'))
+    {
+        bail!("Invalid manifest path: {}", path);
+    }
+    Ok(segments.into_iter().collect())
+}
+
+/// Chunks of a published file written to the destination so far
+#[derive(Debug, Serialize, Deserialize)]
+struct DownloadState {
+    hash: String,
+    file_size: u64,
+    chunk_size: u64,
+    completed: u64,
+}
+
+impl DownloadState {
+    fn path(dst_path: &Path) -> PathBuf {
+        let mut name = dst_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".gftp-state");
+        dst_path.with_file_name(name)
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read(path).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_vec(self)?;
+        fs::write(path, contents)
+            .with_context(|| format!("Can't write download state: [{}].", path.display()))
+    }
+
+    fn resumes(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.file_size == other.file_size
+            && self.chunk_size == other.chunk_size
+    }
+}
+
 // =========================================== //
 // File upload - publisher side ("requestor")
 // =========================================== //
 
+/// File being uploaded, with ranges of bytes received so far
+struct UploadDesc {
+    file: File,
+    received: BTreeMap<u64, u64>,
+}
+
+impl UploadDesc {
+    /// Number of bytes received from the beginning of the file without gaps
+    fn offset(&self) -> u64 {
+        let mut end = 0;
+        for (offset, size) in self.received.iter() {
+            if *offset > end {
+                break;
+            }
+            end = end.max(offset + size);
+        }
+        end
+    }
+}
+
 pub async fn open_for_upload(filepath: &Path) -> Result<Url> {
     let hash_name = rand::thread_rng()
         .sample_iter(&Alphanumeric)
@@ -176,38 +586,52 @@ pub async fn open_for_upload(filepath: &Path) -> Result<Url> {
         .take(65)
         .collect::<String>();
 
-    let file = Arc::new(Mutex::new(create_dest_file(filepath)?));
+    let desc = Arc::new(Mutex::new(UploadDesc {
+        file: create_dest_file(filepath)?,
+        received: Default::default(),
+    }));
 
     let gsb_address = model::file_bus_id(&hash_name);
-    let file_clone = file.clone();
+    let desc_clone = desc.clone();
     let _ = bus::bind(&gsb_address, move |msg: model::UploadChunk| {
-        let file = file_clone.clone();
-        async move { chunk_uploaded(file.clone(), msg).await }
+        let desc = desc_clone.clone();
+        async move { chunk_uploaded(desc.clone(), msg).await }
     });
 
-    let file_clone = file.clone();
+    let desc_clone = desc.clone();
+    let _ = bus::bind(&gsb_address, move |_msg: model::GetUploadProgress| {
+        let desc = desc_clone.clone();
+        async move {
+            let offset = desc.lock().await.offset();
+            Ok::<_, model::Error>(model::GftpUploadProgress { offset })
+        }
+    });
+
+    let desc_clone = desc.clone();
     let _ = bus::bind(&gsb_address, move |msg: model::UploadFinished| {
-        let file = file_clone.clone();
-        async move { upload_finished(file.clone(), msg).await }
+        let desc = desc_clone.clone();
+        async move { upload_finished(desc.clone(), msg).await }
     });
 
     gftp_url(&hash_name).await
 }
 
 async fn chunk_uploaded(
-    file: Arc<Mutex<File>>,
+    desc: Arc<Mutex<UploadDesc>>,
     msg: model::UploadChunk,
 ) -> Result<(), model::Error> {
-    let mut file = file.lock().await;
+    let mut desc = desc.lock().await;
     let chunk = msg.chunk;
 
-    file.seek(SeekFrom::Start(chunk.offset)).map_err(|error| {
-        model::Error::ReadError(format!(
-            "Can't seek file at offset {}, {}",
-            chunk.offset, error
-        ))
-    })?;
-    file.write_all(&chunk.content[..]).map_err(|error| {
+    desc.file
+        .seek(SeekFrom::Start(chunk.offset))
+        .map_err(|error| {
+            model::Error::ReadError(format!(
+                "Can't seek file at offset {}, {}",
+                chunk.offset, error
+            ))
+        })?;
+    desc.file.write_all(&chunk.content[..]).map_err(|error| {
         model::Error::WriteError(format!(
             "Can't write {} bytes at offset {}, error: {}",
             chunk.content.len(),
@@ -215,21 +639,24 @@ async fn chunk_uploaded(
             error
         ))
     })?;
+    desc.received
+        .insert(chunk.offset, chunk.content.len() as u64);
     Ok(())
 }
 
 async fn upload_finished(
-    file: Arc<Mutex<File>>,
+    desc: Arc<Mutex<UploadDesc>>,
     msg: model::UploadFinished,
 ) -> Result<(), model::Error> {
-    let mut file = file.lock().await;
+    let mut desc = desc.lock().await;
+    let file = &mut desc.file;
     file.flush()
         .map_err(|error| model::Error::WriteError(format!("Can't flush file: {}", error)))?;
 
     if let Some(expected_hash) = msg.hash {
         log::debug!("Upload finished. Verifying hash...");
 
-        let real_hash = hash_file_sha256(&mut file)
+        let real_hash = hash_file_sha256(file)
             .map_err(|error| model::Error::InternalError(error.to_string()))?;
 
         if expected_hash != real_hash {
@@ -256,24 +683,63 @@ async fn upload_finished(
 // =========================================== //
 
 pub async fn upload_file(path: &Path, url: &Url) -> Result<()> {
+    upload_file_with(path, url, &Default::default(), |_| ()).await
+}
+
+/// Uploads a file chunk by chunk. With `resume` enabled, chunks already
+/// received by the publisher are skipped.
+pub async fn upload_file_with<F: FnMut(Progress)>(
+    path: &Path,
+    url: &Url,
+    options: &TransferOptions,
+    mut progress: F,
+) -> Result<()> {
     let (node_id, random_filename) = extract_url(url)?;
     let remote = node_id.try_service(&model::file_bus_id(&random_filename))?;
 
     log::debug!("Opening file to send {}.", path.display());
 
     let chunk_size = DEFAULT_CHUNK_SIZE;
+    let file_size = fs::metadata(path)?.len();
+
+    let first_chunk = match options.resume {
+        true => match remote.call(model::GetUploadProgress {}).await {
+            Ok(Ok(received)) => received.offset.min(file_size) / chunk_size,
+            Ok(Err(error)) => bail!(error),
+            Err(error) => {
+                log::debug!("Upload progress unavailable, starting over: {}", error);
+                0
+            }
+        },
+        false => 0,
+    };
+
+    let mut transferred = first_chunk * chunk_size;
+    progress(Progress {
+        transferred,
+        total: file_size,
+    });
 
-    futures::stream::iter(get_chunks(path, chunk_size)?)
+    let chunks = futures::stream::iter(get_chunks(path, chunk_size, first_chunk)?)
         .map(|chunk| {
             let remote = remote.clone();
             async move {
                 let chunk = chunk?;
-                Ok::<_, anyhow::Error>(remote.call(model::UploadChunk { chunk }).await??)
+                let size = chunk.content.len() as u64;
+                remote.call(model::UploadChunk { chunk }).await??;
+                Ok::<_, anyhow::Error>(size)
             }
         })
-        .buffered(3)
-        .try_for_each(|_| future::ok(()))
-        .await?;
+        .buffered(3);
+    futures::pin_mut!(chunks);
+
+    while let Some(size) = chunks.try_next().await? {
+        transferred += size;
+        progress(Progress {
+            transferred,
+            total: file_size,
+        });
+    }
 
     log::debug!("Computing file hash.");
     let hash = hash_file_sha256(&mut File::open(path)?)?;
@@ -293,14 +759,16 @@ pub async fn upload_file(path: &Path, url: &Url) -> Result<()> {
 fn get_chunks(
     file_path: &Path,
     chunk_size: u64,
+    first_chunk: u64,
 ) -> Result<impl Iterator<Item = Result<model::GftpChunk, std::io::Error>> + 'static, std::io::Error>
 {
     let mut file = OpenOptions::new().read(true).open(file_path)?;
 
     let file_size = file.metadata()?.len();
     let n_chunks = (file_size + chunk_size - 1) / chunk_size;
+    file.seek(SeekFrom::Start(first_chunk * chunk_size))?;
 
-    Ok((0..n_chunks).map(move |n| {
+    Ok((first_chunk..n_chunks).map(move |n| {
         let offset = n * chunk_size;
         let bytes_to_read = if offset + chunk_size > file_size {
             file_size - offset
@@ -316,6 +784,78 @@ fn get_chunks(
     }))
 }
 
+/// Lists files and directories of a tree, sorted by path
+fn list_tree(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("Can't read directory {}.", dir.display()))?
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.file_name());
+
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let (sub_files, sub_dirs) = list_tree(&entry.path())?;
+            dirs.push(entry.path());
+            dirs.extend(sub_dirs);
+            files.extend(sub_files);
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        } else {
+            log::debug!("Skipping {}.", entry.path().display());
+        }
+    }
+    Ok((files, dirs))
+}
+
+/// Path relative to `root`, with `/` separators as used in manifests
+fn relative_path(root: &Path, path: &Path) -> Result<String> {
+    Ok(path
+        .strip_prefix(root)?
+        .components()
+        .map(|c| {
+            c.as_os_str()
+                .to_str()
+                .ok_or_else(|| anyhow!("Non UTF-8 path: {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .join("/"))
+}
+
+fn hash_bytes_sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha3_256::digest(bytes))
+}
+
+/// Hashes the whole file and each of its `chunk_size` chunks in a single pass
+fn hash_file_chunks(file: &mut fs::File, chunk_size: u64) -> Result<(String, Vec<String>)> {
+    let mut hasher = Sha3_256::new();
+    let mut hashes = Vec::new();
+    let mut buffer = Vec::with_capacity(chunk_size as usize);
+
+    file.seek(SeekFrom::Start(0))
+        .with_context(|| "Can't seek file at offset 0.".to_string())?;
+    loop {
+        buffer.clear();
+        (&mut *file).take(chunk_size).read_to_end(&mut buffer)?;
+        if buffer.is_empty() {
+            break;
+        }
+        hasher.input(&buffer);
+        hashes.push(hash_bytes_sha256(&buffer));
+    }
+
+    Ok((format!("{:x}", hasher.result()), hashes))
+}
+
+fn hash_file_chunk(file: &mut fs::File, chunk_number: u64, chunk_size: u64) -> Result<String> {
+    let mut buffer = Vec::with_capacity(chunk_size as usize);
+    file.seek(SeekFrom::Start(chunk_number * chunk_size))
+        .with_context(|| format!("Can't seek file at chunk {}.", chunk_number))?;
+    (&mut *file).take(chunk_size).read_to_end(&mut buffer)?;
+    Ok(hash_bytes_sha256(&buffer))
+}
+
 fn hash_file_sha256(mut file: &mut fs::File) -> Result<String> {
     let mut hasher = Sha3_256::new();
 
@@ -362,6 +902,10 @@ fn ensure_dir_exists(file_path: &Path) -> Result<()> {
 }
 
 fn create_dest_file(file_path: &Path) -> Result<File> {
+    open_dest_file(file_path, true)
+}
+
+fn open_dest_file(file_path: &Path, truncate: bool) -> Result<File> {
     ensure_dir_exists(file_path).with_context(|| {
         format!(
             "Can't create destination directory for file: [{}].",
@@ -372,7 +916,139 @@ fn create_dest_file(file_path: &Path) -> Result<File> {
         .read(true)
         .write(true)
         .create(true)
-        .truncate(true)
+        .truncate(truncate)
         .open(file_path)
         .with_context(|| format!("Can't create destination file: [{}].", file_path.display()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_offset() -> Result<()> {
+        let dir = tempdir::TempDir::new("gftp-upload")?;
+        let mut desc = UploadDesc {
+            file: create_dest_file(&dir.path().join("file"))?,
+            received: Default::default(),
+        };
+        assert_eq!(desc.offset(), 0);
+
+        desc.received.insert(10, 10);
+        assert_eq!(desc.offset(), 0);
+
+        desc.received.insert(0, 10);
+        desc.received.insert(30, 10);
+        assert_eq!(desc.offset(), 20);
+
+        desc.received.insert(20, 10);
+        assert_eq!(desc.offset(), 40);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_file_chunks() -> Result<()> {
+        let dir = tempdir::TempDir::new("gftp-hash")?;
+        let path = dir.path().join("file");
+        let content = (0..250u8).collect::<Vec<_>>();
+        fs::write(&path, &content)?;
+
+        let mut file = File::open(&path)?;
+        let (hash, hashes) = hash_file_chunks(&mut file, 100)?;
+        assert_eq!(hash, hash_file_sha256(&mut file)?);
+        assert_eq!(
+            hashes,
+            content
+                .chunks(100)
+                .map(hash_bytes_sha256)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(hash_file_chunk(&mut file, 1, 100)?, hashes[1]);
+        assert_eq!(hash_file_chunk(&mut file, 2, 100)?, hashes[2]);
+
+        fs::write(&path, [])?;
+        let (_, hashes) = hash_file_chunks(&mut File::open(&path)?, 100)?;
+        assert!(hashes.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_path() {
+        assert_eq!(
+            manifest_path("dir/file.txt").unwrap(),
+            Path::new("dir").join("file.txt")
+        );
+        for path in [
+            "",
+            "/etc/passwd",
+            "../file",
+            "dir/../../file",
+            "dir//file",
+            "a\\b",
+        ] {
+            assert!(manifest_path(path).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_list_files() -> Result<()> {
+        let dir = tempdir::TempDir::new("gftp-list")?;
+        for name in ["b", "a/2", "a/1", "a/c/d"] {
+            let path = dir.path().join(name);
+            ensure_dir_exists(&path)?;
+            File::create(path)?;
+        }
+        fs::create_dir_all(dir.path().join("empty"))?;
+
+        let (files, dirs) = list_tree(dir.path())?;
+        let relative = |paths: Vec<PathBuf>| {
+            paths
+                .iter()
+                .map(|p| relative_path(dir.path(), p).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(relative(files), ["a/1", "a/2", "a/c/d", "b"]);
+        assert_eq!(relative(dirs), ["a", "a/c", "empty"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_binding_refcount() {
+        assert_eq!(release("refcount-hash"), None);
+
+        assert!(acquire("refcount-hash"));
+        assert!(!acquire("refcount-hash"));
+        assert_eq!(release("refcount-hash"), Some(false));
+        assert_eq!(release("refcount-hash"), Some(true));
+        assert_eq!(release("refcount-hash"), None);
+    }
+
+    #[test]
+    fn test_download_state() -> Result<()> {
+        let dir = tempdir::TempDir::new("gftp-download")?;
+        let path = DownloadState::path(&dir.path().join("file.bin"));
+        assert_eq!(path, dir.path().join("file.bin.gftp-state"));
+
+        let state = DownloadState {
+            hash: "hash".to_string(),
+            file_size: 100,
+            chunk_size: 10,
+            completed: 3,
+        };
+        state.save(&path)?;
+
+        let loaded = DownloadState::load(&path).unwrap();
+        assert_eq!(loaded.completed, 3);
+        assert!(loaded.resumes(&DownloadState {
+            completed: 0,
+            ..state
+        }));
+        assert!(!loaded.resumes(&DownloadState {
+            hash: "other".to_string(),
+            file_size: 100,
+            chunk_size: 10,
+            completed: 0,
+        }));
+        Ok(())
+    }
+}
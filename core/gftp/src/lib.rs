@@ -2,6 +2,7 @@ mod gftp;
 pub mod rpc;
 
 pub use self::gftp::{
-    close, download_file, download_from_url, extract_url, open_for_upload, publish, upload_file,
-    DEFAULT_CHUNK_SIZE,
+    close, download_dir_from_url_with, download_dir_with, download_file, download_file_with,
+    download_from_url, download_from_url_with, extract_url, open_for_upload, publish, upload_file,
+    upload_file_with, Progress, TransferOptions, DEFAULT_CHUNK_SIZE,
 };
@@ -5,6 +5,8 @@ use std::path::PathBuf;
 use structopt::StructOpt;
 use url::Url;
 
+use crate::Progress;
+
 const JSON_RPC_VERSION: &str = "2.0";
 
 #[allow(unused)]
@@ -104,6 +106,22 @@ impl RpcMessage {
         }
     }
 
+    pub fn progress(id: Option<&RpcId>, file: PathBuf, url: Url, progress: Progress) -> Self {
+        RpcMessage {
+            jsonrpc: JSON_RPC_VERSION.to_string(),
+            id: None,
+            body: RpcBody::Notification {
+                notification: RpcNotification::Progress(RpcProgress {
+                    request_id: id.cloned(),
+                    file,
+                    url,
+                    transferred: progress.transferred,
+                    total: progress.total,
+                }),
+            },
+        }
+    }
+
     pub fn request_error(id: Option<&RpcId>) -> Self {
         Self::error(id, JsonRpcError::InvalidRequest)
     }
@@ -148,6 +166,10 @@ pub enum RpcBody {
     Error {
         error: RpcError,
     },
+    Notification {
+        #[serde(flatten)]
+        notification: RpcNotification,
+    },
 }
 
 #[derive(Serialize, Deserialize, StructOpt, Debug, Clone)]
@@ -156,16 +178,36 @@ pub enum RpcBody {
 pub enum RpcRequest {
     /// Prints out version
     Version {},
-    /// Publishes files (blocking)
+    /// Publishes files or directories (blocking)
     Publish { files: Vec<PathBuf> },
     /// Stops publishing a file
     Close { urls: Vec<Url> },
-    /// Downloads a file
+    /// Downloads a file or a directory
     Download {
         /// Source URL
         url: Url,
         /// Destination path
         output_file: PathBuf,
+        /// Downloads a published directory into the destination path
+        #[structopt(long)]
+        #[serde(default)]
+        recursive: bool,
+        /// Number of files downloaded in parallel, when recursive
+        #[structopt(long)]
+        #[serde(default)]
+        concurrency: Option<usize>,
+        /// Resumes an interrupted download
+        #[structopt(long)]
+        #[serde(default)]
+        resume: bool,
+        /// Verifies each chunk against hashes provided by the publisher
+        #[structopt(long)]
+        #[serde(default)]
+        verify: bool,
+        /// Emits progress notifications
+        #[structopt(long)]
+        #[serde(default)]
+        progress: bool,
     },
     /// Waits for file upload (blocking)
     Receive {
@@ -178,11 +220,37 @@ pub enum RpcRequest {
         url: Url,
         /// Source path
         file: PathBuf,
+        /// Resumes an interrupted upload
+        #[structopt(long)]
+        #[serde(default)]
+        resume: bool,
+        /// Emits progress notifications
+        #[structopt(long)]
+        #[serde(default)]
+        progress: bool,
     },
     /// Shuts down the server
     Shutdown {},
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "method", content = "params")]
+#[serde(rename_all = "snake_case")]
+pub enum RpcNotification {
+    /// Reports transfer progress of a pending request
+    Progress(RpcProgress),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct RpcProgress {
+    pub request_id: Option<RpcId>,
+    pub file: PathBuf,
+    pub url: Url,
+    pub transferred: u64,
+    pub total: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum RpcResult {
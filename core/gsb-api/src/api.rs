@@ -1,5 +1,6 @@
+use crate::events::{EventBroker, EventFilter, EventsHandler, Subscribe};
 use crate::model::{
-    GsbApiError, ServiceListenResponse, ServicePath, ServiceRequest, ServiceResponse,
+    EventsQuery, GsbApiError, ServiceListenResponse, ServicePath, ServiceRequest, ServiceResponse,
 };
 use crate::service::StartBuffering;
 use crate::services::{Bind, Find, Services, Unbind};
@@ -12,14 +13,18 @@ use actix_web::Scope;
 use actix_web::{web, HttpRequest, Responder, Result};
 use actix_web_actors::ws::{self};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine as _};
+use std::str::FromStr;
+use ya_core_model::event::EventCategory;
 use ya_service_api_web::middleware::Identity;
 
-pub(crate) fn web_scope(services: Addr<Services>) -> Scope {
+pub(crate) fn web_scope(services: Addr<Services>, events: Addr<EventBroker>) -> Scope {
     actix_web::web::scope(&format!("/{}", crate::GSB_API_PATH))
         .app_data(Data::new(services))
+        .app_data(Data::new(events))
         .service(post_services)
         .service(delete_services)
         .service(get_service_messages)
+        .service(get_events)
 }
 
 #[actix_web::post("/services")]
@@ -93,6 +98,49 @@ async fn get_service_messages(
     Ok(resp)
 }
 
+/// Streams internal events as JSON text messages over WebSocket.
+/// Each event carries a `token`, which can be passed as `resumeToken` after reconnection
+/// to receive events missed in the meantime. Clients, which don't keep up with events,
+/// are disconnected with `Again` close code and should reconnect with the last token.
+#[actix_web::get("/events")]
+async fn get_events(
+    query: web::Query<EventsQuery>,
+    req: HttpRequest,
+    stream: web::Payload,
+    id: Identity,
+    events: Data<Addr<EventBroker>>,
+) -> Result<impl Responder, GsbApiError> {
+    log::debug!("GET WS events: {:?}", query);
+    let query = query.into_inner();
+    let filter = EventFilter {
+        identity: id.identity,
+        categories: split_list(&query.categories)
+            .map(|category| {
+                EventCategory::from_str(category).map_err(|_| {
+                    GsbApiError::BadRequest(format!("Unknown event category: {category}"))
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        kinds: split_list(&query.kinds).map(str::to_string).collect(),
+    };
+    let subscribe = Subscribe {
+        filter,
+        resume_token: query.resume_token,
+    };
+    let receiver = events.send(subscribe).await??;
+
+    let (_addr, resp) =
+        ws::WsResponseBuilder::new(EventsHandler::new(receiver), &req, stream).start_with_addr()?;
+    Ok(resp)
+}
+
+fn split_list(list: &Option<String>) -> impl Iterator<Item = &str> {
+    list.iter()
+        .flat_map(|list| list.split(','))
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
 fn decode_addr(addr_encoded: &str) -> Result<String, GsbApiError> {
     BASE64
         .decode(addr_encoded)
@@ -112,6 +160,7 @@ fn decode_addr(addr_encoded: &str) -> Result<String, GsbApiError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::Publish;
     use crate::model::ServiceListenRequest;
     use crate::{GsbApiService, GsbError, GSB_API_PATH};
     use actix::Actor;
@@ -128,6 +177,7 @@ mod tests {
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Duration;
     use test_case::test_case;
+    use ya_core_model::event::Event;
     use ya_core_model::gftp::{GetChunk, GftpChunk};
     use ya_core_model::NodeId;
     use ya_service_api_interfaces::Provider;
@@ -165,11 +215,16 @@ mod tests {
     const PAYLOAD_LEN: usize = 10;
 
     fn dummy_api() -> TestServer {
-        actix_test::start(|| {
+        dummy_api_with_events(EventBroker::default().start())
+    }
+
+    fn dummy_api_with_events(events: Addr<EventBroker>) -> TestServer {
+        actix_test::start(move || {
             App::new()
                 .service(GsbApiService::rest_internal(
                     &TestContext {},
                     Services::default().start(),
+                    events.clone(),
                 ))
                 .wrap(dummy_auth())
         })
@@ -863,4 +918,116 @@ mod tests {
 
         assert!(ws_res_1.is_ok());
     }
+
+    async fn next_event<S>(ws_frames: &mut S) -> Value
+    where
+        S: futures::Stream<Item = Result<Frame, ws::ProtocolError>> + Unpin,
+    {
+        match ws_frames.next().await {
+            Some(Ok(Frame::Text(text))) => serde_json::from_slice(&text).unwrap(),
+            msg => panic!("Unexpected msg: {:?}", msg),
+        }
+    }
+
+    #[actix_web::test]
+    #[serial]
+    async fn events_filtered_and_resumed_test() {
+        let events = EventBroker::default().start();
+        let mut api = dummy_api_with_events(events.clone());
+        let other_node: NodeId = "0x0101010101010101010101010101010101010101"
+            .parse()
+            .unwrap();
+
+        let mut ws_frames = api
+            .ws_at(&format!("{GSB_API_PATH}/events?categories=market"))
+            .await
+            .unwrap();
+
+        let node = Some(NodeId::default());
+        for (category, kind, owner_id) in [
+            (EventCategory::Payment, "InvoiceSettledEvent", node),
+            (
+                EventCategory::Market,
+                "AgreementApprovedEvent",
+                Some(other_node),
+            ),
+            (EventCategory::Market, "AgreementApprovedEvent", None),
+            (EventCategory::Market, "AgreementApprovedEvent", node),
+            (EventCategory::Market, "AgreementTerminatedEvent", node),
+        ] {
+            let event = Event::new(category, kind, owner_id, json!({}));
+            events.send(Publish(event)).await.unwrap();
+        }
+
+        let approved = next_event(&mut ws_frames).await;
+        assert_eq!(approved["category"], "market");
+        assert_eq!(approved["kind"], "AgreementApprovedEvent");
+        assert_eq!(approved["ownerId"], NodeId::default().to_string());
+        let terminated = next_event(&mut ws_frames).await;
+        assert_eq!(terminated["kind"], "AgreementTerminatedEvent");
+        drop(ws_frames);
+
+        let token = approved["token"].as_str().unwrap();
+        let mut ws_frames = api
+            .ws_at(&format!(
+                "{GSB_API_PATH}/events?categories=market&resumeToken={token}"
+            ))
+            .await
+            .unwrap();
+        let resumed = next_event(&mut ws_frames).await;
+        assert_eq!(resumed["token"], terminated["token"]);
+    }
+
+    #[actix_web::test]
+    #[serial]
+    async fn events_of_other_identity_test() {
+        let events = EventBroker::default().start();
+        let mut api = dummy_api_with_events(events.clone());
+        let other_node: NodeId = "0x0101010101010101010101010101010101010101"
+            .parse()
+            .unwrap();
+
+        let mut ws_frames = api
+            .ws_at(&format!("{GSB_API_PATH}/events?categories=activity"))
+            .await
+            .unwrap();
+
+        for (activity_id, owner_id) in [
+            ("other", Some(other_node)),
+            ("unowned", None),
+            ("own", Some(NodeId::default())),
+        ] {
+            let event = Event::new(
+                EventCategory::Activity,
+                "ActivityStateChangedEvent",
+                owner_id,
+                json!({ "activityId": activity_id, "state": { "state": ["Ready", null] } }),
+            );
+            events.send(Publish(event)).await.unwrap();
+        }
+
+        let event = next_event(&mut ws_frames).await;
+        assert_eq!(event["kind"], "ActivityStateChangedEvent");
+        assert_eq!(event["payload"]["activityId"], "own");
+    }
+
+    #[actix_web::test]
+    #[serial]
+    async fn events_invalid_subscription_test() {
+        let api = dummy_api();
+
+        let resp = api
+            .get(format!("/{GSB_API_PATH}/events?categories=weather"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = api
+            .get(format!("/{GSB_API_PATH}/events?resumeToken=restarted.1"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::GONE);
+    }
 }
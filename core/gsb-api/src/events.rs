@@ -0,0 +1,272 @@
+//! Streaming of internal yagna events (see `ya_core_model::event`) to WebSocket clients.
+use actix::prelude::*;
+use actix_http::ws::{CloseCode, CloseReason, ProtocolError};
+use actix_web_actors::ws::{self, WebsocketContext};
+use futures::channel::mpsc;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use thiserror::Error;
+
+use ya_client_model::NodeId;
+use ya_core_model::event::{Event, EventCategory};
+
+/// Number of recent events kept to allow resuming streams.
+const HISTORY_SIZE: usize = 1000;
+/// Number of events buffered for a single subscriber.
+/// Subscribers falling further behind are disconnected.
+const SUBSCRIBER_BUFFER: usize = 100;
+
+lazy_static! {
+    pub(crate) static ref EVENTS: Addr<EventBroker> = EventBroker::default().start();
+}
+
+/// Event sent to WebSocket clients.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StreamedEvent {
+    /// Allows to resume the stream after this event.
+    pub token: String,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct EventFilter {
+    /// Identity of subscriber. Events owned by other identities or without owner are skipped.
+    pub identity: NodeId,
+    /// Empty set passes all categories.
+    pub categories: HashSet<EventCategory>,
+    /// Empty set passes all kinds.
+    pub kinds: HashSet<String>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &Event) -> bool {
+        // All categories relate to objects owned by an identity, so event without
+        // owner can't be attributed to subscriber and must not leak to everybody.
+        event.owner_id == Some(self.identity)
+            && (self.categories.is_empty() || self.categories.contains(&event.category))
+            && (self.kinds.is_empty() || self.kinds.contains(&event.kind))
+    }
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum SubscribeError {
+    #[error("Invalid resume token: {0}")]
+    InvalidToken(String),
+    #[error("Events following token {0} are no longer available")]
+    Expired(String),
+}
+
+struct Subscriber {
+    filter: EventFilter,
+    sender: mpsc::Sender<Arc<StreamedEvent>>,
+}
+
+/// Keeps recent events and distributes new ones to subscribers.
+pub(crate) struct EventBroker {
+    /// Distinguishes tokens issued before yagna restart.
+    session: String,
+    last_seq: u64,
+    history: VecDeque<(u64, Arc<StreamedEvent>)>,
+    subscribers: HashMap<u64, Subscriber>,
+    next_subscriber: u64,
+}
+
+impl Default for EventBroker {
+    fn default() -> Self {
+        EventBroker {
+            session: uuid::Uuid::new_v4().simple().to_string(),
+            last_seq: 0,
+            history: VecDeque::with_capacity(HISTORY_SIZE),
+            subscribers: HashMap::new(),
+            next_subscriber: 0,
+        }
+    }
+}
+
+impl EventBroker {
+    fn parse_token(&self, token: &str) -> Result<u64, SubscribeError> {
+        let (session, seq) = token
+            .rsplit_once('.')
+            .ok_or_else(|| SubscribeError::InvalidToken(token.to_string()))?;
+        let seq = seq
+            .parse::<u64>()
+            .map_err(|_| SubscribeError::InvalidToken(token.to_string()))?;
+
+        if session != self.session {
+            return Err(SubscribeError::Expired(token.to_string()));
+        }
+        if seq > self.last_seq {
+            return Err(SubscribeError::InvalidToken(token.to_string()));
+        }
+        let oldest = self
+            .history
+            .front()
+            .map(|(seq, _)| *seq)
+            .unwrap_or(self.last_seq + 1);
+        if seq + 1 < oldest {
+            return Err(SubscribeError::Expired(token.to_string()));
+        }
+        Ok(seq)
+    }
+}
+
+impl Actor for EventBroker {
+    type Context = Context<Self>;
+}
+
+#[derive(Message, Debug)]
+#[rtype(result = "()")]
+pub(crate) struct Publish(pub Event);
+
+impl Handler<Publish> for EventBroker {
+    type Result = ();
+
+    fn handle(&mut self, msg: Publish, _ctx: &mut Self::Context) -> Self::Result {
+        self.last_seq += 1;
+        let event = Arc::new(StreamedEvent {
+            token: format!("{}.{}", self.session, self.last_seq),
+            event: msg.0,
+        });
+
+        if self.history.len() == HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back((self.last_seq, event.clone()));
+
+        self.subscribers.retain(|id, subscriber| {
+            if !subscriber.filter.matches(&event.event) {
+                return true;
+            }
+            match subscriber.sender.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(e) if e.is_full() => {
+                    log::warn!("Events subscriber {id} is lagging behind. Disconnecting.");
+                    false
+                }
+                Err(_) => {
+                    log::debug!("Events subscriber {id} disconnected.");
+                    false
+                }
+            }
+        });
+    }
+}
+
+/// Subscribes to events passing the filter. Events published after
+/// `resume_token` are replayed first.
+#[derive(Message, Debug)]
+#[rtype(result = "Result<mpsc::Receiver<Arc<StreamedEvent>>, SubscribeError>")]
+pub(crate) struct Subscribe {
+    pub filter: EventFilter,
+    pub resume_token: Option<String>,
+}
+
+impl Handler<Subscribe> for EventBroker {
+    type Result = <Subscribe as Message>::Result;
+
+    fn handle(&mut self, msg: Subscribe, _ctx: &mut Self::Context) -> Self::Result {
+        let replay = match msg.resume_token {
+            Some(token) => {
+                let after = self.parse_token(&token)?;
+                self.history
+                    .iter()
+                    .filter(|(seq, event)| *seq > after && msg.filter.matches(&event.event))
+                    .map(|(_, event)| event.clone())
+                    .collect()
+            }
+            None => vec![],
+        };
+
+        let (mut sender, receiver) = mpsc::channel(SUBSCRIBER_BUFFER + replay.len());
+        for event in replay {
+            // Channel is large enough to fit all replayed events.
+            sender.try_send(event).ok();
+        }
+
+        let id = self.next_subscriber;
+        self.next_subscriber += 1;
+        log::debug!("New events subscriber {id}: {:?}", msg.filter);
+        self.subscribers.insert(
+            id,
+            Subscriber {
+                filter: msg.filter,
+                sender,
+            },
+        );
+        Ok(receiver)
+    }
+}
+
+/// Sends events to WebSocket client as JSON text messages.
+pub(crate) struct EventsHandler {
+    events: Option<mpsc::Receiver<Arc<StreamedEvent>>>,
+}
+
+impl EventsHandler {
+    pub fn new(events: mpsc::Receiver<Arc<StreamedEvent>>) -> Self {
+        EventsHandler {
+            events: Some(events),
+        }
+    }
+}
+
+impl Actor for EventsHandler {
+    type Context = WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        if let Some(events) = self.events.take() {
+            ctx.add_stream(events);
+        }
+    }
+}
+
+impl StreamHandler<Arc<StreamedEvent>> for EventsHandler {
+    fn handle(&mut self, event: Arc<StreamedEvent>, ctx: &mut Self::Context) {
+        match serde_json::to_string(&*event) {
+            Ok(text) => ctx.text(text),
+            Err(e) => log::warn!("Failed to serialize event {}: {e}", event.token),
+        }
+    }
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        // Broker drops subscribers, which don't keep up with events.
+        ctx.close(Some(CloseReason {
+            code: CloseCode::Again,
+            description: Some(
+                "Events subscriber lagging behind. Resume with last received token.".to_string(),
+            ),
+        }));
+        ctx.stop();
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ProtocolError>> for EventsHandler {
+    fn handle(&mut self, item: Result<ws::Message, ProtocolError>, ctx: &mut Self::Context) {
+        match item {
+            Ok(ws::Message::Ping(message)) => ctx.pong(&message),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(ws::Message::Pong(_)) | Ok(ws::Message::Nop) => (),
+            Ok(_) => {
+                ctx.close(Some(CloseReason {
+                    code: CloseCode::Unsupported,
+                    description: Some("Events stream doesn't accept messages.".to_string()),
+                }));
+                ctx.stop();
+            }
+            Err(cause) => {
+                ctx.close(Some(CloseReason {
+                    code: CloseCode::Error,
+                    description: Some(format!("ProtocolError: {cause}")),
+                }));
+                ctx.stop();
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
 mod api;
+mod events;
 mod model;
 mod service;
 mod services;
 
+use crate::events::{EventBroker, Publish, EVENTS};
 use crate::service::{DropMessages, StartBuffering, StartRelaying};
 use actix::prelude::*;
 use actix::ActorFutureExt;
@@ -16,6 +18,9 @@ use futures::FutureExt;
 use serde::{Deserialize, Serialize};
 use service::Service;
 use services::Services;
+use ya_client_model::ErrorMessage;
+use ya_core_model::event::{Event, BUS_ID as EVENTS_BUS_ID};
+use ya_service_bus::typed as bus;
 
 pub const GSB_API_PATH: &str = "gsb-api/v1";
 
@@ -23,18 +28,25 @@ pub struct GsbApiService;
 
 impl GsbApiService {
     pub async fn gsb<Context>(_: Context) -> anyhow::Result<()> {
+        let _ = bus::bind(EVENTS_BUS_ID, |event: Event| async move {
+            EVENTS
+                .send(Publish(event))
+                .await
+                .map_err(|e| ErrorMessage::new(e.to_string()))
+        });
         Ok(())
     }
 
     pub fn rest<Context>(ctx: &Context) -> actix_web::Scope {
-        Self::rest_internal(ctx, crate::services::SERVICES.clone())
+        Self::rest_internal(ctx, crate::services::SERVICES.clone(), EVENTS.clone())
     }
 
     pub(crate) fn rest_internal<Context>(
         _: &Context,
         services: Addr<Services>,
+        events: Addr<EventBroker>,
     ) -> actix_web::Scope {
-        api::web_scope(services)
+        api::web_scope(services, events)
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::{
+    events::SubscribeError,
     services::{BindError, FindError, UnbindError},
     GsbError,
 };
@@ -52,12 +53,27 @@ pub(crate) struct ServiceListenResponse {
     pub(crate) components: Vec<String>,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EventsQuery {
+    /// Comma separated event categories.
+    /// Example value: "market,payment"
+    pub categories: Option<String>,
+    /// Comma separated event kinds.
+    /// Example value: "AgreementApprovedEvent,InvoiceSettledEvent"
+    pub kinds: Option<String>,
+    /// Token of the last received event. Stream will start from the following one.
+    pub resume_token: Option<String>,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum GsbApiError {
     #[error("Bad request: {0}")]
     BadRequest(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Gone: {0}")]
+    Gone(String),
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -90,6 +106,15 @@ impl From<FindError> for GsbApiError {
     }
 }
 
+impl From<SubscribeError> for GsbApiError {
+    fn from(error: SubscribeError) -> Self {
+        match error {
+            SubscribeError::InvalidToken(_) => Self::BadRequest(error.to_string()),
+            SubscribeError::Expired(_) => Self::Gone(error.to_string()),
+        }
+    }
+}
+
 impl From<GsbError> for GsbApiError {
     fn from(value: GsbError) -> Self {
         GsbApiError::InternalError(format!("GSB error: {value}"))
@@ -119,6 +144,7 @@ impl ResponseError for GsbApiError {
         match *self {
             Self::BadRequest(_) => StatusCode::BAD_REQUEST,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Gone(_) => StatusCode::GONE,
             Self::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -131,6 +157,7 @@ impl ResponseError for GsbApiError {
             GsbApiError::NotFound(message) => {
                 HttpResponse::NotFound().json(ErrorMessage::new(message))
             }
+            GsbApiError::Gone(message) => HttpResponse::Gone().json(ErrorMessage::new(message)),
             GsbApiError::InternalError(message) => {
                 HttpResponse::InternalServerError().json(ErrorMessage::new(message))
             }
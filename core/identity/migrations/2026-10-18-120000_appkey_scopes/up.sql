@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE app_key ADD COLUMN "scopes" TEXT NULL;
+ALTER TABLE app_key ADD COLUMN "expires_at" DATETIME NULL;
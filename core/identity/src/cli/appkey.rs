@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
 use structopt::*;
 
 use ya_core_model::appkey as model;
@@ -21,6 +22,22 @@ pub enum AppKeyCommand {
         /// Set cors policy for request made using this app-key.
         #[structopt(long)]
         allow_origins: Vec<String>,
+        /// Restrict the app-key to given scopes, e.g. `market:read`, `payment:write`,
+        /// `activity:exec`. Grants access to the whole API when omitted.
+        #[structopt(long = "scope")]
+        scopes: Vec<model::Scope>,
+        /// Make the app-key expire after given time, e.g. `30days` or `12h`.
+        #[structopt(long, parse(try_from_str = humantime::parse_duration))]
+        expires_in: Option<std::time::Duration>,
+    },
+    /// Replace the key of an existing app-key, keeping its name and scopes.
+    Rotate {
+        name: String,
+        #[structopt(long)]
+        id: Option<String>,
+        /// Set new expiration time counted from now, e.g. `30days` or `12h`.
+        #[structopt(long, parse(try_from_str = humantime::parse_duration))]
+        expires_in: Option<std::time::Duration>,
     },
     Drop {
         name: String,
@@ -50,6 +67,12 @@ impl AppKeyCommand {
             .ok_or_else(|| anyhow::Error::msg("Identity not found"))
     }
 
+    fn expires_at(expires_in: &Option<std::time::Duration>) -> Result<Option<NaiveDateTime>> {
+        expires_in
+            .map(|expires_in| Ok(Utc::now().naive_utc() + Duration::from_std(expires_in)?))
+            .transpose()
+    }
+
     pub async fn run_command(&self, _ctx: &CliCtx) -> Result<CommandOutput> {
         match &self {
             AppKeyCommand::Create {
@@ -57,6 +80,8 @@ impl AppKeyCommand {
                 role,
                 id,
                 allow_origins: allow_origin,
+                scopes,
+                expires_in,
             } => {
                 let identity = match id {
                     Some(id) => {
@@ -75,10 +100,26 @@ impl AppKeyCommand {
                     role: role.clone(),
                     identity,
                     allow_origins: allow_origin.clone(),
+                    scopes: (!scopes.is_empty()).then(|| scopes.clone()),
+                    expires_at: Self::expires_at(expires_in)?,
+                    key: None,
                 };
                 let key = bus::service(model::BUS_ID).send(create).await??;
                 Ok(CommandOutput::Object(serde_json::to_value(key)?))
             }
+            AppKeyCommand::Rotate {
+                name,
+                id,
+                expires_in,
+            } => {
+                let rotate = model::Rotate {
+                    name: name.clone(),
+                    identity: id.clone(),
+                    expires_at: Self::expires_at(expires_in)?,
+                };
+                let key = bus::service(model::BUS_ID).send(rotate).await??;
+                Ok(CommandOutput::Object(serde_json::to_value(key)?))
+            }
             AppKeyCommand::Drop { name, id } => {
                 let remove = model::Remove {
                     name: name.clone(),
@@ -118,6 +159,8 @@ impl AppKeyCommand {
                         "id".into(),
                         "role".into(),
                         "created".into(),
+                        "scopes".into(),
+                        "expires".into(),
                     ],
                     values: result
                         .0
@@ -126,6 +169,7 @@ impl AppKeyCommand {
                             serde_json::json! {[
                                 app_key.name, app_key.key, app_key.identity,
                                 app_key.role, app_key.created_date,
+                                app_key.scopes, app_key.expires_at,
                             ]}
                         })
                         .collect(),
@@ -16,6 +16,7 @@ use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::typed as bus;
 use ya_service_bus::RpcEndpoint;
 
+mod backup;
 mod drop_id;
 mod list;
 
@@ -136,6 +137,11 @@ pub enum IdentityCommand {
         #[structopt(long = "from-private-key")]
         from_private_key: Option<String>,
 
+        /// Existing private key as a 24-word key phrase exported with `--key-phrase` (unsafe) - use keystore instead.
+        /// Wallet seed phrases are not supported.
+        #[structopt(long = "from-key-phrase")]
+        from_key_phrase: Option<String>,
+
         /// Password from argument (unsafe) - do not pass this argument and you will be prompted for password in safe way.
         #[structopt(long = "password")]
         password: Option<String>,
@@ -173,10 +179,35 @@ pub enum IdentityCommand {
         #[structopt(long = "plain")]
         plain: bool,
 
+        /// Export unencrypted private key as a 24-word yagna key phrase.
+        /// It can be imported with `--from-key-phrase`, but not into wallets.
+        #[structopt(long = "key-phrase", conflicts_with = "plain")]
+        key_phrase: bool,
+
         /// File path where identity will be written. Defaults to `stdout`
         #[structopt(long = "file-path")]
         file_path: Option<PathBuf>,
     },
+
+    /// Writes identities, app-keys and payment accounts to an encrypted file
+    Backup {
+        /// File path where backup will be written
+        file_path: PathBuf,
+
+        /// Password from argument (unsafe) - do not pass this argument and you will be prompted for password in safe way.
+        #[structopt(long = "password")]
+        password: Option<String>,
+    },
+
+    /// Restores identities, app-keys and payment accounts from a backup file
+    Restore {
+        /// Backup file path
+        file_path: PathBuf,
+
+        /// Password from argument (unsafe) - do not pass this argument and you will be prompted for password in safe way.
+        #[structopt(long = "password")]
+        password: Option<String>,
+    },
 }
 
 #[derive(StructOpt, Debug)]
@@ -211,16 +242,7 @@ fn to_private_key(key_file_json: &str) -> Result<[u8; 32], anyhow::Error> {
         Err(e) => return Err(anyhow!(e)),
     };
 
-    // HACK, due to hidden secret key data we have to use this little hack to extract private key
-    let pass = Protected::new::<Vec<u8>>("hack".into());
-
-    secret
-        .to_crypto(&pass, 1)
-        .map_err(|err| anyhow!("Failed to encrypt private key: {}", err))?
-        .decrypt(&pass)
-        .map_err(|err| anyhow!("Failed to decrypt private key: {}", err))?
-        .try_into()
-        .map_err(|_| anyhow!("Wrong key length after decryption"))
+    crate::id_key::private_key_bytes(&secret)
 }
 
 impl IdentityCommand {
@@ -312,13 +334,19 @@ impl IdentityCommand {
                 alias,
                 from_keystore,
                 from_private_key,
+                from_key_phrase,
                 password,
                 no_password,
             } => {
-                if from_keystore.is_some() && from_private_key.is_some() {
-                    anyhow::bail!("Only one of --from-keystore or --from-private-key can be used")
+                let sources = [
+                    from_keystore.is_some(),
+                    from_private_key.is_some(),
+                    from_key_phrase.is_some(),
+                ];
+                if sources.iter().filter(|source| **source).count() > 1 {
+                    anyhow::bail!("Only one of --from-keystore, --from-private-key or --from-key-phrase can be used")
                 }
-                if from_private_key.is_some() {
+                if from_private_key.is_some() || from_key_phrase.is_some() {
                     log::warn!("Using private key directly is not recommended. Use keystore instead. Your key could leak in command history, check and clean logs.")
                 }
 
@@ -334,6 +362,8 @@ impl IdentityCommand {
                         anyhow::anyhow!("Ethereum key has to be 32 bytes long. Provide hex string of length 64 - {e}")
                     )?;
                     Some(slice)
+                } else if let Some(phrase) = from_key_phrase {
+                    Some(crate::id_key::private_key_from_key_phrase(phrase)?)
                 } else {
                     None
                 };
@@ -415,6 +445,7 @@ impl IdentityCommand {
                 node_or_alias,
                 file_path,
                 plain,
+                key_phrase,
             } => {
                 let node_id = node_or_alias.clone().unwrap_or_default().resolve().await?;
                 let mut key_file = bus::service(identity::BUS_ID)
@@ -429,6 +460,9 @@ impl IdentityCommand {
                         Err(e) => anyhow::bail!(e),
                     };
                     key_file = decrypted_key;
+                } else if *key_phrase {
+                    let private_key = to_private_key(&key_file)?;
+                    key_file = crate::id_key::key_phrase_from_private_key(&private_key)?;
                 }
 
                 match file_path {
@@ -443,6 +477,14 @@ impl IdentityCommand {
                     None => CommandOutput::object(key_file),
                 }
             }
+            IdentityCommand::Backup {
+                file_path,
+                password,
+            } => backup::backup(file_path, password.clone()).await,
+            IdentityCommand::Restore {
+                file_path,
+                password,
+            } => backup::restore(file_path, password.clone()).await,
         }
     }
 }
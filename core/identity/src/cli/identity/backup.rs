@@ -0,0 +1,270 @@
+use std::path::Path;
+
+use ethsign::keyfile::Crypto;
+use ethsign::Protected;
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::payment::Account;
+use ya_client_model::NodeId;
+use ya_core_model::appkey;
+use ya_core_model::driver::{driver_bus_id, AccountMode, Init};
+use ya_core_model::payment::local as pay;
+
+use super::{anyhow, bus, identity, CommandOutput, Context, Result, RpcEndpoint};
+use crate::id_key::KEY_ITERATIONS;
+
+/// Version of the backup bundle format, bumped on incompatible changes.
+const BACKUP_VERSION: u32 = 1;
+const APP_KEYS_PAGE_SIZE: u32 = 100;
+
+/// Bundle written to disk. Content is encrypted the same way as keystore files.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupFile {
+    version: u32,
+    created_date: chrono::DateTime<chrono::Utc>,
+    crypto: Crypto,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Backup {
+    identities: Vec<IdentityBackup>,
+    app_keys: Vec<appkey::AppKey>,
+    payment_accounts: Vec<Account>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdentityBackup {
+    node_id: NodeId,
+    alias: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+    is_default: bool,
+    /// Keystore JSON, still protected with identity password.
+    key_file: String,
+}
+
+pub async fn backup(file_path: &Path, password: Option<String>) -> Result<CommandOutput> {
+    if file_path.exists() {
+        anyhow::bail!("File already exists")
+    }
+
+    let mut identities = Vec::new();
+    for id in list_identities().await? {
+        let key_file = bus::service(identity::BUS_ID)
+            .send(identity::GetKeyFile(id.node_id))
+            .await?
+            .map_err(anyhow::Error::msg)?;
+        let note = bus::service(identity::BUS_ID)
+            .send(identity::GetNote(id.node_id))
+            .await?
+            .map_err(anyhow::Error::msg)?;
+        identities.push(IdentityBackup {
+            node_id: id.node_id,
+            alias: id.alias,
+            note,
+            is_default: id.is_default,
+            key_file,
+        });
+    }
+
+    let backup = Backup {
+        identities,
+        app_keys: list_app_keys().await?,
+        payment_accounts: list_payment_accounts().await,
+    };
+
+    let password = match password {
+        Some(password) => Protected::from(password.as_str()),
+        None => read_new_password()?,
+    };
+    let plain = serde_json::to_vec(&backup)?;
+    let crypto = Crypto::encrypt(&plain, &password, KEY_ITERATIONS)
+        .map_err(|e| anyhow!("Failed to encrypt backup: {}", e))?;
+    let file = BackupFile {
+        version: BACKUP_VERSION,
+        created_date: chrono::Utc::now(),
+        crypto,
+    };
+    std::fs::write(file_path, serde_json::to_string_pretty(&file)?)?;
+
+    CommandOutput::object(serde_json::json!({
+        "file": file_path.display().to_string(),
+        "identities": backup.identities.len(),
+        "appKeys": backup.app_keys.len(),
+        "paymentAccounts": backup.payment_accounts.len(),
+    }))
+}
+
+pub async fn restore(file_path: &Path, password: Option<String>) -> Result<CommandOutput> {
+    let content = std::fs::read_to_string(file_path)
+        .with_context(|| format!("unable to read backup file {}", file_path.display()))?;
+    let file: BackupFile = serde_json::from_str(&content).context("invalid backup file")?;
+    if file.version > BACKUP_VERSION {
+        anyhow::bail!(
+            "Backup version {} is not supported, upgrade yagna first",
+            file.version
+        )
+    }
+
+    let password = match password {
+        Some(password) => Protected::from(password.as_str()),
+        None => rpassword::read_password_from_tty(Some("Backup password: "))?.into(),
+    };
+    let plain = file.crypto.decrypt(&password).map_err(|e| match e {
+        ethsign::Error::InvalidPassword => anyhow!("Invalid password"),
+        e => anyhow!("Failed to decrypt backup: {}", e),
+    })?;
+    let backup: Backup = serde_json::from_slice(&plain).context("invalid backup content")?;
+
+    let mut restored_ids = Vec::new();
+    let mut skipped_ids = Vec::new();
+    for id in &backup.identities {
+        let existing = bus::service(identity::BUS_ID)
+            .send(identity::Get::ByNodeId(id.node_id))
+            .await?
+            .map_err(anyhow::Error::msg)?;
+        if existing.is_some() {
+            skipped_ids.push(id.node_id);
+            continue;
+        }
+
+        bus::service(identity::BUS_ID)
+            .send(identity::CreateGenerated {
+                alias: id.alias.clone(),
+                from_keystore: Some(id.key_file.clone()),
+            })
+            .await?
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("unable to restore identity {}", id.node_id))?;
+        restored_ids.push(id.node_id);
+
+        if id.note.is_some() {
+            bus::service(identity::BUS_ID)
+                .send(identity::SetNote {
+                    node_id: id.node_id,
+                    note: id.note.clone(),
+                })
+                .await?
+                .map_err(anyhow::Error::msg)?;
+        }
+
+        if id.is_default {
+            bus::service(identity::BUS_ID)
+                .send(identity::Update::with_id(id.node_id).with_default(true))
+                .await?
+                .map_err(anyhow::Error::msg)?;
+        }
+    }
+
+    let mut restored_keys = Vec::new();
+    for app_key in backup.app_keys {
+        let name = app_key.name.clone();
+        let create = appkey::Create {
+            name: app_key.name,
+            role: app_key.role,
+            identity: app_key.identity,
+            allow_origins: app_key.allow_origins,
+            scopes: app_key.scopes,
+            expires_at: app_key.expires_at,
+            key: Some(app_key.key),
+        };
+        match bus::service(appkey::BUS_ID).send(create).await? {
+            Ok(_) => restored_keys.push(name),
+            Err(e) => log::warn!("Unable to restore app-key {}: {}", name, e),
+        }
+    }
+
+    let mut restored_accounts = Vec::new();
+    for account in backup.payment_accounts {
+        let address = account.address.clone();
+        match init_payment_account(account).await {
+            Ok(_) => restored_accounts.push(address),
+            Err(e) => log::warn!("Unable to restore payment account {}: {}", address, e),
+        }
+    }
+
+    CommandOutput::object(serde_json::json!({
+        "restoredIdentities": restored_ids,
+        "skippedIdentities": skipped_ids,
+        "restoredAppKeys": restored_keys,
+        "restoredPaymentAccounts": restored_accounts,
+    }))
+}
+
+async fn list_identities() -> Result<Vec<identity::IdentityInfo>> {
+    let identities = bus::service(identity::BUS_ID)
+        .send(identity::List::default())
+        .await?
+        .map_err(anyhow::Error::msg)?;
+    Ok(identities.into_iter().filter(|id| !id.deleted).collect())
+}
+
+async fn list_app_keys() -> Result<Vec<appkey::AppKey>> {
+    let mut app_keys = Vec::new();
+    let mut page = 1;
+    loop {
+        let list = appkey::List {
+            identity: None,
+            page,
+            per_page: APP_KEYS_PAGE_SIZE,
+        };
+        let (keys, pages) = bus::service(appkey::BUS_ID)
+            .send(list)
+            .await?
+            .map_err(anyhow::Error::msg)?;
+        app_keys.extend(
+            keys.into_iter()
+                // autoconfigured key comes from the environment, not from the database
+                .filter(|key| key.name != appkey::AUTOCONFIGURED_KEY_NAME),
+        );
+        if page >= pages {
+            break;
+        }
+        page += 1;
+    }
+    Ok(app_keys)
+}
+
+async fn list_payment_accounts() -> Vec<Account> {
+    match bus::service(pay::BUS_ID).send(pay::GetAccounts {}).await {
+        Ok(Ok(accounts)) => accounts,
+        Ok(Err(e)) => {
+            log::warn!("Payment accounts not included in backup: {}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            log::warn!("Payment accounts not included in backup: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn init_payment_account(account: Account) -> Result<()> {
+    let mut mode = AccountMode::empty();
+    mode.set(AccountMode::SEND, account.send);
+    mode.set(AccountMode::RECV, account.receive);
+
+    bus::service(driver_bus_id(&account.driver))
+        .call(Init::new(
+            account.address,
+            Some(account.network),
+            Some(account.token),
+            mode,
+        ))
+        .await?
+        .map_err(anyhow::Error::msg)?;
+    Ok(())
+}
+
+fn read_new_password() -> Result<Protected> {
+    let password: Protected = rpassword::read_password_from_tty(Some("Backup password: "))?.into();
+    let password2: Protected =
+        rpassword::read_password_from_tty(Some("Confirm password: "))?.into();
+    if password.as_ref() != password2.as_ref() {
+        anyhow::bail!("Password and confirmation do not match.")
+    }
+    Ok(password)
+}
@@ -1,11 +1,12 @@
 pub use crate::dao::Error as DaoError;
 pub use crate::db::models::{AppKey, Role};
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 
 use diesel::{ExpressionMethods, RunQueryDsl};
 use std::cmp::max;
 use ya_client_model::NodeId;
+use ya_core_model::appkey::Scope;
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -49,12 +50,16 @@ impl<'c> AppKeyDao<'c> {
         role: String,
         identity: NodeId,
         cors_allow_origin: Vec<String>,
+        scopes: Option<Vec<Scope>>,
+        expires_at: Option<NaiveDateTime>,
     ) -> Result<()> {
         use crate::db::schema::app_key as app_key_dsl;
         use crate::db::schema::role as role_dsl;
 
         let cors_allow_origin =
             Some(serde_json::to_string(&cors_allow_origin).unwrap_or_else(|_| "[]".to_string()));
+        let scopes = scopes
+            .map(|scopes| serde_json::to_string(&scopes).unwrap_or_else(|_| "[]".to_string()));
 
         do_with_transaction(self.pool, "app_key_dao_create", move |conn| {
             let role: Role = role_dsl::table
@@ -69,6 +74,8 @@ impl<'c> AppKeyDao<'c> {
                     app_key_dsl::identity_id.eq(identity),
                     app_key_dsl::created_date.eq(Utc::now().naive_utc()),
                     app_key_dsl::allow_origins.eq(cors_allow_origin),
+                    app_key_dsl::scopes.eq(scopes),
+                    app_key_dsl::expires_at.eq(expires_at),
                 ))
                 .execute(conn)?;
 
@@ -155,6 +162,37 @@ impl<'c> AppKeyDao<'c> {
         .await
     }
 
+    pub async fn rotate(
+        &self,
+        name: String,
+        identity: Option<String>,
+        key: String,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        use crate::db::schema::app_key as app_key_dsl;
+
+        self.with_transaction("app_key_dao_rotate", move |conn| {
+            let mut query = app_key_dsl::table
+                .select(app_key_dsl::id)
+                .filter(app_key_dsl::name.eq(name))
+                .into_boxed();
+            if let Some(id) = identity {
+                query = query.filter(app_key_dsl::identity_id.eq(id));
+            }
+            let id: i32 = query.first(conn).optional()?.ok_or(DaoError::NotFound)?;
+
+            diesel::update(app_key_dsl::table.find(id))
+                .set((
+                    app_key_dsl::key.eq(key),
+                    expires_at.map(|expires_at| app_key_dsl::expires_at.eq(expires_at)),
+                ))
+                .execute(conn)?;
+
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn remove(&self, name: String, identity: Option<String>) -> Result<()> {
         use crate::db::schema::app_key as app_key_dsl;
 
@@ -7,6 +7,7 @@ use ya_persistence::executor::{
 
 pub use crate::db::models::Identity;
 use crate::db::schema as s;
+use ya_client_model::NodeId;
 
 type Result<T> = std::result::Result<T, super::Error>;
 
@@ -81,6 +82,28 @@ impl<'c> IdentityDao<'c> {
         Ok(())
     }
 
+    pub async fn get_note(&self, identity_id: NodeId) -> Result<Option<String>> {
+        readonly_transaction(self.pool, "identity_dao_get_note", move |conn| {
+            Ok(s::identity::table
+                .filter(s::identity::identity_id.eq(identity_id))
+                .select(s::identity::note)
+                .first::<Option<String>>(conn)
+                .optional()?
+                .flatten())
+        })
+        .await
+    }
+
+    pub async fn update_note(&self, identity_id: NodeId, note: Option<String>) -> Result<()> {
+        self.with_transaction("identity_dao_update_note", move |conn| {
+            diesel::update(s::identity::table.filter(s::identity::identity_id.eq(identity_id)))
+                .set(s::identity::note.eq(note))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn mark_deleted(&self, identity_id: String) -> Result<()> {
         use crate::db::schema::app_key as app_key_dsl;
 
@@ -30,6 +30,8 @@ pub struct AppKey {
     pub identity_id: NodeId,
     pub created_date: NaiveDateTime,
     pub allow_origins: Option<String>,
+    pub scopes: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
@@ -51,6 +53,11 @@ impl AppKey {
                 .allow_origins
                 .map(|allowed| serde_json::from_str(&allowed).unwrap_or(vec![]))
                 .unwrap_or(vec![]),
+            // unreadable scopes must not turn into full access
+            scopes: self
+                .scopes
+                .map(|scopes| serde_json::from_str(&scopes).unwrap_or(vec![])),
+            expires_at: self.expires_at,
         }
     }
 }
@@ -9,6 +9,8 @@ diesel::table! {
         identity_id -> Text,
         created_date -> Timestamp,
         allow_origins -> Nullable<Text>,
+        scopes -> Nullable<Text>,
+        expires_at -> Nullable<Timestamp>,
     }
 }
 
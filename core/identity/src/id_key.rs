@@ -1,6 +1,6 @@
 #![allow(unused)]
 
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 use std::{mem, slice};
 
 use anyhow::Context;
@@ -130,7 +130,7 @@ impl TryFrom<Identity> for IdentityKey {
     }
 }
 
-const KEY_ITERATIONS: u32 = 10240;
+pub(crate) const KEY_ITERATIONS: u32 = 10240;
 const KEYSTORE_VERSION: u64 = 3;
 
 pub fn default_password() -> Protected {
@@ -178,9 +178,42 @@ pub fn generate_new_keyfile(
     serde_json::to_string(&key_file).context("serialize keyfile")
 }
 
+/// Extracts raw private key bytes, which `SecretKey` doesn't expose.
+pub fn private_key_bytes(secret: &SecretKey) -> anyhow::Result<[u8; 32]> {
+    // HACK, due to hidden secret key data we have to use this little hack to extract private key
+    let pass = Protected::new::<Vec<u8>>("hack".into());
+
+    secret
+        .to_crypto(&pass, 1)
+        .map_err(|err| anyhow::anyhow!("Failed to encrypt private key: {}", err))?
+        .decrypt(&pass)
+        .map_err(|err| anyhow::anyhow!("Failed to decrypt private key: {}", err))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Wrong key length after decryption"))
+}
+
+/// Encodes private key as a yagna key phrase: 24 BIP-39 words with the key bytes used
+/// as entropy. It is not a wallet seed phrase - there is no BIP-32/44 derivation, so
+/// the phrase can only be imported back with [`private_key_from_key_phrase`].
+pub fn key_phrase_from_private_key(private_key: &[u8; 32]) -> anyhow::Result<String> {
+    let mnemonic = bip39::Mnemonic::from_entropy(private_key, bip39::Language::English)?;
+    Ok(mnemonic.phrase().to_string())
+}
+
+/// Decodes private key from a yagna key phrase created by [`key_phrase_from_private_key`].
+/// Wallet seed phrases are rejected unless they happen to have 24 words, in which case
+/// they import a different key than the wallet would derive.
+pub fn private_key_from_key_phrase(phrase: &str) -> anyhow::Result<[u8; 32]> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mnemonic = bip39::Mnemonic::from_phrase(&phrase, bip39::Language::English)?;
+    <[u8; 32]>::try_from(mnemonic.entropy())
+        .map_err(|_| anyhow::anyhow!("Yagna key phrase has to consist of 24 words"))
+}
+
 #[cfg(test)]
 mod test {
     use rustc_hex::FromHex;
+    use std::convert::TryInto;
 
     use super::*;
 
@@ -194,4 +227,44 @@ mod test {
         println!("{}", serde_json::to_string_pretty(&key_file)?);
         Ok(())
     }
+
+    #[test]
+    fn test_key_phrase_roundtrip() -> anyhow::Result<()> {
+        let pk = "c19a9a827c9efb910e3e4efb955b57d072775c5ebb93dbdd4d6856d97e555eca";
+        let pk_bytes: [u8; 32] = pk.from_hex::<Vec<u8>>()?.as_slice().try_into()?;
+
+        let phrase = key_phrase_from_private_key(&pk_bytes)?;
+        assert_eq!(phrase.split(' ').count(), 24);
+        assert_eq!(
+            private_key_from_key_phrase(&format!(" {phrase}\n"))?,
+            pk_bytes
+        );
+
+        let short = phrase.split(' ').take(12).collect::<Vec<_>>().join(" ");
+        assert!(private_key_from_key_phrase(&short).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_phrase_export_import() -> anyhow::Result<()> {
+        // export: keystore -> private key -> key phrase
+        let exported: KeyFile =
+            serde_json::from_str(&generate_new_keyfile(Protected::new("old"), None)?)?;
+        let secret = exported.to_secret_key(&Protected::new("old"))?;
+        let phrase = key_phrase_from_private_key(&private_key_bytes(&secret)?)?;
+
+        // import: key phrase -> private key -> keystore with a new password
+        let private_key = private_key_from_key_phrase(&phrase)?;
+        let imported: KeyFile = serde_json::from_str(&generate_new_keyfile(
+            Protected::new("new"),
+            Some(private_key),
+        )?)?;
+
+        let imported_secret = imported.to_secret_key(&Protected::new("new"))?;
+        assert_eq!(
+            imported_secret.public().address(),
+            secret.public().address()
+        );
+        Ok(())
+    }
 }
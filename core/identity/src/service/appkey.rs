@@ -69,6 +69,8 @@ pub async fn preconfigured_to_appkey_model(
         identity: node_id,
         created_date,
         allow_origins: vec![],
+        scopes: None,
+        expires_at: None,
     })
 }
 
@@ -100,7 +102,10 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
         let db = db.clone();
         let preconfigured_appkey = preconfigured_appkey.clone();
         let _ = bus::bind(model::BUS_ID, move |create: model::Create| {
-            let key = Uuid::new_v4().to_simple().to_string();
+            let key = create
+                .key
+                .clone()
+                .unwrap_or_else(|| Uuid::new_v4().to_simple().to_string());
             let db = db.clone();
             let preconfigured_appkey = preconfigured_appkey.clone();
             let mut create_tx = create_tx.clone();
@@ -133,6 +138,8 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
                             create.role,
                             create.identity,
                             create.allow_origins,
+                            create.scopes,
+                            create.expires_at,
                         )
                         .await
                         .map_err(model::Error::internal)
@@ -246,6 +253,48 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
         });
     }
 
+    {
+        let rotate_tx = tx.clone();
+        let db = db.clone();
+        let preconfigured_appkey = preconfigured_appkey.clone();
+        let _ = bus::bind(model::BUS_ID, move |rotate: model::Rotate| {
+            let db = db.clone();
+            let preconfigured_appkey = preconfigured_appkey.clone();
+            let mut rotate_tx = rotate_tx.clone();
+            async move {
+                if preconfigured_appkey.is_some() && model::AUTOCONFIGURED_KEY_NAME == rotate.name {
+                    return Err(model::Error::bad_request(
+                        "Cannot rotate autoconfigured key",
+                    ));
+                }
+
+                let dao = db.as_dao::<AppKeyDao>();
+                let (old_appkey, old_role) = dao
+                    .get_for_name(rotate.name.clone())
+                    .await
+                    .map_err(|e| model::Error::internal(e.to_string()))?;
+
+                let key = Uuid::new_v4().to_simple().to_string();
+                dao.rotate(rotate.name, rotate.identity, key.clone(), rotate.expires_at)
+                    .await
+                    .map_err(Into::<model::Error>::into)?;
+
+                let (appkey, role) = dao
+                    .get(key.clone())
+                    .await
+                    .map_err(|e| model::Error::internal(e.to_string()))?;
+
+                let _ = rotate_tx
+                    .send(AppKeyEvent::DroppedKey(old_appkey.to_core_model(old_role)))
+                    .await;
+                let _ = rotate_tx
+                    .send(AppKeyEvent::NewKey(appkey.to_core_model(role)))
+                    .await;
+                Ok(key)
+            }
+        });
+    }
+
     {
         let create_tx = tx;
         let db = db.clone();
@@ -465,6 +465,25 @@ impl IdentityService {
         key.to_key_file().map_err(model::Error::new_err_msg)
     }
 
+    pub async fn get_note(&mut self, get: model::GetNote) -> Result<Option<String>, model::Error> {
+        self.get_key_by_id(&get.0)?;
+        self.db
+            .as_dao::<IdentityDao>()
+            .get_note(get.0)
+            .await
+            .map_err(model::Error::new_err_msg)
+    }
+
+    pub async fn set_note(&mut self, set: model::SetNote) -> Result<model::Ack, model::Error> {
+        self.get_key_by_id(&set.node_id)?;
+        self.db
+            .as_dao::<IdentityDao>()
+            .update_note(set.node_id, set.note)
+            .await
+            .map_err(model::Error::new_err_msg)?;
+        Ok(model::Ack {})
+    }
+
     pub fn bind_service(me: Arc<Mutex<Self>>) {
         let this = me.clone();
         let _ = bus::bind(model::BUS_ID, move |_list: model::List| {
@@ -590,6 +609,16 @@ impl IdentityService {
             let this = this.clone();
             async move { this.lock().await.get_key_file(node_id).await }
         });
+        let this = me.clone();
+        let _ = bus::bind(model::BUS_ID, move |get: model::GetNote| {
+            let this = this.clone();
+            async move { this.lock().await.get_note(get).await }
+        });
+        let this = me.clone();
+        let _ = bus::bind(model::BUS_ID, move |set: model::SetNote| {
+            let this = this.clone();
+            async move { this.lock().await.set_note(set).await }
+        });
         let this = me;
         let _ = bus::bind(model::BUS_ID, move |drop_cmd: model::DropId| {
             let this = this.clone();
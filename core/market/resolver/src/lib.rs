@@ -6,7 +6,8 @@ pub mod resolver;
 
 use resolver::error::MatchError as InternalMatchErorr;
 
-use crate::resolver::properties::PropertyRef;
+use crate::resolver::expression::{Expression, ResolveResult};
+use crate::resolver::properties::{PropertyRef, PropertySet};
 use flatten::{flatten_properties, FlattenError};
 use resolver::error::PrepareError;
 pub use resolver::matching::{match_weak, MatchResult};
@@ -60,6 +61,113 @@ pub fn match_demand_offer(
     }
 }
 
+/// Outcome of resolving constraints (or a single constraint clause).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Yes,
+    No,
+    Undefined,
+}
+
+/// Constraint clause, which wasn't satisfied by the other side properties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClauseExplanation {
+    /// Clause rendered in LDAP filter syntax.
+    pub clause: String,
+    pub verdict: Verdict,
+    /// Properties referenced by the clause, which are missing on the other side.
+    pub undefined_properties: Vec<String>,
+}
+
+/// Constraints of one side resolved against properties of the other side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintsExplanation {
+    pub verdict: Verdict,
+    /// Top-level clauses of the constraints, which resolved to `No` or `Undefined`.
+    pub clauses: Vec<ClauseExplanation>,
+}
+
+/// Structured explanation of the match relation between Demand and Offer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub verdict: Verdict,
+    /// Offer constraints resolved against Demand properties.
+    pub offer: ConstraintsExplanation,
+    /// Demand constraints resolved against Offer properties.
+    pub demand: ConstraintsExplanation,
+}
+
+/// Same as `match_demand_offer`, but instead of a bare result tells which
+/// constraint clauses failed or couldn't be resolved, and on which side.
+pub fn explain_demand_offer(
+    demand_properties: &str,
+    demand_constraints: &str,
+    offer_properties: &str,
+    offer_constraints: &str,
+) -> Result<Explanation, MatchError> {
+    let demand = Demand::from(demand_properties, demand_constraints)?;
+    let prep_demand = PreparedDemand::from(&demand)?;
+    let offer = Offer::from(offer_properties, offer_constraints)?;
+    let prep_offer = PreparedOffer::from(&offer)?;
+
+    let demand_explanation =
+        explain_constraints("Demand", &prep_demand.constraints, &prep_offer.properties)?;
+    let offer_explanation =
+        explain_constraints("Offer", &prep_offer.constraints, &prep_demand.properties)?;
+
+    let verdict = match (demand_explanation.verdict, offer_explanation.verdict) {
+        (Verdict::Undefined, _) | (_, Verdict::Undefined) => Verdict::Undefined,
+        (Verdict::Yes, Verdict::Yes) => Verdict::Yes,
+        _ => Verdict::No,
+    };
+
+    Ok(Explanation {
+        verdict,
+        offer: offer_explanation,
+        demand: demand_explanation,
+    })
+}
+
+fn explain_constraints(
+    side: &str,
+    constraints: &Expression,
+    properties: &PropertySet,
+) -> Result<ConstraintsExplanation, InternalMatchErorr> {
+    let (verdict, _) = explain_result(side, constraints.resolve(properties))?;
+    let mut clauses = vec![];
+
+    for clause in constraints.clauses() {
+        let (clause_verdict, undefined) = explain_result(side, clause.resolve(properties))?;
+        if clause_verdict != Verdict::Yes {
+            let mut undefined_properties = extract_names(&undefined);
+            undefined_properties.sort();
+            undefined_properties.dedup();
+            clauses.push(ClauseExplanation {
+                clause: clause.to_string(),
+                verdict: clause_verdict,
+                undefined_properties,
+            });
+        }
+    }
+
+    Ok(ConstraintsExplanation { verdict, clauses })
+}
+
+fn explain_result<'a>(
+    side: &str,
+    result: ResolveResult<'a>,
+) -> Result<(Verdict, Vec<&'a PropertyRef>), InternalMatchErorr> {
+    match result {
+        ResolveResult::True => Ok((Verdict::Yes, vec![])),
+        ResolveResult::False(props, _) => Ok((Verdict::No, props)),
+        ResolveResult::Undefined(props, _) => Ok((Verdict::Undefined, props)),
+        ResolveResult::Err(e) => Err(InternalMatchErorr::new(&format!(
+            "Error resolving {} constraints: {}",
+            side, e
+        ))),
+    }
+}
+
 fn extract_names(props_vec: &[&PropertyRef]) -> Vec<String> {
     props_vec
         .iter()
@@ -1,9 +1,12 @@
+use std::fmt;
 use std::str;
 
 use asnom::structures::{ExplicitTag, OctetString, Tag};
+use semver::VersionReq;
 
 use super::error::{ExpressionError, ResolveError};
 use super::ldap_parser;
+use super::prop_parser::parse_prop_ref_as_list;
 use super::properties::{parse_prop_ref, Property, PropertyRef, PropertySet, PropertyValue};
 
 // Expression resolution result enum
@@ -23,6 +26,10 @@ pub enum Expression {
     GreaterEqual(PropertyRef, String), // property ref, value
     Less(PropertyRef, String),         // property ref, value
     LessEqual(PropertyRef, String),    // property ref, value
+    Approx(PropertyRef, String),       // property ref, value
+    In(PropertyRef, String),           // property ref, list of values
+    Contains(PropertyRef, String),     // property ref, list of values
+    VersionMatch(PropertyRef, String), // property ref, version requirement
     Present(PropertyRef),              // property ref
     Or(Vec<Expression>),               // operands
     And(Vec<Expression>),              // operands
@@ -71,6 +78,10 @@ impl Expression {
             | Expression::GreaterEqual(prop, _)
             | Expression::Less(prop, _)
             | Expression::LessEqual(prop, _)
+            | Expression::Approx(prop, _)
+            | Expression::In(prop, _)
+            | Expression::Contains(prop, _)
+            | Expression::VersionMatch(prop, _)
             | Expression::Present(prop) => vec![prop],
             Expression::And(exprs) | Expression::Or(exprs) => {
                 exprs.iter().flat_map(|expr| expr.property_refs()).collect()
@@ -133,6 +144,30 @@ impl Expression {
                 property_set,
                 |prop_value: &PropertyValue, val: &str| -> bool { prop_value.greater_equal(val) },
             ),
+            Expression::Approx(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.approx_equals(val) },
+            ),
+            Expression::In(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.in_list(val) },
+            ),
+            Expression::Contains(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.contains_list(val) },
+            ),
+            Expression::VersionMatch(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.matches_version(val) },
+            ),
             // other binary operators here if needed...
             Expression::And(inner_expressions) => self.resolve_and(inner_expressions, property_set),
             Expression::Or(inner_expressions) => self.resolve_or(inner_expressions, property_set),
@@ -373,6 +408,46 @@ impl Expression {
             }
         }
     }
+
+    // Split the expression into top-level clauses, ie. operands of (nested) AND expressions.
+    // Each clause has to be satisfied for the whole expression to be satisfied.
+    pub fn clauses(&self) -> Vec<&Expression> {
+        match self {
+            Expression::And(exprs) => exprs.iter().flat_map(|expr| expr.clauses()).collect(),
+            expr => vec![expr],
+        }
+    }
+}
+
+// Renders the expression back in LDAP filter syntax
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Equals(attr, val) => write!(f, "({}={})", attr, val),
+            Expression::Greater(attr, val) => write!(f, "({}>{})", attr, val),
+            Expression::GreaterEqual(attr, val) => write!(f, "({}>={})", attr, val),
+            Expression::Less(attr, val) => write!(f, "({}<{})", attr, val),
+            Expression::LessEqual(attr, val) => write!(f, "({}<={})", attr, val),
+            Expression::Approx(attr, val) => write!(f, "({}~={})", attr, val),
+            Expression::In(attr, val) => write!(f, "({}:in:={})", attr, val),
+            Expression::Contains(attr, val) => write!(f, "({}:contains:={})", attr, val),
+            Expression::VersionMatch(attr, val) => write!(f, "({}:version:={})", attr, val),
+            Expression::Present(attr) => write!(f, "({}=*)", attr),
+            Expression::Or(exprs) => {
+                f.write_str("(|")?;
+                exprs.iter().try_for_each(|expr| write!(f, "{}", expr))?;
+                f.write_str(")")
+            }
+            Expression::And(exprs) => {
+                f.write_str("(&")?;
+                exprs.iter().try_for_each(|expr| write!(f, "{}", expr))?;
+                f.write_str(")")
+            }
+            Expression::Not(expr) => write!(f, "(!{})", expr),
+            Expression::Empty(true) => f.write_str("()"),
+            Expression::Empty(false) => f.write_str("(!())"),
+        }
+    }
 }
 
 // #region Expression building
@@ -386,7 +461,9 @@ pub fn build_expression(root: &Tag) -> Result<Expression, ExpressionError> {
             | ldap_parser::TAG_LESS
             | ldap_parser::TAG_LESS_EQUAL
             | ldap_parser::TAG_GREATER
-            | ldap_parser::TAG_GREATER_EQUAL => build_simple_expression(seq.id, &seq.inner),
+            | ldap_parser::TAG_GREATER_EQUAL
+            | ldap_parser::TAG_APPROX => build_simple_expression(seq.id, &seq.inner),
+            ldap_parser::TAG_EXTENSIBLE => build_extensible_expression(&seq.inner),
             _ => Err(ExpressionError::new(&format!(
                 "Unknown sequence type {}",
                 seq.id
@@ -489,6 +566,7 @@ fn build_simple_expression(
                 ldap_parser::TAG_LESS_EQUAL => {
                     Ok(Expression::LessEqual(prop_ref, String::from(result.1)))
                 }
+                ldap_parser::TAG_APPROX => Ok(Expression::Approx(prop_ref, String::from(result.1))),
                 // add other binary operators handling here
                 _ => Err(ExpressionError::new(&format!(
                     "Unknown expression type {}",
@@ -500,6 +578,65 @@ fn build_simple_expression(
     }
 }
 
+// Matching rules supported in extensible match filters: (attr:rule:=value)
+// - in:       property value is one of the listed values, eg. (attr:in:=[a,b])
+//             (for List properties - all list items are listed)
+// - contains: List property contains all listed values, eg. (attr:contains:=[a,b])
+// - version:  Version property matches the requirement, eg. (attr:version:=>=1.2, <2.0)
+fn build_extensible_expression(sequence: &[Tag]) -> Result<Expression, ExpressionError> {
+    if sequence.len() < 3 {
+        return Err(ExpressionError::new(&format!(
+            "Expected 3 tags, got {} tags",
+            sequence.len()
+        )));
+    }
+    let attr = extract_str_from_octet_string(&sequence[0])?;
+    let rule = extract_str_from_octet_string(&sequence[1])?;
+    let value = extract_str_from_octet_string(&sequence[2])?;
+
+    let prop_ref = match parse_prop_ref(attr) {
+        Ok(prop_ref @ PropertyRef::Value(..)) => prop_ref,
+        Ok(PropertyRef::Aspect(..)) => {
+            return Err(ExpressionError::new(&format!(
+                "Matching rule '{}' is not supported for aspect reference {}",
+                rule, attr
+            )))
+        }
+        Err(prop_err) => {
+            return Err(ExpressionError::new(&format!(
+                "Error parsing property reference {}: {}",
+                attr, prop_err
+            )))
+        }
+    };
+
+    match rule {
+        "in" | "contains" => {
+            if let Err(err) = parse_prop_ref_as_list(value) {
+                return Err(ExpressionError::new(&format!(
+                    "Matching rule '{}' requires a list of values, got '{}': {}",
+                    rule, value, err
+                )));
+            }
+            match rule {
+                "in" => Ok(Expression::In(prop_ref, String::from(value))),
+                _ => Ok(Expression::Contains(prop_ref, String::from(value))),
+            }
+        }
+        "version" => match VersionReq::parse(value) {
+            Ok(_) => Ok(Expression::VersionMatch(prop_ref, String::from(value))),
+            Err(err) => Err(ExpressionError::new(&format!(
+                "Invalid version requirement '{}': {}",
+                value, err
+            ))),
+        },
+        _ => Err(ExpressionError::new(&format!(
+            "Unsupported matching rule '{}'",
+            rule
+        ))),
+    }
+}
+
 fn extract_str_from_octet_string(tag: &Tag) -> Result<&str, ExpressionError> {
     match tag {
         Tag::OctetString(oct) => match str::from_utf8(&oct.inner) {
@@ -17,6 +17,8 @@ pub const TAG_GREATER: u64 = 8;
 pub const TAG_GREATER_EQUAL: u64 = 9;
 pub const TAG_LESS: u64 = 10;
 pub const TAG_LESS_EQUAL: u64 = 11;
+pub const TAG_APPROX: u64 = 12;
+pub const TAG_EXTENSIBLE: u64 = 13;
 
 // Parse function
 
@@ -83,13 +85,15 @@ named!(
     })
 );
 
-named!(match_f<Tag>, alt!(present | simple));
+named!(match_f<Tag>, alt!(present | extensible | simple));
 
+// Substring filters, eg. (attr=*value*), are parsed as simple equality with wildcards
 named!(
     present<Tag>,
     do_parse!(
         attr: take_till!(is_delimiter)
             >> tag!("=*")
+            >> peek!(char!(')'))
             >> (Tag::OctetString(OctetString {
                 class: TagClass::Context,
                 id: TAG_PRESENT,
@@ -121,15 +125,47 @@ named!(
     )
 );
 
+// Extensible match in the form of (attr:rule:=value)
+named!(
+    extensible<Tag>,
+    do_parse!(
+        attr: take_till!(is_extensible_delimiter)
+            >> char!(':')
+            >> rule: take_till!(is_extensible_delimiter)
+            >> tag!(":=")
+            >> value: take_until!(")")
+            >> (Tag::Sequence(Sequence {
+                class: TagClass::Context,
+                id: TAG_EXTENSIBLE,
+                inner: vec![
+                    Tag::OctetString(OctetString {
+                        inner: attr.to_vec(),
+                        ..Default::default()
+                    }),
+                    Tag::OctetString(OctetString {
+                        inner: rule.to_vec(),
+                        ..Default::default()
+                    }),
+                    Tag::OctetString(OctetString {
+                        inner: value.to_vec(),
+                        ..Default::default()
+                    })
+                ]
+            }))
+    )
+);
+
 //named!(filtertype <u64>, call!(equal));
 
 named!(
     filtertype<u64>,
-    alt!(equal | less_equal | less | greater_equal | greater)
+    alt!(equal | approx | less_equal | less | greater_equal | greater)
 );
 
 named!(equal<u64>, do_parse!(char!('=') >> (TAG_EQUAL)));
 
+named!(approx<u64>, do_parse!(tag!("~=") >> (TAG_APPROX)));
+
 named!(less<u64>, do_parse!(char!('<') >> (TAG_LESS)));
 
 named!(less_equal<u64>, do_parse!(tag!("<=") >> (TAG_LESS_EQUAL)));
@@ -144,3 +180,7 @@ named!(
 pub fn is_delimiter(chr: u8) -> bool {
     chr == b'=' || chr == b'<' || chr == b'>' || chr == b'~'
 }
+
+pub fn is_extensible_delimiter(chr: u8) -> bool {
+    chr == b':' || is_delimiter(chr)
+}
@@ -3,8 +3,9 @@ use std::str;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, TimeZone, Utc};
 use regex::Regex;
-use semver::Version;
+use semver::{Version, VersionReq};
 use std::collections::HashMap;
+use std::fmt;
 
 use super::error::ParseError;
 use super::prop_parser;
@@ -159,12 +160,86 @@ impl<'a> PropertyValue<'a> {
         }
     }
 
+    // Approximate equality - strings are compared ignoring case, leading, trailing
+    // and repeated whitespace, other types as in equals()
+    pub fn approx_equals(&self, other: &str) -> bool {
+        match self {
+            PropertyValue::Str(value) => PropertyValue::str_equal_with_wildcard(
+                &PropertyValue::normalize_str(other),
+                &PropertyValue::normalize_str(value),
+            ),
+            PropertyValue::Boolean(_) => self.equals(&other.trim().to_lowercase()),
+            PropertyValue::List(items) => items.iter().any(|item| item.approx_equals(other)),
+            _ => self.equals(other.trim()),
+        }
+    }
+
+    // Set membership - value is one of the listed values
+    // For List values - all list items are listed
+    pub fn in_list(&self, other: &str) -> bool {
+        let values = match prop_parser::parse_prop_ref_as_list(other) {
+            Ok(values) => values,
+            _ => return false,
+        }; // ignore parsing error, assume false
+        match self {
+            PropertyValue::List(items) => items
+                .iter()
+                .all(|item| values.iter().any(|val| item.equals(val.trim()))),
+            _ => values.iter().any(|val| self.equals(val.trim())),
+        }
+    }
+
+    // Set containment - all listed values are List items
+    pub fn contains_list(&self, other: &str) -> bool {
+        let values = match prop_parser::parse_prop_ref_as_list(other) {
+            Ok(values) => values,
+            _ => return false,
+        }; // ignore parsing error, assume false
+        match self {
+            PropertyValue::List(items) => values
+                .iter()
+                .all(|val| items.iter().any(|item| item.equals(val.trim()))),
+            _ => values.iter().all(|val| self.equals(val.trim())),
+        }
+    }
+
+    // Version range - value matches the requirement, eg. ">=1.2, <2.0" or "^1.2"
+    pub fn matches_version(&self, other: &str) -> bool {
+        let requirement = match VersionReq::parse(other) {
+            Ok(requirement) => requirement,
+            _ => return false,
+        }; // ignore parsing error, assume false
+        match self {
+            PropertyValue::Version(value) => requirement.matches(value),
+            PropertyValue::Str(value) => match Version::parse(value) {
+                Ok(parsed_value) => requirement.matches(&parsed_value),
+                _ => false,
+            },
+            PropertyValue::List(items) => items.iter().any(|item| item.matches_version(other)),
+            _ => false, // operator meaningless for other types
+        }
+    }
+
+    fn normalize_str(value: &str) -> String {
+        value
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
     // Implement string equality with * wildcard
     // Note: Only str1 may contain wildcard
     // TODO my be sensible to move the Regex building to the point where property is parsed...
     fn str_equal_with_wildcard(str1: &str, str2: &str) -> bool {
         if str1.contains('*') {
-            let regex_text = format!("^{}$", str1.replace('*', ".*"));
+            let regex_text = format!(
+                "^{}$",
+                str1.split('*')
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(".*")
+            );
             match Regex::new(&regex_text) {
                 Ok(regex) => regex.is_match(str2),
                 Err(_error) => false,
@@ -437,6 +512,28 @@ pub enum PropertyRefType {
     DateTime,
 }
 
+// Renders the property reference back in filter expression syntax, eg. "name[aspect]$v"
+impl fmt::Display for PropertyRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ref_type = match self {
+            PropertyRef::Value(name, ref_type) => {
+                f.write_str(name)?;
+                ref_type
+            }
+            PropertyRef::Aspect(name, aspect, ref_type) => {
+                write!(f, "{}[{}]", name, aspect)?;
+                ref_type
+            }
+        };
+        match ref_type {
+            PropertyRefType::Any => Ok(()),
+            PropertyRefType::Decimal => f.write_str("$d"),
+            PropertyRefType::Version => f.write_str("$v"),
+            PropertyRefType::DateTime => f.write_str("$t"),
+        }
+    }
+}
+
 pub fn parse_prop_ref(flat_prop: &str) -> Result<PropertyRef, ParseError> {
     // TODO parse the flat_prop using prop_parser and repack to PropertyRef
     match prop_parser::parse_prop_ref_with_aspect(flat_prop) {
@@ -51,6 +51,54 @@ fn build_expression_equals() {
     assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
 }
 
+#[test]
+fn build_expression_approx() {
+    let f = "(cn~=Babs Jensen)";
+
+    let expression = Expression::Approx(
+        PropertyRef::Value(String::from("cn"), PropertyRefType::Any),
+        String::from("Babs Jensen"),
+    );
+
+    assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
+}
+
+#[test]
+fn build_expression_extensible() {
+    let f = "(golem.inf.cpu.architecture:in:=[x86_64,aarch64])";
+
+    let expression = Expression::In(
+        PropertyRef::Value(
+            String::from("golem.inf.cpu.architecture"),
+            PropertyRefType::Any,
+        ),
+        String::from("[x86_64,aarch64]"),
+    );
+
+    assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
+
+    let f = "(golem.runtime.version:version:=^0.5)";
+
+    let expression = Expression::VersionMatch(
+        PropertyRef::Value(String::from("golem.runtime.version"), PropertyRefType::Any),
+        String::from("^0.5"),
+    );
+
+    assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
+}
+
+#[test]
+fn build_expression_extensible_errors() {
+    for f in [
+        "(cn:soundex:=Babs)",
+        "(cn:in:=Babs)",
+        "(cn:version:=latest)",
+        "(cn[aspect]:in:=[a,b])",
+    ] {
+        assert!(build_expression(&parse(f).unwrap()).is_err(), "{}", f);
+    }
+}
+
 #[test]
 fn build_expression_not() {
     let f = "(!(cn=Tim Howes))";
@@ -84,3 +132,30 @@ fn build_expression_and() {
 
     assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
 }
+
+#[test]
+fn expression_display_roundtrip() {
+    let f = "(&(a=b)(|(c$d<=3)(d[x]~=y))(!(e=*))(f:version:=>=1.2))";
+
+    let expression = build_expression(&parse(f).unwrap()).unwrap();
+
+    assert_eq!(expression.to_string(), f);
+    assert_eq!(
+        build_expression(&parse(&expression.to_string()).unwrap()),
+        Ok(expression)
+    );
+}
+
+#[test]
+fn expression_clauses_flatten_nested_and() {
+    let f = "(&(a=b)(&(c=d)(|(e=f)(g=h))))";
+
+    let expression = build_expression(&parse(f).unwrap()).unwrap();
+    let clauses = expression
+        .clauses()
+        .into_iter()
+        .map(|clause| clause.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(clauses, vec!["(a=b)", "(c=d)", "(|(e=f)(g=h))"]);
+}
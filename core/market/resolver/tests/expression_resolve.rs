@@ -175,6 +175,133 @@ fn resolve_equals_with_wildcard() {
     );
 }
 
+#[test]
+fn resolve_substring() {
+    let f = "(cn=*bs Jen*)";
+
+    // test positive
+
+    run_resolve_test(f, &vec!["cn=\"Babs Jensen\""], ResolveResult::True);
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec!["cn=\"Tim Howes\""],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_approx() {
+    let f = "(cn~=babs  jensen)";
+
+    // test positive
+
+    run_resolve_test(f, &vec!["cn=\"Babs Jensen\""], ResolveResult::True);
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec!["cn=\"Tim Howes\""],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_in() {
+    let f = "(golem.inf.cpu.architecture:in:=[x86_64,aarch64])";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec!["golem.inf.cpu.architecture=\"aarch64\""],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec!["golem.inf.cpu.architecture=\"riscv64\""],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+
+    // test undefined
+
+    run_resolve_test(
+        f,
+        &vec!["cn=\"Babs Jensen\""],
+        ResolveResult::Undefined(
+            vec![&PropertyRef::Value(
+                String::from("golem.inf.cpu.architecture"),
+                PropertyRefType::Any,
+            )],
+            Expression::In(
+                PropertyRef::Value(
+                    String::from("golem.inf.cpu.architecture"),
+                    PropertyRefType::Any,
+                ),
+                String::from("[x86_64,aarch64]"),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn resolve_contains() {
+    let f = "(golem.runtime.capabilities:contains:=[vpn,gpu])";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec!["golem.runtime.capabilities=[\"inet\",\"vpn\",\"gpu\"]"],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec!["golem.runtime.capabilities=[\"inet\",\"vpn\"]"],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_version_match() {
+    let f = "(golem.runtime.version:version:=>=0.5, <1.0)";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec!["golem.runtime.version=\"0.5.3\""],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec!["golem.runtime.version=\"1.2.0\""],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+
+    // test with implied type
+
+    let f = "(golem.runtime.version$v:version:=^0.5)";
+
+    run_resolve_test(
+        f,
+        &vec!["golem.runtime.version=\"0.5.3\""],
+        ResolveResult::True,
+    );
+}
+
 #[test]
 fn resolve_equals_int() {
     let f = "(cn=123)";
@@ -19,6 +19,76 @@ fn present() {
     assert_eq!(parse(f), Ok(tag));
 }
 
+#[test]
+fn substring() {
+    let f = "(cn=*abc*)";
+
+    let tag = Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_EQUAL,
+        inner: vec![
+            Tag::OctetString(OctetString {
+                inner: b"cn".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"*abc*".to_vec(),
+                ..Default::default()
+            }),
+        ],
+    });
+
+    assert_eq!(parse(f), Ok(tag));
+}
+
+#[test]
+fn approx() {
+    let f = "(cn~=babs jensen)";
+
+    let tag = Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_APPROX,
+        inner: vec![
+            Tag::OctetString(OctetString {
+                inner: b"cn".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"babs jensen".to_vec(),
+                ..Default::default()
+            }),
+        ],
+    });
+
+    assert_eq!(parse(f), Ok(tag));
+}
+
+#[test]
+fn extensible() {
+    let f = "(golem.runtime.version:version:=>=0.5, <1.0)";
+
+    let tag = Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_EXTENSIBLE,
+        inner: vec![
+            Tag::OctetString(OctetString {
+                inner: b"golem.runtime.version".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"version".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b">=0.5, <1.0".to_vec(),
+                ..Default::default()
+            }),
+        ],
+    });
+
+    assert_eq!(parse(f), Ok(tag));
+}
+
 #[test]
 fn simple() {
     let f = "(cn=Babs Jensen)";
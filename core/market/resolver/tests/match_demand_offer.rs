@@ -1,4 +1,7 @@
-use ya_market_resolver::{match_demand_offer, Match, MatchError};
+use ya_market_resolver::{
+    explain_demand_offer, match_demand_offer, ClauseExplanation, ConstraintsExplanation, Match,
+    MatchError, Verdict,
+};
 
 mod sample;
 
@@ -119,3 +122,57 @@ fn match_poc_offer_demand_samples_deep() {
         Match::Yes
     );
 }
+
+#[test]
+fn explain_lists_all_failed_clauses_per_side() {
+    let _ = env_logger::builder().try_init();
+    let explanation = explain_demand_offer(
+        "{\"foo\": \"bar1\"}",
+        "(qux=baz)",
+        "{\"qux\": \"baz\"}",
+        "(&(foo=bar)(cpu>=4))",
+    )
+    .unwrap();
+
+    assert_eq!(explanation.verdict, Verdict::No);
+    assert_eq!(
+        explanation.demand,
+        ConstraintsExplanation {
+            verdict: Verdict::Yes,
+            clauses: vec![],
+        }
+    );
+    assert_eq!(
+        explanation.offer,
+        ConstraintsExplanation {
+            verdict: Verdict::No,
+            clauses: vec![
+                ClauseExplanation {
+                    clause: "(foo=bar)".to_string(),
+                    verdict: Verdict::No,
+                    undefined_properties: vec![],
+                },
+                ClauseExplanation {
+                    clause: "(cpu>=4)".to_string(),
+                    verdict: Verdict::Undefined,
+                    undefined_properties: vec!["cpu".to_string()],
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn explain_poc_offer_demand_samples() {
+    let explanation = explain_demand_offer(
+        POC_DEMAND_PROPERTIES_JSON,
+        POC_DEMAND_CONSTRAINTS,
+        POC_OFFER_PROPERTIES_JSON,
+        POC_OFFER_CONSTRAINTS,
+    )
+    .unwrap();
+
+    assert_eq!(explanation.verdict, Verdict::Yes);
+    assert!(explanation.offer.clauses.is_empty());
+    assert!(explanation.demand.clauses.is_empty());
+}
@@ -92,4 +92,45 @@ fn less_equal_for_list_false() {
     assert!(!prop_value.less_equal("abc"));
 }
 
+#[test]
+fn in_list_for_list_true() {
+    let prop_value = PropertyValue::List(vec![
+        Box::new(PropertyValue::Str("abc")),
+        Box::new(PropertyValue::Str("def")),
+    ]);
+
+    assert!(prop_value.in_list("[abc, def, ghi]"));
+}
+
+#[test]
+fn in_list_for_list_false() {
+    let prop_value = PropertyValue::List(vec![
+        Box::new(PropertyValue::Str("abc")),
+        Box::new(PropertyValue::Str("def")),
+    ]);
+
+    assert!(!prop_value.in_list("[abc,ghi]"));
+}
+
+#[test]
+fn in_list_for_string() {
+    let prop_value = PropertyValue::Str("x86_64");
+
+    assert!(prop_value.in_list("[aarch64,x86_64]"));
+    assert!(!prop_value.in_list("[aarch64]"));
+    assert!(!prop_value.in_list("x86_64"));
+}
+
+#[test]
+fn contains_list_for_list() {
+    let prop_value = PropertyValue::List(vec![
+        Box::new(PropertyValue::Str("abc")),
+        Box::new(PropertyValue::Str("def")),
+        Box::new(PropertyValue::Str("ghi")),
+    ]);
+
+    assert!(prop_value.contains_list("[ghi,abc]"));
+    assert!(!prop_value.contains_list("[abc,xyz]"));
+}
+
 // #endregion
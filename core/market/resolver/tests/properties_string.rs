@@ -30,4 +30,33 @@ fn equals_for_strings_wildcard_false() {
     assert!(!prop_value.equals("as*"));
 }
 
+#[test]
+fn equals_for_strings_substring_true() {
+    let prop_value = PropertyValue::Str("golem.runtime");
+
+    assert!(prop_value.equals("*.run*"));
+}
+
+#[test]
+fn equals_for_strings_wildcard_escaped_false() {
+    let prop_value = PropertyValue::Str("golemXruntime");
+
+    assert!(!prop_value.equals("golem.run*"));
+}
+
+#[test]
+fn approx_equals_for_strings_true() {
+    let prop_value = PropertyValue::Str("Babs  Jensen ");
+
+    assert!(prop_value.approx_equals("babs jensen"));
+    assert!(prop_value.approx_equals("BABS J*"));
+}
+
+#[test]
+fn approx_equals_for_strings_false() {
+    let prop_value = PropertyValue::Str("Babs Jensen");
+
+    assert!(!prop_value.approx_equals("babsjensen"));
+}
+
 // #endregion
@@ -46,4 +46,28 @@ fn greater_equal_for_version_simple_true() {
     assert!(prop_value.greater_equal("0.5.0"));
 }
 
+#[test]
+fn matches_version_for_version() {
+    let prop_value = PropertyValue::Version(Version::parse("0.5.1").unwrap());
+
+    assert!(prop_value.matches_version(">=0.5.0, <0.6.0"));
+    assert!(prop_value.matches_version("~0.5"));
+    assert!(!prop_value.matches_version("^0.6"));
+}
+
+#[test]
+fn matches_version_for_string() {
+    let prop_value = PropertyValue::Str("1.2.3");
+
+    assert!(prop_value.matches_version("^1.0"));
+    assert!(!PropertyValue::Str("latest").matches_version("^1.0"));
+}
+
+#[test]
+fn matches_version_invalid_requirement_false() {
+    let prop_value = PropertyValue::Version(Version::parse("0.5.0").unwrap());
+
+    assert!(!prop_value.matches_version("not a version"));
+}
+
 // #endregion
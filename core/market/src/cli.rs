@@ -1,7 +1,9 @@
+use anyhow::anyhow;
 use chrono::{DateTime, Utc};
+use std::path::PathBuf;
 use structopt::StructOpt;
 use ya_client::model::market::{agreement::State, Role};
-use ya_core_model::market::{GetAgreement, ListAgreements};
+use ya_core_model::market::{ExplainMatch, GetAgreement, ListAgreements, MatchSubject};
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
@@ -9,12 +11,15 @@ use ya_service_bus::{typed as bus, RpcEndpoint};
 #[derive(StructOpt, Debug)]
 pub enum Command {
     Agreements(AgreementsCommand),
+    /// Explain, why an Offer and a Demand do or don't match
+    Explain(ExplainCommand),
 }
 
 impl Command {
     pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
         match self {
             Command::Agreements(agreements_cmd) => agreements_cmd.run_command(ctx).await,
+            Command::Explain(explain_cmd) => explain_cmd.run_command(ctx).await,
         }
     }
 }
@@ -98,3 +103,64 @@ impl AgreementsCommand {
         }
     }
 }
+
+#[derive(StructOpt, Debug)]
+pub struct ExplainCommand {
+    #[structopt(
+        long,
+        required_unless = "offer-file",
+        help = "Offer subscription ID known to the local market"
+    )]
+    offer_id: Option<String>,
+    #[structopt(
+        long,
+        conflicts_with = "offer-id",
+        help = "JSON file with Offer properties and constraints"
+    )]
+    offer_file: Option<PathBuf>,
+    #[structopt(
+        long,
+        required_unless = "demand-file",
+        help = "Demand subscription ID known to the local market"
+    )]
+    demand_id: Option<String>,
+    #[structopt(
+        long,
+        conflicts_with = "demand-id",
+        help = "JSON file with Demand properties and constraints"
+    )]
+    demand_file: Option<PathBuf>,
+}
+
+impl ExplainCommand {
+    pub async fn run_command(self, _ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
+        let request = ExplainMatch {
+            offer: match_subject(self.offer_id, self.offer_file)?,
+            demand: match_subject(self.demand_id, self.demand_file)?,
+        };
+
+        let explanation = bus::service(ya_core_model::market::local::BUS_ID)
+            .send(request)
+            .await??;
+
+        CommandOutput::object(explanation)
+    }
+}
+
+fn match_subject(id: Option<String>, file: Option<PathBuf>) -> anyhow::Result<MatchSubject> {
+    match (id, file) {
+        (Some(id), _) => Ok(MatchSubject::Id(id)),
+        (None, Some(path)) => {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+            match serde_json::from_str(&content)? {
+                subject @ MatchSubject::Raw { .. } => Ok(subject),
+                MatchSubject::Id(_) => Err(anyhow!(
+                    "{} should contain an object with properties and constraints",
+                    path.display()
+                )),
+            }
+        }
+        (None, None) => Err(anyhow!("Either subscription ID or JSON file is required")),
+    }
+}
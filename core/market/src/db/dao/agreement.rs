@@ -6,7 +6,7 @@ use ya_client::model::NodeId;
 use ya_persistence::executor::{do_with_transaction, readonly_transaction, ConnType, PoolType};
 
 use crate::config::DbConfig;
-use crate::db::dao::agreement_events::create_event;
+use crate::db::dao::agreement_events::{create_event, publish_event};
 use crate::db::dao::proposal::{has_counter_proposal, update_proposal_state};
 use crate::db::dao::sql_functions::datetime;
 use crate::db::model::{
@@ -297,6 +297,10 @@ impl<'c> AgreementDao<'c> {
             Ok(agreement)
         })
         .await
+        .map(|agreement| {
+            publish_event(&agreement, None, Owner::Provider);
+            agreement
+        })
     }
 
     pub async fn reject(
@@ -313,11 +317,15 @@ impl<'c> AgreementDao<'c> {
                 market_agreement.filter(agreement::id.eq(&id)).first(conn)?;
 
             update_state(conn, &mut agreement, AgreementState::Rejected)?;
-            create_event(conn, &agreement, reason, Owner::Provider, timestamp)?;
+            create_event(conn, &agreement, reason.clone(), Owner::Provider, timestamp)?;
 
-            Ok(agreement)
+            Ok((agreement, reason))
         })
         .await
+        .map(|(agreement, reason)| {
+            publish_event(&agreement, reason, Owner::Provider);
+            agreement
+        })
     }
 
     pub async fn cancel(
@@ -334,11 +342,21 @@ impl<'c> AgreementDao<'c> {
                 market_agreement.filter(agreement::id.eq(&id)).first(conn)?;
 
             update_state(conn, &mut agreement, AgreementState::Cancelled)?;
-            create_event(conn, &agreement, reason, Owner::Requestor, timestamp)?;
+            create_event(
+                conn,
+                &agreement,
+                reason.clone(),
+                Owner::Requestor,
+                timestamp,
+            )?;
 
-            Ok(agreement)
+            Ok((agreement, reason))
         })
         .await
+        .map(|(agreement, reason)| {
+            publish_event(&agreement, reason, Owner::Requestor);
+            agreement
+        })
     }
 
     pub async fn terminate(
@@ -356,11 +374,15 @@ impl<'c> AgreementDao<'c> {
                 market_agreement.filter(agreement::id.eq(&id)).first(conn)?;
 
             update_state(conn, &mut agreement, AgreementState::Terminated)?;
-            create_event(conn, &agreement, reason, terminator, timestamp)?;
+            create_event(conn, &agreement, reason.clone(), terminator, timestamp)?;
 
-            Ok(true)
+            Ok((agreement, reason))
         })
         .await
+        .map(|(agreement, reason)| {
+            publish_event(&agreement, reason, terminator);
+            true
+        })
     }
 
     pub async fn revert_approving(&self, id: &AgreementId) -> Result<bool, AgreementDaoError> {
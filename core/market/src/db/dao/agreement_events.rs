@@ -3,9 +3,11 @@ use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl};
 
 use ya_client::model::market::Reason;
 use ya_client::model::NodeId;
+use ya_core_model::event::{self as model, Event, EventCategory};
 use ya_persistence::executor::PoolType;
 use ya_persistence::executor::{readonly_transaction, ConnType};
 use ya_persistence::types::AdaptTimestamp;
+use ya_service_bus::typed as bus;
 
 use crate::db::dao::AgreementDaoError;
 use crate::db::model::{Agreement, AgreementEvent, AgreementId, NewAgreementEvent};
@@ -84,6 +86,31 @@ impl<'c> AgreementEventsDao<'c> {
     }
 }
 
+/// Publishes Agreement state change to the internal event stream.
+/// Should be called after the transaction, which changed the state, is committed.
+pub(crate) fn publish_event(agreement: &Agreement, reason: Option<Reason>, issuer: Owner) {
+    let owner_id = match agreement.id.owner() {
+        Owner::Provider => agreement.provider_id,
+        Owner::Requestor => agreement.requestor_id,
+    };
+    let event = Event::new(
+        EventCategory::Market,
+        format!("Agreement{}Event", agreement.state),
+        Some(owner_id),
+        serde_json::json!({
+            "agreementId": agreement.id.clone().into_client(),
+            "state": agreement.state.to_string(),
+            "issuer": issuer,
+            "reason": reason,
+        }),
+    );
+    tokio::task::spawn_local(async move {
+        if let Err(e) = bus::service(model::BUS_ID).send(event).await {
+            log::trace!("Failed to publish Agreement event: {e}");
+        }
+    });
+}
+
 pub(crate) fn create_event(
     conn: &ConnType,
     agreement: &Agreement,
@@ -23,16 +23,18 @@ pub(crate) mod resolver;
 pub(crate) mod store;
 
 use crate::db::dao::{DemandDao, DemandState};
-use error::{MatcherError, MatcherInitError, QueryOfferError, QueryOffersError};
+use error::{ExplainError, MatcherError, MatcherInitError, QueryOfferError, QueryOffersError};
 use futures::FutureExt;
 use log::debug;
 use resolver::Resolver;
 use store::SubscriptionStore;
 use tracing::Level;
+use ya_core_model::market::{ExplainMatch, MatchExplanation, MatchSubject, RpcMessageError};
 use ya_core_model::net::local::{
     BindBroadcastError, BroadcastMessage, NewNeighbour, SendBroadcastMessage,
 };
 use ya_net::bind_broadcast_with_caller;
+use ya_service_bus::typed::ServiceBinder;
 
 /// Stores proposal generated from resolver.
 #[derive(Debug)]
@@ -118,6 +120,15 @@ impl Matcher {
 
         self.bind_neighbourhood_bcast(local_prefix).await.ok();
 
+        ServiceBinder::new(local_prefix, &(), self.clone()).bind_with_processor(
+            |_, myself, _caller: String, msg: ExplainMatch| async move {
+                myself
+                    .explain_match(&msg.offer, &msg.demand)
+                    .await
+                    .map_err(RpcMessageError::from)
+            },
+        );
+
         self.bind_expiration_tracker()
             .await
             .map_err(|e| MatcherInitError::ExpirationTrackerError(e.to_string()))?;
@@ -301,6 +312,42 @@ impl Matcher {
         Ok(())
     }
 
+    /// Tells which constraint clauses of given Offer and Demand aren't satisfied
+    /// and which properties couldn't be resolved.
+    pub async fn explain_match(
+        &self,
+        offer: &MatchSubject,
+        demand: &MatchSubject,
+    ) -> Result<MatchExplanation, ExplainError> {
+        let (offer_properties, offer_constraints) = match offer {
+            MatchSubject::Id(id) => {
+                let offer = self.store.get_offer(&id.parse()?).await?;
+                (offer.properties, offer.constraints)
+            }
+            MatchSubject::Raw {
+                properties,
+                constraints,
+            } => (properties.to_string(), constraints.clone()),
+        };
+        let (demand_properties, demand_constraints) = match demand {
+            MatchSubject::Id(id) => {
+                let demand = self.store.get_demand(&id.parse()?).await?;
+                (demand.properties, demand.constraints)
+            }
+            MatchSubject::Raw {
+                properties,
+                constraints,
+            } => (properties.to_string(), constraints.clone()),
+        };
+
+        Ok(resolver::explain(
+            &offer_properties,
+            &offer_constraints,
+            &demand_properties,
+            &demand_constraints,
+        )?)
+    }
+
     pub async fn get_our_active_offer_ids(&self) -> Result<Vec<SubscriptionId>, QueryOffersError> {
         let our_node_ids = self.identity.list().await?;
         self.store.get_active_offer_ids(Some(our_node_ids)).await
@@ -1,7 +1,8 @@
-use crate::db::model::{SubscriptionId, SubscriptionValidationError};
+use crate::db::model::{SubscriptionId, SubscriptionParseError, SubscriptionValidationError};
 use crate::db::DbError;
 use crate::identity::IdentityError;
 use crate::protocol::discovery::error::DiscoveryInitError;
+use ya_core_model::market::RpcMessageError;
 
 #[derive(thiserror::Error, Debug)]
 pub enum DemandError {
@@ -111,6 +112,18 @@ pub enum MatcherError {
     ModifyOffer(#[from] ModifyOfferError),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ExplainError {
+    #[error(transparent)]
+    QueryOffer(#[from] QueryOfferError),
+    #[error(transparent)]
+    Demand(#[from] DemandError),
+    #[error(transparent)]
+    InvalidId(#[from] SubscriptionParseError),
+    #[error("Failed to resolve Offer and Demand. Error: {0}.")]
+    Resolve(#[from] ya_market_resolver::MatchError),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum MatcherInitError {
     #[error("Failed to initialize Discovery interface. Error: {0}.")]
@@ -138,3 +151,18 @@ impl From<ResolverError> for MatcherError {
         }
     }
 }
+
+impl From<ExplainError> for RpcMessageError {
+    fn from(e: ExplainError) -> Self {
+        match e {
+            ExplainError::QueryOffer(QueryOfferError::NotFound(_))
+            | ExplainError::Demand(DemandError::NotFound(_)) => {
+                RpcMessageError::NotFound(e.to_string())
+            }
+            ExplainError::InvalidId(_) | ExplainError::Resolve(_) => {
+                RpcMessageError::BadRequest(e.to_string())
+            }
+            _ => RpcMessageError::Market(e.to_string()),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use ya_market_resolver::{match_demand_offer, Match};
+use ya_core_model::market::{
+    ClauseExplanation, ConstraintsExplanation, MatchExplanation, MatchVerdict,
+};
+use ya_market_resolver::{explain_demand_offer, match_demand_offer, Match, MatchError, Verdict};
 
 use super::{error::ResolverError, RawProposal, SubscriptionStore};
 use crate::db::model::{Demand, Offer, SubscriptionId};
@@ -133,13 +136,81 @@ fn matches(offer: &Offer, demand: &Demand) -> bool {
     }
 }
 
+/// Explains the match relation for raw Offer and Demand properties and constraints.
+pub(crate) fn explain(
+    offer_properties: &str,
+    offer_constraints: &str,
+    demand_properties: &str,
+    demand_constraints: &str,
+) -> Result<MatchExplanation, MatchError> {
+    let explanation = explain_demand_offer(
+        demand_properties,
+        demand_constraints,
+        offer_properties,
+        offer_constraints,
+    )?;
+
+    Ok(MatchExplanation {
+        verdict: verdict_into_model(explanation.verdict),
+        offer: constraints_into_model(explanation.offer),
+        demand: constraints_into_model(explanation.demand),
+    })
+}
+
+fn constraints_into_model(
+    explanation: ya_market_resolver::ConstraintsExplanation,
+) -> ConstraintsExplanation {
+    ConstraintsExplanation {
+        verdict: verdict_into_model(explanation.verdict),
+        clauses: explanation
+            .clauses
+            .into_iter()
+            .map(|clause| ClauseExplanation {
+                clause: clause.clause,
+                verdict: verdict_into_model(clause.verdict),
+                undefined_properties: clause.undefined_properties,
+            })
+            .collect(),
+    }
+}
+
+fn verdict_into_model(verdict: Verdict) -> MatchVerdict {
+    match verdict {
+        Verdict::Yes => MatchVerdict::Yes,
+        Verdict::No => MatchVerdict::No,
+        Verdict::Undefined => MatchVerdict::Undefined,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::matcher::resolver::matches;
+    use ya_core_model::market::MatchVerdict;
+
+    use crate::matcher::resolver::{explain, matches};
     use crate::testing::mock_offer::{sample_demand, sample_offer};
 
     #[test]
     fn matches_empty() {
         assert!(matches(&sample_offer(), &sample_demand()))
     }
+
+    #[test]
+    fn explain_undefined_property() {
+        let offer = sample_offer();
+        let demand = sample_demand();
+        let explanation = explain(
+            &offer.properties,
+            "(golem.inf.cpu.cores>=4)",
+            &demand.properties,
+            &demand.constraints,
+        )
+        .unwrap();
+
+        assert_eq!(explanation.verdict, MatchVerdict::Undefined);
+        assert_eq!(explanation.demand.verdict, MatchVerdict::Yes);
+        assert_eq!(
+            explanation.offer.clauses[0].undefined_properties,
+            vec!["golem.inf.cpu.cores".to_string()]
+        );
+    }
 }
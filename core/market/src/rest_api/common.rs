@@ -8,6 +8,7 @@ use std::sync::Arc;
 
 use ya_client::model::market::scan::NewScan;
 use ya_client::model::market::{Offer, Reason};
+use ya_core_model::market::ExplainMatch;
 use ya_service_api_web::middleware::Identity;
 use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_std_utils::LogErr;
@@ -31,6 +32,7 @@ pub fn register_endpoints(scope: Scope) -> Scope {
         .service(scan_begin)
         .service(scan_collect)
         .service(scan_end)
+        .service(explain_match)
 }
 
 #[actix_web::get("/agreements")]
@@ -246,3 +248,19 @@ async fn scan_end(
     scan_set.end(id.identity, scan_id).await?;
     Ok(HttpResponse::NoContent().finish())
 }
+
+/// Non-spec endpoint explaining, why given Offer and Demand do or don't match.
+#[actix_web::post("/explain")]
+async fn explain_match(
+    market: Data<Arc<MarketService>>,
+    body: Json<ExplainMatch>,
+    _id: Identity,
+) -> impl Responder {
+    let msg = body.into_inner();
+    market
+        .matcher
+        .explain_match(&msg.offer, &msg.demand)
+        .await
+        .log_err()
+        .map(|explanation| HttpResponse::Ok().json(explanation))
+}
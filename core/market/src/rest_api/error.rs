@@ -10,8 +10,8 @@ use crate::{
     db::dao::TakeEventsError,
     market::MarketError,
     matcher::error::{
-        DemandError, MatcherError, ModifyOfferError, QueryDemandsError, QueryOfferError,
-        QueryOffersError, ResolverError, SaveOfferError,
+        DemandError, ExplainError, MatcherError, ModifyOfferError, QueryDemandsError,
+        QueryOfferError, QueryOffersError, ResolverError, SaveOfferError,
     },
     negotiation::error::{
         AgreementError, GetProposalError, NegotiationError, ProposalError, QueryEventsError,
@@ -91,6 +91,18 @@ impl ResponseError for QueryOfferError {
     }
 }
 
+impl ResponseError for ExplainError {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            ExplainError::QueryOffer(e) => e.error_response(),
+            ExplainError::Demand(e) => e.error_response(),
+            ExplainError::InvalidId(_) | ExplainError::Resolve(_) => {
+                HttpResponse::BadRequest().json(ErrorMessage::new(self.to_string()))
+            }
+        }
+    }
+}
+
 impl ResponseError for SaveOfferError {
     fn error_response(&self) -> HttpResponse {
         let msg = ErrorMessage::new(self.to_string());
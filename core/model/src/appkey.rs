@@ -1,5 +1,8 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use thiserror::Error;
 use ya_client_model::NodeId;
 use ya_service_bus::RpcMessage;
@@ -9,6 +12,9 @@ pub const BUS_ID: &str = "/local/appkey";
 pub const DEFAULT_ROLE: &str = "manager";
 pub const AUTOCONFIGURED_KEY_NAME: &str = "autoconfigured";
 
+/// Services which access can be restricted by app-key scopes.
+pub const SCOPE_SERVICES: &[&str] = &["market", "payment", "activity", "net", "gsb"];
+
 const DEFAULT_PAGE_SIZE: u32 = 20;
 
 #[derive(Clone, Error, Debug, Serialize, Deserialize)]
@@ -41,6 +47,14 @@ pub struct Create {
     pub role: String,
     pub identity: NodeId,
     pub allow_origins: Vec<String>,
+    /// Access to the whole API is granted when not set.
+    #[serde(default)]
+    pub scopes: Option<Vec<Scope>>,
+    #[serde(default)]
+    pub expires_at: Option<NaiveDateTime>,
+    /// Use given key instead of generating a new one, e.g. when restoring a backup.
+    #[serde(default)]
+    pub key: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -86,6 +100,16 @@ pub struct Remove {
     pub identity: Option<String>,
 }
 
+/// Replaces the key of an existing app-key, keeping its name, role and scopes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rotate {
+    pub name: String,
+    pub identity: Option<String>,
+    /// New expiration date. Previous one is kept when not set.
+    pub expires_at: Option<NaiveDateTime>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppKey {
@@ -95,6 +119,116 @@ pub struct AppKey {
     pub identity: NodeId,
     pub created_date: NaiveDateTime,
     pub allow_origins: Vec<String>,
+    #[serde(default)]
+    pub scopes: Option<Vec<Scope>>,
+    #[serde(default)]
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl AppKey {
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at <= Utc::now().naive_utc(),
+            None => false,
+        }
+    }
+
+    /// Keys without scopes are not restricted.
+    pub fn grants(&self, service: &str, access: Access) -> bool {
+        match &self.scopes {
+            Some(scopes) => scopes.iter().any(|s| s.grants(service, access)),
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Access {
+    Read,
+    Write,
+    Exec,
+    All,
+}
+
+/// App-key permission in `<service>:<access>` form, e.g. `market:read`,
+/// `payment:write` or `activity:exec`. Both parts accept a `*` wildcard.
+///
+/// `write` access implies `read`, `exec` does not.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Scope {
+    pub service: String,
+    pub access: Access,
+}
+
+impl Scope {
+    pub fn grants(&self, service: &str, access: Access) -> bool {
+        if self.service != "*" && self.service != service {
+            return false;
+        }
+        match (self.access, access) {
+            (Access::All, _) | (Access::Write, Access::Read) => true,
+            (granted, requested) => granted == requested,
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (service, access) = s.split_once(':').ok_or_else(|| {
+            Error::bad_request(format!("invalid scope '{s}', expected <service>:<access>"))
+        })?;
+
+        if service != "*" && !SCOPE_SERVICES.contains(&service) {
+            return Err(Error::bad_request(format!(
+                "invalid scope '{s}', unknown service '{service}'"
+            )));
+        }
+        let access = match access {
+            "read" => Access::Read,
+            "write" => Access::Write,
+            "exec" => Access::Exec,
+            "*" => Access::All,
+            _ => {
+                return Err(Error::bad_request(format!(
+                    "invalid scope '{s}', unknown access '{access}'"
+                )))
+            }
+        };
+
+        Ok(Scope {
+            service: service.to_string(),
+            access,
+        })
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+            Access::Exec => "exec",
+            Access::All => "*",
+        };
+        write!(f, "{}:{}", self.service, access)
+    }
+}
+
+impl TryFrom<String> for Scope {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        scope.to_string()
+    }
 }
 
 impl RpcMessage for Create {
@@ -127,6 +261,12 @@ impl RpcMessage for Remove {
     type Error = Error;
 }
 
+impl RpcMessage for Rotate {
+    const ID: &'static str = "Rotate";
+    type Item = String;
+    type Error = Error;
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Subscribe {
@@ -158,3 +298,40 @@ pub mod event {
         type Error = Error;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(s: &str) -> Scope {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_scope() {
+        assert_eq!(
+            scope("market:read"),
+            Scope {
+                service: "market".to_string(),
+                access: Access::Read
+            }
+        );
+        assert_eq!(scope("*:*").to_string(), "*:*");
+        assert_eq!(scope("activity:exec").to_string(), "activity:exec");
+        assert!("market".parse::<Scope>().is_err());
+        assert!("market:delete".parse::<Scope>().is_err());
+        assert!("identity:read".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn scope_grants() {
+        assert!(scope("market:read").grants("market", Access::Read));
+        assert!(!scope("market:read").grants("market", Access::Write));
+        assert!(!scope("market:read").grants("payment", Access::Read));
+        assert!(scope("payment:write").grants("payment", Access::Read));
+        assert!(!scope("activity:write").grants("activity", Access::Exec));
+        assert!(!scope("activity:exec").grants("activity", Access::Read));
+        assert!(scope("activity:*").grants("activity", Access::Exec));
+        assert!(scope("*:read").grants("net", Access::Read));
+    }
+}
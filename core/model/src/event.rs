@@ -0,0 +1,59 @@
+//! Internal events published by yagna services.
+//!
+//! Events are sent to `BUS_ID` and streamed to external clients by GSB API.
+//! Nobody may be listening, so publishers should ignore delivery errors.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use ya_client_model::{ErrorMessage, NodeId};
+use ya_service_bus::RpcMessage;
+
+pub const BUS_ID: &str = "/local/event";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EventCategory {
+    Market,
+    Payment,
+    Activity,
+}
+
+/// Publish internal event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub category: EventCategory,
+    /// Type of the event within category, e.g. `AgreementApproved`.
+    pub kind: String,
+    /// Identity, which owns the object the event relates to.
+    /// Events without owner are not streamed to clients.
+    pub owner_id: Option<NodeId>,
+    pub timestamp: DateTime<Utc>,
+    pub payload: serde_json::Value,
+}
+
+impl Event {
+    pub fn new(
+        category: EventCategory,
+        kind: impl ToString,
+        owner_id: Option<NodeId>,
+        payload: serde_json::Value,
+    ) -> Self {
+        Event {
+            category,
+            kind: kind.to_string(),
+            owner_id,
+            timestamp: Utc::now(),
+            payload,
+        }
+    }
+}
+
+impl RpcMessage for Event {
+    const ID: &'static str = "Event";
+    type Item = ();
+    type Error = ErrorMessage;
+}
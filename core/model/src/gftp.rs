@@ -57,6 +57,59 @@ impl RpcMessage for GetChunk {
     type Error = Error;
 }
 
+/// Gets SHA3-256 hashes of consecutive file chunks of the given size.
+/// Allows the downloader to verify each chunk as soon as it's received.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetChunkHashes {
+    pub chunk_size: u64,
+}
+
+impl RpcMessage for GetChunkHashes {
+    const ID: &'static str = "GetChunkHashes";
+    type Item = GftpChunkHashes;
+    type Error = Error;
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GftpChunkHashes {
+    pub chunk_size: u64,
+    /// Hex-encoded hash for each chunk, ordered by offset.
+    pub hashes: Vec<String>,
+}
+
+/// Gets the manifest of a published directory.
+/// Each file in the tree is published separately under its own hash.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetManifest;
+
+impl RpcMessage for GetManifest {
+    const ID: &'static str = "GetManifest";
+    type Item = GftpManifest;
+    type Error = Error;
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GftpManifest {
+    pub entries: Vec<GftpManifestEntry>,
+    /// Directories in the tree, relative to the published one, so that empty ones
+    /// are recreated as well.
+    #[serde(default)]
+    pub dirs: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GftpManifestEntry {
+    /// Path relative to the published directory, with `/` separators.
+    pub path: String,
+    pub file_size: u64,
+    pub hash: String,
+}
+
 // =========================================== //
 // Upload messages
 // =========================================== //
@@ -88,6 +141,25 @@ impl RpcMessage for UploadFinished {
     type Error = Error;
 }
 
+/// Gets the number of bytes received by the upload publisher, counted
+/// from the beginning of the file without gaps.
+/// Allows the uploader to resume an interrupted upload.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUploadProgress;
+
+impl RpcMessage for GetUploadProgress {
+    const ID: &'static str = "GetUploadProgress";
+    type Item = GftpUploadProgress;
+    type Error = Error;
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GftpUploadProgress {
+    pub offset: u64,
+}
+
 // =========================================== //
 // Chunk structure
 // =========================================== //
@@ -259,6 +259,29 @@ impl RpcMessage for GetKeyFile {
     type Error = Error;
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNote(pub NodeId);
+
+impl RpcMessage for GetNote {
+    const ID: &'static str = "GetNote";
+    type Item = Option<String>;
+    type Error = Error;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetNote {
+    pub node_id: NodeId,
+    pub note: Option<String>,
+}
+
+impl RpcMessage for SetNote {
+    const ID: &'static str = "SetNote";
+    type Item = Ack;
+    type Error = Error;
+}
+
 pub mod event {
     use super::Error;
     use serde::{Deserialize, Serialize};
@@ -10,6 +10,9 @@ pub mod appkey;
 #[cfg(any(feature = "driver", feature = "payment"))]
 pub mod driver;
 
+#[cfg(feature = "event")]
+pub mod event;
+
 #[cfg(feature = "identity")]
 pub mod identity;
 
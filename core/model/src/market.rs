@@ -58,6 +58,68 @@ impl RpcMessage for ListAgreements {
     type Error = RpcMessageError;
 }
 
+/// Offer or Demand passed to `ExplainMatch`: either id of a subscription
+/// known to the local market, or raw properties and constraints.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MatchSubject {
+    Id(String),
+    Raw {
+        properties: serde_json::Value,
+        constraints: String,
+    },
+}
+
+/// Explains, why given Offer and Demand do or don't match.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainMatch {
+    pub offer: MatchSubject,
+    pub demand: MatchSubject,
+}
+
+impl RpcMessage for ExplainMatch {
+    const ID: &'static str = "ExplainMatch";
+    type Item = MatchExplanation;
+    type Error = RpcMessageError;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchVerdict {
+    Yes,
+    No,
+    Undefined,
+}
+
+/// Constraint clause, which wasn't satisfied by the other side properties.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClauseExplanation {
+    pub clause: String,
+    pub verdict: MatchVerdict,
+    /// Properties referenced by the clause, which are missing on the other side.
+    pub undefined_properties: Vec<String>,
+}
+
+/// Constraints of one side resolved against properties of the other side.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintsExplanation {
+    pub verdict: MatchVerdict,
+    pub clauses: Vec<ClauseExplanation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchExplanation {
+    pub verdict: MatchVerdict,
+    /// Offer constraints resolved against Demand properties.
+    pub offer: ConstraintsExplanation,
+    /// Demand constraints resolved against Offer properties.
+    pub demand: ConstraintsExplanation,
+}
+
 /// Error message for market service bus API.
 #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
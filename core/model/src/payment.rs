@@ -23,6 +23,7 @@ pub mod local {
     use bigdecimal::{BigDecimal, Zero};
     use chrono::{DateTime, Utc};
     use std::fmt::Display;
+    use std::str::FromStr;
     use std::time::Duration;
     use structopt::*;
     use strum::{EnumProperty, IntoEnumIterator, VariantNames};
@@ -282,7 +283,7 @@ pub mod local {
     pub struct GetRpcEndpoints {
         pub address: String,
         pub driver: DriverName,
-        pub network: Option<NetworkName>,
+        pub network: Option<String>,
         pub verify: bool,
         pub resolve: bool,
         pub no_wait: bool,
@@ -453,6 +454,81 @@ pub mod local {
         type Error = GenericError;
     }
 
+    #[derive(
+        EnumString,
+        EnumVariantNames,
+        EnumIter,
+        IntoStaticStr,
+        Display,
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+    )]
+    #[strum(serialize_all = "camelCase")]
+    #[serde(rename_all = "camelCase")]
+    pub enum LedgerEntryKind {
+        Invoice,
+        DebitNote,
+        Payment,
+        Allocation,
+    }
+
+    /// Single line of the payment ledger.
+    ///
+    /// Payments covering several agreements or activities are split into one entry
+    /// per agreement / activity payment, all sharing the payment id and tx hash.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LedgerEntry {
+        pub kind: LedgerEntryKind,
+        pub id: String,
+        pub timestamp: DateTime<Utc>,
+        pub role: Option<String>,
+        pub platform: String,
+        pub agreement_id: Option<String>,
+        pub activity_id: Option<String>,
+        pub allocation_id: Option<String>,
+        pub peer_id: Option<NodeId>,
+        pub payer_addr: Option<String>,
+        pub payee_addr: Option<String>,
+        pub amount: BigDecimal,
+        pub status: Option<String>,
+        pub tx_hash: Option<String>,
+    }
+
+    /// Fetches one page of the ledger, ordered by timestamp.
+    ///
+    /// `after` is the opaque cursor returned in [`LedgerPage::next`].
+    /// `limit` counts documents, so a page may hold more entries than `limit`
+    /// when payments are split per agreement.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ExportLedger {
+        pub node_id: NodeId,
+        pub since: Option<DateTime<Utc>>,
+        pub until: Option<DateTime<Utc>>,
+        pub platform: Option<String>,
+        pub kinds: Vec<LedgerEntryKind>,
+        pub after: Option<String>,
+        pub limit: u32,
+    }
+
+    impl RpcMessage for ExportLedger {
+        const ID: &'static str = "ExportLedger";
+        type Item = LedgerPage;
+        type Error = GenericError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LedgerPage {
+        pub entries: Vec<LedgerEntry>,
+        pub next: Option<String>,
+    }
+
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct ReleaseDeposit {
         pub platform: String,
@@ -566,6 +642,8 @@ pub mod local {
         Polygon,
         #[strum(props(token = "tGLM"))]
         Mumbai,
+        #[strum(props(token = "tGLM"))]
+        Dev,
     }
 
     impl NetworkName {
@@ -583,7 +661,7 @@ pub mod local {
     impl NetworkName {
         pub fn is_fundable(&self) -> bool {
             use NetworkName::*;
-            matches!(self, Goerli | Holesky)
+            matches!(self, Goerli | Holesky | Dev)
         }
 
         pub fn all_fundable() -> Vec<NetworkName> {
@@ -619,9 +697,9 @@ pub mod local {
         /// Payment driver
         #[structopt(long, possible_values = DriverName::VARIANTS, default_value = DriverName::Erc20.into())]
         pub driver: DriverName,
-        /// Payment network
-        #[structopt(long, possible_values = NetworkName::VARIANTS, default_value = NetworkName::Holesky.into())]
-        pub network: NetworkName,
+        /// Payment network: one of the built-in networks or a network added to payment driver config
+        #[structopt(long, default_value = NetworkName::Holesky.into())]
+        pub network: String,
     }
 
     impl AccountCli {
@@ -634,11 +712,18 @@ pub mod local {
         }
 
         pub fn network(&self) -> String {
-            self.network.to_string()
+            self.network.clone()
+        }
+
+        /// Built-in network. `None` for networks known only to payment drivers.
+        pub fn network_name(&self) -> Option<NetworkName> {
+            NetworkName::from_str(&self.network).ok()
         }
 
-        pub fn token(&self) -> String {
-            self.network.get_str("token").unwrap().to_string()
+        /// Default token of a built-in network.
+        pub fn token(&self) -> Option<String> {
+            self.network_name()
+                .map(|network| network.get_token().to_string())
         }
     }
 
@@ -652,7 +737,16 @@ pub mod local {
             assert_eq!(None, a.address());
             assert_eq!("erc20", a.driver());
             assert_eq!("holesky", a.network());
-            assert_eq!("tGLM", a.token());
+            assert_eq!(Some(NetworkName::Holesky), a.network_name());
+            assert_eq!(Some("tGLM".to_string()), a.token());
+        }
+
+        #[test]
+        fn test_cli_custom_network() {
+            let a = AccountCli::from_iter(&["", "--network", "arbitrum"]);
+            assert_eq!("arbitrum", a.network());
+            assert_eq!(None, a.network_name());
+            assert_eq!(None, a.token());
         }
     }
 }
@@ -737,6 +831,17 @@ pub mod public {
     pub struct RejectDebitNote {
         pub debit_note_id: String,
         pub rejection: Rejection,
+        pub issuer_id: NodeId,
+    }
+
+    impl RejectDebitNote {
+        pub fn new(debit_note_id: String, rejection: Rejection, issuer_id: NodeId) -> Self {
+            Self {
+                debit_note_id,
+                rejection,
+                issuer_id,
+            }
+        }
     }
 
     impl RpcMessage for RejectDebitNote {
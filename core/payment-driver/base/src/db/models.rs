@@ -119,6 +119,7 @@ pub enum Network {
     Holesky = 17000, //Holesky is testnet for Holesky network
     Mumbai = 80001, //Mumbai is testnet for Polygon network
     Polygon = 137, //Polygon is Polygon production network
+    Dev = 31337, //Dev is local development chain (Anvil, Hardhat)
 }
 
 impl FromStr for Network {
@@ -132,6 +133,7 @@ impl FromStr for Network {
             "holesky" => Ok(Network::Holesky),
             "polygon" => Ok(Network::Polygon),
             "mumbai" => Ok(Network::Mumbai),
+            "dev" => Ok(Network::Dev),
             _ => Err(DbError::InvalidData(format!("Invalid network: {}", s))),
         }
     }
@@ -146,6 +148,7 @@ impl Display for Network {
             Network::Holesky => f.write_str("holesky"),
             Network::Mumbai => f.write_str("mumbai"),
             Network::Polygon => f.write_str("polygon"),
+            Network::Dev => f.write_str("dev"),
         }
     }
 }
@@ -172,6 +175,7 @@ where
             137 => Network::Polygon,
             17000 => Network::Holesky,
             80001 => Network::Mumbai,
+            31337 => Network::Dev,
             _ => return Err(anyhow::anyhow!("invalid value").into()),
         })
     }
@@ -38,20 +38,28 @@ use ya_payment_driver::{
 // Local uses
 use crate::erc20::utils;
 use crate::erc20::utils::{big_dec_to_u256, u256_to_big_dec};
-use crate::network::platform_to_currency;
+use crate::network::Networks;
 use crate::signer::IdentitySigner;
-use crate::{driver::PaymentDetails, network, HOLESKY_NETWORK};
-use crate::{network::SUPPORTED_NETWORKS, DRIVER_NAME};
+use crate::DRIVER_NAME;
+use crate::{driver::PaymentDetails, HOLESKY_NETWORK};
 
 mod cli;
 
 pub struct Erc20Driver {
     payment_runtime: PaymentRuntime,
+    networks: Networks,
 }
 
 impl Erc20Driver {
-    pub fn new(payment_runtime: PaymentRuntime, recv: Receiver<DriverEvent>) -> Arc<Self> {
-        let this = Arc::new(Self { payment_runtime });
+    pub fn new(
+        payment_runtime: PaymentRuntime,
+        networks: Networks,
+        recv: Receiver<DriverEvent>,
+    ) -> Arc<Self> {
+        let this = Arc::new(Self {
+            payment_runtime,
+            networks,
+        });
 
         let this_ = Arc::clone(&this);
         tokio::task::spawn_local(Self::payment_confirm_job(this_, recv));
@@ -81,6 +89,22 @@ impl Erc20Driver {
         }
     }
 
+    pub(crate) fn networks(&self) -> &Networks {
+        &self.networks
+    }
+
+    /// Chain id of the network as set in the payment config.
+    /// Chain ids of `dev` and user-defined networks are not known in advance.
+    fn chain_id(&self, network: &str) -> Result<i64, GenericError> {
+        self.payment_runtime
+            .setup
+            .chain_setup
+            .iter()
+            .find(|(_, chain)| chain.network == network)
+            .map(|(chain_id, _)| *chain_id)
+            .ok_or_else(|| GenericError::new(format!("Missing chain config for network {network}")))
+    }
+
     async fn is_account_active(&self, address: &str) -> Result<(), GenericError> {
         //todo: check if account is active
         let eth_address = Address::from_str(address).map_err(|err| {
@@ -705,11 +729,11 @@ impl PaymentDriver for Erc20Driver {
         let token_balance = u256_to_big_dec(token_balance).map_err(|e| {
             GenericError::new(format!("Error converting token balance to big int: {}", e))
         })?;
-        let (currency_short_name, currency_long_name) = platform_to_currency(platform)?;
+        let network_info = self.networks.by_platform(&platform)?;
         Ok(GetAccountBalanceResult {
             gas_details: Some(GasDetails {
-                currency_short_name,
-                currency_long_name,
+                currency_short_name: network_info.currency_short.clone(),
+                currency_long_name: network_info.currency_long.clone(),
                 balance: gas_balance,
             }),
             token_balance,
@@ -727,7 +751,7 @@ impl PaymentDriver for Erc20Driver {
     }
 
     fn get_networks(&self) -> HashMap<String, NetworkConfig> {
-        SUPPORTED_NETWORKS.clone()
+        self.networks.driver_networks()
     }
 
     fn recv_init_required(&self) -> bool {
@@ -742,7 +766,7 @@ impl PaymentDriver for Erc20Driver {
     async fn fund(&self, _caller: String, msg: Fund) -> Result<String, GenericError> {
         log::debug!("fund: {:?}", msg);
         let address = msg.address();
-        let network = network::network_like_to_network(msg.network());
+        let network = self.networks.network_like(msg.network())?.name.as_str();
         let result = {
             let address = utils::str_to_addr(&address)?;
             log::info!(
@@ -754,7 +778,7 @@ impl PaymentDriver for Erc20Driver {
                 .payment_runtime
                 .setup
                 .chain_setup
-                .get(&(network as i64))
+                .get(&self.chain_id(network)?)
                 .ok_or(GenericError::new(format!(
                     "Missing chain config for network {}",
                     network
@@ -1083,13 +1107,13 @@ impl PaymentDriver for Erc20Driver {
         msg: VerifyPayment,
     ) -> Result<PaymentDetails, GenericError> {
         log::debug!("verify_payment: {:?}", msg);
-        let (network, _) = network::platform_to_network_token(msg.platform())?;
+        let network = self.networks.by_platform(&msg.platform())?.name.as_str();
         let tx_hash = format!("0x{}", hex::encode(msg.confirmation().confirmation));
         log::info!("Verifying transaction: {} on network {}", tx_hash, network);
         let verify_res = self
             .payment_runtime
             .verify_transaction(
-                network as i64,
+                self.chain_id(network)?,
                 H256::from_str(&tx_hash)
                     .map_err(|_| GenericError::new("Hash cannot be converted to string"))?,
                 H160::from_str(&msg.details.payer_addr)
@@ -12,7 +12,7 @@ use ya_payment_driver::{
 };
 
 // Local uses
-use crate::{driver::Erc20Driver, network, DRIVER_NAME};
+use crate::{driver::Erc20Driver, DRIVER_NAME};
 
 pub async fn init(driver: &Erc20Driver, msg: Init) -> Result<(), GenericError> {
     log::debug!("init: {:?}", msg);
@@ -24,16 +24,22 @@ pub async fn init(driver: &Erc20Driver, msg: Init) -> Result<(), GenericError> {
         driver.is_account_active(&address).await?
     }
 
-    let network = network::network_like_to_network(msg.network());
-    let token = network::get_network_token(network, msg.token());
-    bus::register_account(driver, &msg.address(), &network.to_string(), &token, mode).await?;
+    let network = driver.networks().network_like(msg.network())?;
+    let token = msg.token().unwrap_or_else(|| network.token.clone());
+    if token != network.token {
+        return Err(GenericError::new(format!(
+            "Token {} is not supported on network {}",
+            token, network.name
+        )));
+    }
+    bus::register_account(driver, &msg.address(), &network.name, &token, mode).await?;
 
     log::info!(
         "Initialised payment account. mode={:?}, address={}, driver={}, network={}, token={}",
         mode,
         &msg.address(),
         DRIVER_NAME,
-        network,
+        network.name,
         token
     );
     Ok(())
@@ -549,6 +549,7 @@ fn get_rpc_addr_from_env(network: Network) -> Vec<String> {
             "MUMBAI_GETH_ADDR",
             "https://matic-mumbai.chainstacklabs.com",
         ),
+        Network::Dev => collect_rpc_addr_from("DEV_GETH_ADDR", "http://127.0.0.1:8545"),
     }
 }
 
@@ -598,6 +599,7 @@ fn get_env(network: Network) -> config::EnvConfiguration {
         Network::Holesky => *config::HOLESKY_CONFIG,
         Network::Mumbai => *config::MUMBAI_CONFIG,
         Network::Polygon => *config::POLYGON_MAINNET_CONFIG,
+        Network::Dev => *config::DEV_CONFIG,
     }
 }
 
@@ -1,3 +1,4 @@
+use erc20_payment_lib::config::Config;
 use maplit::hashmap;
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -7,6 +8,7 @@ use ya_payment_driver::{db::models::Network as DbNetwork, driver::Network, model
 
 // Local uses
 use crate::{
+    DEV_CURRENCY_LONG, DEV_CURRENCY_SHORT, DEV_NETWORK, DEV_PLATFORM, DEV_TOKEN, DRIVER_NAME,
     GOERLI_CURRENCY_LONG, GOERLI_CURRENCY_SHORT, GOERLI_NETWORK, GOERLI_PLATFORM, GOERLI_TOKEN,
     HOLESKY_CURRENCY_LONG, HOLESKY_CURRENCY_SHORT, HOLESKY_NETWORK, HOLESKY_PLATFORM,
     HOLESKY_TOKEN, MAINNET_CURRENCY_LONG, MAINNET_CURRENCY_SHORT, MAINNET_NETWORK,
@@ -54,6 +56,12 @@ lazy_static::lazy_static! {
             tokens: hashmap! {
                 POLYGON_MAINNET_TOKEN.to_string() => POLYGON_MAINNET_PLATFORM.to_string()
             }
+        },
+        DEV_NETWORK.to_string() => Network {
+            default_token: DEV_TOKEN.to_string(),
+            tokens: hashmap! {
+                DEV_TOKEN.to_string() => DEV_PLATFORM.to_string()
+            }
         }
     };
     pub static ref RINKEBY_DB_NETWORK: DbNetwork = DbNetwork::from_str(RINKEBY_NETWORK).unwrap();
@@ -62,6 +70,7 @@ lazy_static::lazy_static! {
     pub static ref MAINNET_DB_NETWORK: DbNetwork = DbNetwork::from_str(MAINNET_NETWORK).unwrap();
     pub static ref MUMBAI_DB_NETWORK: DbNetwork = DbNetwork::from_str(MUMBAI_NETWORK).unwrap();
     pub static ref POLYGON_MAINNET_DB_NETWORK: DbNetwork = DbNetwork::from_str(POLYGON_MAINNET_NETWORK).unwrap();
+    pub static ref DEV_DB_NETWORK: DbNetwork = DbNetwork::from_str(DEV_NETWORK).unwrap();
 }
 
 pub fn platform_to_network_token(platform: String) -> Result<(DbNetwork, String), GenericError> {
@@ -75,6 +84,7 @@ pub fn platform_to_network_token(platform: String) -> Result<(DbNetwork, String)
             *POLYGON_MAINNET_DB_NETWORK,
             POLYGON_MAINNET_TOKEN.to_owned(),
         )),
+        DEV_PLATFORM => Ok((*DEV_DB_NETWORK, DEV_TOKEN.to_owned())),
         other => Err(GenericError::new(format!(
             "Unable to find network for platform: {}",
             other
@@ -108,6 +118,7 @@ pub fn platform_to_currency(platform: String) -> Result<(String, String), Generi
             POLYGON_MAINNET_CURRENCY_SHORT.to_owned(),
             POLYGON_MAINNET_CURRENCY_LONG.to_owned(),
         )),
+        DEV_PLATFORM => Ok((DEV_CURRENCY_SHORT.to_owned(), DEV_CURRENCY_LONG.to_owned())),
         other => Err(GenericError::new(format!(
             "Unable to find network currency for platform: {}",
             other
@@ -115,16 +126,181 @@ pub fn platform_to_currency(platform: String) -> Result<(String, String), Generi
     }
 }
 
-pub fn get_network_token(network: DbNetwork, token: Option<String>) -> String {
-    // Fetch network config, safe as long as all DbNetwork entries are in SUPPORTED_NETWORKS
-    let network_config = (*SUPPORTED_NETWORKS).get(&(network.to_string())).unwrap();
-    // TODO: Check if token in network.tokens
-    token.unwrap_or_else(|| network_config.default_token.clone())
+/// Payment network served by the driver.
+#[derive(Clone, Debug)]
+pub struct NetworkInfo {
+    pub name: String,
+    pub token: String,
+    pub platform: String,
+    pub currency_short: String,
+    pub currency_long: String,
 }
 
-pub fn network_like_to_network(network_like: Option<String>) -> DbNetwork {
-    match network_like {
-        Some(n) => DbNetwork::from_str(&n).unwrap_or(*HOLESKY_DB_NETWORK),
-        None => *HOLESKY_DB_NETWORK,
+/// Networks served by the driver: built-in ones and chains added to the payment config.
+///
+/// Platform of a chain from config is derived from its name and token symbol,
+/// e.g. `[chain.arbitrum]` with `GLM` token is served as `erc20-arbitrum-glm`.
+#[derive(Clone, Debug)]
+pub struct Networks(HashMap<String, NetworkInfo>);
+
+impl Networks {
+    pub fn new(config: &Config) -> Self {
+        let mut networks: HashMap<String, NetworkInfo> = SUPPORTED_NETWORKS
+            .iter()
+            .map(|(name, network)| {
+                let platform = network.tokens[&network.default_token].clone();
+                let (currency_short, currency_long) = platform_to_currency(platform.clone())
+                    .expect("Built-in network without currency");
+                let info = NetworkInfo {
+                    name: name.clone(),
+                    token: network.default_token.clone(),
+                    platform,
+                    currency_short,
+                    currency_long,
+                };
+                (name.clone(), info)
+            })
+            .collect();
+
+        for (name, chain) in &config.chain {
+            if networks.contains_key(name) {
+                continue;
+            }
+            // Platform is parsed as `driver-network-token`, so names are restricted
+            // to what payment service accepts as a network.
+            if !is_valid_network_name(name) {
+                log::warn!(
+                    "Skipping network {name} from payment config: name can contain only lowercase letters, digits and '_'"
+                );
+                continue;
+            }
+            let token = chain.token.symbol.clone();
+            let platform = format!("{DRIVER_NAME}-{name}-{}", token.to_lowercase());
+            log::info!("Using network {name} from payment config, platform: {platform}");
+            networks.insert(
+                name.clone(),
+                NetworkInfo {
+                    name: name.clone(),
+                    token,
+                    platform,
+                    currency_short: chain.currency_symbol.clone(),
+                    currency_long: chain.chain_name.clone(),
+                },
+            );
+        }
+        Networks(networks)
+    }
+
+    /// Finds network by name. Missing name resolves to Holesky.
+    pub fn network_like(&self, network_like: Option<String>) -> Result<&NetworkInfo, GenericError> {
+        let name = network_like.unwrap_or_else(|| HOLESKY_NETWORK.to_string());
+        self.0
+            .get(&name)
+            .ok_or_else(|| GenericError::new(format!("Unsupported network: {name}")))
+    }
+
+    pub fn by_platform(&self, platform: &str) -> Result<&NetworkInfo, GenericError> {
+        self.0
+            .values()
+            .find(|network| network.platform == platform)
+            .ok_or_else(|| {
+                GenericError::new(format!("Unable to find network for platform: {platform}"))
+            })
+    }
+
+    /// Networks as reported to the payment service.
+    pub fn driver_networks(&self) -> HashMap<String, Network> {
+        self.0
+            .values()
+            .map(|network| {
+                (
+                    network.name.clone(),
+                    Network {
+                        default_token: network.token.clone(),
+                        tokens: hashmap! { network.token.clone() => network.platform.clone() },
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+fn is_valid_network_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT_CONFIG: &str = include_str!("../config-payments.toml");
+
+    /// Bundled config with `dev` chain copied under another name and chain id.
+    fn config_with_chain(name: &str) -> Config {
+        let start = DEFAULT_CONFIG.find("[chain.dev]").unwrap();
+        let chain = DEFAULT_CONFIG[start..]
+            .replace("chain.dev", &format!("chain.{name}"))
+            .replace("chain-id = 31337", "chain-id = 42161")
+            .replace(
+                "chain-name = \"Local devnet\"",
+                "chain-name = \"Custom chain\"",
+            )
+            .replace("currency-symbol = \"tETH\"", "currency-symbol = \"cETH\"")
+            .replace("symbol = \"tGLM\"", "symbol = \"GLM\"");
+        Config::load_from_str(&format!("{DEFAULT_CONFIG}\n{chain}")).unwrap()
+    }
+
+    #[test]
+    fn builtin_networks() {
+        let networks = Networks::new(&Config::load_from_str(DEFAULT_CONFIG).unwrap());
+
+        assert_eq!(networks.driver_networks().len(), SUPPORTED_NETWORKS.len());
+        let holesky = networks.by_platform(HOLESKY_PLATFORM).unwrap();
+        assert_eq!(holesky.name, HOLESKY_NETWORK);
+        assert_eq!(holesky.token, HOLESKY_TOKEN);
+        assert_eq!(holesky.currency_short, HOLESKY_CURRENCY_SHORT);
+        assert_eq!(networks.network_like(None).unwrap().name, HOLESKY_NETWORK);
+        assert_eq!(
+            networks
+                .network_like(Some(DEV_NETWORK.into()))
+                .unwrap()
+                .platform,
+            DEV_PLATFORM
+        );
+        assert!(networks.network_like(Some("arbitrum".into())).is_err());
+    }
+
+    #[test]
+    fn custom_network_from_config() {
+        let networks = Networks::new(&config_with_chain("arbitrum"));
+
+        let arbitrum = networks.by_platform("erc20-arbitrum-glm").unwrap();
+        assert_eq!(arbitrum.name, "arbitrum");
+        assert_eq!(arbitrum.token, "GLM");
+        assert_eq!(arbitrum.currency_short, "cETH");
+        assert_eq!(arbitrum.currency_long, "Custom chain");
+
+        let driver_networks = networks.driver_networks();
+        assert_eq!(driver_networks.len(), SUPPORTED_NETWORKS.len() + 1);
+        assert_eq!(driver_networks["arbitrum"].default_token, "GLM");
+        assert_eq!(
+            driver_networks["arbitrum"].tokens["GLM"],
+            "erc20-arbitrum-glm"
+        );
+        // Built-in networks keep their platforms.
+        assert_eq!(driver_networks[DEV_NETWORK].tokens[DEV_TOKEN], DEV_PLATFORM);
+    }
+
+    #[test]
+    fn invalid_network_name_skipped() {
+        for name in ["arbitrum-one", "Arbitrum"] {
+            let networks = Networks::new(&config_with_chain(name));
+            assert_eq!(networks.driver_networks().len(), SUPPORTED_NETWORKS.len());
+            assert!(networks.by_platform("erc20-arbitrum-one-glm").is_err());
+            assert!(networks.by_platform("erc20-Arbitrum-glm").is_err());
+        }
     }
 }
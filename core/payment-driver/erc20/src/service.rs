@@ -17,7 +17,7 @@ use ethereum_types::H160;
 use ya_payment_driver::bus;
 
 // Local uses
-use crate::{driver::Erc20Driver, signer::IdentitySigner};
+use crate::{driver::Erc20Driver, network::Networks, signer::IdentitySigner};
 
 pub struct Erc20Service;
 
@@ -95,6 +95,7 @@ impl Erc20Service {
                 let wrapper_contract_env = format!("{prefix}_WRAPPER_CONTRACT_ADDRESS");
                 let multi_payment_addr_env = format!("{prefix}_MULTI_PAYMENT_CONTRACT_ADDRESS");
                 let lock_payment_addr_env = format!("{prefix}_LOCK_PAYMENT_CONTRACT_ADDRESS");
+                let mint_addr_env = format!("{prefix}_MINT_CONTRACT_ADDRESS");
                 let confirmations_env = format!("ERC20_{prefix}_REQUIRED_CONFIRMATIONS");
 
                 if let Ok(addr) = env::var(&rpc_env) {
@@ -201,6 +202,24 @@ impl Erc20Service {
                         }
                     };
                 }
+                if let Ok(mint_addr) = env::var(&mint_addr_env) {
+                    match (H160::from_str(&mint_addr), chain.mint_contract.as_mut()) {
+                        (Ok(parsed), Some(mint_contract)) => {
+                            log::info!("{network} mint contract address set to {mint_addr}");
+                            mint_contract.address = parsed;
+                        }
+                        (Ok(_), None) => {
+                            log::warn!(
+                                "{network} has no mint contract configured, ignoring {mint_addr_env}"
+                            );
+                        }
+                        (Err(e), _) => {
+                            log::warn!(
+                                "Value {mint_addr} for {mint_addr_env} is not valid H160 address: {e}"
+                            );
+                        }
+                    };
+                }
                 if let Ok(wrapper_contract_addr) = env::var(&wrapper_contract_env) {
                     match H160::from_str(&wrapper_contract_addr) {
                         Ok(parsed) => {
@@ -226,6 +245,8 @@ impl Erc20Service {
                 }
             }
 
+            let networks = Networks::new(&config);
+
             log::debug!("Starting payment engine: {:#?}", config);
             let signer = IdentitySigner;
 
@@ -251,7 +272,7 @@ impl Erc20Service {
             //    .await?;
 
             log::debug!("Bind erc20 driver");
-            let driver = Erc20Driver::new(pr, recv);
+            let driver = Erc20Driver::new(pr, networks, recv);
             driver.load_active_accounts().await;
             bus::bind_service(driver).await?;
 
@@ -0,0 +1,404 @@
+//! End-to-end tests of the driver against a local development chain.
+//!
+//! The devnet is started with `tests/devnet/deploy.sh` (needs Docker), which prints
+//! addresses of deployed contracts. Tests are ignored by default:
+//!
+//! ```sh
+//! cargo test -p ya-erc20-driver --test devnet -- --ignored
+//! ```
+//!
+//! `ERC20_DEVNET_DEPLOYMENT` can point to output of `deploy.sh` for an already running node.
+
+use anyhow::{anyhow, Context};
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use ethsign::SecretKey;
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+use web3::contract::{Contract, Options};
+use web3::transports::Http;
+use web3::types::{Address, TransactionRequest, U256};
+use web3::Web3;
+
+use ya_client_model::payment::Payment;
+use ya_client_model::NodeId;
+use ya_core_model::driver::{self, driver_bus_id, AccountMode, PaymentConfirmation};
+use ya_core_model::identity;
+use ya_core_model::payment::local as payment_srv;
+use ya_core_model::payment::public::Ack;
+use ya_erc20_driver::{PaymentDriverService, DEV_NETWORK, DEV_PLATFORM, DRIVER_NAME};
+use ya_service_bus::typed as bus;
+
+const TIMEOUT: Duration = Duration::from_secs(180);
+/// ABI of contract functions called by the test.
+const ABI: &str = include_str!("devnet/abi.json");
+
+/// Output of `deploy.sh`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Deployment {
+    rpc: String,
+    chain_id: u64,
+    contracts: Addresses,
+}
+
+#[derive(Deserialize)]
+struct Addresses {
+    token: Address,
+    faucet: Address,
+    lock: Address,
+    multi: Address,
+}
+
+/// Devnet node, removed on drop if it was started by the test.
+struct Devnet {
+    deployment: Deployment,
+    script: Option<PathBuf>,
+}
+
+impl Devnet {
+    fn start() -> anyhow::Result<Devnet> {
+        if let Ok(path) = env::var("ERC20_DEVNET_DEPLOYMENT") {
+            let deployment = std::fs::read(&path).with_context(|| format!("Reading {path}"))?;
+            return Ok(Devnet {
+                deployment: serde_json::from_slice(&deployment)?,
+                script: None,
+            });
+        }
+
+        let script = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/devnet/deploy.sh");
+        let output = Command::new(&script)
+            .output()
+            .with_context(|| format!("Running {}", script.display()))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "{} failed: {}",
+                script.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let deployment = serde_json::from_slice(&output.stdout)
+            .with_context(|| format!("Parsing output of {}", script.display()))?;
+        Ok(Devnet {
+            deployment,
+            script: Some(script),
+        })
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        if let Some(script) = &self.script {
+            Command::new(script).arg("stop").status().ok();
+        }
+    }
+}
+
+/// Contracts deployed on the devnet.
+struct Contracts {
+    token: Contract<Http>,
+    faucet: Contract<Http>,
+    lock: Contract<Http>,
+}
+
+impl Contracts {
+    fn new(web3: &Web3<Http>, addresses: &Addresses) -> anyhow::Result<Contracts> {
+        let abi: serde_json::Value = serde_json::from_str(ABI)?;
+        let contract = |name: &str, address: Address| -> anyhow::Result<Contract<Http>> {
+            let abi = serde_json::to_vec(&abi[name])?;
+            Ok(Contract::from_json(web3.eth(), address, &abi)?)
+        };
+        Ok(Contracts {
+            token: contract("token", addresses.token)?,
+            faucet: contract("faucet", addresses.faucet)?,
+            lock: contract("lock", addresses.lock)?,
+        })
+    }
+}
+
+/// Writes driver config for the devnet's chain id and points `dev` network
+/// to the devnet with environment variables.
+fn prepare_data_dir(deployment: &Deployment) -> anyhow::Result<TempDir> {
+    let config = include_str!("../config-payments.toml")
+        .replace(
+            "chain-id = 31337",
+            &format!("chain-id = {}", deployment.chain_id),
+        )
+        .replace("process-interval = 15", "process-interval = 1");
+
+    let data_dir = TempDir::new()?;
+    std::fs::write(data_dir.path().join("config-payments.toml"), config)?;
+
+    let contracts = &deployment.contracts;
+    env::set_var("ERC20_SENDOUT_INTERVAL_SECS", "1");
+    env::set_var("DEV_GETH_ADDR", &deployment.rpc);
+    for (var, address) in [
+        ("DEV_TGLM_CONTRACT_ADDRESS", contracts.token),
+        ("DEV_MINT_CONTRACT_ADDRESS", contracts.faucet),
+        ("DEV_LOCK_PAYMENT_CONTRACT_ADDRESS", contracts.lock),
+        ("DEV_MULTI_PAYMENT_CONTRACT_ADDRESS", contracts.multi),
+    ] {
+        env::set_var(var, format!("{address:#x}"));
+    }
+    Ok(data_dir)
+}
+
+/// Binds identity and payment services used by the driver.
+/// Payment notifications are forwarded to returned channel.
+fn bind_services(
+    key: Arc<SecretKey>,
+    node_id: NodeId,
+) -> mpsc::UnboundedReceiver<payment_srv::NotifyPayment> {
+    let _ = bus::bind(identity::BUS_ID, |_: identity::Subscribe| async {
+        Ok(identity::Ack {})
+    });
+    let _ = bus::bind(identity::BUS_ID, move |_: identity::List| async move {
+        Ok(vec![identity::IdentityInfo {
+            alias: None,
+            node_id,
+            is_locked: false,
+            is_default: true,
+            deleted: false,
+        }])
+    });
+    let sign_key = key.clone();
+    let _ = bus::bind(identity::BUS_ID, move |msg: identity::Sign| {
+        let key = sign_key.clone();
+        async move {
+            let signature = key
+                .sign(&msg.payload)
+                .map_err(|e| identity::Error::new_err_msg(e.to_string()))?;
+            let mut bytes = vec![signature.v];
+            bytes.extend_from_slice(&signature.r);
+            bytes.extend_from_slice(&signature.s);
+            Ok(bytes)
+        }
+    });
+    let _ = bus::bind(identity::BUS_ID, move |_: identity::GetPubKey| {
+        let key = key.clone();
+        async move { Ok(key.public().bytes().to_vec()) }
+    });
+
+    let _ = bus::bind(
+        payment_srv::BUS_ID,
+        |_: payment_srv::RegisterDriver| async { Ok(()) },
+    );
+    let _ = bus::bind(
+        payment_srv::BUS_ID,
+        |_: payment_srv::RegisterAccount| async { Ok(()) },
+    );
+    let _ = bus::bind(
+        payment_srv::BUS_ID,
+        |_: payment_srv::PaymentDriverStatusChange| async { Ok(Ack {}) },
+    );
+    let (tx, rx) = mpsc::unbounded_channel();
+    let _ = bus::bind(
+        payment_srv::BUS_ID,
+        move |msg: payment_srv::NotifyPayment| {
+            tx.send(msg).ok();
+            async { Ok(()) }
+        },
+    );
+    rx
+}
+
+async fn wait_for_payment(
+    payments: &mut mpsc::UnboundedReceiver<payment_srv::NotifyPayment>,
+    order_id: &str,
+) -> anyhow::Result<payment_srv::NotifyPayment> {
+    tokio::time::timeout(TIMEOUT, async {
+        while let Some(payment) = payments.recv().await {
+            if payment.order_ids.iter().any(|id| id == order_id) {
+                return Ok(payment);
+            }
+        }
+        Err(anyhow!("Payment service mock unbound"))
+    })
+    .await
+    .with_context(|| format!("Payment {order_id} not confirmed"))?
+}
+
+async fn verify(notification: &payment_srv::NotifyPayment) -> anyhow::Result<BigDecimal> {
+    let payment = Payment {
+        payment_id: uuid::Uuid::new_v4().to_string(),
+        payer_id: NodeId::from_str(&notification.sender)?,
+        payee_id: NodeId::from_str(&notification.recipient)?,
+        payer_addr: notification.sender.clone(),
+        payee_addr: notification.recipient.clone(),
+        payment_platform: notification.platform.clone(),
+        amount: notification.amount.clone(),
+        timestamp: Utc::now(),
+        activity_payments: vec![],
+        agreement_payments: vec![],
+        details: String::new(),
+    };
+    let details = bus::service(driver_bus_id(DRIVER_NAME))
+        .send(driver::VerifyPayment::new(
+            PaymentConfirmation::from(&notification.confirmation.confirmation),
+            DEV_PLATFORM.to_string(),
+            payment,
+        ))
+        .await??;
+    Ok(details.amount)
+}
+
+async fn token_balance(token: &Contract<Http>, address: Address) -> anyhow::Result<U256> {
+    Ok(token
+        .query("balanceOf", (address,), None, Options::default(), None)
+        .await?)
+}
+
+#[ignore]
+#[actix_rt::test]
+async fn devnet_payment_flow() -> anyhow::Result<()> {
+    env_logger::builder().is_test(true).try_init().ok();
+
+    let devnet = Devnet::start()?;
+    let web3 = Web3::new(Http::new(&devnet.deployment.rpc)?);
+    let accounts = web3.eth().accounts().await?;
+    let (deployer, funder) = match accounts.as_slice() {
+        [deployer, funder, ..] => (*deployer, *funder),
+        _ => anyhow::bail!("Devnet node should provide at least two unlocked accounts"),
+    };
+    let contracts = Contracts::new(&web3, &devnet.deployment.contracts)?;
+    let data_dir = prepare_data_dir(&devnet.deployment)?;
+
+    // Yagna identity paying for tasks. Gas comes from the node's prefunded account.
+    let key = Arc::new(SecretKey::from_raw(&[0x11; 32]).map_err(|e| anyhow!("{e:?}"))?);
+    let payer = Address::from_slice(key.public().address());
+    let payer_id = NodeId::from(key.public().address().as_ref());
+    web3.eth()
+        .send_transaction(TransactionRequest {
+            from: deployer,
+            to: Some(payer),
+            value: Some(U256::exp10(19)),
+            ..Default::default()
+        })
+        .await?;
+
+    let mut payments = bind_services(key, payer_id);
+    PaymentDriverService::gsb(data_dir.path().to_path_buf()).await?;
+    let driver = bus::service(driver_bus_id(DRIVER_NAME));
+    let payer_addr = format!("{payer:#x}");
+
+    // init
+    driver
+        .send(driver::Init::new(
+            payer_addr.clone(),
+            Some(DEV_NETWORK.to_string()),
+            None,
+            AccountMode::SEND,
+        ))
+        .await??;
+
+    // fund
+    let message = driver
+        .send(driver::Fund::new(
+            payer_addr.clone(),
+            Some(DEV_NETWORK.to_string()),
+            None,
+        ))
+        .await??;
+    log::info!("{message}");
+    assert!(token_balance(&contracts.token, payer).await? > U256::zero());
+
+    // schedule_payment and verify_payment
+    let amount = BigDecimal::from_str("1.5")?;
+    let order_id = driver
+        .send(driver::SchedulePayment::new(
+            amount.clone(),
+            payer_addr.clone(),
+            format!("{:#x}", Address::repeat_byte(0x22)),
+            DEV_PLATFORM.to_string(),
+            None,
+            Utc::now(),
+        ))
+        .await??;
+    let notification = wait_for_payment(&mut payments, &order_id).await?;
+    assert_eq!(verify(&notification).await?, amount);
+
+    // Funder locks tokens in a deposit, which can be spent by the payer.
+    contracts
+        .faucet
+        .call("create", (), funder, Options::default())
+        .await?;
+    let deposit_amount = U256::exp10(19);
+    contracts
+        .token
+        .call(
+            "approve",
+            (contracts.lock.address(), deposit_amount),
+            funder,
+            Options::default(),
+        )
+        .await?;
+    let nonce = U256::from(1);
+    let valid_to = U256::from((Utc::now() + chrono::Duration::hours(1)).timestamp());
+    contracts
+        .lock
+        .call(
+            "createDeposit",
+            (nonce, payer, deposit_amount, U256::zero(), valid_to),
+            funder,
+            Options::with(|opt| opt.gas = Some(500_000.into())),
+        )
+        .await?;
+    let deposit_id: U256 = contracts
+        .lock
+        .query(
+            "idFromNonceAndFunder",
+            (nonce, funder),
+            None,
+            Options::default(),
+            None,
+        )
+        .await?;
+    let deposit_contract = format!("{:#x}", contracts.lock.address());
+    let deposit = serde_json::from_value(json!({
+        "id": format!("{deposit_id:#x}"),
+        "contract": deposit_contract,
+    }))?;
+
+    // schedule_payment from deposit
+    let order_id = driver
+        .send(driver::SchedulePayment::new(
+            amount.clone(),
+            payer_addr.clone(),
+            format!("{:#x}", Address::repeat_byte(0x33)),
+            DEV_PLATFORM.to_string(),
+            Some(deposit),
+            Utc::now(),
+        ))
+        .await??;
+    let notification = wait_for_payment(&mut payments, &order_id).await?;
+    assert_eq!(verify(&notification).await?, amount);
+
+    // release_deposit returns remaining tokens to the funder
+    let funder_balance = token_balance(&contracts.token, funder).await?;
+    driver
+        .send(driver::DriverReleaseDeposit {
+            platform: DEV_PLATFORM.to_string(),
+            from: payer_addr,
+            deposit_contract,
+            deposit_id: format!("{deposit_id:#x}"),
+        })
+        .await??;
+    tokio::time::timeout(TIMEOUT, async {
+        while token_balance(&contracts.token, funder).await? <= funder_balance {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        Ok::<_, anyhow::Error>(())
+    })
+    .await
+    .context("Deposit not released")??;
+
+    Ok(())
+}
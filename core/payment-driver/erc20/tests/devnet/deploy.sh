@@ -0,0 +1,47 @@
+#!/bin/sh
+# Starts a local devnet with Golem contracts deployed and prints the deployment as JSON.
+# Uses the same image as goth tests, which deploys contracts on start.
+#
+#   deploy.sh        start the node and print the deployment
+#   deploy.sh stop   remove the node
+set -eu
+
+IMAGE=ghcr.io/golemfactory/gnt2/gnt2-docker-yagna:b628fa5bfe0d
+NAME=${ERC20_DEVNET_NAME:-erc20-devnet}
+PORT=${ERC20_DEVNET_PORT:-8545}
+CHAIN_ID=${ERC20_DEVNET_CHAIN_ID:-31337}
+
+if [ "${1:-}" = "stop" ]; then
+    docker rm -f "$NAME" >/dev/null
+    exit 0
+fi
+
+docker run -d --rm --name "$NAME" -p "$PORT:8545" -e GANACHE_CHAIN_ID="$CHAIN_ID" "$IMAGE" >/dev/null
+
+# The image reports supplied wallets after all contracts are deployed.
+elapsed=0
+until docker logs "$NAME" 2>&1 | grep -q "Wallets supplied."; do
+    if [ "$elapsed" -ge 120 ]; then
+        echo "Devnet not ready within ${elapsed}s" >&2
+        docker logs "$NAME" >&2
+        docker rm -f "$NAME" >/dev/null
+        exit 1
+    fi
+    sleep 1
+    elapsed=$((elapsed + 1))
+done
+
+# Contracts are deployed from the image's fixed accounts, so their addresses don't change.
+# They match the `dev` chain defaults in config-payments.toml.
+cat <<JSON
+{
+    "rpc": "http://127.0.0.1:$PORT",
+    "chainId": $CHAIN_ID,
+    "contracts": {
+        "token": "0x8888888815bf4DB87e57B609A50f938311EEd068",
+        "faucet": "0xFACe100969FF47EB58d2CF603321B581A84bcEaC",
+        "lock": "0xD756fb6A081CC11e7F513C39399DB296b1DE3036",
+        "multi": "0xAaAAAaA00E1841A63342db7188abA84BDeE236c7"
+    }
+}
+JSON
@@ -0,0 +1,2 @@
+DROP TABLE pay_allocation_policy;
+ALTER TABLE pay_order DROP COLUMN timestamp;
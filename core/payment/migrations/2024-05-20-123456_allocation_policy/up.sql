@@ -0,0 +1,14 @@
+CREATE TABLE pay_allocation_policy(
+    allocation_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    max_per_provider VARCHAR(32) NULL,
+    max_per_hour VARCHAR(32) NULL,
+    max_per_day VARCHAR(32) NULL,
+    max_debit_note_increment VARCHAR(32) NULL,
+    enforce_price_model BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY(allocation_id, owner_id),
+    FOREIGN KEY(allocation_id) REFERENCES pay_allocation(id) ON DELETE CASCADE
+);
+
+-- Orders created before this migration have no timestamp and do not count towards hourly / daily limits.
+ALTER TABLE pay_order ADD COLUMN timestamp DATETIME NULL;
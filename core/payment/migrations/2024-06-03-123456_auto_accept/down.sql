@@ -0,0 +1,3 @@
+DROP INDEX pay_auto_accept_audit_owner_timestamp_idx;
+DROP TABLE pay_auto_accept_audit;
+DROP TABLE pay_auto_accept_rule;
@@ -0,0 +1,25 @@
+CREATE TABLE pay_auto_accept_rule(
+    allocation_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    price_tolerance VARCHAR(32) NULL,
+    time_tolerance VARCHAR(32) NULL,
+    max_total VARCHAR(32) NULL,
+    reject_invoices BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY(allocation_id, owner_id),
+    FOREIGN KEY(allocation_id) REFERENCES pay_allocation(id) ON DELETE CASCADE
+);
+
+CREATE TABLE pay_auto_accept_audit(
+    id VARCHAR(50) NOT NULL PRIMARY KEY,
+    owner_id VARCHAR(50) NOT NULL,
+    allocation_id VARCHAR(50) NOT NULL,
+    document_type VARCHAR(50) NOT NULL,
+    document_id VARCHAR(50) NOT NULL,
+    agreement_id VARCHAR(50) NOT NULL,
+    amount VARCHAR(32) NOT NULL,
+    decision VARCHAR(50) NOT NULL,
+    reason TEXT NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW'))
+);
+
+CREATE INDEX pay_auto_accept_audit_owner_timestamp_idx ON pay_auto_accept_audit(owner_id, timestamp);
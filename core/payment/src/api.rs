@@ -6,19 +6,23 @@ use ya_service_api_web::scope::ExtendableScope;
 
 mod accounts;
 pub mod allocations;
+pub(crate) mod auto_accept;
 mod debit_notes;
 mod invoices;
+mod ledger;
 mod payments;
 
 mod guard;
 
 pub fn api_scope(scope: Scope) -> Scope {
     scope
-        .app_data(web::Data::new(guard::AgreementLock::arc()))
+        .app_data(web::Data::new(guard::AgreementLock::shared()))
         .extend(accounts::register_endpoints)
         .extend(allocations::register_endpoints)
+        .extend(auto_accept::register_endpoints)
         .extend(debit_notes::register_endpoints)
         .extend(invoices::register_endpoints)
+        .extend(ledger::register_endpoints)
         .extend(payments::register_endpoints)
 }
 
@@ -32,6 +32,7 @@ const DEFAULT_PAYMENT_DRIVER: DriverName = DriverName::Erc20;
 
 mod api_error;
 mod platform_triple;
+pub mod policy;
 mod token_name;
 
 use platform_triple::PaymentPlatformTriple;
@@ -46,6 +47,18 @@ pub fn register_endpoints(scope: Scope) -> Scope {
             "/allocations/{allocation_id}",
             delete().to(release_allocation),
         )
+        .route(
+            "/allocations/{allocation_id}/policy",
+            get().to(policy::get_allocation_policy),
+        )
+        .route(
+            "/allocations/{allocation_id}/policy",
+            put().to(policy::set_allocation_policy),
+        )
+        .route(
+            "/allocations/{allocation_id}/policy",
+            delete().to(policy::remove_allocation_policy),
+        )
         .route("/demandDecorations", get().to(get_demand_decorations))
 }
 
@@ -9,7 +9,7 @@ use ya_core_model::payment::local::{DriverName, NetworkName};
 
 pub struct PaymentPlatformTriple {
     driver: DriverName,
-    network: NetworkName,
+    network: String,
     token: TokenName,
 }
 
@@ -18,7 +18,7 @@ impl PaymentPlatformTriple {
         &self.driver
     }
 
-    pub fn network(&self) -> &NetworkName {
+    pub fn network(&self) -> &str {
         &self.network
     }
 
@@ -29,7 +29,7 @@ impl PaymentPlatformTriple {
     pub fn default_testnet() -> Self {
         PaymentPlatformTriple {
             driver: DEFAULT_PAYMENT_DRIVER,
-            network: DEFAULT_TESTNET_NETWORK,
+            network: DEFAULT_TESTNET_NETWORK.to_string(),
             token: TokenName::default(&DEFAULT_PAYMENT_DRIVER, &DEFAULT_TESTNET_NETWORK),
         }
     }
@@ -37,7 +37,7 @@ impl PaymentPlatformTriple {
     pub fn default_mainnet() -> Self {
         PaymentPlatformTriple {
             driver: DEFAULT_PAYMENT_DRIVER,
-            network: DEFAULT_MAINNET_NETWORK,
+            network: DEFAULT_MAINNET_NETWORK.to_string(),
             token: TokenName::default(&DEFAULT_PAYMENT_DRIVER, &DEFAULT_MAINNET_NETWORK),
         }
     }
@@ -101,16 +101,33 @@ impl PaymentPlatformTriple {
                 );
                 DEFAULT_PAYMENT_DRIVER.into()
             });
-            let driver = validate_driver(&network, driver_str)
+            let driver = validate_driver(driver_str)
                 .map_err(|err| anyhow!("Validate driver failed (1): {err}"))?;
 
+            let network = match network {
+                Some(network) => network,
+                None => {
+                    let token = p.token.as_ref().ok_or_else(|| {
+                        anyhow!("Token has to be specified for network {network_str}")
+                    })?;
+                    let token = TokenName::from_custom_token_string(token)
+                        .map_err(|err| anyhow!("Validate token failed (1): {err}"))?;
+                    log::debug!("Selected network {}-{}-{}", driver, network_str, token);
+                    return Ok(Self {
+                        driver,
+                        network: network_str.to_string(),
+                        token,
+                    });
+                }
+            };
+
             if let Some(token) = p.token.as_ref() {
                 let token = TokenName::from_token_string(&driver, &network, token)
                     .map_err(|err| anyhow!("Validate token failed (1): {err}"))?;
                 log::debug!("Selected network {}-{}-{}", driver, network, token);
                 Self {
                     driver,
-                    network,
+                    network: network.to_string(),
                     token,
                 }
             } else {
@@ -124,7 +141,7 @@ impl PaymentPlatformTriple {
                 );
                 Self {
                     driver,
-                    network,
+                    network: network.to_string(),
                     token: default_token,
                 }
             }
@@ -151,15 +168,18 @@ impl PaymentPlatformTriple {
         let network = validate_network(network_str)
             .map_err(|err| anyhow!("Validate network failed (2): {err}"))?;
 
-        let driver = validate_driver(&network, driver_str)
+        let driver = validate_driver(driver_str)
             .map_err(|err| anyhow!("Validate driver failed (2): {err}"))?;
 
-        let token = TokenName::from_token_string(&driver, &network, token_str)
-            .map_err(|err| anyhow!("Validate token failed (2): {err}"))?;
+        let token = match &network {
+            Some(network) => TokenName::from_token_string(&driver, network, token_str),
+            None => TokenName::from_custom_token_string(token_str),
+        }
+        .map_err(|err| anyhow!("Validate token failed (2): {err}"))?;
 
         Ok(Self {
             driver,
-            network,
+            network: network_str.to_string(),
             token,
         })
     }
@@ -171,17 +191,101 @@ impl Display for PaymentPlatformTriple {
     }
 }
 
-fn validate_network(network: &str) -> Result<NetworkName, String> {
+/// Returns `None` for networks unknown to yagna. Such networks can be
+/// added to payment drivers by configuration, so they are checked by the driver.
+fn validate_network(network: &str) -> Result<Option<NetworkName>, String> {
     match NetworkName::from_str(network) {
         Ok(NetworkName::Rinkeby) => Err("Rinkeby is no longer supported".to_string()),
-        Ok(network_name) => Ok(network_name),
+        Ok(network_name) => Ok(Some(network_name)),
+        Err(_) if is_valid_custom_network(network) => Ok(None),
         Err(_) => Err(format!("Invalid network name: {network}")),
     }
 }
 
-fn validate_driver(network: &NetworkName, driver: &str) -> Result<DriverName, String> {
+fn is_valid_custom_network(network: &str) -> bool {
+    !network.is_empty()
+        && network
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn validate_driver(driver: &str) -> Result<DriverName, String> {
     match DriverName::from_str(driver) {
         Err(_) => Err(format!("Invalid driver name {}", driver)),
         Ok(driver_name) => Ok(driver_name),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn platform(network: Option<&str>, token: Option<&str>) -> PaymentPlatform {
+        PaymentPlatform {
+            driver: None,
+            network: network.map(ToString::to_string),
+            token: token.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn builtin_platform_str() {
+        let triple =
+            PaymentPlatformTriple::from_payment_platform_str("erc20-holesky-tglm").unwrap();
+        assert_eq!(triple.network(), "holesky");
+        assert_eq!(triple.to_string(), "erc20-holesky-tglm");
+
+        assert!(PaymentPlatformTriple::from_payment_platform_str("erc20-holesky-glm").is_err());
+        assert!(PaymentPlatformTriple::from_payment_platform_str("erc20-rinkeby-tglm").is_err());
+    }
+
+    #[test]
+    fn custom_platform_str() {
+        let triple =
+            PaymentPlatformTriple::from_payment_platform_str("erc20-arbitrum_one-glm").unwrap();
+        assert_eq!(triple.driver(), &DriverName::Erc20);
+        assert_eq!(triple.network(), "arbitrum_one");
+        assert_eq!(triple.token().to_string(), "glm");
+
+        for invalid in [
+            "erc20-Arbitrum-glm",
+            "erc20-arbitrum-GLM",
+            "erc20-arbitrum-one-glm",
+            "erc20--glm",
+            "zksync-arbitrum-glm",
+        ] {
+            assert!(
+                PaymentPlatformTriple::from_payment_platform_str(invalid).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn custom_platform_input() {
+        let triple = PaymentPlatformTriple::from_payment_platform_input(&platform(
+            Some("arbitrum"),
+            Some("glm"),
+        ))
+        .unwrap();
+        assert_eq!(triple.to_string(), "erc20-arbitrum-glm");
+
+        // Token of a custom network can't be defaulted.
+        assert!(
+            PaymentPlatformTriple::from_payment_platform_input(&platform(Some("arbitrum"), None))
+                .is_err()
+        );
+        assert!(
+            PaymentPlatformTriple::from_payment_platform_input(&platform(
+                Some("arbitrum"),
+                Some("GLM")
+            ))
+            .is_err()
+        );
+
+        let triple =
+            PaymentPlatformTriple::from_payment_platform_input(&platform(Some("polygon"), None))
+                .unwrap();
+        assert_eq!(triple.to_string(), "erc20-polygon-glm");
+    }
+}
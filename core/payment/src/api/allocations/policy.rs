@@ -0,0 +1,464 @@
+use actix_web::web::{Data, Json, Path};
+use actix_web::HttpResponse;
+use bigdecimal::{BigDecimal, Zero};
+use serde_json::value::Value::Null;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use ya_agreement_utils::AgreementView;
+use ya_client_model::market::{Agreement, Role};
+use ya_client_model::payment::{params, DebitNote, Invoice};
+use ya_client_model::NodeId;
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::middleware::Identity;
+
+use crate::dao::*;
+use crate::error::DbError;
+use crate::models::allocation_policy::{AllocationPolicy, AllocationSpending};
+use crate::utils::{get_agreement, response};
+
+const LINEAR_COEFFS_PROPERTY: &str = "/offer/properties/golem/com/pricing/model/linear/coeffs";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    #[error("Allocation policy violated: {0}")]
+    Violated(String),
+    #[error("Debit note exceeds the agreed price model: {0}")]
+    PriceExceeded(String),
+    #[error("Cannot verify debit note against price model: {0}")]
+    PriceModel(String),
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl PolicyError {
+    /// Whether the document breaks the policy, as opposed to failing to check it.
+    pub fn is_violation(&self) -> bool {
+        matches!(
+            self,
+            PolicyError::Violated(_) | PolicyError::PriceExceeded(_)
+        )
+    }
+
+    pub fn to_response(&self) -> HttpResponse {
+        match self {
+            PolicyError::Violated(_) | PolicyError::PriceExceeded(_) => response::bad_request(self),
+            PolicyError::PriceModel(_) | PolicyError::Db(_) => response::server_error(self),
+        }
+    }
+}
+
+pub async fn get_allocation_policy(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+
+    match db
+        .as_dao::<AllocationPolicyDao>()
+        .get(allocation_id, node_id)
+        .await
+    {
+        Ok(Some(policy)) => response::ok(policy),
+        Ok(None) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+pub async fn set_allocation_policy(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    body: Json<AllocationPolicy>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+    let policy = body.into_inner();
+
+    let limits = [
+        &policy.max_per_provider,
+        &policy.max_per_hour,
+        &policy.max_per_day,
+        &policy.max_debit_note_increment,
+    ];
+    if limits
+        .iter()
+        .flat_map(|limit| limit.iter())
+        .any(|limit| limit < &BigDecimal::zero())
+    {
+        return response::bad_request(&"Allocation policy limits cannot be negative");
+    }
+
+    match db
+        .as_dao::<AllocationDao>()
+        .get(allocation_id.clone(), node_id)
+        .await
+    {
+        Ok(AllocationStatus::Active(_)) => (),
+        Ok(AllocationStatus::Gone) => {
+            return response::gone(&format!(
+                "Allocation {} has been already released",
+                allocation_id
+            ))
+        }
+        Ok(AllocationStatus::NotFound) => return response::not_found(),
+        Err(e) => return response::server_error(&e),
+    }
+
+    match db
+        .as_dao::<AllocationPolicyDao>()
+        .set(allocation_id, node_id, policy.clone())
+        .await
+    {
+        Ok(()) => response::ok(policy),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+pub async fn remove_allocation_policy(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+
+    match db
+        .as_dao::<AllocationPolicyDao>()
+        .remove(allocation_id, node_id)
+        .await
+    {
+        Ok(true) => response::ok(Null),
+        Ok(false) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+/// Checks accepting `debit_note` against the policy of the allocation it is paid from.
+///
+/// `amount_to_pay` is the part of the debit note that is not scheduled yet, i.e. the
+/// increment over previously accepted debit notes for the activity.
+///
+/// The caller must hold the allocation lock until the payment is scheduled,
+/// otherwise concurrent acceptances could exceed the limits together.
+pub async fn check_debit_note(
+    db: &DbExecutor,
+    node_id: NodeId,
+    allocation_id: &str,
+    debit_note: &DebitNote,
+    amount_to_pay: &BigDecimal,
+) -> Result<(), PolicyError> {
+    let (policy, spending) = match db
+        .as_dao::<AllocationPolicyDao>()
+        .get_with_spending(allocation_id.to_string(), node_id, debit_note.issuer_id)
+        .await?
+    {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    check_increment(&policy, &debit_note.debit_note_id, amount_to_pay)?;
+    if policy.enforce_price_model {
+        check_price_model(debit_note).await?;
+    }
+    check_limits(
+        &policy,
+        &spending,
+        allocation_id,
+        debit_note.issuer_id,
+        amount_to_pay,
+    )
+}
+
+/// Checks accepting `invoice` against the policy of the allocation it is paid from.
+///
+/// Same locking requirements as [`check_debit_note`].
+pub async fn check_invoice(
+    db: &DbExecutor,
+    node_id: NodeId,
+    allocation_id: &str,
+    invoice: &Invoice,
+    amount_to_pay: &BigDecimal,
+) -> Result<(), PolicyError> {
+    match db
+        .as_dao::<AllocationPolicyDao>()
+        .get_with_spending(allocation_id.to_string(), node_id, invoice.issuer_id)
+        .await?
+    {
+        Some((policy, spending)) => check_limits(
+            &policy,
+            &spending,
+            allocation_id,
+            invoice.issuer_id,
+            amount_to_pay,
+        ),
+        None => Ok(()),
+    }
+}
+
+fn check_increment(
+    policy: &AllocationPolicy,
+    debit_note_id: &str,
+    amount_to_pay: &BigDecimal,
+) -> Result<(), PolicyError> {
+    if let Some(max) = &policy.max_debit_note_increment {
+        if amount_to_pay > max {
+            return Err(PolicyError::Violated(format!(
+                "debit note {} increases amount due by {}, limit is {}",
+                debit_note_id, amount_to_pay, max
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_limits(
+    policy: &AllocationPolicy,
+    spending: &AllocationSpending,
+    allocation_id: &str,
+    provider_id: NodeId,
+    amount_to_pay: &BigDecimal,
+) -> Result<(), PolicyError> {
+    if amount_to_pay <= &BigDecimal::zero() {
+        return Ok(());
+    }
+
+    if let Some(max) = &policy.max_per_provider {
+        let total = &spending.to_provider + amount_to_pay;
+        if &total > max {
+            return Err(PolicyError::Violated(format!(
+                "provider {} would receive {} from allocation {}, limit is {}",
+                provider_id, total, allocation_id, max
+            )));
+        }
+    }
+
+    let windows = [
+        ("hour", &spending.last_hour, &policy.max_per_hour),
+        ("day", &spending.last_day, &policy.max_per_day),
+    ];
+    for (name, scheduled, max) in windows.iter() {
+        if let Some(max) = max {
+            let total = *scheduled + amount_to_pay;
+            if &total > max {
+                return Err(PolicyError::Violated(format!(
+                    "allocation {} would spend {} within the last {}, limit is {}",
+                    allocation_id, total, name, max
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recomputes the cost of the usage reported in the debit note using the linear
+/// price model from the agreement. Other pricing models are not verified.
+async fn check_price_model(debit_note: &DebitNote) -> Result<(), PolicyError> {
+    let (_, view) = agreement_view(&debit_note.agreement_id)
+        .await
+        .map_err(PolicyError::PriceModel)?;
+    let price = match LinearPrice::from_agreement(&view).map_err(PolicyError::PriceModel)? {
+        Some(price) => price,
+        None => {
+            log::warn!(
+                "Agreement [{}] has no linear price model, debit note [{}] not verified",
+                debit_note.agreement_id,
+                debit_note.debit_note_id
+            );
+            return Ok(());
+        }
+    };
+    let expected = price
+        .cost(&usage_vector(debit_note).map_err(PolicyError::PriceModel)?)
+        .map_err(PolicyError::PriceModel)?;
+
+    if debit_note.total_amount_due > expected {
+        return Err(PolicyError::PriceExceeded(format!(
+            "debit note {} charges {}, while the agreed price model yields {} for the reported usage",
+            debit_note.debit_note_id, debit_note.total_amount_due, expected
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) async fn agreement_view(
+    agreement_id: &str,
+) -> Result<(Agreement, AgreementView), String> {
+    let agreement = match get_agreement(agreement_id.to_string(), Role::Requestor).await {
+        Ok(Some(agreement)) => agreement,
+        Ok(None) => return Err(format!("agreement {} not found", agreement_id)),
+        Err(e) => return Err(e.to_string()),
+    };
+    let view = AgreementView::try_from(&agreement).map_err(|e| e.to_string())?;
+    Ok((agreement, view))
+}
+
+pub(crate) fn usage_vector(debit_note: &DebitNote) -> Result<Vec<f64>, String> {
+    match &debit_note.usage_counter_vector {
+        Some(usage) => serde_json::from_value(usage.clone())
+            .map_err(|e| format!("invalid usage vector: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn to_decimal(value: f64) -> Result<BigDecimal, String> {
+    BigDecimal::from_str(&value.to_string()).map_err(|e| format!("invalid number {}: {}", value, e))
+}
+
+/// Coefficients of the agreement's linear price model.
+pub(crate) struct LinearPrice {
+    usage_coeffs: Vec<BigDecimal>,
+    fixed: BigDecimal,
+}
+
+impl LinearPrice {
+    /// Returns `None` if the agreement uses another pricing model.
+    pub fn from_agreement(view: &AgreementView) -> Result<Option<Self>, String> {
+        let coeffs = match view.pointer_typed::<Vec<f64>>(LINEAR_COEFFS_PROPERTY) {
+            Ok(coeffs) => coeffs,
+            Err(_) => return Ok(None),
+        };
+        // Last coefficient is the constant initial price.
+        let (fixed, usage_coeffs) = match coeffs.split_last() {
+            Some(split) => split,
+            None => return Err("empty price coefficients".into()),
+        };
+        Ok(Some(Self {
+            usage_coeffs: usage_coeffs
+                .iter()
+                .map(|coeff| to_decimal(*coeff))
+                .collect::<Result<_, _>>()?,
+            fixed: to_decimal(*fixed)?,
+        }))
+    }
+
+    pub fn fixed(&self) -> &BigDecimal {
+        &self.fixed
+    }
+
+    pub fn cost(&self, usage: &[f64]) -> Result<BigDecimal, String> {
+        let mut cost = self.fixed.clone();
+        for (coeff, value) in self.usage_coeffs.iter().zip(usage.iter()) {
+            cost += coeff * &to_decimal(*value)?;
+        }
+        Ok(cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn amount(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    fn provider() -> NodeId {
+        NodeId::from_str("0x0000000000000000000000000000000000000001").unwrap()
+    }
+
+    fn spending(to_provider: &str, last_hour: &str, last_day: &str) -> AllocationSpending {
+        AllocationSpending {
+            to_provider: amount(to_provider),
+            last_hour: amount(last_hour),
+            last_day: amount(last_day),
+        }
+    }
+
+    #[test]
+    fn limits_not_set() {
+        let spending = spending("100", "100", "100");
+        let result = check_limits(
+            &AllocationPolicy::default(),
+            &spending,
+            "alloc",
+            provider(),
+            &amount("1000"),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn limit_per_provider() {
+        let policy = AllocationPolicy {
+            max_per_provider: Some(amount("10")),
+            ..Default::default()
+        };
+        let spending = spending("7", "0", "0");
+
+        assert!(check_limits(&policy, &spending, "alloc", provider(), &amount("3")).is_ok());
+        let err =
+            check_limits(&policy, &spending, "alloc", provider(), &amount("3.5")).unwrap_err();
+        assert!(err.is_violation());
+    }
+
+    #[test]
+    fn limits_per_window() {
+        let policy = AllocationPolicy {
+            max_per_hour: Some(amount("5")),
+            max_per_day: Some(amount("20")),
+            ..Default::default()
+        };
+
+        let within = spending("0", "2", "15");
+        assert!(check_limits(&policy, &within, "alloc", provider(), &amount("3")).is_ok());
+
+        let hour = spending("0", "4", "4");
+        let err = check_limits(&policy, &hour, "alloc", provider(), &amount("2")).unwrap_err();
+        assert!(err.to_string().contains("last hour"));
+
+        let day = spending("0", "0", "19");
+        let err = check_limits(&policy, &day, "alloc", provider(), &amount("2")).unwrap_err();
+        assert!(err.to_string().contains("last day"));
+    }
+
+    #[test]
+    fn zero_amount_is_not_limited() {
+        let policy = AllocationPolicy {
+            max_per_provider: Some(amount("0")),
+            ..Default::default()
+        };
+        let spending = spending("1", "1", "1");
+        assert!(check_limits(&policy, &spending, "alloc", provider(), &amount("0")).is_ok());
+    }
+
+    #[test]
+    fn debit_note_increment() {
+        let policy = AllocationPolicy {
+            max_debit_note_increment: Some(amount("1")),
+            ..Default::default()
+        };
+        assert!(check_increment(&policy, "dn", &amount("1")).is_ok());
+        assert!(check_increment(&policy, "dn", &amount("1.01"))
+            .unwrap_err()
+            .is_violation());
+    }
+
+    #[test]
+    fn linear_price_cost() {
+        let view = AgreementView {
+            json: json!({
+                "offer": {"properties": {"golem": {"com": {"pricing": {"model": {"linear": {
+                    "coeffs": [0.1, 0.01, 1.0]
+                }}}}}}}
+            }),
+            id: "agreement".into(),
+        };
+        let price = LinearPrice::from_agreement(&view).unwrap().unwrap();
+        assert_eq!(price.fixed(), &amount("1"));
+        assert_eq!(price.cost(&[10.0, 100.0]).unwrap(), amount("3"));
+        assert_eq!(price.cost(&[]).unwrap(), amount("1"));
+    }
+
+    #[test]
+    fn other_price_model() {
+        let view = AgreementView {
+            json: json!({"offer": {"properties": {}}}),
+            id: "agreement".into(),
+        };
+        assert!(LinearPrice::from_agreement(&view).unwrap().is_none());
+    }
+}
@@ -36,4 +36,17 @@ impl TokenName {
         }
         Ok(Self(token.to_string()))
     }
+
+    /// Token of a network unknown to yagna, which can't be validated
+    /// until payment driver is asked about the platform.
+    pub fn from_custom_token_string(token: &str) -> Result<Self, String> {
+        if token != token.to_lowercase() {
+            return Err(format!(
+                "Uppercase token names are not supported. Use lowercase {} instead of {}",
+                token.to_lowercase(),
+                token
+            ));
+        }
+        Ok(Self(token.to_string()))
+    }
 }
@@ -0,0 +1,876 @@
+//! Requestor-side automatic acceptance of debit notes and invoices.
+//!
+//! Documents arriving for an agreement paid from an allocation with auto-accept rules
+//! are checked against the agreement's linear price model and accepted on the
+//! requestor's behalf. Every decision is recorded in an audit trail.
+
+// External crates
+use actix_web::web::{delete, get, put, Data, Json, Path, Query};
+use actix_web::{HttpResponse, Scope};
+use bigdecimal::{BigDecimal, One, ToPrimitive, Zero};
+use chrono::{DateTime, Utc};
+use serde_json::value::Value::Null;
+use std::time::Duration;
+
+// Workspace uses
+use ya_agreement_utils::AgreementView;
+use ya_client_model::payment::*;
+use ya_client_model::NodeId;
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::middleware::Identity;
+
+// Local uses
+use super::allocations::policy::{agreement_view, usage_vector, LinearPrice};
+use super::debit_notes::accept_debit_note_as;
+use super::guard::AgreementLock;
+use super::invoices::{accept_invoice_as, reject_invoice_as};
+use crate::dao::*;
+use crate::error::DbError;
+use crate::models::auto_accept::{
+    AutoAcceptAuditEntry, AutoAcceptDecision, AutoAcceptDocument, AutoAcceptRules,
+};
+use crate::utils::*;
+
+const DURATION_USAGE_COUNTER: &str = "golem.usage.duration_sec";
+const USAGE_VECTOR_PROPERTY: &str = "/offer/properties/golem/com/usage/vector";
+
+lazy_static::lazy_static! {
+    /// Delay before a received document is processed. Gives the provider time to mark
+    /// the document as delivered before the acceptance reaches it.
+    static ref AUTO_ACCEPT_DELAY: Duration = Duration::from_secs(
+            std::env::var("PAYMENT_AUTO_ACCEPT_DELAY_SECS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(5),
+        );
+}
+
+pub fn register_endpoints(scope: Scope) -> Scope {
+    scope
+        .route(
+            "/allocations/{allocation_id}/autoAccept",
+            get().to(get_auto_accept_rules),
+        )
+        .route(
+            "/allocations/{allocation_id}/autoAccept",
+            put().to(set_auto_accept_rules),
+        )
+        .route(
+            "/allocations/{allocation_id}/autoAccept",
+            delete().to(remove_auto_accept_rules),
+        )
+        .route(
+            "/allocations/{allocation_id}/autoAccept/audit",
+            get().to(get_allocation_audit),
+        )
+        .route("/autoAcceptAudit", get().to(get_audit))
+}
+
+async fn get_auto_accept_rules(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+
+    match db
+        .as_dao::<AutoAcceptDao>()
+        .get_rules(allocation_id, node_id)
+        .await
+    {
+        Ok(Some(rules)) => response::ok(rules),
+        Ok(None) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn set_auto_accept_rules(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    body: Json<AutoAcceptRules>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+    let rules = body.into_inner();
+
+    let values = [
+        &rules.price_tolerance,
+        &rules.time_tolerance,
+        &rules.max_total,
+    ];
+    if values
+        .iter()
+        .flat_map(|value| value.iter())
+        .any(|value| value < &BigDecimal::zero())
+    {
+        return response::bad_request(&"Auto-accept tolerances and limits cannot be negative");
+    }
+    if let Err(e) = time_tolerance(&rules) {
+        return response::bad_request(&e);
+    }
+
+    match db
+        .as_dao::<AllocationDao>()
+        .get(allocation_id.clone(), node_id)
+        .await
+    {
+        Ok(AllocationStatus::Active(_)) => (),
+        Ok(AllocationStatus::Gone) => {
+            return response::gone(&format!(
+                "Allocation {} has been already released",
+                allocation_id
+            ))
+        }
+        Ok(AllocationStatus::NotFound) => return response::not_found(),
+        Err(e) => return response::server_error(&e),
+    }
+
+    match db
+        .as_dao::<AutoAcceptDao>()
+        .set_rules(allocation_id, node_id, rules.clone())
+        .await
+    {
+        Ok(()) => response::ok(rules),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn remove_auto_accept_rules(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+
+    match db
+        .as_dao::<AutoAcceptDao>()
+        .remove_rules(allocation_id, node_id)
+        .await
+    {
+        Ok(true) => response::ok(Null),
+        Ok(false) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn get_allocation_audit(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    query: Query<params::FilterParams>,
+    id: Identity,
+) -> HttpResponse {
+    let after_timestamp = query.after_timestamp.map(|d| d.naive_utc());
+    match db
+        .as_dao::<AutoAcceptDao>()
+        .get_audit(
+            id.identity,
+            Some(path.allocation_id.clone()),
+            after_timestamp,
+            query.max_items,
+        )
+        .await
+    {
+        Ok(entries) => response::ok(entries),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn get_audit(
+    db: Data<DbExecutor>,
+    query: Query<params::FilterParams>,
+    id: Identity,
+) -> HttpResponse {
+    let after_timestamp = query.after_timestamp.map(|d| d.naive_utc());
+    match db
+        .as_dao::<AutoAcceptDao>()
+        .get_audit(id.identity, None, after_timestamp, query.max_items)
+        .await
+    {
+        Ok(entries) => response::ok(entries),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+/// Processes documents received before the service (re)started, whose scheduled
+/// auto-acceptance was lost.
+pub(crate) fn process_received_job(db: DbExecutor) {
+    tokio::task::spawn_local(async move {
+        tokio::time::sleep(*AUTO_ACCEPT_DELAY).await;
+        let documents = match db.as_dao::<AutoAcceptDao>().received_documents().await {
+            Ok(documents) => documents,
+            Err(e) => {
+                log::error!("Auto-accept: listing received documents failed: {}", e);
+                return;
+            }
+        };
+        log::debug!(
+            "Auto-accept: processing {} documents received earlier",
+            documents.len()
+        );
+        for (document_type, document_id, owner_id) in documents {
+            let result = match document_type {
+                AutoAcceptDocument::DebitNote => {
+                    process_debit_note(&db, owner_id, document_id.clone()).await
+                }
+                AutoAcceptDocument::Invoice => {
+                    process_invoice(&db, owner_id, document_id.clone()).await
+                }
+            };
+            if let Err(e) = result {
+                log::warn!(
+                    "Auto-accept of {} [{}] failed: {}",
+                    document_type,
+                    document_id,
+                    e
+                );
+            }
+        }
+    });
+}
+
+/// Schedules auto-acceptance of a freshly received debit note.
+pub(crate) fn debit_note_received(db: DbExecutor, owner_id: NodeId, debit_note_id: String) {
+    tokio::task::spawn_local(async move {
+        tokio::time::sleep(*AUTO_ACCEPT_DELAY).await;
+        if let Err(e) = process_debit_note(&db, owner_id, debit_note_id.clone()).await {
+            log::warn!("Auto-accept of DebitNote [{}] failed: {}", debit_note_id, e);
+        }
+    });
+}
+
+/// Schedules auto-acceptance of a freshly received invoice.
+pub(crate) fn invoice_received(db: DbExecutor, owner_id: NodeId, invoice_id: String) {
+    tokio::task::spawn_local(async move {
+        tokio::time::sleep(*AUTO_ACCEPT_DELAY).await;
+        if let Err(e) = process_invoice(&db, owner_id, invoice_id.clone()).await {
+            log::warn!("Auto-accept of Invoice [{}] failed: {}", invoice_id, e);
+        }
+    });
+}
+
+async fn process_debit_note(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    debit_note_id: String,
+) -> Result<(), DbError> {
+    let debit_note = match db
+        .as_dao::<DebitNoteDao>()
+        .get(debit_note_id.clone(), owner_id)
+        .await?
+    {
+        Some(debit_note) if debit_note.status == DocumentStatus::Received => debit_note,
+        _ => return Ok(()),
+    };
+    let (allocation_id, rules) = match agreement_rules(
+        db,
+        owner_id,
+        AutoAcceptDocument::DebitNote,
+        &debit_note_id,
+        &debit_note.agreement_id,
+        &debit_note.payment_platform,
+        &debit_note.payer_addr,
+        &debit_note.total_amount_due,
+    )
+    .await?
+    {
+        Some(rules) => rules,
+        None => return Ok(()),
+    };
+
+    let check = check_debit_note(&rules, &debit_note).await;
+    let (decision, reason) = match planned_decision(&rules, AutoAcceptDocument::DebitNote, check) {
+        (AutoAcceptDecision::Accepted, reason) => {
+            let acceptance = Acceptance {
+                total_amount_accepted: debit_note.total_amount_due.clone(),
+                allocation_id: allocation_id.clone(),
+            };
+            let response = accept_debit_note_as(
+                db,
+                &AgreementLock::shared(),
+                owner_id,
+                debit_note_id.clone(),
+                acceptance,
+                params::DEFAULT_ACK_TIMEOUT,
+            )
+            .await;
+            outcome(response, AutoAcceptDecision::Accepted, reason).await
+        }
+        (decision, reason) => (decision, reason),
+    };
+
+    log::info!(
+        "Auto-accept: DebitNote [{}] {}: {}",
+        debit_note_id,
+        decision,
+        reason
+    );
+    db.as_dao::<AutoAcceptDao>()
+        .record(
+            owner_id,
+            AutoAcceptAuditEntry {
+                document_type: AutoAcceptDocument::DebitNote,
+                document_id: debit_note_id,
+                agreement_id: debit_note.agreement_id,
+                allocation_id,
+                amount: debit_note.total_amount_due,
+                decision,
+                reason,
+                timestamp: Utc::now(),
+            },
+        )
+        .await
+}
+
+async fn process_invoice(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    invoice_id: String,
+) -> Result<(), DbError> {
+    let invoice = match db
+        .as_dao::<InvoiceDao>()
+        .get(invoice_id.clone(), owner_id)
+        .await?
+    {
+        Some(invoice) if invoice.status == DocumentStatus::Received => invoice,
+        _ => return Ok(()),
+    };
+    let (allocation_id, rules) = match agreement_rules(
+        db,
+        owner_id,
+        AutoAcceptDocument::Invoice,
+        &invoice_id,
+        &invoice.agreement_id,
+        &invoice.payment_platform,
+        &invoice.payer_addr,
+        &invoice.amount,
+    )
+    .await?
+    {
+        Some(rules) => rules,
+        None => return Ok(()),
+    };
+
+    let check = check_invoice(db, &rules, &invoice, owner_id).await?;
+    let (decision, reason) = match planned_decision(&rules, AutoAcceptDocument::Invoice, check) {
+        (AutoAcceptDecision::Accepted, reason) => {
+            let acceptance = Acceptance {
+                total_amount_accepted: invoice.amount.clone(),
+                allocation_id: allocation_id.clone(),
+            };
+            let response = accept_invoice_as(
+                db,
+                &AgreementLock::shared(),
+                owner_id,
+                invoice_id.clone(),
+                acceptance,
+                params::DEFAULT_ACK_TIMEOUT,
+            )
+            .await;
+            outcome(response, AutoAcceptDecision::Accepted, reason).await
+        }
+        (AutoAcceptDecision::Rejected, reason) => {
+            let rejection = Rejection {
+                rejection_reason: RejectionReason::IncorrectAmount,
+                total_amount_accepted: BigDecimal::zero(),
+                message: Some(reason.clone()),
+            };
+            let response = reject_invoice_as(
+                db,
+                owner_id,
+                invoice_id.clone(),
+                rejection,
+                params::DEFAULT_ACK_TIMEOUT,
+            )
+            .await;
+            outcome(response, AutoAcceptDecision::Rejected, reason).await
+        }
+        (decision, reason) => (decision, reason),
+    };
+
+    log::info!(
+        "Auto-accept: Invoice [{}] {}: {}",
+        invoice_id,
+        decision,
+        reason
+    );
+    db.as_dao::<AutoAcceptDao>()
+        .record(
+            owner_id,
+            AutoAcceptAuditEntry {
+                document_type: AutoAcceptDocument::Invoice,
+                document_id: invoice_id,
+                agreement_id: invoice.agreement_id,
+                allocation_id,
+                amount: invoice.amount,
+                decision,
+                reason,
+                timestamp: Utc::now(),
+            },
+        )
+        .await
+}
+
+/// Allocation and rules the document is checked against. Documents that could be paid
+/// from several allocations are skipped, with the reason recorded for each of them.
+#[allow(clippy::too_many_arguments)]
+async fn agreement_rules(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    document_type: AutoAcceptDocument,
+    document_id: &str,
+    agreement_id: &str,
+    payment_platform: &str,
+    payer_addr: &str,
+    amount: &BigDecimal,
+) -> Result<Option<(String, AutoAcceptRules)>, DbError> {
+    let dao = db.as_dao::<AutoAcceptDao>();
+    match dao
+        .rules_for_agreement(
+            owner_id,
+            agreement_id.to_string(),
+            payment_platform.to_string(),
+            payer_addr.to_string(),
+        )
+        .await?
+    {
+        AgreementRules::Resolved {
+            allocation_id,
+            rules,
+        } => Ok(Some((allocation_id, rules))),
+        AgreementRules::Missing => {
+            log::debug!(
+                "Auto-accept: no rules for Agreement [{}], {} [{}] left for manual acceptance",
+                agreement_id,
+                document_type,
+                document_id
+            );
+            Ok(None)
+        }
+        AgreementRules::Ambiguous(allocation_ids) => {
+            let reason = format!(
+                "agreement can be paid from several allocations with auto-accept rules: {}",
+                allocation_ids.join(", ")
+            );
+            log::info!(
+                "Auto-accept: {} [{}] {}: {}",
+                document_type,
+                document_id,
+                AutoAcceptDecision::Skipped,
+                reason
+            );
+            for allocation_id in allocation_ids {
+                dao.record(
+                    owner_id,
+                    AutoAcceptAuditEntry {
+                        document_type,
+                        document_id: document_id.to_string(),
+                        agreement_id: agreement_id.to_string(),
+                        allocation_id,
+                        amount: amount.clone(),
+                        decision: AutoAcceptDecision::Skipped,
+                        reason: reason.clone(),
+                        timestamp: Utc::now(),
+                    },
+                )
+                .await?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// What to do with a document given the result of checking it against the rules.
+fn planned_decision(
+    rules: &AutoAcceptRules,
+    document: AutoAcceptDocument,
+    check: Result<String, String>,
+) -> (AutoAcceptDecision, String) {
+    match check {
+        Ok(reason) => (AutoAcceptDecision::Accepted, reason),
+        Err(reason) if rules.reject_invoices && document == AutoAcceptDocument::Invoice => {
+            (AutoAcceptDecision::Rejected, reason)
+        }
+        Err(reason) => (AutoAcceptDecision::Skipped, reason),
+    }
+}
+
+/// Turns the response of the accept / reject call into the audited decision.
+async fn outcome(
+    response: HttpResponse,
+    decision: AutoAcceptDecision,
+    reason: String,
+) -> (AutoAcceptDecision, String) {
+    if response.status().is_success() {
+        return (decision, reason);
+    }
+    let status = response.status();
+    let body = actix_web::body::to_bytes(response.into_body())
+        .await
+        .map(|body| String::from_utf8_lossy(&body).to_string())
+        .unwrap_or_default();
+    (
+        AutoAcceptDecision::Failed,
+        format!(
+            "{}, but the request failed with {}: {}",
+            reason, status, body
+        ),
+    )
+}
+
+fn exceeds_max_total(rules: &AutoAcceptRules, amount: &BigDecimal) -> Option<String> {
+    match &rules.max_total {
+        Some(max_total) if amount > max_total => {
+            Some(format!("amount {} exceeds maxTotal {}", amount, max_total))
+        }
+        _ => None,
+    }
+}
+
+fn with_tolerance(value: &BigDecimal, tolerance: &Option<BigDecimal>) -> BigDecimal {
+    match tolerance {
+        Some(tolerance) => value * &(BigDecimal::one() + tolerance),
+        None => value.clone(),
+    }
+}
+
+/// Relative time tolerance as a float. Fails for values too large to represent.
+fn time_tolerance(rules: &AutoAcceptRules) -> Result<Option<f64>, String> {
+    match &rules.time_tolerance {
+        Some(tolerance) => match tolerance.to_f64() {
+            Some(value) if value.is_finite() => Ok(Some(value)),
+            _ => Err(format!("timeTolerance {} is out of range", tolerance)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Returns the reason for accepting the debit note, or for leaving it alone.
+async fn check_debit_note(
+    rules: &AutoAcceptRules,
+    debit_note: &DebitNote,
+) -> Result<String, String> {
+    let (agreement, view) = agreement_view(&debit_note.agreement_id).await?;
+    let approved = agreement.approved_date.unwrap_or(agreement.timestamp);
+    verify_debit_note(rules, debit_note, &view, approved)
+}
+
+/// Reported duration is compared with the time elapsed until the debit note was issued,
+/// so documents processed late are judged the same as fresh ones.
+fn verify_debit_note(
+    rules: &AutoAcceptRules,
+    debit_note: &DebitNote,
+    view: &AgreementView,
+    approved: DateTime<Utc>,
+) -> Result<String, String> {
+    if let Some(reason) = exceeds_max_total(rules, &debit_note.total_amount_due) {
+        return Err(reason);
+    }
+    let price = LinearPrice::from_agreement(view)?
+        .ok_or_else(|| "agreement has no linear price model".to_string())?;
+    let usage = usage_vector(debit_note)?;
+
+    let allowed = with_tolerance(&price.cost(&usage)?, &rules.price_tolerance);
+    if debit_note.total_amount_due > allowed {
+        return Err(format!(
+            "amount due {} exceeds {} allowed by the price model for the reported usage",
+            debit_note.total_amount_due, allowed
+        ));
+    }
+
+    if let Some(tolerance) = time_tolerance(rules)? {
+        let counters = view
+            .pointer_typed::<Vec<String>>(USAGE_VECTOR_PROPERTY)
+            .map_err(|e| e.to_string())?;
+        let reported = counters
+            .iter()
+            .position(|counter| counter == DURATION_USAGE_COUNTER)
+            .and_then(|idx| usage.get(idx).copied());
+        if let Some(reported) = reported {
+            let elapsed = (debit_note.timestamp - approved).num_milliseconds() as f64 / 1000.0;
+            let allowed = elapsed * (1.0 + tolerance);
+            if reported > allowed {
+                return Err(format!(
+                    "reported duration {}s exceeds {:.0}s allowed since the agreement was approved",
+                    reported, allowed
+                ));
+            }
+        }
+    }
+
+    Ok(format!(
+        "amount due {} within {} allowed by the price model",
+        debit_note.total_amount_due, allowed
+    ))
+}
+
+/// Invoice is expected to match the debit notes already accepted for the agreement,
+/// or the constant price when there were none.
+async fn check_invoice(
+    db: &DbExecutor,
+    rules: &AutoAcceptRules,
+    invoice: &Invoice,
+    owner_id: NodeId,
+) -> Result<Result<String, String>, DbError> {
+    let accepted = match db
+        .as_dao::<AgreementDao>()
+        .get(invoice.agreement_id.clone(), owner_id)
+        .await?
+    {
+        Some(agreement) => agreement.total_amount_accepted.0,
+        None => BigDecimal::zero(),
+    };
+    let price = match agreement_view(&invoice.agreement_id)
+        .await
+        .and_then(|(_, view)| LinearPrice::from_agreement(&view))
+    {
+        Ok(Some(price)) => price,
+        Ok(None) => return Ok(Err("agreement has no linear price model".to_string())),
+        Err(e) => return Ok(Err(e)),
+    };
+    Ok(verify_invoice(rules, invoice, &accepted, &price))
+}
+
+fn verify_invoice(
+    rules: &AutoAcceptRules,
+    invoice: &Invoice,
+    accepted: &BigDecimal,
+    price: &LinearPrice,
+) -> Result<String, String> {
+    if let Some(reason) = exceeds_max_total(rules, &invoice.amount) {
+        return Err(reason);
+    }
+    let expected = if accepted > price.fixed() {
+        accepted
+    } else {
+        price.fixed()
+    };
+    let allowed = with_tolerance(expected, &rules.price_tolerance);
+    if invoice.amount > allowed {
+        return Err(format!(
+            "invoice amount {} exceeds {} allowed by accepted debit notes",
+            invoice.amount, allowed
+        ));
+    }
+    Ok(format!(
+        "invoice amount {} within {} allowed by accepted debit notes",
+        invoice.amount, allowed
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn amount(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    /// 0.01 per second, 0.1 per cpu second and 1 for start.
+    fn agreement() -> AgreementView {
+        AgreementView {
+            json: json!({
+                "offer": {"properties": {"golem": {
+                    "com": {
+                        "pricing": {"model": {"linear": {"coeffs": [0.01, 0.1, 1.0]}}},
+                        "usage": {"vector": ["golem.usage.duration_sec", "golem.usage.cpu_sec"]}
+                    }
+                }}}
+            }),
+            id: "agreement".into(),
+        }
+    }
+
+    fn debit_note(amount_due: &str, usage: Vec<f64>) -> DebitNote {
+        DebitNote {
+            debit_note_id: "debit-note".into(),
+            issuer_id: NodeId::default(),
+            recipient_id: NodeId::default(),
+            payee_addr: "payee".into(),
+            payer_addr: "payer".into(),
+            payment_platform: "erc20-holesky-tglm".into(),
+            previous_debit_note_id: None,
+            timestamp: Utc::now(),
+            agreement_id: "agreement".into(),
+            activity_id: "activity".into(),
+            total_amount_due: amount(amount_due),
+            usage_counter_vector: Some(json!(usage)),
+            payment_due_date: None,
+            status: DocumentStatus::Received,
+        }
+    }
+
+    fn invoice(amount_due: &str) -> Invoice {
+        Invoice {
+            invoice_id: "invoice".into(),
+            issuer_id: NodeId::default(),
+            recipient_id: NodeId::default(),
+            payee_addr: "payee".into(),
+            payer_addr: "payer".into(),
+            payment_platform: "erc20-holesky-tglm".into(),
+            timestamp: Utc::now(),
+            agreement_id: "agreement".into(),
+            activity_ids: vec![],
+            amount: amount(amount_due),
+            payment_due_date: Utc::now(),
+            status: DocumentStatus::Received,
+        }
+    }
+
+    fn verify(rules: &AutoAcceptRules, debit_note: &DebitNote) -> Result<String, String> {
+        verify_debit_note(
+            rules,
+            debit_note,
+            &agreement(),
+            debit_note.timestamp - Duration::seconds(100),
+        )
+    }
+
+    #[test]
+    fn debit_note_price_tolerance() {
+        let rules = AutoAcceptRules::default();
+        assert!(verify(&rules, &debit_note("3", vec![100.0, 10.0])).is_ok());
+        let err = verify(&rules, &debit_note("3.1", vec![100.0, 10.0])).unwrap_err();
+        assert!(err.contains("exceeds"), "{}", err);
+
+        let rules = AutoAcceptRules {
+            price_tolerance: Some(amount("0.05")),
+            ..Default::default()
+        };
+        assert!(verify(&rules, &debit_note("3.1", vec![100.0, 10.0])).is_ok());
+        assert!(verify(&rules, &debit_note("3.2", vec![100.0, 10.0])).is_err());
+    }
+
+    #[test]
+    fn debit_note_time_tolerance() {
+        let debit_note = debit_note("3", vec![105.0, 9.5]);
+        assert!(verify(&AutoAcceptRules::default(), &debit_note).is_ok());
+
+        let strict = AutoAcceptRules {
+            time_tolerance: Some(amount("0.01")),
+            ..Default::default()
+        };
+        let err = verify(&strict, &debit_note).unwrap_err();
+        assert!(err.contains("exceeds 101s allowed"), "{}", err);
+
+        let lenient = AutoAcceptRules {
+            time_tolerance: Some(amount("0.1")),
+            ..Default::default()
+        };
+        assert!(verify(&lenient, &debit_note).is_ok());
+
+        // Elapsed time ends when the debit note was issued, not when it is processed.
+        let approved = debit_note.timestamp - Duration::seconds(100);
+        let issued_earlier = DebitNote {
+            timestamp: debit_note.timestamp - Duration::seconds(50),
+            ..debit_note
+        };
+        let err = verify_debit_note(&lenient, &issued_earlier, &agreement(), approved).unwrap_err();
+        assert!(err.contains("exceeds 55s allowed"), "{}", err);
+    }
+
+    #[test]
+    fn debit_note_max_total() {
+        let rules = AutoAcceptRules {
+            max_total: Some(amount("2")),
+            ..Default::default()
+        };
+        let err = verify(&rules, &debit_note("3", vec![100.0, 10.0])).unwrap_err();
+        assert!(err.contains("maxTotal"), "{}", err);
+        assert!(verify(&rules, &debit_note("1", vec![0.0, 0.0])).is_ok());
+    }
+
+    #[test]
+    fn debit_note_without_linear_price() {
+        let view = AgreementView {
+            json: json!({"offer": {"properties": {}}}),
+            id: "agreement".into(),
+        };
+        let result = verify_debit_note(
+            &AutoAcceptRules::default(),
+            &debit_note("0", vec![]),
+            &view,
+            Utc::now(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn invoice_matches_accepted_debit_notes() {
+        let price = LinearPrice::from_agreement(&agreement()).unwrap().unwrap();
+        let rules = AutoAcceptRules::default();
+
+        assert!(verify_invoice(&rules, &invoice("5"), &amount("5"), &price).is_ok());
+        assert!(verify_invoice(&rules, &invoice("5.5"), &amount("5"), &price).is_err());
+        // Without accepted debit notes only the start price is expected.
+        assert!(verify_invoice(&rules, &invoice("1"), &amount("0"), &price).is_ok());
+        assert!(verify_invoice(&rules, &invoice("2"), &amount("0"), &price).is_err());
+
+        let rules = AutoAcceptRules {
+            price_tolerance: Some(amount("0.1")),
+            max_total: Some(amount("10")),
+            ..Default::default()
+        };
+        assert!(verify_invoice(&rules, &invoice("5.5"), &amount("5"), &price).is_ok());
+        let err = verify_invoice(&rules, &invoice("11"), &amount("11"), &price).unwrap_err();
+        assert!(err.contains("maxTotal"), "{}", err);
+    }
+
+    #[test]
+    fn reject_only_invoices_when_enabled() {
+        let keep = AutoAcceptRules::default();
+        let reject = AutoAcceptRules {
+            reject_invoices: true,
+            ..Default::default()
+        };
+        let failed = || Err("too expensive".to_string());
+
+        let (decision, reason) = planned_decision(&reject, AutoAcceptDocument::Invoice, failed());
+        assert_eq!(decision, AutoAcceptDecision::Rejected);
+        assert_eq!(reason, "too expensive");
+        let (decision, _) = planned_decision(&keep, AutoAcceptDocument::Invoice, failed());
+        assert_eq!(decision, AutoAcceptDecision::Skipped);
+        let (decision, _) = planned_decision(&reject, AutoAcceptDocument::DebitNote, failed());
+        assert_eq!(decision, AutoAcceptDecision::Skipped);
+        let (decision, _) =
+            planned_decision(&reject, AutoAcceptDocument::Invoice, Ok("fine".into()));
+        assert_eq!(decision, AutoAcceptDecision::Accepted);
+    }
+
+    #[tokio::test]
+    async fn failed_request_is_audited() {
+        let (decision, reason) = outcome(
+            response::bad_request(&"Invoice accepted"),
+            AutoAcceptDecision::Rejected,
+            "too expensive".into(),
+        )
+        .await;
+        assert_eq!(decision, AutoAcceptDecision::Failed);
+        assert!(reason.starts_with("too expensive, but the request failed with 400"));
+        assert!(reason.contains("Invoice accepted"));
+
+        let (decision, reason) = outcome(
+            response::ok(Null),
+            AutoAcceptDecision::Rejected,
+            "too expensive".into(),
+        )
+        .await;
+        assert_eq!(decision, AutoAcceptDecision::Rejected);
+        assert_eq!(reason, "too expensive");
+    }
+
+    #[test]
+    fn time_tolerance_out_of_range() {
+        let rules = AutoAcceptRules {
+            time_tolerance: Some(amount("1e400")),
+            ..Default::default()
+        };
+        assert!(time_tolerance(&rules).is_err());
+        assert_eq!(time_tolerance(&AutoAcceptRules::default()), Ok(None));
+    }
+}
@@ -3,15 +3,18 @@ use std::sync::Arc;
 // Extrnal crates
 use actix_web::web::{get, post, Data, Json, Path, Query};
 use actix_web::{HttpResponse, Scope};
+use bigdecimal::BigDecimal;
 use serde_json::value::Value::Null;
 use std::time::Instant;
 
 // Workspace uses
 use metrics::{counter, timing};
 use ya_client_model::payment::*;
+use ya_client_model::NodeId;
 use ya_core_model::payment::local::{SchedulePayment, BUS_ID as LOCAL_SERVICE};
 use ya_core_model::payment::public::{
-    AcceptDebitNote, AcceptRejectError, SendDebitNote, SendError, BUS_ID as PUBLIC_SERVICE,
+    AcceptDebitNote, AcceptRejectError, RejectDebitNote, SendDebitNote, SendError,
+    BUS_ID as PUBLIC_SERVICE,
 };
 use ya_core_model::payment::RpcMessageError;
 use ya_net::RemoteEndpoint;
@@ -22,6 +25,7 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
+use super::allocations::policy;
 use super::guard::AgreementLock;
 use crate::dao::*;
 use crate::error::{DbError, Error};
@@ -296,12 +300,30 @@ async fn accept_debit_note(
     query: Query<params::Timeout>,
     body: Json<Acceptance>,
     id: Identity,
+) -> HttpResponse {
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    accept_debit_note_as(
+        &db,
+        &agreement_lock,
+        id.identity,
+        path.debit_note_id.clone(),
+        body.into_inner(),
+        timeout,
+    )
+    .await
+}
+
+/// Accepts the debit note on behalf of `node_id`, shared by the REST API and auto-acceptance.
+pub(super) async fn accept_debit_note_as(
+    db: &DbExecutor,
+    agreement_lock: &Arc<AgreementLock>,
+    node_id: NodeId,
+    debit_note_id: String,
+    acceptance: Acceptance,
+    timeout: f64,
 ) -> HttpResponse {
     let start = Instant::now();
 
-    let debit_note_id = path.debit_note_id.clone();
-    let node_id = id.identity;
-    let acceptance = body.into_inner();
     let allocation_id = acceptance.allocation_id.clone();
 
     log::debug!("Requested accept DebitNote [{}]", debit_note_id);
@@ -318,7 +340,7 @@ async fn accept_debit_note(
     };
 
     // Required to serialize complex DB access patterns related to debit note / invoice acceptances.
-    let _agreement_lock = agreement_lock.lock(debit_note.agreement_id.clone());
+    let _agreement_lock = agreement_lock.lock(debit_note.agreement_id.clone()).await;
 
     if debit_note.total_amount_due != acceptance.total_amount_accepted {
         return response::bad_request(&"Invalid amount accepted");
@@ -396,6 +418,11 @@ async fn accept_debit_note(
     };
     let amount_to_pay = &debit_note.total_amount_due - &activity.total_amount_scheduled.0;
 
+    // Held until the payment is scheduled, so that the allocation policy sees all spendings.
+    let _allocation_lock = AgreementLock::allocations()
+        .lock(allocation_id.clone())
+        .await;
+
     log::trace!(
         "Querying DB for Allocation [{}] for Debit Note [{}]",
         allocation_id,
@@ -425,8 +452,32 @@ async fn accept_debit_note(
         );
         return response::bad_request(&msg);
     }
+    if let Err(e) =
+        policy::check_debit_note(db, node_id, &allocation_id, &debit_note, &amount_to_pay).await
+    {
+        log::warn!("Refusing to accept DebitNote [{}]: {}", debit_note_id, e);
+        if e.is_violation() {
+            counter!("payment.allocations.policy.violations", 1);
+        }
+        if let policy::PolicyError::PriceExceeded(_) = &e {
+            let rejection = Rejection {
+                rejection_reason: RejectionReason::IncorrectAmount,
+                total_amount_accepted: BigDecimal::from(0),
+                message: Some(e.to_string()),
+            };
+            let response =
+                reject_debit_note_as(db, node_id, debit_note_id.clone(), rejection, timeout).await;
+            if !response.status().is_success() {
+                log::warn!(
+                    "Failed to reject DebitNote [{}] exceeding the price model",
+                    debit_note_id
+                );
+            }
+        }
+        return e.to_response();
+    }
 
-    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    let document_id = debit_note_id.clone();
     let result = async move {
         let issuer_id = debit_note.issuer_id;
         let accept_msg = AcceptDebitNote::new(debit_note_id.clone(), acceptance, issuer_id);
@@ -473,7 +524,7 @@ async fn accept_debit_note(
             Ok(Ok(_)) => {
                 log::info!(
                     "DebitNote [{}] for Activity [{}] accepted.",
-                    path.debit_note_id,
+                    document_id,
                     activity_id
                 );
                 counter!("payment.debit_notes.requestor.accepted", 1);
@@ -501,6 +552,98 @@ async fn reject_debit_note(
     path: Path<params::DebitNoteId>,
     query: Query<params::Timeout>,
     body: Json<Rejection>,
+    id: Identity,
 ) -> HttpResponse {
-    response::not_implemented() // TODO
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    reject_debit_note_as(
+        &db,
+        id.identity,
+        path.debit_note_id.clone(),
+        body.into_inner(),
+        timeout,
+    )
+    .await
+}
+
+/// Rejects the debit note on behalf of `node_id`.
+///
+/// Debit notes have no rejection sync, so the rejection is stored only after the issuer
+/// acknowledged it. Otherwise the debit note stays received and can be rejected again.
+pub(super) async fn reject_debit_note_as(
+    db: &DbExecutor,
+    node_id: NodeId,
+    debit_note_id: String,
+    rejection: Rejection,
+    timeout: f64,
+) -> HttpResponse {
+    let start = Instant::now();
+
+    log::debug!("Requested reject DebitNote [{}]", debit_note_id);
+    counter!("payment.debit_notes.requestor.rejected.call", 1);
+
+    let dao: DebitNoteDao = db.as_dao();
+
+    log::trace!("Querying DB for Debit Note [{}]", debit_note_id);
+    let debit_note: DebitNote = match dao.get(debit_note_id.clone(), node_id).await {
+        Ok(Some(debit_note)) => debit_note,
+        Ok(None) => return response::not_found(),
+        Err(e) => return response::server_error(&e),
+    };
+
+    match debit_note.status {
+        DocumentStatus::Received => (),
+        DocumentStatus::Rejected => return response::ok(Null),
+        DocumentStatus::Failed => (),
+        DocumentStatus::Accepted => return response::bad_request(&"Debit note accepted"),
+        DocumentStatus::Settled => return response::bad_request(&"Debit note settled"),
+        DocumentStatus::Cancelled => return response::bad_request(&"Debit note cancelled"),
+        DocumentStatus::Issued => return response::server_error(&"Illegal status: issued"),
+    }
+
+    let document_id = debit_note_id.clone();
+    let result = async move {
+        let issuer_id = debit_note.issuer_id;
+        let reject_msg = RejectDebitNote::new(debit_note_id.clone(), rejection.clone(), issuer_id);
+        match async move {
+            log::debug!(
+                "Sending RejectDebitNote [{}] to [{}]",
+                debit_note_id,
+                issuer_id
+            );
+            ya_net::from(node_id)
+                .to(issuer_id)
+                .service(PUBLIC_SERVICE)
+                .call(reject_msg)
+                .await??;
+
+            log::trace!("Rejecting DebitNote [{}] in DB", debit_note_id);
+            dao.reject(debit_note_id.clone(), node_id, rejection)
+                .await?;
+            log::trace!("DebitNote rejected successfully for [{}]", debit_note_id);
+
+            Ok(())
+        }
+        .timeout(Some(timeout))
+        .await
+        {
+            Ok(Ok(_)) => {
+                counter!("payment.debit_notes.requestor.rejected", 1);
+                log::info!("DebitNote [{}] rejected.", document_id);
+                response::ok(Null)
+            }
+            Ok(Err(Error::Rpc(RpcMessageError::AcceptReject(AcceptRejectError::BadRequest(
+                e,
+            ))))) => response::bad_request(&e),
+            Ok(Err(e)) => response::server_error(&e),
+            Err(_) => response::timeout(&"Timeout rejecting Debit Note on remote Node."),
+        }
+    }
+    .await;
+
+    timing!(
+        "payment.debit_notes.requestor.rejected.time",
+        start,
+        Instant::now()
+    );
+    result
 }
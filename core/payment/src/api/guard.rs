@@ -4,6 +4,11 @@ use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 use tokio::sync::Mutex as TokioMutex;
 
+lazy_static::lazy_static! {
+    static ref SHARED: Arc<AgreementLock> = AgreementLock::arc();
+    static ref ALLOCATIONS: Arc<AgreementLock> = AgreementLock::arc();
+}
+
 /// Registry of locks for agreements
 pub(super) struct AgreementLock {
     locks: StdMutex<HashMap<String, Arc<TokioMutex<()>>>>,
@@ -15,6 +20,20 @@ impl AgreementLock {
         Arc::new(Self::default())
     }
 
+    /// Process-wide instance, shared by the REST API and auto-acceptance.
+    pub fn shared() -> Arc<Self> {
+        Arc::clone(&SHARED)
+    }
+
+    /// Process-wide instance keyed by allocation id.
+    ///
+    /// Held from the allocation policy check until the payment is scheduled, so that
+    /// acceptances of different agreements cannot exceed the limits together.
+    /// Always taken after the agreement lock.
+    pub fn allocations() -> Arc<Self> {
+        Arc::clone(&ALLOCATIONS)
+    }
+
     /// Take a lock for a given agreement.
     ///
     /// The entry in the internal registry will be automatically cleaned up.
@@ -45,8 +64,8 @@ impl Default for AgreementLock {
 
 /// Lock guard ensuring unique operation on an agreement.
 ///
-/// For use in REST API only. Motivated by a need to synchronize debit note and
-/// invoice acceptances.
+/// For use in REST API and auto-acceptance only. Motivated by a need to synchronize
+/// debit note and invoice acceptances.
 pub(super) struct AgreementLockGuard {
     guard: Option<tokio::sync::OwnedMutexGuard<()>>,
     lock_map: Arc<AgreementLock>,
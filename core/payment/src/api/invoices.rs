@@ -9,6 +9,7 @@ use std::time::Instant;
 // Workspace uses
 use metrics::{counter, timing};
 use ya_client_model::payment::*;
+use ya_client_model::NodeId;
 use ya_core_model::payment::local::{SchedulePayment, BUS_ID as LOCAL_SERVICE};
 use ya_core_model::payment::public::{
     AcceptInvoice, AcceptRejectError, CancelError, CancelInvoice, RejectInvoiceV2, SendError,
@@ -23,6 +24,7 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
+use super::allocations::policy;
 use super::guard::AgreementLock;
 use crate::dao::*;
 use crate::error::{DbError, Error};
@@ -404,12 +406,30 @@ async fn accept_invoice(
     query: Query<params::Timeout>,
     body: Json<Acceptance>,
     id: Identity,
+) -> HttpResponse {
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    accept_invoice_as(
+        &db,
+        &agreement_lock,
+        id.identity,
+        path.invoice_id.clone(),
+        body.into_inner(),
+        timeout,
+    )
+    .await
+}
+
+/// Accepts the invoice on behalf of `node_id`, shared by the REST API and auto-acceptance.
+pub(super) async fn accept_invoice_as(
+    db: &DbExecutor,
+    agreement_lock: &Arc<AgreementLock>,
+    node_id: NodeId,
+    invoice_id: String,
+    acceptance: Acceptance,
+    timeout: f64,
 ) -> HttpResponse {
     let start = Instant::now();
 
-    let invoice_id = path.invoice_id.clone();
-    let node_id = id.identity;
-    let acceptance = body.into_inner();
     let allocation_id = acceptance.allocation_id.clone();
 
     log::debug!("Requested accept invoice [{}]", invoice_id);
@@ -426,7 +446,7 @@ async fn accept_invoice(
     };
 
     // Required to serialize complex DB access patterns related to debit note / invoice acceptances.
-    let _agreement_lock = agreement_lock.lock(invoice.agreement_id.clone());
+    let _agreement_lock = agreement_lock.lock(invoice.agreement_id.clone()).await;
 
     if invoice.amount != acceptance.total_amount_accepted {
         return response::bad_request(&"Invalid amount accepted");
@@ -471,6 +491,11 @@ async fn accept_invoice(
     }
     let amount_to_pay = &invoice.amount - &agreement.total_amount_scheduled.0;
 
+    // Held until the payment is scheduled, so that the allocation policy sees all spendings.
+    let _allocation_lock = AgreementLock::allocations()
+        .lock(allocation_id.clone())
+        .await;
+
     log::trace!(
         "Querying DB for Allocation [{}] for Invoice [{}]",
         allocation_id,
@@ -502,8 +527,17 @@ async fn accept_invoice(
         counter!("payment.invoices.requestor.not-enough-funds", 1);
         return response::bad_request(&msg);
     }
+    if let Err(e) =
+        policy::check_invoice(db, node_id, &allocation_id, &invoice, &amount_to_pay).await
+    {
+        log::warn!("Refusing to accept Invoice [{}]: {}", invoice_id, e);
+        if e.is_violation() {
+            counter!("payment.allocations.policy.violations", 1);
+        }
+        return e.to_response();
+    }
 
-    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    let document_id = invoice_id.clone();
     let result = async move {
         let issuer_id = invoice.issuer_id;
         let accept_msg = AcceptInvoice::new(invoice_id.clone(), acceptance, issuer_id);
@@ -546,7 +580,7 @@ async fn accept_invoice(
                 counter!("payment.invoices.requestor.accepted", 1);
                 log::info!(
                     "Invoice [{}] for Agreement [{}] accepted.",
-                    path.invoice_id,
+                    document_id,
                     agreement_id
                 );
                 response::ok(Null)
@@ -575,11 +609,26 @@ async fn reject_invoice(
     body: Json<Rejection>,
     id: Identity,
 ) -> HttpResponse {
-    let start = Instant::now();
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    reject_invoice_as(
+        &db,
+        id.identity,
+        path.invoice_id.clone(),
+        body.into_inner(),
+        timeout,
+    )
+    .await
+}
 
-    let invoice_id = path.invoice_id.clone();
-    let node_id = id.identity;
-    let rejection = body.into_inner();
+/// Rejects the invoice on behalf of `node_id`, shared by the REST API and auto-acceptance.
+pub(super) async fn reject_invoice_as(
+    db: &DbExecutor,
+    node_id: NodeId,
+    invoice_id: String,
+    rejection: Rejection,
+    timeout: f64,
+) -> HttpResponse {
+    let start = Instant::now();
 
     log::debug!("Requested reject invoice [{}]", invoice_id);
     counter!("payment.invoices.requestor.rejected.call", 1);
@@ -604,7 +653,7 @@ async fn reject_invoice(
         DocumentStatus::Issued => return response::server_error(&"Illegal status: issued"),
     }
 
-    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    let document_id = invoice_id.clone();
     let result = async move {
         let issuer_id = invoice.issuer_id;
         let reject_msg = RejectInvoiceV2::new(invoice_id.clone(), rejection.clone(), issuer_id);
@@ -641,7 +690,7 @@ async fn reject_invoice(
         {
             Ok(Ok(_)) => {
                 counter!("payment.invoices.requestor.rejected", 1);
-                log::info!("Invoice [{}] rejected.", path.invoice_id);
+                log::info!("Invoice [{}] rejected.", document_id);
                 response::ok(Null)
             }
             Ok(Err(Error::Rpc(RpcMessageError::AcceptReject(AcceptRejectError::BadRequest(
@@ -0,0 +1,89 @@
+// External crates
+use actix_web::web::{get, Data, Query};
+use actix_web::{HttpResponse, Scope};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::str::FromStr;
+
+// Workspace uses
+use ya_core_model::payment::local::{ExportLedger, LedgerEntryKind};
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::middleware::Identity;
+
+// Local uses
+use crate::ledger::{self, LedgerCursor};
+use crate::utils::*;
+
+pub const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+
+pub fn register_endpoints(scope: Scope) -> Scope {
+    scope.route("/ledger", get().to(export_ledger))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LedgerParams {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    platform: Option<String>,
+    /// Comma separated list of entry kinds, all kinds by default.
+    kinds: Option<String>,
+    after_cursor: Option<String>,
+    max_items: Option<u32>,
+    /// `json` (default) or `csv`.
+    format: Option<String>,
+}
+
+async fn export_ledger(
+    db: Data<DbExecutor>,
+    query: Query<LedgerParams>,
+    id: Identity,
+) -> HttpResponse {
+    let query = query.into_inner();
+    let csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => return response::bad_request(&format!("Unsupported format: {}", other)),
+    };
+    let kinds = match query
+        .kinds
+        .as_deref()
+        .map(|kinds| {
+            kinds
+                .split(',')
+                .map(|kind| LedgerEntryKind::from_str(kind.trim()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+    {
+        Ok(kinds) => kinds.unwrap_or_default(),
+        Err(e) => return response::bad_request(&e),
+    };
+    if let Some(Err(e)) = query.after_cursor.as_deref().map(LedgerCursor::from_str) {
+        return response::bad_request(&e);
+    }
+
+    let msg = ExportLedger {
+        node_id: id.identity,
+        since: query.since,
+        until: query.until,
+        platform: query.platform,
+        kinds,
+        after: query.after_cursor,
+        limit: query.max_items.unwrap_or(ledger::DEFAULT_PAGE_SIZE),
+    };
+    let page = match ledger::export(&db, msg).await {
+        Ok(page) => page,
+        Err(e) => return response::server_error(&e),
+    };
+
+    if !csv {
+        return response::ok(page);
+    }
+    let mut builder = HttpResponse::Ok();
+    builder.content_type("text/csv");
+    if let Some(next) = page.next {
+        builder.insert_header((NEXT_CURSOR_HEADER, next));
+    }
+    builder.body(ledger::csv_header() + &ledger::csv_rows(&page.entries))
+}
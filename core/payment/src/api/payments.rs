@@ -7,8 +7,7 @@ use ya_service_bus::typed::service;
 // Workspace uses
 use ya_client_model::payment::*;
 use ya_core_model::payment::local::{
-    DriverName, NetworkName, PaymentDriverStatus, PaymentDriverStatusError,
-    BUS_ID as PAYMENT_BUS_ID,
+    DriverName, GetDrivers, PaymentDriverStatus, PaymentDriverStatusError, BUS_ID as PAYMENT_BUS_ID,
 };
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
@@ -35,14 +34,13 @@ async fn get_payments(
         .timeout
         .unwrap_or(params::DEFAULT_EVENT_TIMEOUT);
     let after_timestamp = query.event_params.after_timestamp.map(|d| d.naive_utc());
-    let network = match query
-        .network
-        .as_ref()
-        .map(|n| NetworkName::from_str(n.as_str()))
-        .map_or(Ok(None), |v| v.map(Some))
-    {
-        Ok(network) => network,
-        Err(e) => return response::server_error(&e),
+    let network = match &query.network {
+        Some(network) => match is_registered_network(network).await {
+            Ok(true) => Some(network.clone()),
+            Ok(false) => return response::bad_request(&format!("Unsupported network: {network}")),
+            Err(e) => return response::server_error(&e),
+        },
+        None => None,
     };
     let driver = match query
         .driver
@@ -75,6 +73,14 @@ async fn get_payments(
     }
 }
 
+/// Networks are not known in advance, payment drivers can serve networks added to their config.
+async fn is_registered_network(network: &str) -> anyhow::Result<bool> {
+    let drivers = service(PAYMENT_BUS_ID).send(GetDrivers {}).await??;
+    Ok(drivers
+        .values()
+        .any(|driver| driver.networks.contains_key(network)))
+}
+
 async fn get_payment(
     db: Data<DbExecutor>,
     path: Path<params::PaymentId>,
@@ -2,11 +2,15 @@ mod rpc;
 
 // External crates
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use serde_json::to_value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::UNIX_EPOCH;
 use structopt::*;
+use strum::VariantNames;
 use ya_client_model::payment::DriverStatusProperty;
 use ya_core_model::payment::local::NetworkName;
 
@@ -18,6 +22,7 @@ use ya_service_bus::{typed as bus, RpcEndpoint};
 // Local uses
 use crate::accounts::{init_account, Account};
 use crate::cli::rpc::{run_command_rpc, RpcCommandParams};
+use crate::ledger;
 use crate::wallet;
 
 /// Payment driver management.
@@ -135,6 +140,31 @@ pub enum PaymentCli {
         command: InvoiceCommand,
     },
 
+    /// Export invoices, debit notes, payments and allocations as a ledger
+    Export {
+        address: Option<String>,
+        /// Include entries from this date on (RFC 3339 or YYYY-MM-DD)
+        #[structopt(long, parse(try_from_str = parse_date))]
+        since: Option<DateTime<Utc>>,
+        /// Include entries before this date (RFC 3339 or YYYY-MM-DD)
+        #[structopt(long, parse(try_from_str = parse_date))]
+        until: Option<DateTime<Utc>>,
+        /// Payment platform, e.g. erc20-polygon-glm
+        #[structopt(long)]
+        platform: Option<String>,
+        /// Entry kinds to export [default: all]
+        #[structopt(long, possible_values = pay::LedgerEntryKind::VARIANTS)]
+        kind: Vec<pay::LedgerEntryKind>,
+        #[structopt(long, possible_values = &["csv", "json"], default_value = "csv")]
+        format: String,
+        /// Write the ledger to a file instead of standard output
+        #[structopt(long, short)]
+        output: Option<PathBuf>,
+        /// Number of documents fetched per request
+        #[structopt(long, default_value = "500")]
+        page_size: u32,
+    },
+
     /// Clear all existing allocations
     ReleaseAllocations,
 }
@@ -153,9 +183,12 @@ impl PaymentCli {
             PaymentCli::Fund { account } => {
                 let address = resolve_address(account.address()).await?;
 
+                // Networks added to driver config have no faucet nor onboarding.
+                let network = account.network_name();
                 let onboarding_supported =
-                    matches!(account.network, NetworkName::Polygon | NetworkName::Mainnet);
-                if !account.network.is_fundable() && !onboarding_supported {
+                    matches!(network, Some(NetworkName::Polygon | NetworkName::Mainnet));
+                let fundable = network.as_ref().is_some_and(NetworkName::is_fundable);
+                if !fundable && !onboarding_supported {
                     log::error!(
                         "Network {} does not support automatic funding. Consider using one of the following: {:?}",
                         account.network,
@@ -403,6 +436,48 @@ Typically operation should take less than 1 minute.
                         .await??,
                 )
             }
+            PaymentCli::Export {
+                address,
+                since,
+                until,
+                platform,
+                kind,
+                format,
+                output,
+                page_size,
+            } => {
+                let node_id = resolve_address(address).await?.parse()?;
+                let writer: Box<dyn Write> = match &output {
+                    Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                    None => Box::new(BufWriter::new(std::io::stdout())),
+                };
+                let mut writer = ledger::LedgerWriter::new(writer, format == "csv")?;
+                let mut after = None;
+                loop {
+                    let page = bus::service(pay::BUS_ID)
+                        .call(pay::ExportLedger {
+                            node_id,
+                            since,
+                            until,
+                            platform: platform.clone(),
+                            kinds: kind.clone(),
+                            after,
+                            limit: page_size,
+                        })
+                        .await??;
+                    writer.write_page(&page.entries)?;
+                    match page.next {
+                        Some(next) => after = Some(next),
+                        None => break,
+                    }
+                }
+
+                let entries = writer.finish()?;
+                if let Some(path) = output {
+                    log::info!("Exported {} entries to {}", entries, path.display());
+                }
+                Ok(CommandOutput::NoOutput)
+            }
             PaymentCli::Enter { account, amount } => CommandOutput::object(
                 wallet::enter(
                     BigDecimal::from_str(&amount)?,
@@ -590,6 +665,14 @@ Typically operation should take less than 1 minute.
     }
 }
 
+fn parse_date(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(s) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    let date = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")?;
+    Ok(Utc.from_utc_datetime(&date.and_time(chrono::NaiveTime::MIN)))
+}
+
 async fn resolve_address(address: Option<String>) -> anyhow::Result<String> {
     if let Some(id) = address {
         return Ok(id);
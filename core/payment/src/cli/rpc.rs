@@ -1,11 +1,10 @@
 use std::collections::BTreeMap;
 // External crates
-use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use erc20_payment_lib::rpc_pool::{VerifyEndpointResult, Web3ExternalSources, Web3FullNodeData};
 use serde_json::json;
 use std::str::FromStr;
-use ya_core_model::payment::local::{AccountCli, DriverName, NetworkName};
+use ya_core_model::payment::local::{AccountCli, DriverName};
 
 // Workspace uses
 use ya_core_model::payment::local as pay;
@@ -32,7 +31,7 @@ pub struct RpcCommandParams {
 
 pub fn run_command_rpc_entry(
     driver: &DriverName,
-    network: &NetworkName,
+    network: &str,
     sources: &Option<&Web3ExternalSources>,
     node_infos: &Vec<Web3FullNodeData>,
     ctx: &CliCtx,
@@ -175,13 +174,9 @@ pub async fn run_command_rpc(
         .iter()
         .map(|(network, node_infos)| {
             let sources = sources.get(network);
-            let network = NetworkName::from_str(network)
-                .map_err(|_| anyhow!("Invalid network name {network}"))?;
-            Ok(run_command_rpc_entry(
-                &driver, &network, &sources, node_infos, ctx, &params,
-            ))
+            run_command_rpc_entry(&driver, network, &sources, node_infos, ctx, &params)
         })
-        .collect::<anyhow::Result<Vec<CommandOutput>>>()?;
+        .collect();
 
     Ok(CommandOutput::MultiTable { tables: v })
 }
@@ -1,6 +1,41 @@
+/// Loads one page of ledger documents from `$query`: applies [`crate::ledger::LedgerFilter`]
+/// and orders rows by `timestamp` and `id` columns of `$dsl` table.
+/// `$platform` is the column holding payment platform of the document.
+macro_rules! ledger_page {
+    ($conn:expr, $query:expr, $dsl:ident, $platform:expr, $filter:expr) => {{
+        let filter: crate::ledger::LedgerFilter = $filter;
+        let mut query = $query
+            .filter($dsl::owner_id.eq(filter.owner_id))
+            .into_boxed();
+        if let Some(since) = filter.since {
+            query = query.filter($dsl::timestamp.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter($dsl::timestamp.lt(until));
+        }
+        if let Some(platform) = filter.platform {
+            query = query.filter($platform.eq(platform));
+        }
+        if let Some(after) = filter.after {
+            query = query.filter(
+                $dsl::timestamp.gt(after.timestamp).or($dsl::timestamp
+                    .eq(after.timestamp)
+                    .and($dsl::id.gt(after.id))),
+            );
+        }
+        query
+            .order_by($dsl::timestamp.asc())
+            .then_order_by($dsl::id.asc())
+            .limit(filter.limit.into())
+            .load($conn)
+    }};
+}
+
 mod activity;
 mod agreement;
 mod allocation;
+mod allocation_policy;
+mod auto_accept;
 mod debit_note;
 mod debit_note_event;
 mod invoice;
@@ -14,6 +49,8 @@ pub use self::agreement::AgreementDao;
 pub use self::allocation::AllocationDao;
 pub use self::allocation::AllocationReleaseStatus;
 pub use self::allocation::AllocationStatus;
+pub use self::allocation_policy::AllocationPolicyDao;
+pub use self::auto_accept::{AgreementRules, AutoAcceptDao};
 pub use self::debit_note::DebitNoteDao;
 pub use self::debit_note_event::DebitNoteEventDao;
 pub use self::invoice::InvoiceDao;
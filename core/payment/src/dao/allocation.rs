@@ -1,12 +1,16 @@
 use crate::error::{DbError, DbResult};
+use crate::ledger::LedgerFilter;
 use crate::models::allocation::{ReadObj, WriteObj};
 use crate::schema::pay_allocation::dsl;
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
-use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use diesel::{
+    self, BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+};
 use ya_client_model::payment::allocation::Deposit;
 use ya_client_model::payment::{Allocation, NewAllocation};
 use ya_client_model::NodeId;
+use ya_core_model::payment::local::{LedgerEntry, LedgerEntryKind};
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -185,6 +189,45 @@ impl<'c> AllocationDao<'c> {
         .await
     }
 
+    pub async fn ledger(&self, filter: LedgerFilter) -> DbResult<Vec<LedgerEntry>> {
+        readonly_transaction(self.pool, "allocation_dao_ledger", move |conn| {
+            let allocations: Vec<ReadObj> = ledger_page!(
+                conn,
+                dsl::pay_allocation,
+                dsl,
+                dsl::payment_platform,
+                filter
+            )?;
+            Ok(allocations
+                .into_iter()
+                .map(|allocation| LedgerEntry {
+                    kind: LedgerEntryKind::Allocation,
+                    allocation_id: Some(allocation.id.clone()),
+                    id: allocation.id,
+                    timestamp: Utc.from_utc_datetime(&allocation.timestamp),
+                    role: Some("requestor".to_string()),
+                    platform: allocation.payment_platform,
+                    agreement_id: None,
+                    activity_id: None,
+                    peer_id: None,
+                    payer_addr: Some(allocation.address),
+                    payee_addr: None,
+                    amount: allocation.total_amount.0,
+                    status: Some(
+                        if allocation.released {
+                            "released"
+                        } else {
+                            "active"
+                        }
+                        .to_string(),
+                    ),
+                    tx_hash: None,
+                })
+                .collect())
+        })
+        .await
+    }
+
     pub async fn release(
         &self,
         allocation_id: String,
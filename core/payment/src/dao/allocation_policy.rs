@@ -0,0 +1,113 @@
+use crate::dao::order;
+use crate::error::DbResult;
+use crate::models::allocation_policy::{AllocationPolicy, AllocationSpending, ReadObj, WriteObj};
+use crate::schema::pay_allocation_policy::dsl;
+use chrono::{Duration, Utc};
+use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use ya_client_model::NodeId;
+use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
+
+pub struct AllocationPolicyDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for AllocationPolicyDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> AllocationPolicyDao<'c> {
+    pub async fn get(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<AllocationPolicy>> {
+        readonly_transaction(self.pool, "allocation_policy_dao_get", move |conn| {
+            let policy: Option<ReadObj> = dsl::pay_allocation_policy
+                .filter(dsl::allocation_id.eq(allocation_id))
+                .filter(dsl::owner_id.eq(owner_id))
+                .first(conn)
+                .optional()?;
+            Ok(policy.map(Into::into))
+        })
+        .await
+    }
+
+    /// Returns the policy of the allocation together with the amounts already scheduled
+    /// from it, read in a single transaction.
+    pub async fn get_with_spending(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+        provider_id: NodeId,
+    ) -> DbResult<Option<(AllocationPolicy, AllocationSpending)>> {
+        readonly_transaction(
+            self.pool,
+            "allocation_policy_dao_get_with_spending",
+            move |conn| {
+                let policy: Option<ReadObj> = dsl::pay_allocation_policy
+                    .filter(dsl::allocation_id.eq(&allocation_id))
+                    .filter(dsl::owner_id.eq(owner_id))
+                    .first(conn)
+                    .optional()?;
+                let policy = match policy {
+                    Some(policy) => policy.into(),
+                    None => return Ok(None),
+                };
+                let now = Utc::now();
+                let spending = AllocationSpending {
+                    to_provider: order::scheduled_from_allocation(
+                        &allocation_id,
+                        Some(provider_id),
+                        None,
+                        conn,
+                    )?,
+                    last_hour: order::scheduled_from_allocation(
+                        &allocation_id,
+                        None,
+                        Some((now - Duration::hours(1)).naive_utc()),
+                        conn,
+                    )?,
+                    last_day: order::scheduled_from_allocation(
+                        &allocation_id,
+                        None,
+                        Some((now - Duration::days(1)).naive_utc()),
+                        conn,
+                    )?,
+                };
+                Ok(Some((policy, spending)))
+            },
+        )
+        .await
+    }
+
+    pub async fn set(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+        policy: AllocationPolicy,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, "allocation_policy_dao_set", move |conn| {
+            diesel::replace_into(dsl::pay_allocation_policy)
+                .values(WriteObj::new(allocation_id, owner_id, policy))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns `false` if the allocation had no policy.
+    pub async fn remove(&self, allocation_id: String, owner_id: NodeId) -> DbResult<bool> {
+        do_with_transaction(self.pool, "allocation_policy_dao_remove", move |conn| {
+            let removed = diesel::delete(
+                dsl::pay_allocation_policy
+                    .filter(dsl::allocation_id.eq(allocation_id))
+                    .filter(dsl::owner_id.eq(owner_id)),
+            )
+            .execute(conn)?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+}
@@ -0,0 +1,489 @@
+use crate::error::{DbError, DbResult};
+use crate::models::auto_accept::{
+    AuditReadObj, AuditWriteObj, AutoAcceptAuditEntry, AutoAcceptDecision, AutoAcceptDocument,
+    AutoAcceptRules, RuleReadObj, RuleWriteObj,
+};
+use crate::schema::pay_activity::dsl as activity_dsl;
+use crate::schema::pay_allocation::dsl as allocation_dsl;
+use crate::schema::pay_auto_accept_audit::dsl as audit_dsl;
+use crate::schema::pay_auto_accept_rule::dsl;
+use crate::schema::pay_debit_note::dsl as debit_note_dsl;
+use crate::schema::pay_invoice::dsl as invoice_dsl;
+use crate::schema::pay_order::dsl as order_dsl;
+use chrono::NaiveDateTime;
+use diesel::{
+    self, BoolExpressionMethods, ExpressionMethods, JoinOnDsl, NullableExpressionMethods,
+    OptionalExtension, QueryDsl, RunQueryDsl,
+};
+use std::convert::TryFrom;
+use ya_client_model::payment::DocumentStatus;
+use ya_client_model::NodeId;
+use ya_persistence::executor::{
+    do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
+};
+use ya_persistence::types::Role;
+
+/// Auto-accept rules applying to an agreement's document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AgreementRules {
+    /// No active allocation with rules matches the agreement.
+    Missing,
+    Resolved {
+        allocation_id: String,
+        rules: AutoAcceptRules,
+    },
+    /// The agreement was never paid yet and several allocations could pay it.
+    Ambiguous(Vec<String>),
+}
+
+pub struct AutoAcceptDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for AutoAcceptDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> AutoAcceptDao<'c> {
+    pub async fn get_rules(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<AutoAcceptRules>> {
+        readonly_transaction(self.pool, "auto_accept_dao_get_rules", move |conn| {
+            let rules: Option<RuleReadObj> = dsl::pay_auto_accept_rule
+                .filter(dsl::allocation_id.eq(allocation_id))
+                .filter(dsl::owner_id.eq(owner_id))
+                .first(conn)
+                .optional()?;
+            Ok(rules.map(Into::into))
+        })
+        .await
+    }
+
+    pub async fn set_rules(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+        rules: AutoAcceptRules,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, "auto_accept_dao_set_rules", move |conn| {
+            diesel::replace_into(dsl::pay_auto_accept_rule)
+                .values(RuleWriteObj::new(allocation_id, owner_id, rules))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns `false` if the allocation had no rules.
+    pub async fn remove_rules(&self, allocation_id: String, owner_id: NodeId) -> DbResult<bool> {
+        do_with_transaction(self.pool, "auto_accept_dao_remove_rules", move |conn| {
+            let removed = diesel::delete(
+                dsl::pay_auto_accept_rule
+                    .filter(dsl::allocation_id.eq(allocation_id))
+                    .filter(dsl::owner_id.eq(owner_id)),
+            )
+            .execute(conn)?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    /// Rules of the allocation the agreement is paid from.
+    ///
+    /// Once the agreement was paid, only the allocation used for it is considered.
+    /// Before that, the only active allocation with rules for the document's payment
+    /// platform and payer address is used. Released allocations and allocations for
+    /// another platform or address than the document's are ignored.
+    pub async fn rules_for_agreement(
+        &self,
+        owner_id: NodeId,
+        agreement_id: String,
+        payment_platform: String,
+        address: String,
+    ) -> DbResult<AgreementRules> {
+        readonly_transaction(
+            self.pool,
+            "auto_accept_dao_rules_for_agreement",
+            move |conn| {
+                let mut query = dsl::pay_auto_accept_rule
+                    .inner_join(
+                        allocation_dsl::pay_allocation.on(dsl::allocation_id
+                            .eq(allocation_dsl::id)
+                            .and(dsl::owner_id.eq(allocation_dsl::owner_id))),
+                    )
+                    .filter(dsl::owner_id.eq(owner_id))
+                    .filter(allocation_dsl::payment_platform.eq(payment_platform))
+                    .filter(allocation_dsl::address.eq(address))
+                    .filter(allocation_dsl::released.eq(false))
+                    .select(crate::schema::pay_auto_accept_rule::all_columns)
+                    .into_boxed();
+                if let Some(allocation_id) = agreement_allocation(&agreement_id, owner_id, conn)? {
+                    query = query.filter(dsl::allocation_id.eq(allocation_id));
+                }
+                let mut rules: Vec<RuleReadObj> =
+                    query.order_by(dsl::allocation_id.asc()).load(conn)?;
+
+                Ok(match rules.len() {
+                    0 => AgreementRules::Missing,
+                    1 => {
+                        let rules = rules.remove(0);
+                        AgreementRules::Resolved {
+                            allocation_id: rules.allocation_id.clone(),
+                            rules: rules.into(),
+                        }
+                    }
+                    _ => AgreementRules::Ambiguous(
+                        rules.into_iter().map(|rules| rules.allocation_id).collect(),
+                    ),
+                })
+            },
+        )
+        .await
+    }
+
+    /// Received requestor documents of nodes having any auto-accept rules.
+    pub async fn received_documents(&self) -> DbResult<Vec<(AutoAcceptDocument, String, NodeId)>> {
+        readonly_transaction(
+            self.pool,
+            "auto_accept_dao_received_documents",
+            move |conn| {
+                let debit_notes: Vec<(String, NodeId)> = debit_note_dsl::pay_debit_note
+                    .filter(debit_note_dsl::role.eq(Role::Requestor.to_string()))
+                    .filter(debit_note_dsl::status.eq(DocumentStatus::Received.to_string()))
+                    .filter(
+                        debit_note_dsl::owner_id
+                            .eq_any(dsl::pay_auto_accept_rule.select(dsl::owner_id)),
+                    )
+                    .order_by(debit_note_dsl::timestamp.asc())
+                    .select((debit_note_dsl::id, debit_note_dsl::owner_id))
+                    .load(conn)?;
+                let invoices: Vec<(String, NodeId)> = invoice_dsl::pay_invoice
+                    .filter(invoice_dsl::role.eq(Role::Requestor.to_string()))
+                    .filter(invoice_dsl::status.eq(DocumentStatus::Received.to_string()))
+                    .filter(
+                        invoice_dsl::owner_id
+                            .eq_any(dsl::pay_auto_accept_rule.select(dsl::owner_id)),
+                    )
+                    .order_by(invoice_dsl::timestamp.asc())
+                    .select((invoice_dsl::id, invoice_dsl::owner_id))
+                    .load(conn)?;
+
+                let debit_notes = debit_notes
+                    .into_iter()
+                    .map(|(id, owner_id)| (AutoAcceptDocument::DebitNote, id, owner_id));
+                let invoices = invoices
+                    .into_iter()
+                    .map(|(id, owner_id)| (AutoAcceptDocument::Invoice, id, owner_id));
+                Ok(debit_notes.chain(invoices).collect())
+            },
+        )
+        .await
+    }
+
+    pub async fn record(&self, owner_id: NodeId, entry: AutoAcceptAuditEntry) -> DbResult<()> {
+        do_with_transaction(self.pool, "auto_accept_dao_record", move |conn| {
+            diesel::insert_into(audit_dsl::pay_auto_accept_audit)
+                .values(AuditWriteObj::new(owner_id, entry))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_audit(
+        &self,
+        owner_id: NodeId,
+        allocation_id: Option<String>,
+        after_timestamp: Option<NaiveDateTime>,
+        max_items: Option<u32>,
+    ) -> DbResult<Vec<AutoAcceptAuditEntry>> {
+        readonly_transaction(self.pool, "auto_accept_dao_get_audit", move |conn| {
+            let mut query = audit_dsl::pay_auto_accept_audit
+                .filter(audit_dsl::owner_id.eq(owner_id))
+                .into_boxed();
+            if let Some(allocation_id) = allocation_id {
+                query = query.filter(audit_dsl::allocation_id.eq(allocation_id));
+            }
+            if let Some(after_timestamp) = after_timestamp {
+                query = query.filter(audit_dsl::timestamp.gt(after_timestamp));
+            }
+            if let Some(max_items) = max_items {
+                query = query.limit(max_items.into());
+            }
+            let entries: Vec<AuditReadObj> =
+                query.order_by(audit_dsl::timestamp.asc()).load(conn)?;
+            entries
+                .into_iter()
+                .map(|entry| {
+                    AutoAcceptAuditEntry::try_from(entry)
+                        .map_err(|e| DbError::Integrity(e.to_string()))
+                })
+                .collect()
+        })
+        .await
+    }
+}
+
+/// Allocation the agreement's payments were scheduled from, falling back to the
+/// allocation of earlier auto-acceptances (zero-amount debit notes create no orders).
+fn agreement_allocation(
+    agreement_id: &str,
+    owner_id: NodeId,
+    conn: &ConnType,
+) -> DbResult<Option<String>> {
+    let from_debit_notes: Option<String> = order_dsl::pay_order
+        .inner_join(
+            debit_note_dsl::pay_debit_note.on(order_dsl::debit_note_id
+                .eq(debit_note_dsl::id.nullable())
+                .and(order_dsl::payer_id.eq(debit_note_dsl::owner_id))),
+        )
+        .inner_join(
+            activity_dsl::pay_activity.on(debit_note_dsl::activity_id
+                .eq(activity_dsl::id)
+                .and(debit_note_dsl::owner_id.eq(activity_dsl::owner_id))),
+        )
+        .filter(order_dsl::payer_id.eq(owner_id))
+        .filter(activity_dsl::agreement_id.eq(agreement_id))
+        .order_by(order_dsl::timestamp.desc())
+        .select(order_dsl::allocation_id)
+        .first(conn)
+        .optional()?;
+    if from_debit_notes.is_some() {
+        return Ok(from_debit_notes);
+    }
+
+    let from_invoices: Option<String> = order_dsl::pay_order
+        .inner_join(
+            invoice_dsl::pay_invoice.on(order_dsl::invoice_id
+                .eq(invoice_dsl::id.nullable())
+                .and(order_dsl::payer_id.eq(invoice_dsl::owner_id))),
+        )
+        .filter(order_dsl::payer_id.eq(owner_id))
+        .filter(invoice_dsl::agreement_id.eq(agreement_id))
+        .order_by(order_dsl::timestamp.desc())
+        .select(order_dsl::allocation_id)
+        .first(conn)
+        .optional()?;
+    if from_invoices.is_some() {
+        return Ok(from_invoices);
+    }
+
+    Ok(audit_dsl::pay_auto_accept_audit
+        .filter(audit_dsl::owner_id.eq(owner_id))
+        .filter(audit_dsl::agreement_id.eq(agreement_id))
+        .filter(audit_dsl::decision.eq(AutoAcceptDecision::Accepted.to_string()))
+        .order_by(audit_dsl::timestamp.desc())
+        .select(audit_dsl::allocation_id)
+        .first(conn)
+        .optional()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::AllocationDao;
+    use bigdecimal::BigDecimal;
+    use chrono::{Duration, Utc};
+    use ya_client_model::payment::NewAllocation;
+    use ya_persistence::executor::DbExecutor;
+
+    const PLATFORM: &str = "erc20-holesky-tglm";
+
+    async fn allocation_with_rules(
+        db: &DbExecutor,
+        owner_id: NodeId,
+        rules: &AutoAcceptRules,
+    ) -> String {
+        let allocation_id = db
+            .as_dao::<AllocationDao>()
+            .create(
+                NewAllocation {
+                    address: None,
+                    payment_platform: None,
+                    total_amount: BigDecimal::from(10),
+                    timeout: None,
+                    make_deposit: false,
+                    deposit: None,
+                    extend_timeout: None,
+                },
+                owner_id,
+                PLATFORM.to_string(),
+                owner_id.to_string(),
+            )
+            .await
+            .unwrap();
+        db.as_dao::<AutoAcceptDao>()
+            .set_rules(allocation_id.clone(), owner_id, rules.clone())
+            .await
+            .unwrap();
+        allocation_id
+    }
+
+    fn entry(
+        agreement_id: &str,
+        allocation_id: &str,
+        decision: AutoAcceptDecision,
+        secs_ago: i64,
+    ) -> AutoAcceptAuditEntry {
+        AutoAcceptAuditEntry {
+            document_type: AutoAcceptDocument::DebitNote,
+            document_id: format!("{}-{}", agreement_id, secs_ago),
+            agreement_id: agreement_id.to_string(),
+            allocation_id: allocation_id.to_string(),
+            amount: BigDecimal::from(1),
+            decision,
+            reason: decision.to_string(),
+            timestamp: Utc::now() - Duration::seconds(secs_ago),
+        }
+    }
+
+    #[tokio::test]
+    async fn rules_resolved_for_agreement() {
+        let db = DbExecutor::in_memory("auto_accept_rules").unwrap();
+        db.apply_migration(crate::migrations::run_with_output)
+            .unwrap();
+        let owner_id = NodeId::default();
+        let address = owner_id.to_string();
+        let dao: AutoAcceptDao = db.as_dao();
+
+        let rules = AutoAcceptRules {
+            max_total: Some(BigDecimal::from(5)),
+            ..Default::default()
+        };
+        let allocation_id = allocation_with_rules(&db, owner_id, &rules).await;
+        let resolved = |allocation_id: &str| AgreementRules::Resolved {
+            allocation_id: allocation_id.to_string(),
+            rules: rules.clone(),
+        };
+
+        let rules_for = |agreement_id: &str, platform: &str| {
+            dao.rules_for_agreement(
+                owner_id,
+                agreement_id.to_string(),
+                platform.to_string(),
+                address.clone(),
+            )
+        };
+        // The only allocation with rules for the platform and address pays new agreements.
+        assert_eq!(
+            rules_for("accepted", PLATFORM).await.unwrap(),
+            resolved(&allocation_id)
+        );
+        assert_eq!(
+            rules_for("accepted", "erc20-polygon-glm").await.unwrap(),
+            AgreementRules::Missing
+        );
+
+        dao.record(
+            owner_id,
+            entry("accepted", &allocation_id, AutoAcceptDecision::Accepted, 10),
+        )
+        .await
+        .unwrap();
+        dao.record(
+            owner_id,
+            entry("skipped", &allocation_id, AutoAcceptDecision::Skipped, 5),
+        )
+        .await
+        .unwrap();
+
+        // With several candidates only agreements already paid from one of them resolve.
+        let other_allocation_id = allocation_with_rules(&db, owner_id, &rules).await;
+        assert_eq!(
+            rules_for("accepted", PLATFORM).await.unwrap(),
+            resolved(&allocation_id)
+        );
+        let mut candidates = vec![allocation_id.clone(), other_allocation_id.clone()];
+        candidates.sort();
+        assert_eq!(
+            rules_for("skipped", PLATFORM).await.unwrap(),
+            AgreementRules::Ambiguous(candidates)
+        );
+
+        dao.remove_rules(other_allocation_id, owner_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            rules_for("unknown", PLATFORM).await.unwrap(),
+            resolved(&allocation_id)
+        );
+        dao.remove_rules(allocation_id, owner_id).await.unwrap();
+        assert_eq!(
+            rules_for("accepted", PLATFORM).await.unwrap(),
+            AgreementRules::Missing
+        );
+    }
+
+    #[tokio::test]
+    async fn audit_records() {
+        let db = DbExecutor::in_memory("auto_accept_audit").unwrap();
+        db.apply_migration(crate::migrations::run_with_output)
+            .unwrap();
+        let owner_id = NodeId::default();
+        let dao: AutoAcceptDao = db.as_dao();
+
+        dao.record(
+            owner_id,
+            entry("a1", "alloc-1", AutoAcceptDecision::Accepted, 30),
+        )
+        .await
+        .unwrap();
+        dao.record(
+            owner_id,
+            entry("a1", "alloc-1", AutoAcceptDecision::Rejected, 20),
+        )
+        .await
+        .unwrap();
+        dao.record(
+            owner_id,
+            entry("a2", "alloc-2", AutoAcceptDecision::Failed, 10),
+        )
+        .await
+        .unwrap();
+
+        let all = dao.get_audit(owner_id, None, None, None).await.unwrap();
+        let decisions: Vec<_> = all.iter().map(|entry| entry.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                AutoAcceptDecision::Accepted,
+                AutoAcceptDecision::Rejected,
+                AutoAcceptDecision::Failed
+            ]
+        );
+        assert_eq!(all[1].reason, "REJECTED");
+        assert_eq!(all[1].agreement_id, "a1");
+
+        let allocation = dao
+            .get_audit(owner_id, Some("alloc-1".to_string()), None, None)
+            .await
+            .unwrap();
+        assert_eq!(allocation.len(), 2);
+
+        let after = (Utc::now() - Duration::seconds(15)).naive_utc();
+        let recent = dao
+            .get_audit(owner_id, None, Some(after), None)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].allocation_id, "alloc-2");
+
+        let limited = dao.get_audit(owner_id, None, None, Some(1)).await.unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].decision, AutoAcceptDecision::Accepted);
+
+        let other: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        assert!(dao
+            .get_audit(other, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(dao.received_documents().await.unwrap().is_empty());
+    }
+}
@@ -1,19 +1,23 @@
 use crate::dao::{activity, debit_note_event};
 use crate::error::{DbError, DbResult};
+use crate::ledger::{self, LedgerFilter};
 use crate::models::debit_note::{ReadObj, WriteObj};
 use crate::schema::pay_activity::dsl as activity_dsl;
 use crate::schema::pay_agreement::dsl as agreement_dsl;
 use crate::schema::pay_debit_note::dsl;
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, TimeZone, Utc};
 use diesel::{
     self, BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl,
     RunQueryDsl,
 };
 use std::collections::HashMap;
 use std::convert::TryInto;
-use ya_client_model::payment::{DebitNote, DebitNoteEventType, DocumentStatus, NewDebitNote};
+use ya_client_model::payment::{
+    DebitNote, DebitNoteEventType, DocumentStatus, NewDebitNote, Rejection,
+};
 use ya_client_model::NodeId;
+use ya_core_model::payment::local::{LedgerEntry, LedgerEntryKind};
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -249,6 +253,33 @@ impl<'c> DebitNoteDao<'c> {
         .await
     }
 
+    pub async fn ledger(&self, filter: LedgerFilter) -> DbResult<Vec<LedgerEntry>> {
+        readonly_transaction(self.pool, "debit_note_dao_ledger", move |conn| {
+            let debit_notes: Vec<ReadObj> =
+                ledger_page!(conn, query!(), dsl, agreement_dsl::payment_platform, filter)?;
+            Ok(debit_notes
+                .into_iter()
+                .map(|debit_note| LedgerEntry {
+                    kind: LedgerEntryKind::DebitNote,
+                    id: debit_note.id,
+                    timestamp: Utc.from_utc_datetime(&debit_note.timestamp),
+                    role: Some(ledger::role_name(&debit_note.role)),
+                    platform: debit_note.payment_platform,
+                    agreement_id: Some(debit_note.agreement_id),
+                    activity_id: Some(debit_note.activity_id),
+                    allocation_id: None,
+                    peer_id: Some(debit_note.peer_id),
+                    payer_addr: Some(debit_note.payer_addr),
+                    payee_addr: Some(debit_note.payee_addr),
+                    amount: debit_note.total_amount_due.0,
+                    status: Some(debit_note.status),
+                    tx_hash: None,
+                })
+                .collect())
+        })
+        .await
+    }
+
     pub async fn mark_received(&self, debit_note_id: String, owner_id: NodeId) -> DbResult<()> {
         do_with_transaction(self.pool, "debit_note_dao_mark_received", move |conn| {
             diesel::update(dsl::pay_debit_note.find((debit_note_id, owner_id)))
@@ -385,30 +416,27 @@ impl<'c> DebitNoteDao<'c> {
         .await
     }
 
-    // TODO: Implement reject debit note
-    // pub async fn reject(&self, debit_note_id: String, owner_id: NodeId) -> DbResult<()> {
-    //     do_with_transaction(self.pool, move |conn| {
-    //         let (activity_id, role): (String, Role) = dsl::pay_debit_note
-    //             .find((&debit_note_id, &owner_id))
-    //             .select((dsl::activity_id, dsl::role))
-    //             .first(conn)?;
-    //         update_status(
-    //             &vec![debit_note_id.clone()],
-    //             &owner_id,
-    //             &DocumentStatus::Rejected,
-    //             conn,
-    //         )?;
-    //         if let Role::Provider = role {
-    //             debit_note_event::create::<()>(
-    //                 debit_note_id,
-    //                 owner_id,
-    //                 DebitNoteEventType::DebitNoteRejectedEvent,
-    //                 None,
-    //                 conn,
-    //             )?;
-    //         }
-    //         Ok(())
-    //     })
-    //     .await
-    // }
+    pub async fn reject(
+        &self,
+        debit_note_id: String,
+        owner_id: NodeId,
+        rejection: Rejection,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, "debit_note_reject", move |conn| {
+            update_status(
+                &vec![debit_note_id.clone()],
+                &owner_id,
+                &DocumentStatus::Rejected,
+                conn,
+            )?;
+            debit_note_event::create(
+                debit_note_id,
+                owner_id,
+                DebitNoteEventType::DebitNoteRejectedEvent { rejection },
+                conn,
+            )?;
+            Ok(())
+        })
+        .await
+    }
 }
@@ -20,10 +20,19 @@ pub fn create(
     event_type: DebitNoteEventType,
     conn: &ConnType,
 ) -> DbResult<()> {
-    let event = WriteObj::new(debit_note_id, owner_id, event_type)?;
+    let event = WriteObj::new(debit_note_id.clone(), owner_id, event_type.clone())?;
     diesel::insert_into(write_dsl::pay_debit_note_event)
         .values(event)
         .execute(conn)?;
+
+    crate::events::publish(
+        event_type.discriminant(),
+        owner_id,
+        serde_json::json!({
+            "debitNoteId": debit_note_id,
+            "details": event_type.details(),
+        }),
+    );
     Ok(())
 }
 
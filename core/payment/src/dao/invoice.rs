@@ -1,11 +1,12 @@
 use crate::dao::{agreement, invoice_event};
 use crate::error::{DbError, DbResult};
+use crate::ledger::{self, LedgerFilter};
 use crate::models::invoice::{equivalent, InvoiceXActivity, ReadObj, WriteObj};
 use crate::schema::pay_agreement::dsl as agreement_dsl;
 use crate::schema::pay_invoice::dsl;
 use crate::schema::pay_invoice_x_activity::dsl as activity_dsl;
 use bigdecimal::{BigDecimal, Zero};
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use diesel::{
     BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl, RunQueryDsl,
 };
@@ -13,7 +14,7 @@ use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use ya_client_model::payment::{DocumentStatus, Invoice, InvoiceEventType, NewInvoice, Rejection};
 use ya_client_model::NodeId;
-use ya_core_model::payment::local::StatValue;
+use ya_core_model::payment::local::{LedgerEntry, LedgerEntryKind, StatValue};
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -267,6 +268,33 @@ impl<'c> InvoiceDao<'c> {
         .await
     }
 
+    pub async fn ledger(&self, filter: LedgerFilter) -> DbResult<Vec<LedgerEntry>> {
+        readonly_transaction(self.pool, "invoice_dao_ledger", move |conn| {
+            let invoices: Vec<ReadObj> =
+                ledger_page!(conn, query!(), dsl, agreement_dsl::payment_platform, filter)?;
+            Ok(invoices
+                .into_iter()
+                .map(|invoice| LedgerEntry {
+                    kind: LedgerEntryKind::Invoice,
+                    id: invoice.id,
+                    timestamp: Utc.from_utc_datetime(&invoice.timestamp),
+                    role: Some(ledger::role_name(&invoice.role)),
+                    platform: invoice.payment_platform,
+                    agreement_id: Some(invoice.agreement_id),
+                    activity_id: None,
+                    allocation_id: None,
+                    peer_id: Some(invoice.peer_id),
+                    payer_addr: Some(invoice.payer_addr),
+                    payee_addr: Some(invoice.payee_addr),
+                    amount: invoice.amount.0,
+                    status: Some(invoice.status),
+                    tx_hash: None,
+                })
+                .collect())
+        })
+        .await
+    }
+
     pub async fn last_invoice_stats(
         &self,
         node_id: NodeId,
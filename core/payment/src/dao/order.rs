@@ -4,14 +4,41 @@ use crate::models::order::{ReadObj, WriteObj};
 use crate::schema::pay_debit_note::dsl as debit_note_dsl;
 use crate::schema::pay_invoice::dsl as invoice_dsl;
 use crate::schema::pay_order::dsl;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
 use diesel::{
     self, BoolExpressionMethods, ExpressionMethods, JoinOnDsl, NullableExpressionMethods, QueryDsl,
     RunQueryDsl,
 };
+use ya_client_model::NodeId;
 use ya_core_model::payment::local::{
     DebitNotePayment, InvoicePayment, PaymentTitle, SchedulePayment,
 };
-use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
+use ya_persistence::executor::{
+    do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
+};
+use ya_persistence::types::{BigDecimalField, Summable};
+
+/// Total amount scheduled from the allocation, optionally only to the given payee
+/// and only by orders created after `since`.
+pub fn scheduled_from_allocation(
+    allocation_id: &str,
+    payee_id: Option<NodeId>,
+    since: Option<NaiveDateTime>,
+    conn: &ConnType,
+) -> DbResult<BigDecimal> {
+    let mut query = dsl::pay_order
+        .filter(dsl::allocation_id.eq(allocation_id))
+        .select(dsl::amount)
+        .into_boxed();
+    if let Some(payee_id) = payee_id {
+        query = query.filter(dsl::payee_id.eq(payee_id));
+    }
+    if let Some(since) = since {
+        query = query.filter(dsl::timestamp.gt(since));
+    }
+    Ok(query.load::<BigDecimalField>(conn)?.sum())
+}
 
 pub struct OrderDao<'c> {
     pool: &'c PoolType,
@@ -1,5 +1,6 @@
 use crate::dao::{activity, agreement};
 use crate::error::DbResult;
+use crate::ledger::{self, LedgerFilter};
 use crate::models::payment::{
     ActivityPayment as DbActivityPayment, AgreementPayment as DbAgreementPayment, ReadObj, WriteObj,
 };
@@ -9,7 +10,7 @@ use crate::schema::pay_agreement::dsl as agreement_dsl;
 use crate::schema::pay_agreement_payment::dsl as agreement_pay_dsl;
 use crate::schema::pay_payment::dsl;
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, TimeZone, Utc};
 use diesel::{
     BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl, RunQueryDsl,
     TextExpressionMethods,
@@ -17,7 +18,7 @@ use diesel::{
 use std::collections::HashMap;
 use ya_client_model::payment::{ActivityPayment, AgreementPayment, Payment, Signed};
 use ya_client_model::NodeId;
-use ya_core_model::payment::local::{DriverName, NetworkName};
+use ya_core_model::payment::local::{DriverName, LedgerEntry, LedgerEntryKind};
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -259,7 +260,7 @@ impl<'c> PaymentDao<'c> {
         after_timestamp: Option<NaiveDateTime>,
         max_events: Option<u32>,
         app_session_id: Option<String>,
-        network: Option<NetworkName>,
+        network: Option<String>,
         driver: Option<DriverName>,
     ) -> DbResult<Vec<Signed<Payment>>> {
         readonly_transaction(self.pool, "payment_dao_get_for_node_id", move |conn| {
@@ -351,6 +352,90 @@ impl<'c> PaymentDao<'c> {
         .await
     }
 
+    pub async fn ledger(&self, filter: LedgerFilter) -> DbResult<Vec<LedgerEntry>> {
+        readonly_transaction(self.pool, "payment_dao_ledger", move |conn| {
+            let owner_id = filter.owner_id;
+            let payments: Vec<ReadObj> =
+                ledger_page!(conn, dsl::pay_payment, dsl, dsl::payment_platform, filter)?;
+            let payment_ids: Vec<String> = payments.iter().map(|p| p.id.clone()).collect();
+
+            let activity_payments: Vec<(DbActivityPayment, String)> =
+                activity_pay_dsl::pay_activity_payment
+                    .inner_join(
+                        activity_dsl::pay_activity.on(activity_pay_dsl::owner_id
+                            .eq(activity_dsl::owner_id)
+                            .and(activity_pay_dsl::activity_id.eq(activity_dsl::id))),
+                    )
+                    .filter(activity_pay_dsl::owner_id.eq(owner_id))
+                    .filter(activity_pay_dsl::payment_id.eq_any(payment_ids.clone()))
+                    .select((
+                        crate::schema::pay_activity_payment::all_columns,
+                        activity_dsl::agreement_id,
+                    ))
+                    .load(conn)?;
+            let agreement_payments: Vec<DbAgreementPayment> =
+                agreement_pay_dsl::pay_agreement_payment
+                    .filter(agreement_pay_dsl::owner_id.eq(owner_id))
+                    .filter(agreement_pay_dsl::payment_id.eq_any(payment_ids))
+                    .load(conn)?;
+
+            let mut lines: HashMap<String, Vec<_>> = HashMap::new();
+            for (activity_payment, agreement_id) in activity_payments {
+                lines.entry(activity_payment.payment_id).or_default().push((
+                    agreement_id,
+                    Some(activity_payment.activity_id),
+                    activity_payment.allocation_id,
+                    activity_payment.amount.0,
+                ));
+            }
+            for agreement_payment in agreement_payments {
+                lines
+                    .entry(agreement_payment.payment_id)
+                    .or_default()
+                    .push((
+                        agreement_payment.agreement_id,
+                        None,
+                        agreement_payment.allocation_id,
+                        agreement_payment.amount.0,
+                    ));
+            }
+
+            let mut entries = Vec::new();
+            for payment in payments {
+                let entry = LedgerEntry {
+                    kind: LedgerEntryKind::Payment,
+                    id: payment.id.clone(),
+                    timestamp: Utc.from_utc_datetime(&payment.timestamp),
+                    role: Some(ledger::role_name(&payment.role)),
+                    platform: payment.payment_platform.clone(),
+                    agreement_id: None,
+                    activity_id: None,
+                    allocation_id: None,
+                    peer_id: Some(payment.peer_id),
+                    payer_addr: Some(payment.payer_addr.clone()),
+                    payee_addr: Some(payment.payee_addr.clone()),
+                    amount: payment.amount.0.clone(),
+                    status: None,
+                    tx_hash: ledger::tx_hash(&payment.details),
+                };
+                match lines.remove(&payment.id) {
+                    Some(lines) => entries.extend(lines.into_iter().map(
+                        |(agreement_id, activity_id, allocation_id, amount)| LedgerEntry {
+                            agreement_id: Some(agreement_id),
+                            activity_id,
+                            allocation_id,
+                            amount,
+                            ..entry.clone()
+                        },
+                    )),
+                    None => entries.push(entry),
+                }
+            }
+            Ok(entries)
+        })
+        .await
+    }
+
     pub async fn list_unsent(&self, peer_id: Option<NodeId>) -> DbResult<Vec<Payment>> {
         readonly_transaction(self.pool, "payment_dao_list_unsent", move |conn| {
             let mut query = dsl::pay_payment
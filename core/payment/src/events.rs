@@ -0,0 +1,51 @@
+//! Forwards debit note and invoice events to the internal event stream.
+//!
+//! Events are created inside database transactions running on blocking threads,
+//! so they are queued here and sent over GSB by a task started with the service.
+//! Event is queued before the transaction is committed, so in rare cases
+//! it can be published for a change, which was rolled back.
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use ya_client_model::NodeId;
+use ya_core_model::event::{self as model, Event, EventCategory};
+use ya_service_bus::typed as bus;
+
+lazy_static::lazy_static! {
+    static ref QUEUE: (UnboundedSender<Event>, Mutex<Option<UnboundedReceiver<Event>>>) = {
+        let (tx, rx) = mpsc::unbounded();
+        (tx, Mutex::new(Some(rx)))
+    };
+}
+
+/// Events are queued only after the publisher was started, so the queue doesn't
+/// grow, when payment service is used without GSB (e.g. in tests).
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn publish(kind: &str, owner_id: NodeId, payload: serde_json::Value) {
+    if !STARTED.load(Ordering::Relaxed) {
+        return;
+    }
+    let event = Event::new(EventCategory::Payment, kind, Some(owner_id), payload);
+    if let Err(e) = QUEUE.0.unbounded_send(event) {
+        log::trace!("Failed to queue payment event: {e}");
+    }
+}
+
+pub(crate) fn start_publisher() {
+    let mut rx = match QUEUE.1.lock().unwrap().take() {
+        Some(rx) => rx,
+        None => return,
+    };
+    STARTED.store(true, Ordering::Relaxed);
+
+    tokio::task::spawn_local(async move {
+        while let Some(event) = rx.next().await {
+            if let Err(e) = bus::service(model::BUS_ID).send(event).await {
+                log::trace!("Failed to publish payment event: {e}");
+            }
+        }
+    });
+}
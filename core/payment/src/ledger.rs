@@ -0,0 +1,497 @@
+//! Line-item export of invoices, debit notes, payments and allocations.
+//!
+//! Every DAO contributes its own entries ordered by `(timestamp, id)`; pages are
+//! produced by merging those streams and continuing from a keyset cursor, so
+//! large histories can be walked without offsets.
+
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{ExportLedger, LedgerEntry, LedgerEntryKind, LedgerPage};
+use ya_persistence::executor::DbExecutor;
+use ya_persistence::types::Role;
+
+use crate::dao::{AllocationDao, DebitNoteDao, InvoiceDao, PaymentDao};
+use crate::error::{DbError, DbResult};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Position of the last exported document.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LedgerCursor {
+    pub timestamp: NaiveDateTime,
+    pub id: String,
+}
+
+impl fmt::Display for LedgerCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = Utc
+            .from_utc_datetime(&self.timestamp)
+            .timestamp_nanos_opt()
+            .ok_or(fmt::Error)?;
+        write!(f, "{}.{}", nanos, self.id)
+    }
+}
+
+impl FromStr for LedgerCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid ledger cursor: {}", s);
+        let (nanos, id) = s.split_once('.').ok_or_else(invalid)?;
+        let nanos = i64::from_str(nanos).map_err(|_| invalid())?;
+        if id.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            timestamp: Utc.timestamp_nanos(nanos).naive_utc(),
+            id: id.to_string(),
+        })
+    }
+}
+
+/// Filter shared by the per-DAO ledger queries.
+#[derive(Clone, Debug)]
+pub struct LedgerFilter {
+    pub owner_id: NodeId,
+    /// Inclusive lower bound.
+    pub since: Option<NaiveDateTime>,
+    /// Exclusive upper bound.
+    pub until: Option<NaiveDateTime>,
+    pub platform: Option<String>,
+    pub after: Option<LedgerCursor>,
+    /// Maximum number of documents (not entries) to return.
+    pub limit: u32,
+}
+
+pub(crate) fn role_name(role: &Role) -> String {
+    match role {
+        Role::Provider => "provider".to_string(),
+        Role::Requestor => "requestor".to_string(),
+    }
+}
+
+pub(crate) fn tx_hash(details: &[u8]) -> Option<String> {
+    if details.is_empty() {
+        None
+    } else {
+        Some(format!("0x{}", hex::encode(details)))
+    }
+}
+
+pub async fn export(db: &DbExecutor, msg: ExportLedger) -> DbResult<LedgerPage> {
+    let after = msg
+        .after
+        .as_deref()
+        .map(LedgerCursor::from_str)
+        .transpose()
+        .map_err(DbError::Query)?;
+    let limit = msg.limit.clamp(1, MAX_PAGE_SIZE);
+    let kinds = if msg.kinds.is_empty() {
+        vec![
+            LedgerEntryKind::Invoice,
+            LedgerEntryKind::DebitNote,
+            LedgerEntryKind::Payment,
+            LedgerEntryKind::Allocation,
+        ]
+    } else {
+        msg.kinds
+    };
+
+    // One extra document per source tells whether there is a next page.
+    let filter = LedgerFilter {
+        owner_id: msg.node_id,
+        since: msg.since.map(|d| d.naive_utc()),
+        until: msg.until.map(|d| d.naive_utc()),
+        platform: msg.platform,
+        after,
+        limit: limit + 1,
+    };
+
+    let mut entries = Vec::new();
+    for kind in kinds {
+        let filter = filter.clone();
+        entries.extend(match kind {
+            LedgerEntryKind::Invoice => db.as_dao::<InvoiceDao>().ledger(filter).await?,
+            LedgerEntryKind::DebitNote => db.as_dao::<DebitNoteDao>().ledger(filter).await?,
+            LedgerEntryKind::Payment => db.as_dao::<PaymentDao>().ledger(filter).await?,
+            LedgerEntryKind::Allocation => db.as_dao::<AllocationDao>().ledger(filter).await?,
+        });
+    }
+
+    Ok(merge_page(entries, limit))
+}
+
+/// Merges entries of all sources and cuts them to `limit` documents.
+fn merge_page(mut entries: Vec<LedgerEntry>, limit: u32) -> LedgerPage {
+    // Stable sort keeps the per-agreement lines of a payment in DAO order.
+    entries.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+
+    let mut documents = 0;
+    let mut last_id: Option<String> = None;
+    let mut next = None;
+    let mut page = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if last_id.as_ref() != Some(&entry.id) {
+            if documents == limit {
+                next = page.last().map(|last: &LedgerEntry| {
+                    LedgerCursor {
+                        timestamp: last.timestamp.naive_utc(),
+                        id: last.id.clone(),
+                    }
+                    .to_string()
+                });
+                break;
+            }
+            documents += 1;
+            last_id = Some(entry.id.clone());
+        }
+        page.push(entry);
+    }
+
+    LedgerPage {
+        entries: page,
+        next,
+    }
+}
+
+const CSV_HEADER: [&str; 14] = [
+    "kind",
+    "id",
+    "timestamp",
+    "role",
+    "platform",
+    "agreement_id",
+    "activity_id",
+    "allocation_id",
+    "peer_id",
+    "payer_addr",
+    "payee_addr",
+    "amount",
+    "status",
+    "tx_hash",
+];
+
+pub fn csv_header() -> String {
+    format!("{}\r\n", CSV_HEADER.join(","))
+}
+
+/// Formats entries as RFC 4180 CSV rows, without the header line.
+pub fn csv_rows(entries: &[LedgerEntry]) -> String {
+    fn field(value: &str) -> String {
+        if value.contains(|c| matches!(c, ',' | '"' | '\r' | '\n')) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+    fn opt(value: &Option<String>) -> String {
+        value.as_deref().map(field).unwrap_or_default()
+    }
+
+    let mut out = String::new();
+    for entry in entries {
+        let row = [
+            entry.kind.to_string(),
+            field(&entry.id),
+            entry.timestamp.to_rfc3339(),
+            opt(&entry.role),
+            field(&entry.platform),
+            opt(&entry.agreement_id),
+            opt(&entry.activity_id),
+            opt(&entry.allocation_id),
+            entry.peer_id.map(|id| id.to_string()).unwrap_or_default(),
+            opt(&entry.payer_addr),
+            opt(&entry.payee_addr),
+            entry.amount.to_string(),
+            opt(&entry.status),
+            opt(&entry.tx_hash),
+        ];
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Writes ledger pages as they are fetched, so exports don't have to fit in memory.
+pub struct LedgerWriter<W: Write> {
+    writer: W,
+    csv: bool,
+    entries: usize,
+}
+
+impl<W: Write> LedgerWriter<W> {
+    /// Writes CSV header or opens JSON array.
+    pub fn new(mut writer: W, csv: bool) -> io::Result<Self> {
+        if csv {
+            writer.write_all(csv_header().as_bytes())?;
+        } else {
+            writer.write_all(b"[")?;
+        }
+        Ok(Self {
+            writer,
+            csv,
+            entries: 0,
+        })
+    }
+
+    pub fn write_page(&mut self, entries: &[LedgerEntry]) -> io::Result<()> {
+        if self.csv {
+            self.writer.write_all(csv_rows(entries).as_bytes())?;
+        } else {
+            for (i, entry) in entries.iter().enumerate() {
+                if self.entries + i > 0 {
+                    self.writer.write_all(b",")?;
+                }
+                self.writer.write_all(b"\n")?;
+                serde_json::to_writer(&mut self.writer, entry)?;
+            }
+        }
+        self.entries += entries.len();
+        Ok(())
+    }
+
+    /// Closes JSON array and returns the number of written entries.
+    pub fn finish(mut self) -> io::Result<usize> {
+        if !self.csv {
+            self.writer.write_all(b"\n]\n")?;
+        }
+        self.writer.flush()?;
+        Ok(self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use chrono::Duration;
+    use ya_client_model::payment::NewAllocation;
+
+    fn entry(kind: LedgerEntryKind, id: &str, secs: i64, amount: u32) -> LedgerEntry {
+        LedgerEntry {
+            kind,
+            id: id.to_string(),
+            timestamp: Utc.timestamp_opt(1_700_000_000 + secs, 0).unwrap(),
+            role: Some("requestor".to_string()),
+            platform: "erc20-holesky-tglm".to_string(),
+            agreement_id: None,
+            activity_id: None,
+            allocation_id: None,
+            peer_id: None,
+            payer_addr: None,
+            payee_addr: None,
+            amount: BigDecimal::from(amount),
+            status: None,
+            tx_hash: None,
+        }
+    }
+
+    fn ids(page: &LedgerPage) -> Vec<&str> {
+        page.entries.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = LedgerCursor {
+            timestamp: Utc
+                .timestamp_opt(1_700_000_000, 123_456_789)
+                .unwrap()
+                .naive_utc(),
+            id: "a.b-c".to_string(),
+        };
+        assert_eq!(LedgerCursor::from_str(&cursor.to_string()).unwrap(), cursor);
+        assert!(LedgerCursor::from_str("1700000000").is_err());
+        assert!(LedgerCursor::from_str("x.id").is_err());
+        assert!(LedgerCursor::from_str("1700000000.").is_err());
+    }
+
+    #[test]
+    fn merge_orders_sources_by_timestamp_and_id() {
+        let page = merge_page(
+            vec![
+                entry(LedgerEntryKind::Invoice, "inv", 2, 1),
+                entry(LedgerEntryKind::DebitNote, "dn", 1, 1),
+                entry(LedgerEntryKind::Allocation, "b-alloc", 2, 1),
+                entry(LedgerEntryKind::Allocation, "a-alloc", 2, 1),
+            ],
+            10,
+        );
+        assert_eq!(ids(&page), ["dn", "a-alloc", "b-alloc", "inv"]);
+        assert_eq!(page.next, None);
+    }
+
+    #[test]
+    fn merge_keeps_payment_lines_on_one_page() {
+        let page = merge_page(
+            vec![
+                entry(LedgerEntryKind::Allocation, "alloc", 0, 1),
+                entry(LedgerEntryKind::Payment, "pay", 1, 1),
+                entry(LedgerEntryKind::Payment, "pay", 1, 2),
+                entry(LedgerEntryKind::Invoice, "inv", 2, 1),
+            ],
+            2,
+        );
+        // Limit counts documents, so both lines of the payment are included.
+        assert_eq!(ids(&page), ["alloc", "pay", "pay"]);
+        assert_eq!(page.entries[1].amount, BigDecimal::from(1));
+        assert_eq!(page.entries[2].amount, BigDecimal::from(2));
+
+        let next = LedgerCursor::from_str(page.next.as_deref().unwrap()).unwrap();
+        assert_eq!(next.id, "pay");
+        assert_eq!(next.timestamp, page.entries[2].timestamp.naive_utc());
+    }
+
+    #[test]
+    fn csv_escaping() {
+        let mut plain = entry(LedgerEntryKind::Payment, "pay", 0, 5);
+        plain.tx_hash = Some("0xabcd".to_string());
+        let mut quoted = entry(LedgerEntryKind::Invoice, "inv,1", 0, 7);
+        quoted.status = Some("say \"hi\"".to_string());
+        quoted.agreement_id = Some("line\nbreak".to_string());
+
+        let rows = csv_rows(&[plain, quoted]);
+        let lines: Vec<&str> = rows.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "payment,pay,2023-11-14T22:13:20+00:00,requestor,erc20-holesky-tglm,,,,,,,5,,0xabcd"
+        );
+        assert_eq!(
+            lines[1],
+            "invoice,\"inv,1\",2023-11-14T22:13:20+00:00,requestor,erc20-holesky-tglm,\
+             \"line\nbreak\",,,,,,7,\"say \"\"hi\"\"\","
+        );
+        assert_eq!(lines[2], "");
+        assert_eq!(csv_header().matches(',').count(), CSV_HEADER.len() - 1);
+    }
+
+    #[test]
+    fn writer_streams_json_array() {
+        let mut out = Vec::new();
+        let mut writer = LedgerWriter::new(&mut out, false).unwrap();
+        writer
+            .write_page(&[entry(LedgerEntryKind::Invoice, "inv", 0, 1)])
+            .unwrap();
+        writer.write_page(&[]).unwrap();
+        writer
+            .write_page(&[entry(LedgerEntryKind::Payment, "pay", 1, 2)])
+            .unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        let entries: Vec<LedgerEntry> = serde_json::from_slice(&out).unwrap();
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["inv", "pay"]);
+
+        let mut out = Vec::new();
+        assert_eq!(
+            LedgerWriter::new(&mut out, false)
+                .unwrap()
+                .finish()
+                .unwrap(),
+            0
+        );
+        assert!(serde_json::from_slice::<Vec<LedgerEntry>>(&out)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn export_pages_through_documents() {
+        let db = DbExecutor::in_memory("ledger_export").unwrap();
+        db.apply_migration(crate::migrations::run_with_output)
+            .unwrap();
+        let owner_id = NodeId::default();
+        let other_id: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+
+        let dao: AllocationDao = db.as_dao();
+        let allocation = |amount: u32| NewAllocation {
+            address: None,
+            payment_platform: None,
+            total_amount: BigDecimal::from(amount),
+            timeout: None,
+            make_deposit: false,
+            deposit: None,
+            extend_timeout: None,
+        };
+        let mut expected = Vec::new();
+        for amount in 0..5 {
+            expected.push(
+                dao.create(
+                    allocation(amount),
+                    owner_id,
+                    "erc20-holesky-tglm".to_string(),
+                    owner_id.to_string(),
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        dao.create(
+            allocation(100),
+            other_id,
+            "erc20-holesky-tglm".to_string(),
+            other_id.to_string(),
+        )
+        .await
+        .unwrap();
+        dao.create(
+            allocation(200),
+            owner_id,
+            "erc20-polygon-glm".to_string(),
+            owner_id.to_string(),
+        )
+        .await
+        .unwrap();
+
+        let request = |after: Option<String>| ExportLedger {
+            node_id: owner_id,
+            since: None,
+            until: None,
+            platform: Some("erc20-holesky-tglm".to_string()),
+            kinds: vec![],
+            after,
+            limit: 2,
+        };
+        let mut exported = Vec::new();
+        let mut after = None;
+        let mut pages = 0;
+        loop {
+            let page = export(&db, request(after)).await.unwrap();
+            assert!(page.entries.len() <= 2);
+            exported.extend(page.entries.into_iter().map(|e| (e.timestamp, e.id)));
+            pages += 1;
+            match page.next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 3);
+
+        let mut sorted = exported.clone();
+        sorted.sort();
+        assert_eq!(exported, sorted);
+        let mut ids: Vec<String> = exported.into_iter().map(|(_, id)| id).collect();
+        ids.sort();
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        // Documents after the `until` bound are skipped.
+        let page = export(
+            &db,
+            ExportLedger {
+                until: Some(Utc::now() - Duration::days(1)),
+                ..request(None)
+            },
+        )
+        .await
+        .unwrap();
+        assert!(page.entries.is_empty());
+        assert_eq!(page.next, None);
+    }
+}
@@ -17,6 +17,8 @@ pub mod api;
 mod cli;
 pub mod dao;
 pub mod error;
+mod events;
+pub mod ledger;
 pub mod models;
 pub mod payment_sync;
 pub mod processor;
@@ -55,6 +57,8 @@ impl PaymentService {
 
         let processor = Arc::new(PaymentProcessor::new(db.clone()));
         self::service::bind_service(&db, processor.clone(), BindOptions::default());
+        events::start_publisher();
+        api::auto_accept::process_received_job(db.clone());
 
         tokio::task::spawn(async move {
             processor.release_allocations(false).await;
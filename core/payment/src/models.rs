@@ -1,6 +1,8 @@
 pub mod activity;
 pub mod agreement;
 pub mod allocation;
+pub mod allocation_policy;
+pub mod auto_accept;
 pub mod debit_note;
 pub mod debit_note_event;
 pub mod invoice;
@@ -0,0 +1,76 @@
+use crate::schema::pay_allocation_policy;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use ya_client_model::NodeId;
+use ya_persistence::types::BigDecimalField;
+
+/// Optional spending limits attached to an allocation, checked when debit notes
+/// and invoices are accepted against it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationPolicy {
+    /// Maximum total amount scheduled from the allocation to a single provider.
+    pub max_per_provider: Option<BigDecimal>,
+    /// Maximum amount scheduled from the allocation within the last hour.
+    pub max_per_hour: Option<BigDecimal>,
+    /// Maximum amount scheduled from the allocation within the last 24 hours.
+    pub max_per_day: Option<BigDecimal>,
+    /// Maximum amount a single debit note may add on top of the previous one.
+    pub max_debit_note_increment: Option<BigDecimal>,
+    /// Refuse debit notes charging more than the agreed linear price model
+    /// yields for the reported usage.
+    #[serde(default)]
+    pub enforce_price_model: bool,
+}
+
+/// Amounts already scheduled from an allocation that count towards its policy limits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocationSpending {
+    /// Total scheduled to the provider whose document is being accepted.
+    pub to_provider: BigDecimal,
+    /// Scheduled within the last hour.
+    pub last_hour: BigDecimal,
+    /// Scheduled within the last 24 hours.
+    pub last_day: BigDecimal,
+}
+
+#[derive(Queryable, Debug, Identifiable, Insertable)]
+#[table_name = "pay_allocation_policy"]
+#[primary_key(allocation_id, owner_id)]
+pub struct WriteObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub max_per_provider: Option<BigDecimalField>,
+    pub max_per_hour: Option<BigDecimalField>,
+    pub max_per_day: Option<BigDecimalField>,
+    pub max_debit_note_increment: Option<BigDecimalField>,
+    pub enforce_price_model: bool,
+}
+
+pub type ReadObj = WriteObj;
+
+impl WriteObj {
+    pub fn new(allocation_id: String, owner_id: NodeId, policy: AllocationPolicy) -> Self {
+        Self {
+            allocation_id,
+            owner_id,
+            max_per_provider: policy.max_per_provider.map(Into::into),
+            max_per_hour: policy.max_per_hour.map(Into::into),
+            max_per_day: policy.max_per_day.map(Into::into),
+            max_debit_note_increment: policy.max_debit_note_increment.map(Into::into),
+            enforce_price_model: policy.enforce_price_model,
+        }
+    }
+}
+
+impl From<ReadObj> for AllocationPolicy {
+    fn from(policy: ReadObj) -> Self {
+        Self {
+            max_per_provider: policy.max_per_provider.map(Into::into),
+            max_per_hour: policy.max_per_hour.map(Into::into),
+            max_per_day: policy.max_per_day.map(Into::into),
+            max_debit_note_increment: policy.max_debit_note_increment.map(Into::into),
+            enforce_price_model: policy.enforce_price_model,
+        }
+    }
+}
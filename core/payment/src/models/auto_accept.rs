@@ -0,0 +1,156 @@
+use crate::schema::{pay_auto_accept_audit, pay_auto_accept_rule};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::str::FromStr;
+use uuid::Uuid;
+use ya_client_model::NodeId;
+use ya_persistence::types::BigDecimalField;
+
+/// Rules for accepting debit notes and invoices paid from an allocation as soon as
+/// they arrive, without waiting for the requestor application.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoAcceptRules {
+    /// Accepted relative excess over the amount computed from the agreement's linear
+    /// price model, e.g. `0.05` for 5%. No excess is accepted by default.
+    pub price_tolerance: Option<BigDecimal>,
+    /// Accepted relative excess of the reported `golem.usage.duration_sec` over the
+    /// time elapsed since the agreement was approved. Not checked by default.
+    pub time_tolerance: Option<BigDecimal>,
+    /// Documents with a higher amount due are never auto-accepted.
+    pub max_total: Option<BigDecimal>,
+    /// Reject invoices breaking the rules instead of leaving them for manual review.
+    #[serde(default)]
+    pub reject_invoices: bool,
+}
+
+#[derive(Queryable, Debug, Identifiable, Insertable)]
+#[table_name = "pay_auto_accept_rule"]
+#[primary_key(allocation_id, owner_id)]
+pub struct RuleWriteObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub price_tolerance: Option<BigDecimalField>,
+    pub time_tolerance: Option<BigDecimalField>,
+    pub max_total: Option<BigDecimalField>,
+    pub reject_invoices: bool,
+}
+
+pub type RuleReadObj = RuleWriteObj;
+
+impl RuleWriteObj {
+    pub fn new(allocation_id: String, owner_id: NodeId, rules: AutoAcceptRules) -> Self {
+        Self {
+            allocation_id,
+            owner_id,
+            price_tolerance: rules.price_tolerance.map(Into::into),
+            time_tolerance: rules.time_tolerance.map(Into::into),
+            max_total: rules.max_total.map(Into::into),
+            reject_invoices: rules.reject_invoices,
+        }
+    }
+}
+
+impl From<RuleReadObj> for AutoAcceptRules {
+    fn from(rules: RuleReadObj) -> Self {
+        Self {
+            price_tolerance: rules.price_tolerance.map(Into::into),
+            time_tolerance: rules.time_tolerance.map(Into::into),
+            max_total: rules.max_total.map(Into::into),
+            reject_invoices: rules.reject_invoices,
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutoAcceptDocument {
+    DebitNote,
+    Invoice,
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutoAcceptDecision {
+    /// Document matched the rules and was accepted.
+    Accepted,
+    /// Invoice broke the rules and was rejected.
+    Rejected,
+    /// Document broke the rules and was left for manual review.
+    Skipped,
+    /// Document matched the rules, but accepting or rejecting it failed.
+    Failed,
+}
+
+/// Audit trail entry explaining what auto-acceptance did with a document.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoAcceptAuditEntry {
+    pub document_type: AutoAcceptDocument,
+    pub document_id: String,
+    pub agreement_id: String,
+    pub allocation_id: String,
+    pub amount: BigDecimal,
+    pub decision: AutoAcceptDecision,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Queryable, Debug, Identifiable, Insertable)]
+#[table_name = "pay_auto_accept_audit"]
+pub struct AuditWriteObj {
+    pub id: String,
+    pub owner_id: NodeId,
+    pub allocation_id: String,
+    pub document_type: String,
+    pub document_id: String,
+    pub agreement_id: String,
+    pub amount: BigDecimalField,
+    pub decision: String,
+    pub reason: String,
+    pub timestamp: NaiveDateTime,
+}
+
+pub type AuditReadObj = AuditWriteObj;
+
+impl AuditWriteObj {
+    pub fn new(owner_id: NodeId, entry: AutoAcceptAuditEntry) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            owner_id,
+            allocation_id: entry.allocation_id,
+            document_type: entry.document_type.to_string(),
+            document_id: entry.document_id,
+            agreement_id: entry.agreement_id,
+            amount: entry.amount.into(),
+            decision: entry.decision.to_string(),
+            reason: entry.reason,
+            timestamp: entry.timestamp.naive_utc(),
+        }
+    }
+}
+
+impl TryFrom<AuditReadObj> for AutoAcceptAuditEntry {
+    type Error = strum::ParseError;
+
+    fn try_from(entry: AuditReadObj) -> Result<Self, Self::Error> {
+        Ok(Self {
+            document_type: AutoAcceptDocument::from_str(&entry.document_type)?,
+            document_id: entry.document_id,
+            agreement_id: entry.agreement_id,
+            allocation_id: entry.allocation_id,
+            amount: entry.amount.into(),
+            decision: AutoAcceptDecision::from_str(&entry.decision)?,
+            reason: entry.reason,
+            timestamp: Utc.from_utc_datetime(&entry.timestamp),
+        })
+    }
+}
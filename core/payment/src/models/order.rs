@@ -1,4 +1,5 @@
 use crate::schema::pay_order;
+use chrono::{NaiveDateTime, Utc};
 use ya_client_model::NodeId;
 use ya_core_model::payment::local::{PaymentTitle, SchedulePayment};
 use ya_persistence::types::BigDecimalField;
@@ -18,6 +19,7 @@ pub struct WriteObj {
     pub debit_note_id: Option<String>,
     pub allocation_id: String,
     pub is_paid: bool,
+    pub timestamp: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
@@ -59,6 +61,7 @@ impl WriteObj {
             debit_note_id,
             allocation_id: msg.allocation_id,
             is_paid: false,
+            timestamp: Some(Utc::now().naive_utc()),
         }
     }
 }
@@ -65,6 +65,44 @@ table! {
     }
 }
 
+table! {
+    pay_allocation_policy (allocation_id, owner_id) {
+        allocation_id -> Text,
+        owner_id -> Text,
+        max_per_provider -> Nullable<Text>,
+        max_per_hour -> Nullable<Text>,
+        max_per_day -> Nullable<Text>,
+        max_debit_note_increment -> Nullable<Text>,
+        enforce_price_model -> Bool,
+    }
+}
+
+table! {
+    pay_auto_accept_audit (id) {
+        id -> Text,
+        owner_id -> Text,
+        allocation_id -> Text,
+        document_type -> Text,
+        document_id -> Text,
+        agreement_id -> Text,
+        amount -> Text,
+        decision -> Text,
+        reason -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    pay_auto_accept_rule (allocation_id, owner_id) {
+        allocation_id -> Text,
+        owner_id -> Text,
+        price_tolerance -> Nullable<Text>,
+        time_tolerance -> Nullable<Text>,
+        max_total -> Nullable<Text>,
+        reject_invoices -> Bool,
+    }
+}
+
 table! {
     pay_debit_note (id, owner_id) {
         id -> Text,
@@ -175,6 +213,7 @@ table! {
         debit_note_id -> Nullable<Text>,
         allocation_id -> Text,
         is_paid -> Bool,
+        timestamp -> Nullable<Timestamp>,
     }
 }
 
@@ -206,6 +245,8 @@ table! {
 
 joinable!(pay_activity_payment -> pay_allocation (allocation_id));
 joinable!(pay_agreement_payment -> pay_allocation (allocation_id));
+joinable!(pay_allocation_policy -> pay_allocation (allocation_id));
+joinable!(pay_auto_accept_rule -> pay_allocation (allocation_id));
 joinable!(pay_debit_note -> pay_document_status (status));
 joinable!(pay_debit_note_event -> pay_event_type (event_type));
 joinable!(pay_invoice -> pay_document_status (status));
@@ -218,6 +259,9 @@ allow_tables_to_appear_in_same_query!(
     pay_agreement,
     pay_agreement_payment,
     pay_allocation,
+    pay_allocation_policy,
+    pay_auto_accept_audit,
+    pay_auto_accept_rule,
     pay_debit_note,
     pay_debit_note_event,
     pay_debit_note_event_read,
@@ -48,7 +48,6 @@ mod local {
     use super::*;
     use crate::dao::*;
     use chrono::DateTime;
-    use std::str::FromStr;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Instant;
     use std::{collections::BTreeMap, convert::TryInto};
@@ -80,6 +79,7 @@ mod local {
             .bind_with_processor(get_rpc_endpoints)
             .bind_with_processor(get_status)
             .bind_with_processor(get_invoice_stats)
+            .bind_with_processor(export_ledger)
             .bind_with_processor(get_accounts)
             .bind_with_processor(validate_allocation)
             .bind_with_processor(release_allocations)
@@ -242,10 +242,9 @@ mod local {
         } = msg;
 
         let (network2, network_details) = processor
-            .get_network(driver.to_string(), network.as_ref().map(|s| s.to_string()))
+            .get_network(driver.to_string(), network.clone())
             .await
             .map_err(GenericError::new)?;
-        let network2 = NetworkName::from_str(&network2).map_err(GenericError::new)?;
 
         let token = network_details.default_token.clone();
         let platform = match network_details.tokens.get(&token) {
@@ -262,7 +261,7 @@ mod local {
             .get_rpc_endpoints_info(
                 platform,
                 address.to_string(),
-                network.as_ref().map(|s| s.to_string()),
+                network,
                 verify,
                 resolve,
                 no_wait,
@@ -405,6 +404,17 @@ mod local {
         Ok(output_stats)
     }
 
+    async fn export_ledger(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        _caller: String,
+        msg: ExportLedger,
+    ) -> Result<LedgerPage, GenericError> {
+        crate::ledger::export(&db, msg)
+            .await
+            .map_err(GenericError::new)
+    }
+
     async fn validate_allocation(
         db: DbExecutor,
         processor: Arc<PaymentProcessor>,
@@ -822,6 +832,7 @@ mod public {
         }
 
         let node_id = *agreement.requestor_id();
+        let auto_accept = (db.clone(), debit_note_id.clone());
         match async move {
             db.as_dao::<AgreementDao>()
                 .create_if_not_exists(agreement, node_id, Role::Requestor)
@@ -841,7 +852,11 @@ mod public {
         }
         .await
         {
-            Ok(_) => Ok(Ack {}),
+            Ok(_) => {
+                let (db, debit_note_id) = auto_accept;
+                crate::api::auto_accept::debit_note_received(db, node_id, debit_note_id);
+                Ok(Ack {})
+            }
             Err(DbError::Query(e)) => Err(SendError::BadRequest(e)),
             Err(e) => Err(SendError::ServiceError(e.to_string())),
         }
@@ -906,10 +921,57 @@ mod public {
 
     async fn reject_debit_note(
         db: DbExecutor,
-        sender: String,
+        sender_id: String,
         msg: RejectDebitNote,
     ) -> Result<Ack, AcceptRejectError> {
-        unimplemented!() // TODO
+        let debit_note_id = msg.debit_note_id;
+        let rejection = msg.rejection;
+        let owner_id = msg.issuer_id;
+
+        log::debug!(
+            "Got RejectDebitNote [{}] from Node [{}].",
+            debit_note_id,
+            sender_id
+        );
+        counter!("payment.debit_notes.provider.rejected.call", 1);
+
+        let dao: DebitNoteDao = db.as_dao();
+        let debit_note: DebitNote = match dao.get(debit_note_id.clone(), owner_id).await {
+            Ok(Some(debit_note)) => debit_note,
+            Ok(None) => return Err(AcceptRejectError::ObjectNotFound),
+            Err(e) => return Err(AcceptRejectError::ServiceError(e.to_string())),
+        };
+
+        if sender_id != debit_note.recipient_id.to_string() {
+            return Err(AcceptRejectError::Forbidden);
+        }
+
+        match debit_note.status {
+            status @ DocumentStatus::Accepted
+            | status @ DocumentStatus::Settled
+            | status @ DocumentStatus::Cancelled => {
+                return Err(AcceptRejectError::BadRequest(format!(
+                    "Cannot reject {status:?} debit note"
+                )));
+            }
+            DocumentStatus::Rejected => return Ok(Ack {}),
+            _ => (),
+        }
+
+        match dao.reject(debit_note_id.clone(), owner_id, rejection).await {
+            Ok(_) => {
+                log::info!(
+                    "Node [{}] rejected DebitNote [{}] for Activity [{}].",
+                    sender_id,
+                    debit_note_id,
+                    debit_note.activity_id
+                );
+                counter!("payment.debit_notes.provider.rejected", 1);
+                Ok(Ack {})
+            }
+            Err(DbError::Query(e)) => Err(AcceptRejectError::BadRequest(e)),
+            Err(e) => Err(AcceptRejectError::ServiceError(e.to_string())),
+        }
     }
 
     async fn cancel_debit_note(
@@ -989,6 +1051,7 @@ mod public {
 
         let owner_id = *agreement.requestor_id();
         let sender_id = *agreement.provider_id();
+        let auto_accept = (db.clone(), invoice_id.clone());
         match async move {
             db.as_dao::<AgreementDao>()
                 .create_if_not_exists(agreement, owner_id, Role::Requestor)
@@ -1015,7 +1078,11 @@ mod public {
         }
         .await
         {
-            Ok(_) => Ok(Ack {}),
+            Ok(_) => {
+                let (db, invoice_id) = auto_accept;
+                crate::api::auto_accept::invoice_received(db, owner_id, invoice_id);
+                Ok(Ack {})
+            }
             Err(DbError::Query(e)) => Err(SendError::BadRequest(e)),
             Err(e) => Err(SendError::ServiceError(e.to_string())),
         }
@@ -84,6 +84,9 @@ async fn main() -> anyhow::Result<()> {
                         role: model::DEFAULT_ROLE.to_string(),
                         identity,
                         allow_origins: vec![],
+                        scopes: None,
+                        expires_at: None,
+                        key: None,
                     };
 
                     let app_key = bus::service(model::BUS_ID)
@@ -1,5 +1,6 @@
 pub mod dummy;
 pub mod ident;
+mod permissions;
 pub mod resolver;
 
 pub use crate::middleware::auth::ident::Identity;
@@ -7,7 +8,7 @@ pub use crate::middleware::auth::resolver::AppKeyCache;
 
 use actix_service::{Service, Transform};
 use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::error::{Error, ErrorUnauthorized, ParseError};
+use actix_web::error::{Error, ErrorForbidden, ErrorUnauthorized, ParseError};
 use actix_web::{web, HttpMessage};
 use actix_web_httpauth::headers::authorization::{Bearer, Scheme};
 use futures::future::{ok, Future, Ready};
@@ -95,6 +96,26 @@ where
         Box::pin(async move {
             match header {
                 Some(key) => match cache.get_appkey(&key) {
+                    Some(app_key) if app_key.is_expired() => {
+                        log::debug!(
+                            "{} {} Expired application key: {}",
+                            req.method(),
+                            req.path(),
+                            app_key.name,
+                        );
+                        Err(ErrorUnauthorized("Expired application key"))
+                    }
+                    Some(app_key) if !permissions::is_authorized(&app_key, &req) => {
+                        log::debug!(
+                            "{} {} Application key {} out of scope",
+                            req.method(),
+                            req.path(),
+                            app_key.name,
+                        );
+                        Err(ErrorForbidden(
+                            "Application key scope does not permit this request",
+                        ))
+                    }
                     Some(app_key) => {
                         req.extensions_mut().insert(Identity::from(app_key));
                         let fut = { service.borrow_mut().call(req) };
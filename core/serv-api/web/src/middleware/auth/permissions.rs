@@ -0,0 +1,235 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::http::{header, Method};
+
+use ya_core_model::appkey::{Access, AppKey};
+
+/// REST API path prefixes of services, which access can be granted with scopes.
+const SERVICE_PATHS: &[(&str, &str)] = &[
+    ("/market-api/", "market"),
+    ("/payment-api/", "payment"),
+    ("/activity-api/", "activity"),
+    ("/net-api/", "net"),
+    ("/gsb-api/", "gsb"),
+];
+
+/// Paths available to every valid app-key.
+const PUBLIC_PATHS: &[&str] = &["/me"];
+
+/// Checks whether app-key scopes allow the request.
+/// Endpoints outside of [`SERVICE_PATHS`] require an unrestricted key.
+pub(crate) fn is_authorized(app_key: &AppKey, req: &ServiceRequest) -> bool {
+    if app_key.scopes.is_none() || PUBLIC_PATHS.contains(&req.path()) {
+        return true;
+    }
+    match required_access(req) {
+        Some((service, access)) => app_key.grants(service, access),
+        None => false,
+    }
+}
+
+fn required_access(req: &ServiceRequest) -> Option<(&'static str, Access)> {
+    let path = req.path();
+    let (prefix, service) = SERVICE_PATHS
+        .iter()
+        .find(|(prefix, _)| path.starts_with(prefix))?;
+
+    // opening a WebSocket tunnel is never a read-only operation
+    let upgrade = req.headers().contains_key(header::UPGRADE);
+    let read = !upgrade && matches!(*req.method(), Method::GET | Method::HEAD);
+
+    let access = if *service == "activity" && !read && path[prefix.len()..].contains("/exec") {
+        Access::Exec
+    } else if read {
+        Access::Read
+    } else {
+        Access::Write
+    };
+    Some((service, access))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn app_key(scopes: Option<&[&str]>) -> AppKey {
+        AppKey {
+            name: "test".to_string(),
+            key: "key".to_string(),
+            role: "manager".to_string(),
+            identity: "0x0000000000000000000000000000000000000000"
+                .parse()
+                .unwrap(),
+            created_date: Default::default(),
+            allow_origins: vec![],
+            scopes: scopes.map(|scopes| scopes.iter().map(|s| s.parse().unwrap()).collect()),
+            expires_at: None,
+        }
+    }
+
+    fn request(method: Method, path: &str, upgrade: bool) -> ServiceRequest {
+        let mut req = TestRequest::default().method(method).uri(path);
+        if upgrade {
+            req = req.insert_header((header::UPGRADE, "websocket"));
+        }
+        req.to_srv_request()
+    }
+
+    #[test]
+    fn test_required_access() {
+        let cases = [
+            (
+                Method::GET,
+                "/market-api/v1/offers",
+                false,
+                Some(("market", Access::Read)),
+            ),
+            (
+                Method::HEAD,
+                "/payment-api/v1/allocations",
+                false,
+                Some(("payment", Access::Read)),
+            ),
+            (
+                Method::POST,
+                "/market-api/v1/offers",
+                false,
+                Some(("market", Access::Write)),
+            ),
+            (
+                Method::DELETE,
+                "/payment-api/v1/allocations/1",
+                false,
+                Some(("payment", Access::Write)),
+            ),
+            (
+                Method::POST,
+                "/activity-api/v1/activity/1/exec",
+                false,
+                Some(("activity", Access::Exec)),
+            ),
+            (
+                Method::GET,
+                "/activity-api/v1/activity/1/exec/2",
+                false,
+                Some(("activity", Access::Read)),
+            ),
+            (
+                Method::POST,
+                "/activity-api/v1/activity",
+                false,
+                Some(("activity", Access::Write)),
+            ),
+            (
+                Method::GET,
+                "/net-api/v2/vpn/net/1/raw/from/a/to/b",
+                true,
+                Some(("net", Access::Write)),
+            ),
+            (
+                Method::GET,
+                "/gsb-api/v1/services",
+                false,
+                Some(("gsb", Access::Read)),
+            ),
+            (Method::GET, "/version-api/v1/version", false, None),
+            (Method::GET, "/market-api", false, None),
+        ];
+
+        for (method, path, upgrade, expected) in cases {
+            let req = request(method.clone(), path, upgrade);
+            assert_eq!(required_access(&req), expected, "{method} {path}");
+        }
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let cases: &[(Option<&[&str]>, Method, &str, bool)] = &[
+            // unrestricted keys
+            (None, Method::POST, "/market-api/v1/offers", true),
+            (None, Method::GET, "/version-api/v1/version", true),
+            // read denials
+            (
+                Some(&["market:read"]),
+                Method::GET,
+                "/market-api/v1/offers",
+                true,
+            ),
+            (
+                Some(&["market:read"]),
+                Method::POST,
+                "/market-api/v1/offers",
+                false,
+            ),
+            (
+                Some(&["activity:exec"]),
+                Method::GET,
+                "/activity-api/v1/activity/1/exec/2",
+                false,
+            ),
+            // write implies read, but not exec
+            (
+                Some(&["payment:write"]),
+                Method::GET,
+                "/payment-api/v1/allocations",
+                true,
+            ),
+            (
+                Some(&["payment:write"]),
+                Method::POST,
+                "/payment-api/v1/allocations",
+                true,
+            ),
+            (
+                Some(&["activity:write"]),
+                Method::POST,
+                "/activity-api/v1/activity/1/exec",
+                false,
+            ),
+            (
+                Some(&["activity:exec"]),
+                Method::POST,
+                "/activity-api/v1/activity/1/exec",
+                true,
+            ),
+            // scope denials
+            (
+                Some(&["market:*"]),
+                Method::GET,
+                "/payment-api/v1/allocations",
+                false,
+            ),
+            (
+                Some(&["market:read", "payment:read"]),
+                Method::GET,
+                "/payment-api/v1/allocations",
+                true,
+            ),
+            (Some(&["*:read"]), Method::GET, "/net-api/v1/net", true),
+            (
+                Some(&["*:read"]),
+                Method::GET,
+                "/net-api/v2/vpn/net/1/raw/from/a/to/b",
+                false,
+            ),
+            // endpoints outside of scoped services require an unrestricted key
+            (
+                Some(&["*:*"]),
+                Method::GET,
+                "/version-api/v1/version",
+                false,
+            ),
+            (Some(&[]), Method::GET, "/me", true),
+        ];
+
+        for (scopes, method, path, expected) in cases {
+            let upgrade = path.contains("/raw/");
+            let req = request(method.clone(), path, upgrade);
+            assert_eq!(
+                is_authorized(&app_key(*scopes), &req),
+                *expected,
+                "{scopes:?} {method} {path}"
+            );
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use std::net::SocketAddr;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Config {
+    /// Address of the SOCKS5 proxy to requestor's networks. Proxy is disabled when not set.
+    #[structopt(env = "YA_VPN_SOCKS5_ADDR")]
+    pub socks5_addr: Option<SocketAddr>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Config, structopt::clap::Error> {
+        // Empty command line arguments, because we want to use ENV fallback
+        // or default values if ENV variables are not set.
+        Config::from_iter_safe(&[""])
+    }
+}
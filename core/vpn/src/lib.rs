@@ -1,7 +1,9 @@
+mod config;
 mod message;
 mod network;
 mod requestor;
 mod service;
+mod socks;
 
 pub use self::service::VpnService;
 
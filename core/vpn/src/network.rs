@@ -1,5 +1,6 @@
 use std::collections::{BTreeSet, HashMap};
 use std::net::IpAddr;
+use std::ops::Range;
 use std::rc::Rc;
 use std::str::FromStr;
 
@@ -7,6 +8,7 @@ use actix::prelude::*;
 use futures::channel::oneshot::Canceled;
 use futures::channel::{mpsc, oneshot};
 use futures::{future, future::BoxFuture, Future, FutureExt, SinkExt, StreamExt, TryFutureExt};
+use rand::Rng;
 use smoltcp::iface::Route;
 use smoltcp::wire::{EthernetAddress, HardwareAddress, IpAddress, IpCidr, IpEndpoint};
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -24,10 +26,15 @@ use ya_service_bus::typed::{self, Endpoint};
 use ya_service_bus::{actix_rpc, RpcEndpoint, RpcEnvelope, RpcRawCall};
 use ya_utils_networking::vpn::common::{to_ip, to_net};
 use ya_utils_networking::vpn::stack::{
-    self as net, EgressReceiver, IngressEvent, IngressReceiver, StackConfig,
+    self as net, connection::ConnectionMeta, EgressReceiver, IngressEvent, IngressReceiver,
+    StackConfig,
 };
 use ya_utils_networking::vpn::*;
 
+/// Ephemeral port range for UDP sockets bound on the requestor's address.
+const UDP_PORTS: Range<u16> = 49152..65535;
+const UDP_BIND_ATTEMPTS: usize = 16;
+
 pub struct VpnSupervisor {
     networks: HashMap<String, Addr<Vpn>>,
     blueprints: HashMap<String, ya_client_model::net::Network>,
@@ -414,12 +421,22 @@ impl Handler<RemoveNode> for Vpn {
 impl Handler<Connect> for Vpn {
     type Result = ActorResponse<Self, Result<UserConnection>>;
 
-    fn handle(&mut self, msg: Connect, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: Connect, ctx: &mut Self::Context) -> Self::Result {
         let remote = match to_ip(&msg.address) {
             Ok(ip) => IpEndpoint::new(ip.into(), msg.port),
             Err(err) => return ActorResponse::reply(Err(err)),
         };
 
+        match msg.protocol {
+            Protocol::Tcp => self.connect_tcp(remote),
+            Protocol::Udp => ActorResponse::reply(self.connect_udp(remote, ctx)),
+            other => ActorResponse::reply(Err(Error::ProtocolNotSupported(other.to_string()))),
+        }
+    }
+}
+
+impl Vpn {
+    fn connect_tcp(&mut self, remote: IpEndpoint) -> ActorResponse<Self, Result<UserConnection>> {
         let vpn_id = self.vpn.id();
         log::info!("VPN {vpn_id}: connecting to {remote:?}");
 
@@ -433,25 +450,76 @@ impl Handler<Connect> for Vpn {
                 log::info!("VPN {id}: connected to {remote:?}");
                 let vpn = ctx.address().recipient();
 
-                let (tx, rx) = mpsc::channel(1);
-
-                this.connections.insert(
-                    stack_connection.meta.into(),
-                    InternalConnection {
-                        stack_connection,
-                        ingress_tx: tx,
-                    },
-                );
-
-                Ok(UserConnection {
-                    vpn,
-                    rx,
-                    stack_connection,
-                })
+                Ok(this.register_connection(stack_connection, vpn))
             });
 
         ActorResponse::r#async(fut)
     }
+
+    /// UDP is connectionless, so a socket is bound to an ephemeral port on the
+    /// requestor's address. Datagrams received on that socket from `remote`
+    /// are forwarded to the user.
+    fn connect_udp(
+        &mut self,
+        remote: IpEndpoint,
+        ctx: &mut <Self as Actor>::Context,
+    ) -> Result<UserConnection> {
+        let vpn_id = self.vpn.id();
+        log::info!("VPN {vpn_id}: binding UDP socket for {remote:?}");
+
+        let local_ip = self
+            .stack_network
+            .stack
+            .addresses()
+            .into_iter()
+            .map(|cidr| cidr.address())
+            .find(|ip| std::mem::discriminant(ip) == std::mem::discriminant(&remote.addr))
+            .ok_or_else(|| {
+                Error::ConnectionError(format!("no local address to reach {remote:?}"))
+            })?;
+
+        let mut rng = rand::thread_rng();
+        let local = (0..UDP_BIND_ATTEMPTS)
+            .map(|_| IpEndpoint::new(local_ip, rng.gen_range(UDP_PORTS.start, UDP_PORTS.end)))
+            .find(|local| {
+                self.stack_network
+                    .get_bound(Protocol::Udp, SocketEndpoint::Ip(*local))
+                    .is_none()
+            })
+            .ok_or_else(|| Error::ConnectionError("no free UDP port".to_string()))?;
+
+        let handle = self.stack_network.bind(Protocol::Udp, local)?;
+        let stack_connection = stack::Connection {
+            handle,
+            meta: ConnectionMeta::new(Protocol::Udp, local, remote),
+        };
+        log::info!("VPN {vpn_id}: bound {local:?} for {remote:?}");
+
+        let vpn = ctx.address().recipient();
+        Ok(self.register_connection(stack_connection, vpn))
+    }
+
+    fn register_connection(
+        &mut self,
+        stack_connection: stack::Connection,
+        vpn: Recipient<Packet>,
+    ) -> UserConnection {
+        let (tx, rx) = mpsc::channel(1);
+
+        self.connections.insert(
+            stack_connection.meta.into(),
+            InternalConnection {
+                stack_connection,
+                ingress_tx: tx,
+            },
+        );
+
+        UserConnection {
+            vpn,
+            rx,
+            stack_connection,
+        }
+    }
 }
 
 impl Handler<Disconnect> for Vpn {
@@ -816,9 +884,100 @@ fn create_stack_network(
 
 #[cfg(test)]
 mod tests {
-    use crate::network::VpnSupervisor;
+    use std::time::Duration;
+
+    use actix::prelude::*;
+    use futures::channel::mpsc;
+    use futures::StreamExt;
+    use smoltcp::phy::ChecksumCapabilities;
+    use smoltcp::wire::{
+        EthernetAddress, EthernetFrame, EthernetProtocol, EthernetRepr, IpAddress, IpCidr,
+        IpEndpoint, IpProtocol, Ipv4Packet, Ipv4Repr, UdpPacket, UdpRepr,
+    };
+
+    use crate::message::{AddNode, Connect, Packet};
+    use crate::network::{create_ethernet_addr, VpnSupervisor};
     use ya_client_model::net::NewNetwork;
+    use ya_core_model::activity::VpnPacket;
     use ya_core_model::NodeId;
+    use ya_service_bus::{actix_rpc, RpcEnvelope, RpcRawCall};
+    use ya_utils_networking::vpn::Protocol;
+
+    /// Collects frames sent to a remote node.
+    struct EgressCapture(mpsc::UnboundedSender<Vec<u8>>);
+
+    impl Actor for EgressCapture {
+        type Context = Context<Self>;
+    }
+
+    impl Handler<RpcRawCall> for EgressCapture {
+        type Result = Result<Vec<u8>, ya_service_bus::Error>;
+
+        fn handle(&mut self, msg: RpcRawCall, _: &mut Self::Context) -> Self::Result {
+            let _ = self.0.unbounded_send(msg.body);
+            Ok(Vec::new())
+        }
+    }
+
+    /// Extracts source, destination and payload of a UDP datagram from an Ethernet frame.
+    fn parse_udp_frame(frame: &[u8]) -> Option<(IpEndpoint, IpEndpoint, Vec<u8>)> {
+        let frame = EthernetFrame::new_checked(frame).ok()?;
+        if frame.ethertype() != EthernetProtocol::Ipv4 {
+            return None;
+        }
+        let packet = Ipv4Packet::new_checked(frame.payload()).ok()?;
+        if packet.protocol() != IpProtocol::Udp {
+            return None;
+        }
+        let datagram = UdpPacket::new_checked(packet.payload()).ok()?;
+        Some((
+            IpEndpoint::new(packet.src_addr().into(), datagram.src_port()),
+            IpEndpoint::new(packet.dst_addr().into(), datagram.dst_port()),
+            datagram.payload().to_vec(),
+        ))
+    }
+
+    /// Builds an Ethernet frame carrying a single UDP datagram.
+    fn udp_frame(src: IpEndpoint, dst: IpEndpoint, payload: &[u8]) -> Vec<u8> {
+        let (src_addr, dst_addr) = match (src.addr, dst.addr) {
+            (IpAddress::Ipv4(src), IpAddress::Ipv4(dst)) => (src, dst),
+            _ => panic!("IPv4 endpoints expected"),
+        };
+        let checksum = ChecksumCapabilities::default();
+
+        let udp = UdpRepr {
+            src_port: src.port,
+            dst_port: dst.port,
+        };
+        let ip = Ipv4Repr {
+            src_addr,
+            dst_addr,
+            protocol: IpProtocol::Udp,
+            payload_len: udp.header_len() + payload.len(),
+            hop_limit: 64,
+        };
+        let eth = EthernetRepr {
+            src_addr: EthernetAddress([0xA0, 0x13, 0, 0, 0, 0]),
+            dst_addr: create_ethernet_addr(IpCidr::new(dst.addr, 0)).unwrap(),
+            ethertype: EthernetProtocol::Ipv4,
+        };
+
+        let mut buffer = vec![0u8; eth.buffer_len() + ip.buffer_len() + ip.payload_len];
+        let mut frame = EthernetFrame::new_unchecked(&mut buffer);
+        eth.emit(&mut frame);
+        let mut packet = Ipv4Packet::new_unchecked(frame.payload_mut());
+        ip.emit(&mut packet, &checksum);
+        let mut datagram = UdpPacket::new_unchecked(packet.payload_mut());
+        udp.emit(
+            &mut datagram,
+            &src.addr,
+            &dst.addr,
+            payload.len(),
+            |buf| buf.copy_from_slice(payload),
+            &checksum,
+        );
+        buffer
+    }
 
     #[actix_rt::test]
     async fn create_remove_network() -> anyhow::Result<()> {
@@ -883,4 +1042,86 @@ mod tests {
         assert!(supervisor.get_network(&node_id, &network2.id).is_ok());
         Ok(())
     }
+
+    #[actix_rt::test]
+    async fn udp_datagrams() -> anyhow::Result<()> {
+        let node_id = NodeId::default();
+
+        let mut supervisor = VpnSupervisor::default();
+        let network = supervisor
+            .create_network(
+                node_id,
+                NewNetwork {
+                    ip: "10.0.0.0".to_string(),
+                    mask: None,
+                    gateway: None,
+                },
+            )
+            .await?;
+        let vpn = supervisor.get_network(&node_id, &network.id)?;
+
+        let remote_id = "0x0000000000000000000000000000000000000002";
+        vpn.send(AddNode {
+            id: remote_id.to_string(),
+            address: "10.0.0.2".to_string(),
+        })
+        .await??;
+        let (egress_tx, mut egress_rx) = mpsc::unbounded();
+        actix_rpc::bind_raw(
+            &format!("/udp/net/{}/vpn/{}/raw", remote_id, network.id),
+            EgressCapture(egress_tx).start().recipient(),
+        );
+
+        let mut conn = vpn
+            .send(Connect {
+                protocol: Protocol::Udp,
+                address: "10.0.0.2".to_string(),
+                port: 53,
+            })
+            .await??;
+
+        let meta = conn.stack_connection.meta;
+        let remote = IpEndpoint::new(IpAddress::v4(10, 0, 0, 2), 53);
+        assert_eq!(meta.protocol, Protocol::Udp);
+        assert_eq!(meta.remote, remote);
+        assert_eq!(meta.local.addr, IpAddress::v4(10, 0, 0, 1));
+
+        // datagrams from the remote are forwarded to the user as they are
+        let frame = udp_frame(remote, meta.local, b"datagram");
+        vpn.send(RpcEnvelope::local(VpnPacket(frame))).await??;
+        let received = tokio::time::timeout(Duration::from_secs(5), conn.rx.next()).await?;
+        assert_eq!(received, Some(b"datagram".to_vec()));
+
+        // datagrams from other hosts are not
+        let other = IpEndpoint::new(IpAddress::v4(10, 0, 0, 3), 53);
+        let frame = udp_frame(other, meta.local, b"other");
+        vpn.send(RpcEnvelope::local(VpnPacket(frame))).await??;
+        let received = tokio::time::timeout(Duration::from_millis(500), conn.rx.next()).await;
+        assert!(received.is_err());
+
+        // datagrams from the user are sent to the remote node from the bound socket
+        conn.vpn
+            .send(Packet {
+                data: b"reply".to_vec(),
+                meta,
+            })
+            .await??;
+        let egress = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(frame) = egress_rx.next().await {
+                // skip frames other than UDP, e.g. ARP requests
+                if let Some(datagram) = parse_udp_frame(&frame) {
+                    return Some(datagram);
+                }
+            }
+            None
+        })
+        .await?;
+        assert_eq!(egress, Some((meta.local, remote, b"reply".to_vec())));
+
+        supervisor
+            .remove_network(&node_id, &network.id)?
+            .await
+            .unwrap();
+        Ok(())
+    }
 }
@@ -51,6 +51,7 @@ fn vpn_web_scope(path: &str) -> actix_web::Scope {
         .service(add_node)
         .service(remove_node)
         .service(connect_tcp)
+        .service(connect_udp)
 }
 
 /// Retrieves existing virtual private networks.
@@ -207,6 +208,30 @@ async fn connect_tcp(
     req: HttpRequest,
     stream: web::Payload,
     identity: Identity,
+) -> Result<HttpResponse> {
+    connect(vpn_sup, path, Protocol::Tcp, req, stream, identity).await
+}
+
+/// Exchanges UDP datagrams via WebSockets with the destination address.
+/// Each WebSocket message carries a single datagram.
+#[actix_web::get("/net/{net_id}/udp/{ip}/{port}")]
+async fn connect_udp(
+    vpn_sup: web::Data<Arc<Mutex<VpnSupervisor>>>,
+    path: web::Path<PathConnect>,
+    req: HttpRequest,
+    stream: web::Payload,
+    identity: Identity,
+) -> Result<HttpResponse> {
+    connect(vpn_sup, path, Protocol::Udp, req, stream, identity).await
+}
+
+async fn connect(
+    vpn_sup: web::Data<Arc<Mutex<VpnSupervisor>>>,
+    path: web::Path<PathConnect>,
+    protocol: Protocol,
+    req: HttpRequest,
+    stream: web::Payload,
+    identity: Identity,
 ) -> Result<HttpResponse> {
     let path = path.into_inner();
     let vpn = {
@@ -215,7 +240,7 @@ async fn connect_tcp(
     };
     let conn = vpn
         .send(Connect {
-            protocol: Protocol::Tcp,
+            protocol,
             address: path.ip.to_string(),
             port: path.port,
         })
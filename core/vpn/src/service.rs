@@ -1,3 +1,4 @@
+use crate::config::Config;
 use crate::network::VpnSupervisor;
 use futures::lock::Mutex;
 use std::sync::Arc;
@@ -12,7 +13,10 @@ pub struct VpnService;
 
 impl VpnService {
     pub async fn gsb<Context: Provider<Self, DbExecutor>>(_: &Context) -> anyhow::Result<()> {
-        let _vpn = VPN_SUPERVISOR.clone();
+        let vpn = VPN_SUPERVISOR.clone();
+        if let Some(addr) = Config::from_env()?.socks5_addr {
+            crate::socks::listen(addr, vpn).await?;
+        }
         Ok(())
     }
 
@@ -0,0 +1,450 @@
+//! SOCKS5 proxy exposing requestor's virtual private networks to local applications.
+//!
+//! Clients authenticate with username / password (RFC 1929): username is the network id,
+//! password is an app-key of the network owner. Only the `CONNECT` command to IP addresses
+//! within the network is supported.
+use crate::message::*;
+use crate::network::{Vpn, VpnSupervisor};
+use actix::prelude::*;
+use futures::future::{self, Either};
+use futures::lock::Mutex;
+use futures::StreamExt;
+use std::cell::Cell;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use ya_core_model::appkey::{self, Access};
+use ya_service_bus::{typed as bus, RpcEndpoint};
+use ya_utils_networking::vpn::{Error as VpnError, Protocol};
+
+const SOCKS_VERSION: u8 = 0x05;
+const AUTH_VERSION: u8 = 0x01;
+
+const METHOD_USER_PASS: u8 = 0x02;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xFF;
+
+const CMD_CONNECT: u8 = 0x01;
+
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+const REPLY_SUCCEEDED: u8 = 0x00;
+const REPLY_FAILURE: u8 = 0x01;
+const REPLY_NOT_ALLOWED: u8 = 0x02;
+const REPLY_HOST_UNREACHABLE: u8 = 0x04;
+const REPLY_CONNECTION_REFUSED: u8 = 0x05;
+const REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+const REPLY_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+const READ_BUFFER_SIZE: usize = 16384;
+/// How long responses are still relayed after the client stopped sending,
+/// counted from the last received data.
+const DRAIN_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Starts accepting SOCKS5 connections on `addr`.
+pub(crate) async fn listen(addr: SocketAddr, vpn_sup: Arc<Mutex<VpnSupervisor>>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("VPN SOCKS5 proxy listening on {addr}");
+
+    tokio::task::spawn_local(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    log::warn!("VPN SOCKS5 proxy: unable to accept connection: {e}");
+                    continue;
+                }
+            };
+            let vpn_sup = vpn_sup.clone();
+            tokio::task::spawn_local(async move {
+                if let Err(e) = serve(stream, vpn_sup).await {
+                    log::debug!("VPN SOCKS5 proxy: connection from {peer} closed: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+async fn serve(mut stream: TcpStream, vpn_sup: Arc<Mutex<VpnSupervisor>>) -> Result<(), Error> {
+    negotiate_method(&mut stream).await?;
+
+    let credentials = read_credentials(&mut stream).await?;
+    let vpn = match authenticate(&vpn_sup, &credentials).await {
+        Ok(vpn) => {
+            stream.write_all(&[AUTH_VERSION, 0x00]).await?;
+            vpn
+        }
+        Err(e) => {
+            stream.write_all(&[AUTH_VERSION, 0x01]).await?;
+            return Err(e);
+        }
+    };
+
+    let conn = match connect(&mut stream, &vpn).await {
+        Ok(conn) => {
+            reply(&mut stream, REPLY_SUCCEEDED).await?;
+            conn
+        }
+        Err(e) => {
+            reply(&mut stream, e.reply_code()).await?;
+            return Err(e);
+        }
+    };
+
+    let meta = conn.stack_connection.meta;
+    log::debug!(
+        "VPN SOCKS5 proxy: connected to {:?} in network {}",
+        meta.remote,
+        credentials.net_id
+    );
+
+    let (mut reader, mut writer) = stream.into_split();
+    let (packet_tx, mut rx) = (conn.vpn, conn.rx);
+
+    let egress = async move {
+        let mut buf = vec![0u8; READ_BUFFER_SIZE];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                return Ok::<_, Error>(());
+            }
+            let data = buf[..n].to_vec();
+            packet_tx.send(Packet { data, meta }).await??;
+        }
+    };
+    let last_received = Rc::new(Cell::new(Instant::now()));
+    let received = last_received.clone();
+    let ingress = async move {
+        while let Some(data) = rx.next().await {
+            received.set(Instant::now());
+            writer.write_all(&data).await?;
+        }
+        writer.shutdown().await?;
+        Ok::<_, Error>(())
+    };
+
+    futures::pin_mut!(egress, ingress);
+    let result = match future::select(egress, ingress).await {
+        // Client half-closed the connection and still waits for the response.
+        Either::Left((Ok(()), mut ingress)) => loop {
+            let deadline = last_received.get() + DRAIN_IDLE_TIMEOUT;
+            match tokio::time::timeout_at(deadline.into(), &mut ingress).await {
+                Ok(result) => break result,
+                Err(_) if last_received.get() + DRAIN_IDLE_TIMEOUT <= Instant::now() => {
+                    break Ok(())
+                }
+                Err(_) => continue,
+            }
+        },
+        Either::Left((result, _)) | Either::Right((result, _)) => result,
+    };
+    vpn.do_send(Disconnect::new(meta.into(), DisconnectReason::SinkClosed));
+    result
+}
+
+async fn negotiate_method<S>(stream: &mut S) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let version = stream.read_u8().await?;
+    if version != SOCKS_VERSION {
+        return Err(Error::Version(version));
+    }
+    let n = stream.read_u8().await? as usize;
+    let mut methods = vec![0u8; n];
+    stream.read_exact(&mut methods).await?;
+
+    if !methods.contains(&METHOD_USER_PASS) {
+        stream
+            .write_all(&[SOCKS_VERSION, METHOD_NONE_ACCEPTABLE])
+            .await?;
+        return Err(Error::AuthMethod);
+    }
+    stream.write_all(&[SOCKS_VERSION, METHOD_USER_PASS]).await?;
+    Ok(())
+}
+
+struct Credentials {
+    net_id: String,
+    app_key: String,
+}
+
+async fn read_credentials<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Credentials, Error> {
+    let version = stream.read_u8().await?;
+    if version != AUTH_VERSION {
+        return Err(Error::Version(version));
+    }
+    let net_id = read_string(stream).await?;
+    let app_key = read_string(stream).await?;
+    Ok(Credentials { net_id, app_key })
+}
+
+async fn read_string<S: AsyncRead + Unpin>(stream: &mut S) -> Result<String, Error> {
+    let len = stream.read_u8().await? as usize;
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+}
+
+async fn authenticate(
+    vpn_sup: &Mutex<VpnSupervisor>,
+    credentials: &Credentials,
+) -> Result<Addr<Vpn>, Error> {
+    let app_key = bus::service(appkey::BUS_ID)
+        .send(appkey::Get::with_key(credentials.app_key.clone()))
+        .await
+        .map_err(|_| Error::Auth)?
+        .map_err(|_| Error::Auth)?;
+
+    if app_key.is_expired() || !app_key.grants("net", Access::Write) {
+        return Err(Error::Auth);
+    }
+
+    let supervisor = vpn_sup.lock().await;
+    supervisor
+        .get_network(&app_key.identity, &credentials.net_id)
+        .map_err(|_| Error::Auth)
+}
+
+async fn connect<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    vpn: &Addr<Vpn>,
+) -> Result<UserConnection, Error> {
+    let (ip, port) = read_request(stream).await?;
+    let conn = vpn
+        .send(Connect {
+            protocol: Protocol::Tcp,
+            address: ip.to_string(),
+            port,
+        })
+        .await??;
+    Ok(conn)
+}
+
+async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Result<(IpAddr, u16), Error> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+    let [version, command, _, address_type] = header;
+    if version != SOCKS_VERSION {
+        return Err(Error::Version(version));
+    }
+
+    let ip = match address_type {
+        ATYP_IPV4 => {
+            let mut octets = [0u8; 4];
+            stream.read_exact(&mut octets).await?;
+            Some(IpAddr::from(Ipv4Addr::from(octets)))
+        }
+        ATYP_IPV6 => {
+            let mut octets = [0u8; 16];
+            stream.read_exact(&mut octets).await?;
+            Some(IpAddr::from(Ipv6Addr::from(octets)))
+        }
+        // There is no name resolution within the network. Clients resolving names
+        // on the proxy side (e.g. `socks5h://`) pass addresses as domain names.
+        ATYP_DOMAIN => read_string(stream).await?.parse().ok(),
+        other => return Err(Error::AddressType(other)),
+    };
+    let port = stream.read_u16().await?;
+
+    if command != CMD_CONNECT {
+        return Err(Error::Command(command));
+    }
+    ip.map(|ip| (ip, port))
+        .ok_or(Error::AddressType(address_type))
+}
+
+async fn reply<S: AsyncWrite + Unpin>(stream: &mut S, code: u8) -> io::Result<()> {
+    // bound address is meaningless outside of the network
+    let reply = [SOCKS_VERSION, code, 0x00, ATYP_IPV4, 0, 0, 0, 0, 0, 0];
+    stream.write_all(&reply).await
+}
+
+#[derive(thiserror::Error, Debug)]
+enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("unsupported protocol version: {0}")]
+    Version(u8),
+    #[error("no acceptable authentication method")]
+    AuthMethod,
+    #[error("authentication failed")]
+    Auth,
+    #[error("unsupported command: {0}")]
+    Command(u8),
+    #[error("unsupported address type: {0}")]
+    AddressType(u8),
+    #[error("VPN communication error: {0}")]
+    Channel(#[from] MailboxError),
+    #[error(transparent)]
+    Vpn(#[from] VpnError),
+}
+
+impl Error {
+    fn reply_code(&self) -> u8 {
+        match self {
+            Self::Command(_) => REPLY_COMMAND_NOT_SUPPORTED,
+            Self::AddressType(_) => REPLY_ADDRESS_NOT_SUPPORTED,
+            Self::Vpn(VpnError::Forbidden) => REPLY_NOT_ALLOWED,
+            Self::Vpn(VpnError::ConnectionTimeout) => REPLY_HOST_UNREACHABLE,
+            Self::Vpn(VpnError::ConnectionError(_)) => REPLY_CONNECTION_REFUSED,
+            _ => REPLY_FAILURE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+    use ya_client_model::net::NewNetwork;
+    use ya_core_model::NodeId;
+
+    const APP_KEY: &str = "socks-key";
+    const READ_ONLY_APP_KEY: &str = "socks-read-only-key";
+    const EXPIRED_APP_KEY: &str = "socks-expired-key";
+
+    fn app_key(key: &str, identity: NodeId) -> appkey::AppKey {
+        appkey::AppKey {
+            name: key.to_string(),
+            key: key.to_string(),
+            role: "manager".to_string(),
+            identity,
+            created_date: Default::default(),
+            allow_origins: vec![],
+            scopes: match key {
+                READ_ONLY_APP_KEY => Some(vec!["net:read".parse().unwrap()]),
+                _ => Some(vec!["net:write".parse().unwrap()]),
+            },
+            expires_at: match key {
+                EXPIRED_APP_KEY => Some(Default::default()),
+                _ => None,
+            },
+        }
+    }
+
+    fn credentials(net_id: &str, app_key: &str) -> Credentials {
+        Credentials {
+            net_id: net_id.to_string(),
+            app_key: app_key.to_string(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn negotiate_user_pass_method() {
+        let (mut client, mut server) = duplex(64);
+        client.write_all(&[5, 2, 0x00, 0x02]).await.unwrap();
+        negotiate_method(&mut server).await.unwrap();
+        let mut response = [0u8; 2];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(response, [SOCKS_VERSION, METHOD_USER_PASS]);
+
+        // no authentication is not acceptable
+        let (mut client, mut server) = duplex(64);
+        client.write_all(&[5, 1, 0x00]).await.unwrap();
+        let err = negotiate_method(&mut server).await.unwrap_err();
+        assert!(matches!(err, Error::AuthMethod));
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(response, [SOCKS_VERSION, METHOD_NONE_ACCEPTABLE]);
+    }
+
+    #[actix_rt::test]
+    async fn parse_credentials() {
+        let mut request: &[u8] = &[1, 3, b'n', b'e', b't', 3, b'k', b'e', b'y'];
+        let credentials = read_credentials(&mut request).await.unwrap();
+        assert_eq!(credentials.net_id, "net");
+        assert_eq!(credentials.app_key, "key");
+
+        let mut request: &[u8] = &[5, 3, b'n', b'e', b't', 3, b'k', b'e', b'y'];
+        let err = read_credentials(&mut request).await.unwrap_err();
+        assert!(matches!(err, Error::Version(5)));
+    }
+
+    #[actix_rt::test]
+    async fn authenticate_app_key() -> anyhow::Result<()> {
+        let owner: NodeId = "0x0000000000000000000000000000000000000001".parse()?;
+        let other: NodeId = "0x0000000000000000000000000000000000000002".parse()?;
+
+        let _ = bus::bind(appkey::BUS_ID, move |get: appkey::Get| async move {
+            match get.key.as_str() {
+                APP_KEY | READ_ONLY_APP_KEY | EXPIRED_APP_KEY => Ok(app_key(&get.key, owner)),
+                "socks-other-key" => Ok(app_key(&get.key, other)),
+                _ => Err(appkey::Error::internal("not found")),
+            }
+        });
+
+        let mut supervisor = VpnSupervisor::default();
+        let network = supervisor
+            .create_network(
+                owner,
+                NewNetwork {
+                    ip: "10.0.0.0".to_string(),
+                    mask: None,
+                    gateway: None,
+                },
+            )
+            .await?;
+        let vpn_sup = Mutex::new(supervisor);
+
+        assert!(authenticate(&vpn_sup, &credentials(&network.id, APP_KEY))
+            .await
+            .is_ok());
+
+        let denied = [
+            // unknown network
+            credentials("unknown", APP_KEY),
+            // unknown app-key
+            credentials(&network.id, "unknown"),
+            // app-key of another identity
+            credentials(&network.id, "socks-other-key"),
+            // app-key without write access to the network
+            credentials(&network.id, READ_ONLY_APP_KEY),
+            credentials(&network.id, EXPIRED_APP_KEY),
+        ];
+        for credentials in denied.iter() {
+            let result = authenticate(&vpn_sup, credentials).await;
+            assert!(
+                matches!(result, Err(Error::Auth)),
+                "{} / {}",
+                credentials.net_id,
+                credentials.app_key
+            );
+        }
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn parse_request() {
+        let mut request: &[u8] = &[5, 1, 0, 1, 10, 0, 0, 2, 0, 22];
+        let (ip, port) = read_request(&mut request).await.unwrap();
+        assert_eq!(ip, IpAddr::from([10, 0, 0, 2]));
+        assert_eq!(port, 22);
+
+        let mut request: &[u8] = &[
+            5, 1, 0, 3, 8, b'1', b'0', b'.', b'0', b'.', b'0', b'.', b'3', 0x1F, 0x90,
+        ];
+        let (ip, port) = read_request(&mut request).await.unwrap();
+        assert_eq!(ip, IpAddr::from([10, 0, 0, 3]));
+        assert_eq!(port, 8080);
+    }
+
+    #[actix_rt::test]
+    async fn reject_unsupported_request() {
+        let mut request: &[u8] = &[
+            5, 1, 0, 3, 7, b'a', b'.', b'g', b'o', b'l', b'e', b'm', 0, 80,
+        ];
+        let err = read_request(&mut request).await.unwrap_err();
+        assert_eq!(err.reply_code(), REPLY_ADDRESS_NOT_SUPPORTED);
+
+        // BIND
+        let mut request: &[u8] = &[5, 2, 0, 1, 10, 0, 0, 2, 0, 22];
+        let err = read_request(&mut request).await.unwrap_err();
+        assert_eq!(err.reply_code(), REPLY_COMMAND_NOT_SUPPORTED);
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 
 use crate::error::{self, CounterError};
@@ -6,6 +7,12 @@ use crate::error::{self, CounterError};
 pub type Result<T> = std::result::Result<T, error::CounterError>;
 pub type CounterData = f64;
 
+static NETWORK_BYTES: AtomicU64 = AtomicU64::new(0);
+static INET_EGRESS_BYTES: AtomicU64 = AtomicU64::new(0);
+static INET_INGRESS_BYTES: AtomicU64 = AtomicU64::new(0);
+
+const GIB: f64 = 1024. * 1024. * 1024.;
+
 #[derive(Clone, Debug)]
 pub enum CounterReport {
     Frame(CounterData),
@@ -47,3 +54,73 @@ impl Counter for TimeCounter {
         self.frame()
     }
 }
+
+/// Bytes sent and received over activity networks, in GiB.
+#[derive(Default)]
+pub struct NetworkCounter {}
+
+impl NetworkCounter {
+    pub const ID: &'static str = "golem.usage.network_gib";
+
+    /// Accounts network traffic of the current process.
+    #[inline]
+    pub fn record(bytes: usize) {
+        NETWORK_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+impl Counter for NetworkCounter {
+    fn frame(&mut self) -> Result<CounterData> {
+        Ok(NETWORK_BYTES.load(Ordering::Relaxed) as CounterData / GIB)
+    }
+
+    fn peak(&mut self) -> Result<CounterData> {
+        self.frame()
+    }
+}
+
+/// Bytes sent to the internet by the outbound network proxy, in GiB.
+#[derive(Default)]
+pub struct InetEgressCounter {}
+
+impl InetEgressCounter {
+    pub const ID: &'static str = "golem.usage.inet_egress_gib";
+
+    #[inline]
+    pub fn record(bytes: usize) {
+        INET_EGRESS_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+impl Counter for InetEgressCounter {
+    fn frame(&mut self) -> Result<CounterData> {
+        Ok(INET_EGRESS_BYTES.load(Ordering::Relaxed) as CounterData / GIB)
+    }
+
+    fn peak(&mut self) -> Result<CounterData> {
+        self.frame()
+    }
+}
+
+/// Bytes received from the internet by the outbound network proxy, in GiB.
+#[derive(Default)]
+pub struct InetIngressCounter {}
+
+impl InetIngressCounter {
+    pub const ID: &'static str = "golem.usage.inet_ingress_gib";
+
+    #[inline]
+    pub fn record(bytes: usize) {
+        INET_INGRESS_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+impl Counter for InetIngressCounter {
+    fn frame(&mut self) -> Result<CounterData> {
+        Ok(INET_INGRESS_BYTES.load(Ordering::Relaxed) as CounterData / GIB)
+    }
+
+    fn peak(&mut self) -> Result<CounterData> {
+        self.frame()
+    }
+}
@@ -7,6 +7,8 @@ pub mod service;
 mod counters;
 
 pub use crate::counters::*;
+#[cfg(all(feature = "os", target_os = "linux"))]
+pub use crate::os::cgroup::*;
 #[cfg(feature = "os")]
 pub use crate::os::counters::*;
 
@@ -0,0 +1,490 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::counters::{Counter, CounterData};
+use crate::error::CounterError;
+use crate::os::counters::{CpuCounter, MemCounter};
+use crate::Result;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+const PROC_SELF_CGROUP: &str = "/proc/self/cgroup";
+const ACTIVITY_PREFIX: &str = "ya-activity-";
+/// Leaf for processes moved out of the cgroup holding activity cgroups.
+const LEAF_NAME: &str = "ya-processes";
+const DELEGATED_CONTROLLERS: [&str; 2] = ["memory", "io"];
+const GIB: f64 = 1024. * 1024. * 1024.;
+
+/// cgroup v2 directory holding the activity's processes.
+///
+/// Unlike process tree sampling, cgroup statistics include processes which
+/// have already exited.
+#[derive(Clone, Debug)]
+pub struct Cgroup {
+    path: PathBuf,
+}
+
+impl Cgroup {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Cgroup { path: path.into() }
+    }
+
+    /// Returns the cgroup v2 directory of the current process, if the unified
+    /// hierarchy is mounted.
+    pub fn current() -> Option<Self> {
+        let content = fs::read_to_string(PROC_SELF_CGROUP).ok()?;
+        let relative = parse_proc_cgroup(&content)?;
+        let cgroup = Cgroup::new(Path::new(CGROUP_ROOT).join(relative));
+        cgroup.path.is_dir().then_some(cgroup)
+    }
+
+    /// Returns the cgroup in which `for_activity` creates activity cgroups.
+    pub fn activities_parent() -> Option<Self> {
+        Self::current().map(Self::delegation_root)
+    }
+
+    /// Creates a child of the current cgroup dedicated to the activity and moves
+    /// the current process into it, so that processes spawned later on are
+    /// accounted there as well. Other ExeUnits and the Provider Agent, which usually
+    /// share the parent cgroup, are not included in the statistics.
+    ///
+    /// Returns `None` if the cgroup can't be created, e.g. when the hierarchy
+    /// is not delegated to the current user.
+    pub fn for_activity(activity_id: &str) -> Option<Self> {
+        Self::activities_parent()?.create_activity(activity_id)
+    }
+
+    /// Processes moved aside by `enable_controllers` share a leaf, so ExeUnits
+    /// started from there use the leaf's parent.
+    fn delegation_root(self) -> Self {
+        match (self.path.file_name(), self.path.parent()) {
+            (Some(name), Some(parent)) if name == LEAF_NAME => Cgroup::new(parent),
+            _ => self,
+        }
+    }
+
+    fn create_activity(&self, activity_id: &str) -> Option<Self> {
+        self.remove_stale_activities();
+
+        let name = format!("{ACTIVITY_PREFIX}{}", sanitize_name(activity_id));
+        let cgroup = Cgroup::new(self.path.join(name));
+        let result = fs::create_dir_all(&cgroup.path).and_then(|_| {
+            fs::write(
+                cgroup.path.join("cgroup.procs"),
+                std::process::id().to_string(),
+            )
+        });
+        if result.is_ok() {
+            self.enable_controllers();
+        }
+
+        match result {
+            Ok(_) if cgroup.path.join("cpu.stat").is_file() => Some(cgroup),
+            Ok(_) => {
+                log::debug!("cgroup v2 counters not available at {:?}", cgroup.path);
+                None
+            }
+            Err(e) => {
+                log::debug!("Unable to create activity cgroup {:?}: {e}", cgroup.path);
+                let _ = fs::remove_dir(&cgroup.path);
+                None
+            }
+        }
+    }
+
+    /// Enables memory and I/O statistics of child cgroups. cgroup v2 allows it only
+    /// in cgroups without processes of their own, so processes left here, e.g. the
+    /// Provider Agent and other ExeUnits, are moved to a leaf child first.
+    fn enable_controllers(&self) {
+        let missing = self.missing_controllers();
+        if missing.is_empty() {
+            return;
+        }
+
+        let control = missing
+            .iter()
+            .map(|controller| format!("+{controller}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let subtree_control = self.path.join("cgroup.subtree_control");
+        let result = fs::write(&subtree_control, &control).or_else(|_| {
+            self.move_processes_to_leaf()?;
+            fs::write(&subtree_control, &control)
+        });
+        if let Err(e) = result {
+            log::debug!("Unable to enable {control} in {:?}: {e}", self.path);
+        }
+    }
+
+    /// Delegated controllers available in the cgroup, but not enabled for its children.
+    fn missing_controllers(&self) -> Vec<&'static str> {
+        let available = self.read("cgroup.controllers").unwrap_or_default();
+        let enabled = self.read("cgroup.subtree_control").unwrap_or_default();
+        DELEGATED_CONTROLLERS
+            .into_iter()
+            .filter(|controller| parse_controllers(&available).any(|c| c == *controller))
+            .filter(|controller| !parse_controllers(&enabled).any(|c| c == *controller))
+            .collect()
+    }
+
+    fn move_processes_to_leaf(&self) -> io::Result<()> {
+        let leaf = self.path.join(LEAF_NAME);
+        fs::create_dir_all(&leaf)?;
+        let procs = fs::read_to_string(self.path.join("cgroup.procs"))?;
+        for pid in procs.split_whitespace() {
+            // Processes may exit in the meantime.
+            let _ = fs::write(leaf.join("cgroup.procs"), pid);
+        }
+        Ok(())
+    }
+
+    /// Removes cgroups of finished activities. Cgroups which still contain
+    /// processes can't be removed, so errors are ignored.
+    fn remove_stale_activities(&self) {
+        let entries = match fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(ACTIVITY_PREFIX)
+            })
+            .for_each(|entry| {
+                let _ = fs::remove_dir(entry.path());
+            });
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn supports_memory(&self) -> bool {
+        self.path.join("memory.current").is_file()
+    }
+
+    pub fn supports_io(&self) -> bool {
+        self.path.join("io.stat").is_file()
+    }
+
+    /// Checks if child cgroups, e.g. ones created by `for_activity`, provide
+    /// I/O statistics or will once `for_activity` enables the controller.
+    pub fn children_support_io(&self) -> bool {
+        let enabled = self
+            .read("cgroup.subtree_control")
+            .map(|content| parse_controllers(&content).any(|c| c == "io"))
+            .unwrap_or(false);
+        let available = self
+            .read("cgroup.controllers")
+            .map(|content| parse_controllers(&content).any(|c| c == "io"))
+            .unwrap_or(false);
+        let delegated = fs::OpenOptions::new()
+            .write(true)
+            .open(self.path.join("cgroup.subtree_control"))
+            .is_ok();
+        enabled || (available && delegated)
+    }
+
+    pub fn cpu_time(&self) -> Result<Duration> {
+        let content = self.read("cpu.stat")?;
+        parse_key_value(&content, "usage_usec")
+            .map(Duration::from_micros)
+            .ok_or_else(|| CounterError::Other("cpu.stat: missing usage_usec".to_string()))
+    }
+
+    /// Current memory usage in bytes.
+    pub fn memory_current(&self) -> Result<u64> {
+        parse_value(&self.read("memory.current")?, "memory.current")
+    }
+
+    /// Peak memory usage in bytes. Available since Linux 5.19.
+    pub fn memory_peak(&self) -> Result<u64> {
+        parse_value(&self.read("memory.peak")?, "memory.peak")
+    }
+
+    /// Total number of bytes read and written, summed over all devices.
+    pub fn io_bytes(&self) -> Result<u64> {
+        Ok(parse_io_stat(&self.read("io.stat")?))
+    }
+
+    fn read(&self, file: &str) -> Result<String> {
+        fs::read_to_string(self.path.join(file)).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => CounterError::Unsupported(format!("cgroup {file}")),
+            _ => CounterError::Other(format!("unable to read cgroup {file}: {e}")),
+        })
+    }
+}
+
+/// Extracts the unified hierarchy entry (`0::/path`) from `/proc/<pid>/cgroup`.
+fn parse_proc_cgroup(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim().trim_start_matches('/'))
+}
+
+/// Activity ids are hex strings, but cgroup names must never contain a path separator.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+fn parse_controllers(content: &str) -> impl Iterator<Item = &str> {
+    content.split_whitespace()
+}
+
+fn parse_key_value(content: &str, key: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let mut split = line.split_whitespace();
+        match (split.next(), split.next()) {
+            (Some(k), Some(v)) if k == key => v.parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+fn parse_value(content: &str, file: &str) -> Result<u64> {
+    content
+        .trim()
+        .parse()
+        .map_err(|e| CounterError::Other(format!("invalid {file} value: {e}")))
+}
+
+/// Sums `rbytes` and `wbytes` of every `<major>:<minor> key=value ...` line.
+fn parse_io_stat(content: &str) -> u64 {
+    content
+        .lines()
+        .flat_map(|line| line.split_whitespace().skip(1))
+        .filter_map(|entry| entry.split_once('='))
+        .filter(|(key, _)| *key == "rbytes" || *key == "wbytes")
+        .filter_map(|(_, value)| value.parse::<u64>().ok())
+        .sum()
+}
+
+/// CPU time of all processes within the cgroup, including exited ones.
+/// Time spent before the counter was created is not reported.
+pub struct CgroupCpuCounter {
+    cgroup: Cgroup,
+    baseline: Duration,
+}
+
+impl CgroupCpuCounter {
+    pub const ID: &'static str = CpuCounter::ID;
+
+    pub fn new(cgroup: Cgroup) -> Self {
+        let baseline = cgroup.cpu_time().unwrap_or_default();
+        CgroupCpuCounter { cgroup, baseline }
+    }
+}
+
+impl Counter for CgroupCpuCounter {
+    #[inline]
+    fn frame(&mut self) -> Result<CounterData> {
+        let cpu_time = self.cgroup.cpu_time()?;
+        Ok(cpu_time.saturating_sub(self.baseline).as_secs_f64())
+    }
+
+    #[inline]
+    fn peak(&mut self) -> Result<CounterData> {
+        self.frame()
+    }
+}
+
+/// Memory charged to the cgroup, in GiB.
+pub struct CgroupMemCounter {
+    cgroup: Cgroup,
+    peak: CounterData,
+}
+
+impl CgroupMemCounter {
+    pub const ID: &'static str = MemCounter::ID;
+
+    pub fn new(cgroup: Cgroup) -> Self {
+        CgroupMemCounter { cgroup, peak: 0. }
+    }
+
+    fn update_peak(&mut self, val: CounterData) -> CounterData {
+        if val > self.peak {
+            self.peak = val;
+        }
+        self.peak
+    }
+}
+
+impl Counter for CgroupMemCounter {
+    fn frame(&mut self) -> Result<CounterData> {
+        let data = self.cgroup.memory_current()? as CounterData / GIB;
+        self.update_peak(data);
+        Ok(data)
+    }
+
+    fn peak(&mut self) -> Result<CounterData> {
+        // `memory.peak` also captures spikes between samples
+        match self.cgroup.memory_peak() {
+            Ok(peak) => Ok(self.update_peak(peak as CounterData / GIB)),
+            Err(CounterError::Unsupported(_)) => {
+                self.frame()?;
+                Ok(self.peak)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Bytes read from and written to block devices by the cgroup, in GiB.
+/// Transfers made before the counter was created are not reported.
+pub struct DiskIoCounter {
+    cgroup: Cgroup,
+    baseline: u64,
+}
+
+impl DiskIoCounter {
+    pub const ID: &'static str = "golem.usage.disk_io_gib";
+
+    pub fn new(cgroup: Cgroup) -> Self {
+        let baseline = cgroup.io_bytes().unwrap_or_default();
+        DiskIoCounter { cgroup, baseline }
+    }
+}
+
+impl Counter for DiskIoCounter {
+    #[inline]
+    fn frame(&mut self) -> Result<CounterData> {
+        let io_bytes = self.cgroup.io_bytes()?;
+        Ok(io_bytes.saturating_sub(self.baseline) as CounterData / GIB)
+    }
+
+    #[inline]
+    fn peak(&mut self) -> Result<CounterData> {
+        self.frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_cgroup() {
+        let content = "12:pids:/user.slice\n0::/system.slice/golem-provider.service\n";
+        assert_eq!(
+            parse_proc_cgroup(content),
+            Some("system.slice/golem-provider.service")
+        );
+        assert_eq!(parse_proc_cgroup("0::/\n"), Some(""));
+        assert_eq!(parse_proc_cgroup("12:pids:/user.slice\n"), None);
+    }
+
+    #[test]
+    fn test_parse_cpu_stat() {
+        let content = "usage_usec 2500000\nuser_usec 2000000\nsystem_usec 500000\n\
+            nr_periods 0\nnr_throttled 0\nthrottled_usec 0\n";
+        assert_eq!(parse_key_value(content, "usage_usec"), Some(2_500_000));
+        assert_eq!(parse_key_value(content, "system_usec"), Some(500_000));
+        assert_eq!(parse_key_value(content, "usage"), None);
+        assert_eq!(parse_key_value("usage_usec\n", "usage_usec"), None);
+    }
+
+    #[test]
+    fn test_parse_io_stat() {
+        let content = "8:0 rbytes=1048576 wbytes=2097152 rios=10 wios=20 dbytes=4096 dios=1\n\
+            259:0 rbytes=512 wbytes=0 rios=1 wios=0 dbytes=0 dios=0\n";
+        assert_eq!(parse_io_stat(content), 1048576 + 2097152 + 512);
+        assert_eq!(parse_io_stat(""), 0);
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        assert_eq!(
+            parse_value("1073741824\n", "memory.current").unwrap(),
+            1 << 30
+        );
+        assert!(matches!(
+            parse_value("max\n", "memory.peak"),
+            Err(CounterError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_controllers() {
+        let controllers = parse_controllers("cpuset cpu io memory pids\n").collect::<Vec<_>>();
+        assert_eq!(controllers, ["cpuset", "cpu", "io", "memory", "pids"]);
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(sanitize_name("0ec929f5acc8"), "0ec929f5acc8");
+        assert_eq!(sanitize_name("../a/b"), "___a_b");
+    }
+
+    #[test]
+    fn test_activity_in_fake_hierarchy() {
+        let dir =
+            std::env::temp_dir().join(format!("ya-counters-hierarchy-{}", std::process::id()));
+        let activity = dir.join(format!("{ACTIVITY_PREFIX}a"));
+        fs::create_dir_all(&activity).unwrap();
+        fs::write(dir.join("cgroup.controllers"), "cpu io memory pids\n").unwrap();
+        fs::write(dir.join("cgroup.subtree_control"), "").unwrap();
+        fs::write(dir.join("cgroup.procs"), "").unwrap();
+        fs::write(activity.join("cpu.stat"), "usage_usec 0\n").unwrap();
+
+        let parent = Cgroup::new(&dir);
+        assert!(parent.children_support_io());
+        assert_eq!(parent.missing_controllers(), ["memory", "io"]);
+
+        let cgroup = parent.create_activity("a").unwrap();
+        assert_eq!(cgroup.path(), activity);
+        assert_eq!(
+            fs::read_to_string(activity.join("cgroup.procs")).unwrap(),
+            std::process::id().to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("cgroup.subtree_control")).unwrap(),
+            "+memory +io"
+        );
+
+        // The kernel lists enabled controllers without the `+` prefix.
+        fs::write(dir.join("cgroup.subtree_control"), "io memory\n").unwrap();
+        assert!(parent.missing_controllers().is_empty());
+
+        // Without `cpu.stat` the hierarchy is not a cgroup v2 one.
+        assert!(parent.create_activity("b").is_none());
+
+        let leaf = Cgroup::new(dir.join(LEAF_NAME)).delegation_root();
+        assert_eq!(leaf.path(), dir);
+        assert_eq!(Cgroup::new(&activity).delegation_root().path(), activity);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_counters_report_deltas() {
+        let dir = std::env::temp_dir().join(format!("ya-counters-cgroup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cpu.stat"), "usage_usec 1000000\n").unwrap();
+        fs::write(dir.join("io.stat"), "8:0 rbytes=1024 wbytes=1024\n").unwrap();
+
+        let cgroup = Cgroup::new(&dir);
+        let mut cpu = CgroupCpuCounter::new(cgroup.clone());
+        let mut io = DiskIoCounter::new(cgroup);
+        assert_eq!(cpu.frame().unwrap(), 0.);
+        assert_eq!(io.frame().unwrap(), 0.);
+
+        fs::write(dir.join("cpu.stat"), "usage_usec 3500000\n").unwrap();
+        fs::write(dir.join("io.stat"), "8:0 rbytes=1024 wbytes=1073742848\n").unwrap();
+        assert_eq!(cpu.frame().unwrap(), 2.5);
+        assert_eq!(io.frame().unwrap(), 1.);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(target_os = "linux")]
+pub mod cgroup;
 #[cfg(unix)]
 mod unix;
 #[cfg(windows)]
@@ -45,6 +45,33 @@ impl CountersServiceBuilder {
         self
     }
 
+    /// Replaces process based CPU and memory counters with ones reading
+    /// cgroup v2 statistics of the activity. Memory counter is replaced only when
+    /// `memory.current` is available. Disk I/O counter is registered when `io.stat`
+    /// is available and the counter is a part of the usage vector.
+    #[cfg(all(feature = "os", target_os = "linux"))]
+    pub fn with_cgroup_counters(&mut self, cgroup: crate::os::cgroup::Cgroup) -> &mut Self {
+        use crate::os::cgroup::{CgroupCpuCounter, CgroupMemCounter, DiskIoCounter};
+
+        log::debug!("Using cgroup counters: {:?}", cgroup.path());
+
+        let io_priced = self.usage_vector.iter().any(|id| id == DiskIoCounter::ID);
+        if io_priced && cgroup.supports_io() {
+            let counter = DiskIoCounter::new(cgroup.clone());
+            self.counters
+                .insert(DiskIoCounter::ID.into(), Box::new(counter));
+        }
+        if cgroup.supports_memory() {
+            let counter = CgroupMemCounter::new(cgroup.clone());
+            self.counters
+                .insert(CgroupMemCounter::ID.into(), Box::new(counter));
+        }
+        let counter = CgroupCpuCounter::new(cgroup);
+        self.counters
+            .insert(CgroupCpuCounter::ID.into(), Box::new(counter));
+        self
+    }
+
     pub fn build(self) -> CountersService {
         let custom_counters_ids: Vec<String> = self
             .usage_vector
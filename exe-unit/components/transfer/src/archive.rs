@@ -3,6 +3,7 @@ use crate::TransferData;
 use async_compression::futures::bufread::{BzDecoder, BzEncoder};
 use async_compression::futures::bufread::{GzipDecoder, GzipEncoder};
 use async_compression::futures::bufread::{XzDecoder, XzEncoder};
+use async_compression::futures::bufread::{ZstdDecoder, ZstdEncoder};
 use bytes::{Bytes, BytesMut};
 use futures::channel::{mpsc, mpsc::Sender};
 use futures::task::{Context, Poll};
@@ -11,7 +12,7 @@ use rand::Rng;
 use std::convert::TryFrom;
 use std::fs::create_dir_all;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
 use std::str::FromStr;
 use tokio::fs::OpenOptions;
@@ -21,6 +22,8 @@ use ya_utils_path::normalize_path;
 use zip::tokio::read::read_zipfile_from_stream;
 use zip::write::FileOptions;
 
+use crate::traverse::GlobFilter;
+
 #[derive(Clone, Copy, Debug, Default)]
 pub enum ArchiveFormat {
     Tar,
@@ -28,6 +31,7 @@ pub enum ArchiveFormat {
     #[default]
     TarGz,
     TarXz,
+    TarZst,
     Zip,
     ZipStored,
 }
@@ -45,6 +49,8 @@ impl FromStr for ArchiveFormat {
             Ok(ArchiveFormat::TarGz)
         } else if s.ends_with(".tar.xz") {
             Ok(ArchiveFormat::TarXz)
+        } else if s.ends_with(".tar.zst") || s.ends_with(".tar.zstd") {
+            Ok(ArchiveFormat::TarZst)
         } else if s.ends_with(".zip") {
             Ok(ArchiveFormat::Zip)
         } else if s.ends_with(".zip.0") {
@@ -64,6 +70,7 @@ impl<'s> TryFrom<&'s str> for ArchiveFormat {
             "tar.bz2" => ArchiveFormat::TarBz2,
             "tar.gz" => ArchiveFormat::TarGz,
             "tar.xz" => ArchiveFormat::TarXz,
+            "tar.zst" | "tar.zstd" => ArchiveFormat::TarZst,
             "zip" => ArchiveFormat::Zip,
             "zip.0" => ArchiveFormat::ZipStored,
             _ => return Err(Error::OutputFormat(s.to_string())),
@@ -156,6 +163,14 @@ where
             ))
             .map(BytesResult::convert),
         ),
+        ArchiveFormat::TarZst => Box::pin(
+            codec_stream(ZstdEncoder::new(
+                archive_tar(path_iter, path_root, evt_sender)
+                    .await
+                    .into_async_read(),
+            ))
+            .map(BytesResult::convert),
+        ),
         ArchiveFormat::Zip | ArchiveFormat::ZipStored => {
             archive_zip(
                 path_iter,
@@ -323,10 +338,13 @@ where
     }
 }
 
+/// Extracts the archive while it's being received, without storing it on disk.
+/// When `filter` is set, only the entries matching it are extracted.
 pub async fn extract<B, S, E, P>(
     stream: S,
     path: P,
     format: ArchiveFormat,
+    filter: Option<GlobFilter>,
     evt_sender: Sender<FileEvent>,
 ) -> Result<(), E>
 where
@@ -341,12 +359,13 @@ where
     let stream = stream.map(|r| r.map(Into::into).map_err(Into::into));
     match format {
         ArchiveFormat::Tar => {
-            extract_tar(stream, path, evt_sender).await?;
+            extract_tar(stream, path, filter, evt_sender).await?;
         }
         ArchiveFormat::TarBz2 => {
             extract_tar(
                 codec_stream(BzDecoder::new(stream.into_async_read())),
                 path,
+                filter,
                 evt_sender,
             )
             .await?;
@@ -355,6 +374,7 @@ where
             extract_tar(
                 codec_stream(GzipDecoder::new(stream.into_async_read())),
                 path,
+                filter,
                 evt_sender,
             )
             .await?;
@@ -363,12 +383,22 @@ where
             extract_tar(
                 codec_stream(XzDecoder::new(stream.into_async_read())),
                 path,
+                filter,
+                evt_sender,
+            )
+            .await?;
+        }
+        ArchiveFormat::TarZst => {
+            extract_tar(
+                codec_stream(ZstdDecoder::new(stream.into_async_read())),
+                path,
+                filter,
                 evt_sender,
             )
             .await?;
         }
         ArchiveFormat::Zip | ArchiveFormat::ZipStored => {
-            extract_zip(stream, path, evt_sender).await?;
+            extract_zip(stream, path, filter, evt_sender).await?;
         }
     }
     Ok(())
@@ -377,6 +407,7 @@ where
 async fn extract_zip<'a, S, P>(
     stream: S,
     path: P,
+    filter: Option<GlobFilter>,
     mut evt_sender: Sender<FileEvent>,
 ) -> Result<(), io::Error>
 where
@@ -391,6 +422,11 @@ where
         .map_err(io_error)?
     {
         let name = result.sanitized_name();
+        if !is_included(filter.as_ref(), &name) {
+            result.exhaust().await;
+            continue;
+        }
+
         let size = result.size() as usize;
         let file_path = path.join(&name);
 
@@ -428,6 +464,7 @@ where
 async fn extract_tar<'a, S, P>(
     stream: S,
     path: P,
+    filter: Option<GlobFilter>,
     mut evt_sender: Sender<FileEvent>,
 ) -> Result<(), io::Error>
 where
@@ -443,6 +480,9 @@ where
         let mut file = file?;
         let header = file.header();
         let name = file.path()?.to_path_buf();
+        if !is_included(filter.as_ref(), &name) {
+            continue;
+        }
 
         let evt = FileEvent::Processing {
             name: name.clone(),
@@ -459,6 +499,17 @@ where
     Ok(())
 }
 
+fn is_included(filter: Option<&GlobFilter>, name: &Path) -> bool {
+    match filter {
+        Some(filter) => filter.is_match(
+            name.components()
+                .filter(|c| !matches!(c, Component::CurDir))
+                .collect::<PathBuf>(),
+        ),
+        None => true,
+    }
+}
+
 fn codec_stream<R>(s: R) -> Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send + Sync + 'static>>
 where
     R: AsyncRead + Send + Sync + Unpin + 'static,
@@ -611,3 +662,61 @@ impl BytesResult for Result<Bytes, io::Error> {
         self.map(|b| B::from(b)).map_err(|e| E::from(e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traverse::PathTraverse;
+    use ya_client_model::activity::{FileSet, SetEntry};
+
+    #[actix_rt::test]
+    async fn test_extract_filtered_tar_zst() -> anyhow::Result<()> {
+        let src = tempdir::TempDir::new("archive-src")?;
+        let dst = tempdir::TempDir::new("archive-dst")?;
+
+        create_dir_all(src.path().join("sub"))?;
+        for name in ["a.txt", "b.bin", "sub/c.txt"] {
+            std::fs::write(src.path().join(name), name)?;
+        }
+
+        // dropped receiver makes sending events a no-op
+        let (evt_tx, _) = mpsc::channel(1);
+        let path_iter = TransferArgs::default().traverse(src.path())?;
+        let stream = archive(
+            path_iter,
+            src.path().to_path_buf(),
+            ArchiveFormat::TarZst,
+            evt_tx.clone(),
+        )
+        .await;
+
+        let fileset = FileSet::Pattern(SetEntry::Single("**/*.txt".into()));
+        let filter = GlobFilter::new(Some(&fileset))?;
+        extract(
+            stream,
+            dst.path().to_path_buf(),
+            ArchiveFormat::TarZst,
+            Some(filter),
+            evt_tx,
+        )
+        .await?;
+
+        assert_eq!(std::fs::read(dst.path().join("a.txt"))?, b"a.txt");
+        assert_eq!(std::fs::read(dst.path().join("sub/c.txt"))?, b"sub/c.txt");
+        assert!(!dst.path().join("b.bin").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format() {
+        for (name, expected) in [
+            ("file.tar.zst", "tar.zst"),
+            ("file.tar.zstd", "tar.zst"),
+            ("file.tar.xz", "tar.xz"),
+        ] {
+            let format = ArchiveFormat::from_str(name).unwrap();
+            let parsed = ArchiveFormat::try_from(expected).unwrap();
+            assert_eq!(format!("{:?}", format), format!("{:?}", parsed));
+        }
+    }
+}
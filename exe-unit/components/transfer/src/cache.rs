@@ -1,23 +1,56 @@
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
 use sha3::Digest;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
 
 use crate::{error::Error as TransferError, TransferUrl};
 
+const BLOB_DIR: &str = "blobs";
+const INDEX_FILE: &str = "index.json";
+const INDEX_LOCK_FILE: &str = "index.lock";
+const LEASE_FILE_EXT: &str = "lease";
+
+/// Content-addressed cache of deployed images, shared by all ExeUnits on the same Provider.
+///
+/// Blobs are stored under their `TransferHash` digest, so identical images downloaded from
+/// different locations are kept only once. The index survives ExeUnit restarts and is guarded
+/// by a file lock. When a byte budget is set, least recently used blobs are evicted, skipping
+/// the ones leased by any running ExeUnit.
 #[derive(Debug, Clone)]
 pub struct Cache {
     dir: PathBuf,
+    blob_dir: PathBuf,
     #[allow(dead_code)]
     tmp_dir: PathBuf,
+    budget: Option<u64>,
+    leases: Rc<RefCell<HashMap<String, (usize, File)>>>,
 }
 
 impl Cache {
-    pub fn new(dir: PathBuf) -> Self {
+    pub fn new(dir: PathBuf, budget: Option<u64>) -> Self {
         let tmp_dir = dir.join("tmp");
-        std::fs::create_dir_all(&tmp_dir)
-            .unwrap_or_else(|_| panic!("Unable to create directory: {}", tmp_dir.display()));
-        Cache { dir, tmp_dir }
+        let blob_dir = dir.join(BLOB_DIR);
+        for dir in [&tmp_dir, &blob_dir] {
+            std::fs::create_dir_all(dir)
+                .unwrap_or_else(|_| panic!("Unable to create directory: {}", dir.display()));
+        }
+        let cache = Cache {
+            dir,
+            blob_dir,
+            tmp_dir,
+            budget,
+            leases: Default::default(),
+        };
+        if let Err(e) = cache.adopt_legacy_files() {
+            log::warn!("Unable to adopt previously cached images: {}", e);
+        }
+        cache
     }
 
     pub fn name(transfer_url: &TransferUrl) -> Result<CachePath, TransferError> {
@@ -33,7 +66,12 @@ impl Cache {
             hex::encode(hash)
         };
 
-        Ok(CachePath::new(name.into(), hash.val.clone(), location_hash))
+        Ok(CachePath::new(
+            name.into(),
+            hash.alg.to_lowercase(),
+            hash.val.clone(),
+            location_hash,
+        ))
     }
 
     #[inline(always)]
@@ -43,10 +81,282 @@ impl Cache {
 
     #[inline(always)]
     pub fn to_final_path(&self, path: &CachePath) -> ProjectedPath {
-        ProjectedPath::local(self.dir.clone(), path.final_path())
+        ProjectedPath::local(self.blob_dir.clone(), path.final_path())
+    }
+
+    /// Protects the blob from eviction until the returned lease is dropped.
+    /// Leases are reference counted within the process and backed by a shared file lock,
+    /// which is released by the OS when the ExeUnit exits.
+    pub fn lease(&self, path: &CachePath) -> Result<CacheLease, TransferError> {
+        let key = path.key();
+        let mut leases = self.leases.borrow_mut();
+        match leases.get_mut(&key) {
+            Some((count, _)) => *count += 1,
+            None => {
+                let file = open_lock_file(&self.lease_path(&key))?;
+                file.lock_shared()?;
+                leases.insert(key.clone(), (1, file));
+            }
+        }
+
+        Ok(CacheLease {
+            key,
+            leases: self.leases.clone(),
+        })
+    }
+
+    /// Marks the blob as recently used
+    pub fn touch(&self, path: &CachePath) -> Result<(), TransferError> {
+        let key = path.key();
+        let size = std::fs::metadata(self.blob_dir.join(&key))?.len();
+        self.with_index(|index| {
+            index.insert(key, size);
+            Ok(())
+        })
+    }
+
+    /// Registers a new blob and evicts least recently used ones when over budget
+    pub fn insert(&self, path: &CachePath) -> Result<(), TransferError> {
+        self.touch(path)?;
+        self.evict()?;
+        Ok(())
+    }
+
+    /// Removes least recently used blobs which are not leased until the cache fits
+    /// within the budget. Returns the number of bytes freed.
+    pub fn evict(&self) -> Result<u64, TransferError> {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return Ok(0),
+        };
+
+        self.with_index(|index| {
+            let mut freed = 0;
+            let mut total = index.total_size();
+            if total <= budget {
+                return Ok(freed);
+            }
+
+            let mut entries = index
+                .entries
+                .iter()
+                .map(|(key, entry)| (entry.last_used, key.clone()))
+                .collect::<Vec<_>>();
+            entries.sort();
+
+            for (_, key) in entries {
+                if total <= budget {
+                    break;
+                }
+                // don't rely on the platform-specific semantics of locks held by this process
+                if self.leases.borrow().contains_key(&key) {
+                    continue;
+                }
+
+                let lease_path = self.lease_path(&key);
+                let lock = open_lock_file(&lease_path)?;
+                if lock.try_lock_exclusive().is_err() {
+                    log::debug!("Cached blob {} is in use, skipping eviction", key);
+                    continue;
+                }
+
+                let size = index.entries.remove(&key).map(|e| e.size).unwrap_or(0);
+                log::info!("Evicting cached blob {} ({} B)", key, size);
+                if let Err(e) = std::fs::remove_file(self.blob_dir.join(&key)) {
+                    if e.kind() != IoErrorKind::NotFound {
+                        log::warn!("Unable to remove cached blob {}: {}", key, e);
+                    }
+                }
+                let _ = std::fs::remove_file(&lease_path);
+
+                total = total.saturating_sub(size);
+                freed += size;
+            }
+
+            if total > budget {
+                log::warn!(
+                    "Cache size {} B exceeds the budget of {} B, all remaining blobs are in use",
+                    total,
+                    budget
+                );
+            }
+            Ok(freed)
+        })
+    }
+
+    /// Moves images cached under the former `<name>_<hash>.<ext>` layout into the blob
+    /// directory, so that they are tracked by the index and can be evicted.
+    fn adopt_legacy_files(&self) -> Result<(), TransferError> {
+        let files = std::fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+            .filter_map(|e| {
+                let key = legacy_key(e.file_name().to_str()?)?;
+                Some((e.path(), key))
+            })
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        self.with_index(|index| {
+            for (path, key) in files {
+                let blob_path = self.blob_dir.join(&key);
+                if blob_path.exists() {
+                    log::info!("Removing duplicate of cached blob {}: {:?}", key, path);
+                    std::fs::remove_file(&path)?;
+                    continue;
+                }
+
+                log::info!("Adopting cached image {:?} as blob {}", path, key);
+                std::fs::rename(&path, &blob_path)?;
+                let size = std::fs::metadata(&blob_path)?.len();
+                // adopted blobs are the first candidates for eviction
+                index
+                    .entries
+                    .entry(key)
+                    .or_insert(CacheEntry { size, last_used: 0 });
+            }
+            Ok(())
+        })
+    }
+
+    fn lease_path(&self, key: &str) -> PathBuf {
+        self.blob_dir.join(format!("{}.{}", key, LEASE_FILE_EXT))
+    }
+
+    /// Runs `f` on the index while holding an exclusive lock, then persists the changes
+    fn with_index<T>(
+        &self,
+        f: impl FnOnce(&mut CacheIndex) -> Result<T, TransferError>,
+    ) -> Result<T, TransferError> {
+        let lock = open_lock_file(&self.dir.join(INDEX_LOCK_FILE))?;
+        lock.lock_exclusive()?;
+
+        let path = self.dir.join(INDEX_FILE);
+        let mut index = CacheIndex::load(&path, &self.blob_dir);
+        let result = f(&mut index);
+        index.store(&path)?;
+
+        lock.unlock()?;
+        result
     }
 }
 
+/// Keeps a cached blob from being evicted
+#[derive(Debug)]
+pub struct CacheLease {
+    key: String,
+    leases: Rc<RefCell<HashMap<String, (usize, File)>>>,
+}
+
+impl Drop for CacheLease {
+    fn drop(&mut self) {
+        let mut leases = self.leases.borrow_mut();
+        if let Some((count, _)) = leases.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                // dropping the file releases the lock
+                leases.remove(&self.key);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    /// Logical clock, incremented on each access
+    clock: u64,
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    size: u64,
+    last_used: u64,
+}
+
+impl CacheIndex {
+    /// Loads the index and reconciles it with the blobs present on disk
+    fn load(path: &Path, blob_dir: &Path) -> Self {
+        let mut index = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                log::warn!("Invalid cache index {}, rebuilding: {}", path.display(), e);
+                CacheIndex::default()
+            }),
+            Err(_) => CacheIndex::default(),
+        };
+
+        let blobs = match std::fs::read_dir(blob_dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .filter(|e| {
+                    e.path()
+                        .extension()
+                        .map(|x| x != LEASE_FILE_EXT)
+                        .unwrap_or(true)
+                })
+                .filter_map(|e| Some((e.file_name().into_string().ok()?, e.metadata().ok()?)))
+                .filter(|(_, meta)| meta.is_file())
+                .map(|(name, meta)| (name, meta.len()))
+                .collect::<HashMap<_, _>>(),
+            Err(_) => Default::default(),
+        };
+
+        index.entries.retain(|key, _| blobs.contains_key(key));
+        for (key, size) in blobs {
+            if !index.entries.contains_key(&key) {
+                // blobs missing from the index are the first candidates for eviction
+                index.entries.insert(key, CacheEntry { size, last_used: 0 });
+            }
+        }
+        index
+    }
+
+    fn store(&self, path: &Path) -> Result<(), TransferError> {
+        let tmp_path = path.with_extension("tmp");
+        let bytes = serde_json::to_vec(self).map_err(|e| TransferError::Other(e.to_string()))?;
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn insert(&mut self, key: String, size: u64) {
+        self.clock += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                size,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    fn total_size(&self) -> u64 {
+        self.entries.values().map(|e| e.size).sum()
+    }
+}
+
+/// Content address of a file named after the former `<name>_<hash>.<ext>` layout
+fn legacy_key(file_name: &str) -> Option<String> {
+    let (_, suffix) = file_name.rsplit_once('_')?;
+    let hash = suffix.split('.').next()?;
+    let hash = hex::decode(hash).ok()?;
+    match hash.len() * 8 {
+        224 | 256 | 384 | 512 => Some(format!("sha3-{}", hex::encode(hash))),
+        _ => None,
+    }
+}
+
+fn open_lock_file(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
 impl TryFrom<ProjectedPath> for TransferUrl {
     type Error = TransferError;
 
@@ -136,14 +446,26 @@ impl From<CachePath> for PathBuf {
 #[derive(Clone, Debug)]
 pub struct CachePath {
     path: PathBuf,
+    alg: String,
     hash: Vec<u8>,
     nonce: String,
 }
 
 impl CachePath {
-    pub fn new(path: PathBuf, hash: Vec<u8>, nonce: String) -> Self {
-        CachePath { path, hash, nonce }
+    pub fn new(path: PathBuf, alg: String, hash: Vec<u8>, nonce: String) -> Self {
+        CachePath {
+            path,
+            alg,
+            hash,
+            nonce,
+        }
+    }
+
+    /// Content address of the blob
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.alg, hex::encode(&self.hash))
     }
+
     /// Creates the long version of path, including hash and the "random" token.
     pub fn temp_path(&self) -> PathBuf {
         let mut digest = sha3::Sha3_224::default();
@@ -153,22 +475,9 @@ impl CachePath {
         PathBuf::from(hex::encode(hash))
     }
 
-    /// Creates a shorter version of path, including hash and excluding the "random" token.
+    /// Creates the content-addressed path, excluding the original name and the "random" token.
     pub fn final_path(&self) -> PathBuf {
-        let stem = self.path.file_stem().unwrap();
-        let extension = self.path.extension();
-        let hash = hex::encode(&self.hash);
-
-        let mut file_name = stem.to_os_string();
-        file_name.push("_");
-        file_name.push(hash);
-
-        if let Some(ext) = extension {
-            file_name.push(".");
-            file_name.push(ext);
-        }
-
-        file_name.into()
+        PathBuf::from(self.key())
     }
 }
 
@@ -228,6 +537,118 @@ mod tests {
         );
     }
 
+    fn cache_path(hash: u8) -> CachePath {
+        CachePath::new(
+            path_buf("image.gvmi"),
+            "sha3".into(),
+            vec![hash; 28],
+            "nonce".into(),
+        )
+    }
+
+    fn create_blob(cache: &Cache, path: &CachePath, size: usize) {
+        std::fs::write(cache.to_final_path(path).to_path_buf(), vec![0u8; size]).unwrap();
+        cache.insert(path).unwrap();
+    }
+
+    fn exists(cache: &Cache, path: &CachePath) -> bool {
+        cache.to_final_path(path).to_path_buf().exists()
+    }
+
+    #[test]
+    fn test_content_address() {
+        let a = TransferUrl::parse("hash:sha3:0a0b0c:http://a.com/image.gvmi", "file").unwrap();
+        let b = TransferUrl::parse("hash:sha3:0a0b0c:http://b.com/other.gvmi", "file").unwrap();
+        let (a, b) = (Cache::name(&a).unwrap(), Cache::name(&b).unwrap());
+
+        assert_eq!(a.final_path(), path_buf("sha3-0a0b0c"));
+        assert_eq!(a.final_path(), b.final_path());
+        assert_ne!(a.temp_path(), b.temp_path());
+    }
+
+    #[test]
+    fn test_evict_lru() {
+        let dir = tempdir::TempDir::new("cache").unwrap();
+        let cache = Cache::new(dir.path().to_path_buf(), Some(250));
+        let (a, b, c) = (cache_path(1), cache_path(2), cache_path(3));
+
+        create_blob(&cache, &a, 100);
+        create_blob(&cache, &b, 100);
+        cache.touch(&a).unwrap();
+        create_blob(&cache, &c, 100);
+
+        assert!(exists(&cache, &a));
+        assert!(!exists(&cache, &b));
+        assert!(exists(&cache, &c));
+    }
+
+    #[test]
+    fn test_evict_skips_leased() {
+        let dir = tempdir::TempDir::new("cache").unwrap();
+        let cache = Cache::new(dir.path().to_path_buf(), Some(150));
+        let (a, b, c) = (cache_path(1), cache_path(2), cache_path(3));
+
+        let lease = cache.lease(&a).unwrap();
+        let lease_2 = cache.lease(&a).unwrap();
+        create_blob(&cache, &a, 100);
+        create_blob(&cache, &b, 100);
+
+        assert!(exists(&cache, &a));
+        assert!(!exists(&cache, &b));
+
+        drop(lease);
+        create_blob(&cache, &c, 100);
+        assert!(exists(&cache, &a));
+        assert!(!exists(&cache, &c));
+
+        drop(lease_2);
+        create_blob(&cache, &c, 100);
+        assert!(!exists(&cache, &a));
+        assert!(exists(&cache, &c));
+    }
+
+    #[test]
+    fn test_index_persistence() {
+        let dir = tempdir::TempDir::new("cache").unwrap();
+        let (a, b, c) = (cache_path(1), cache_path(2), cache_path(3));
+        {
+            let cache = Cache::new(dir.path().to_path_buf(), None);
+            create_blob(&cache, &a, 100);
+            create_blob(&cache, &b, 100);
+            cache.touch(&a).unwrap();
+        }
+
+        // blobs unknown to the index are evicted first
+        std::fs::write(dir.path().join(BLOB_DIR).join(c.key()), vec![0u8; 100]).unwrap();
+
+        let cache = Cache::new(dir.path().to_path_buf(), Some(100));
+        assert_eq!(cache.evict().unwrap(), 200);
+        assert!(exists(&cache, &a));
+        assert!(!exists(&cache, &b));
+        assert!(!exists(&cache, &c));
+    }
+
+    #[test]
+    fn test_adopt_legacy_files() {
+        let dir = tempdir::TempDir::new("cache").unwrap();
+        let (a, b) = (cache_path(1), cache_path(2));
+        let legacy_name = |hash: u8| format!("image_{}.gvmi", hex::encode(vec![hash; 28]));
+        std::fs::write(dir.path().join(legacy_name(1)), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join(legacy_name(2)), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("image_xyz.gvmi"), vec![0u8; 100]).unwrap();
+
+        let cache = Cache::new(dir.path().to_path_buf(), Some(150));
+        assert!(exists(&cache, &a));
+        assert!(exists(&cache, &b));
+        assert!(!dir.path().join(legacy_name(1)).exists());
+        assert!(dir.path().join("image_xyz.gvmi").exists());
+
+        cache.touch(&b).unwrap();
+        assert_eq!(cache.evict().unwrap(), 100);
+        assert!(!exists(&cache, &a));
+        assert!(exists(&cache, &b));
+    }
+
     #[test]
     fn test_remove_base() {
         assert_eq!(path_buf(""), remove_container_path_base(path_buf("")));
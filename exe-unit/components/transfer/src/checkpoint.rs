@@ -0,0 +1,141 @@
+//! Completed byte ranges of a partially downloaded file, persisted in a sidecar file next
+//! to it. A transfer interrupted by an ExeUnit crash resumes from the last checkpoint
+//! instead of starting over.
+
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Written data is synced to disk and checkpointed at least every `CHECKPOINT_INTERVAL` bytes
+pub const CHECKPOINT_INTERVAL: u64 = 4 * 1024 * 1024;
+const CHECKPOINT_EXT: &str = "ranges";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub size: Option<u64>,
+    /// Inclusive byte ranges written and synced to the partial file
+    pub ranges: Vec<(u64, u64)>,
+}
+
+impl Checkpoint {
+    /// Location of the checkpoint of `partial`
+    pub fn path(partial: &Path) -> PathBuf {
+        let mut name = partial
+            .file_name()
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(OsString::new);
+        name.push(".");
+        name.push(CHECKPOINT_EXT);
+        partial.with_file_name(name)
+    }
+
+    /// Reads the checkpoint of `partial`. Unreadable checkpoints are ignored.
+    pub async fn load(partial: &Path) -> Option<Self> {
+        let path = Self::path(partial);
+        let contents = tokio::fs::read(&path).await.ok()?;
+        match serde_json::from_slice(&contents) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(e) => {
+                log::warn!("Ignoring invalid checkpoint {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Replaces the checkpoint of `partial`. The file is renamed into place, so a crash
+    /// never leaves a torn checkpoint behind.
+    pub async fn save(&self, partial: &Path) -> io::Result<()> {
+        let path = Self::path(partial);
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+
+        let contents = serde_json::to_vec(self)?;
+        tokio::fs::write(&tmp, contents).await?;
+        tokio::fs::rename(&tmp, &path).await
+    }
+
+    pub async fn remove(partial: &Path) {
+        let path = Self::path(partial);
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::warn!("Unable to remove checkpoint {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Records the inclusive range `start..=end`, merging it with overlapping or adjacent ranges
+    pub fn complete(&mut self, start: u64, end: u64) {
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.ranges.len());
+        for (start, end) in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    /// Length of the completed prefix of the file, i.e. the offset to resume from
+    pub fn resume_offset(&self) -> u64 {
+        match self.ranges.first() {
+            Some((0, end)) => end + 1,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        assert_eq!(
+            Checkpoint::path(Path::new("/tmp/dir/file.bin")),
+            PathBuf::from("/tmp/dir/file.bin.ranges")
+        );
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut checkpoint = Checkpoint::default();
+        assert_eq!(checkpoint.resume_offset(), 0);
+
+        checkpoint.complete(10, 19);
+        assert_eq!(checkpoint.resume_offset(), 0);
+        checkpoint.complete(0, 9);
+        assert_eq!(checkpoint.ranges, vec![(0, 19)]);
+        assert_eq!(checkpoint.resume_offset(), 20);
+
+        checkpoint.complete(30, 39);
+        checkpoint.complete(15, 24);
+        assert_eq!(checkpoint.ranges, vec![(0, 24), (30, 39)]);
+        assert_eq!(checkpoint.resume_offset(), 25);
+    }
+
+    #[actix_rt::test]
+    async fn test_save_load() {
+        let dir = tempdir::TempDir::new("checkpoint").unwrap();
+        let partial = dir.path().join("file.bin");
+
+        assert_eq!(Checkpoint::load(&partial).await, None);
+
+        let mut checkpoint = Checkpoint {
+            size: Some(100),
+            ..Default::default()
+        };
+        checkpoint.complete(0, 49);
+        checkpoint.save(&partial).await.unwrap();
+        assert_eq!(Checkpoint::load(&partial).await, Some(checkpoint));
+
+        std::fs::write(Checkpoint::path(&partial), b"{\"size\":").unwrap();
+        assert_eq!(Checkpoint::load(&partial).await, None);
+
+        Checkpoint::remove(&partial).await;
+        assert!(!Checkpoint::path(&partial).exists());
+    }
+}
@@ -1,7 +1,8 @@
 use crate::archive::ArchiveFormat;
 use crate::archive::{archive, extract};
+use crate::checkpoint::{Checkpoint, CHECKPOINT_INTERVAL};
 use crate::error::Error;
-use crate::traverse::PathTraverse;
+use crate::traverse::{GlobFilter, PathTraverse};
 use crate::{abortable_sink, abortable_stream, UrlExt};
 use crate::{TransferContext, TransferData, TransferProvider, TransferSink, TransferStream};
 use futures::future::{ready, LocalBoxFuture};
@@ -88,6 +89,7 @@ impl TransferProvider<TransferData, Error> for FileTransferProvider {
 
                 let offset = state.offset();
                 let mut file = if offset == 0 {
+                    Checkpoint::remove(&path).await;
                     OpenOptions::new()
                         .create(true)
                         .write(true)
@@ -96,10 +98,16 @@ impl TransferProvider<TransferData, Error> for FileTransferProvider {
                         .await?
                 } else {
                     let mut file = OpenOptions::new().write(true).open(&path).await?;
+                    // Drop data written after the last checkpoint
+                    file.set_len(offset).await?;
                     file.seek(SeekFrom::Start(offset)).await?;
                     file
                 };
 
+                // Data is written sequentially, so the checkpoint always holds a single range
+                let mut checkpoint = Checkpoint::default();
+                let mut synced = offset;
+
                 while let Some(result) = rx.next().await {
                     let data = result?;
                     let bytes = data.as_ref();
@@ -108,11 +116,38 @@ impl TransferProvider<TransferData, Error> for FileTransferProvider {
                     }
 
                     file.write_all(bytes).await?;
-                    state.set_offset(state.offset() + bytes.len() as u64);
+                    let written = state.offset() + bytes.len() as u64;
+                    state.set_offset(written);
+
+                    if written - synced >= CHECKPOINT_INTERVAL {
+                        file.sync_data().await?;
+                        checkpoint.size = state.size();
+                        checkpoint.complete(0, written - 1);
+                        checkpoint.save(&path).await?;
+                        synced = written;
+                    }
+                }
+                // The source has restarted the transfer, so the partial data is stale
+                if state.offset() < offset {
+                    file.set_len(0).await?;
                 }
                 file.flush().await?;
                 file.sync_all().await?;
 
+                // A failing source ends the stream early as well. Unless the expected size
+                // has been reached, keep track of what was written for the next attempt.
+                let written = state.offset();
+                match state.size() {
+                    Some(size) if written < size => {
+                        checkpoint.size = Some(size);
+                        if written > 0 {
+                            checkpoint.complete(0, written - 1);
+                        }
+                        checkpoint.save(&path).await?;
+                    }
+                    _ => Checkpoint::remove(&path).await,
+                }
+
                 Ok::<(), Error>(())
             }
             .map_err(|error| {
@@ -134,10 +169,23 @@ impl TransferProvider<TransferData, Error> for FileTransferProvider {
         let path = PathBuf::from(extract_file_url(url));
         let state = ctx.state.clone();
         async move {
-            state.set_offset(match tokio::fs::metadata(path).await {
+            let len = match tokio::fs::metadata(&path).await {
                 Ok(meta) => meta.len(),
                 _ => 0,
-            });
+            };
+            // Within a single run the partial file is trusted as is. After a restart only
+            // the data synced to disk before the last checkpoint is reused.
+            let offset = match state.offset() {
+                0 => match Checkpoint::load(&path).await {
+                    Some(checkpoint) => {
+                        state.set_size(checkpoint.size);
+                        checkpoint.resume_offset().min(len)
+                    }
+                    None => len,
+                },
+                _ => len,
+            };
+            state.set_offset(offset);
 
             Ok(())
         }
@@ -192,6 +240,10 @@ impl TransferProvider<TransferData, Error> for DirTransferProvider {
         spawn_local(async move {
             let fut = async move {
                 let format = ArchiveFormat::try_from(&args)?;
+                let filter = match &args.fileset {
+                    Some(fileset) => Some(GlobFilter::new(Some(fileset))?),
+                    None => None,
+                };
 
                 let (evt_tx, mut evt_rx) = futures::channel::mpsc::channel(1);
                 spawn_local(async move {
@@ -200,7 +252,7 @@ impl TransferProvider<TransferData, Error> for DirTransferProvider {
                     }
                 });
 
-                extract(rx, dir, format, evt_tx).await?;
+                extract(rx, dir, format, filter, evt_tx).await?;
                 Ok::<(), Error>(())
             };
 
@@ -26,7 +26,12 @@ pub fn with_hash_stream(
                 match dst_url.url.scheme() {
                     "file" => {
                         log::info!("[HashStream] File transfer from non-zero offset. Initializing hasher from disk..");
-                        Box::new(HashStream::try_started(stream, h, &dst_url.url)?)
+                        Box::new(HashStream::try_started(
+                            stream,
+                            h,
+                            &dst_url.url,
+                            ctx.state.offset(),
+                        )?)
                     }
                     schema => {
                         log::warn!("HashStream is unable to transfer from non-zero offset when using schema: '{schema}'. Resetting offset to 0.'");
@@ -86,20 +91,27 @@ where
         })
     }
 
-    pub fn try_started(stream: S, h: &TransferHash, target: &Url) -> Result<Self, Error> {
-        Self::try_new(stream, &h.alg, h.val.clone())?.init_from_file(target)
+    pub fn try_started(
+        stream: S,
+        h: &TransferHash,
+        target: &Url,
+        offset: u64,
+    ) -> Result<Self, Error> {
+        Self::try_new(stream, &h.alg, h.val.clone())?.init_from_file(target, offset)
     }
 
-    fn init_from_file(mut self, target: &Url) -> Result<Self, Error> {
+    /// Hashes the first `offset` bytes of `target`, already transferred by previous attempts
+    fn init_from_file(mut self, target: &Url, offset: u64) -> Result<Self, Error> {
         let target = extract_file_url(target);
-        let mut file_src = OpenOptions::new().read(true).open(target)?;
+        let mut file_src = OpenOptions::new().read(true).open(target)?.take(offset);
         let mut chunk = vec![0; 4096];
 
-        while let Ok(count) = file_src.read(&mut chunk[..]) {
-            self.hasher.input(&chunk[..count]);
-            if count != 4096 {
+        loop {
+            let count = file_src.read(&mut chunk[..])?;
+            if count == 0 {
                 break;
             }
+            self.hasher.input(&chunk[..count]);
         }
 
         Ok(self)
@@ -1,7 +1,7 @@
 use actix_http::encoding::Decoder;
 use actix_http::header;
 use actix_http::Payload;
-use awc::http::Method;
+use awc::http::{Method, StatusCode};
 use awc::SendClientRequest;
 use bytes::Bytes;
 use futures::future::{ready, LocalBoxFuture};
@@ -35,18 +35,36 @@ impl<'s> From<&'s Url> for HttpAuth<'s> {
     }
 }
 
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const DEFAULT_RANGE_SIZE: u64 = 4 * 1024 * 1024;
+
 pub struct HttpTransferProvider {
     upload_method: Method,
+    concurrency: usize,
+    range_size: u64,
 }
 
 impl Default for HttpTransferProvider {
     fn default() -> Self {
         HttpTransferProvider {
             upload_method: Method::PUT,
+            concurrency: DEFAULT_CONCURRENCY,
+            range_size: DEFAULT_RANGE_SIZE,
         }
     }
 }
 
+impl HttpTransferProvider {
+    /// Downloads are split into `range_size` byte ranges, fetched by up to `concurrency`
+    /// parallel requests when supported by the server. Setting `concurrency` to 1 disables
+    /// parallel downloads.
+    pub fn with_ranges(mut self, concurrency: usize, range_size: u64) -> Self {
+        self.concurrency = concurrency.max(1);
+        self.range_size = range_size.max(1);
+        self
+    }
+}
+
 impl TransferProvider<TransferData, Error> for HttpTransferProvider {
     fn schemes(&self) -> Vec<&'static str> {
         vec!["http", "https"]
@@ -58,6 +76,8 @@ impl TransferProvider<TransferData, Error> for HttpTransferProvider {
 
         let url = url.clone();
         let state = ctx.state.clone();
+        let concurrency = self.concurrency;
+        let range_size = self.range_size;
 
         spawn_local(async move {
             let fut = async move {
@@ -66,9 +86,75 @@ impl TransferProvider<TransferData, Error> for HttpTransferProvider {
                     let _ = tx.send(Ok(TransferData::Bytes(Bytes::new()))).await;
                     return Ok(());
                 }
-                DownloadRequest::get(url, &state)
-                    .send()
-                    .await?
+
+                if concurrency > 1 {
+                    // The first range doubles as a probe: a `206` response carries the total
+                    // size, while servers not supporting ranges reply with the whole resource.
+                    let offset = state.offset();
+                    let first =
+                        DownloadRequest::range(url.clone(), offset, offset + range_size - 1)
+                            .send()
+                            .await?;
+                    if first.status() == StatusCode::RANGE_NOT_SATISFIABLE && offset == 0 {
+                        log::debug!("Empty HTTP resource");
+                        return Ok(());
+                    }
+                    let mut first = first.http_err()?;
+
+                    let range = first
+                        .headers()
+                        .get(header::CONTENT_RANGE)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(content_range);
+                    return match (first.status(), range) {
+                        (StatusCode::PARTIAL_CONTENT, Some((start, size))) if start == offset => {
+                            state.set_size(Some(size));
+                            let head = first.body().limit(range_size as usize).await?;
+                            let next = offset + head.len() as u64;
+                            log::debug!(
+                                "Downloading {} B from offset {} using {} parallel requests",
+                                size,
+                                offset,
+                                concurrency
+                            );
+
+                            // Ranges are downloaded concurrently but passed to the sink in order,
+                            // so the sink's offset always marks the end of completed ranges and
+                            // an interrupted transfer resumes from there.
+                            let rest = futures::stream::iter(byte_ranges(next, size, range_size))
+                                .map(|(start, end)| download_range(url.clone(), start, end))
+                                .buffered(concurrency);
+                            futures::stream::once(ready(Ok(head)))
+                                .chain(rest)
+                                .forward(
+                                    tx.sink_map_err(Error::from)
+                                        .with(|b| ready(Ok(Ok(TransferData::from(b))))),
+                                )
+                                .await
+                        }
+                        (StatusCode::OK, _) if offset == 0 => first
+                            .into_stream()
+                            .map_err(Error::from)
+                            .forward(
+                                tx.sink_map_err(Error::from)
+                                    .with(|b| ready(Ok(Ok(TransferData::from(b))))),
+                            )
+                            .await
+                            .map_err(Error::from),
+                        (StatusCode::OK, _) => Err(restart(&state, offset)),
+                        (status, _) => Err(HttpError::Server(format!(
+                            "range request from offset {} not honoured: {}",
+                            offset, status
+                        ))
+                        .into()),
+                    };
+                }
+
+                let response = DownloadRequest::get(url, &state).send().await?;
+                if response.status() == StatusCode::OK && state.offset() > 0 {
+                    return Err(restart(&state, state.offset()));
+                }
+                response
                     .into_stream()
                     .map_err(Error::from)
                     .forward(
@@ -149,10 +235,68 @@ impl TransferProvider<TransferData, Error> for HttpTransferProvider {
     }
 }
 
+/// Restarts a resumed download, which the server answered with the whole resource.
+/// The returned error is retried from offset 0.
+fn restart(state: &TransferState, offset: u64) -> Error {
+    log::warn!(
+        "Server ignored the range request from offset {}, restarting the download",
+        offset
+    );
+    state.restart();
+    HttpError::Server(format!("range request from offset {} not honoured", offset)).into()
+}
+
+/// Parses a `Content-Range: bytes {start}-{end}/{size}` header value into `(start, size)`
+fn content_range(value: &str) -> Option<(u64, u64)> {
+    let (range, size) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    Some((u64::from_str(start).ok()?, u64::from_str(size).ok()?))
+}
+
+/// Splits `offset..size` into inclusive byte ranges of at most `range_size` bytes
+fn byte_ranges(offset: u64, size: u64, range_size: u64) -> impl Iterator<Item = (u64, u64)> {
+    (offset..size)
+        .step_by(range_size as usize)
+        .map(move |start| (start, (start + range_size).min(size) - 1))
+}
+
+async fn download_range(url: Url, start: u64, end: u64) -> Result<Bytes, Error> {
+    let expected = end - start + 1;
+    let mut response = DownloadRequest::range(url, start, end)
+        .send()
+        .await?
+        .http_err()?;
+
+    if response.status() != StatusCode::PARTIAL_CONTENT {
+        return Err(HttpError::Server(format!(
+            "range request {}-{} not honoured: {}",
+            start,
+            end,
+            response.status()
+        ))
+        .into());
+    }
+
+    let bytes = response.body().limit(expected as usize).await?;
+    if bytes.len() as u64 != expected {
+        return Err(HttpError::Server(format!(
+            "incomplete range {}-{}: received {} B",
+            start,
+            end,
+            bytes.len()
+        ))
+        .into());
+    }
+
+    log::trace!("Downloaded range {}-{}", start, end);
+    Ok(bytes)
+}
+
 struct DownloadRequest {
     method: Method,
     url: Url,
     offset: u64,
+    end: Option<u64>,
     max_redirects: usize,
 }
 
@@ -162,6 +306,18 @@ impl DownloadRequest {
             method: Method::GET,
             url,
             offset: state.offset(),
+            end: None,
+            max_redirects: 10,
+        }
+    }
+
+    /// Requests an inclusive byte range
+    pub fn range(url: Url, start: u64, end: u64) -> Self {
+        Self {
+            method: Method::GET,
+            url,
+            offset: start,
+            end: Some(end),
             max_redirects: 10,
         }
     }
@@ -171,6 +327,7 @@ impl DownloadRequest {
             method: Method::HEAD,
             url,
             offset: 0,
+            end: None,
             max_redirects: 10,
         }
     }
@@ -181,9 +338,10 @@ impl DownloadRequest {
         let mut redirects = self.max_redirects;
         let mut url = self.url.to_string();
 
-        let range = match self.offset {
-            0 => None,
-            off => Some(format!("bytes={}-", off)),
+        let range = match (self.offset, self.end) {
+            (0, None) => None,
+            (off, None) => Some(format!("bytes={}-", off)),
+            (off, Some(end)) => Some(format!("bytes={}-{}", off, end)),
         };
 
         loop {
@@ -228,7 +386,7 @@ impl DownloadRequest {
     }
 }
 
-trait HttpErr<T>
+pub(crate) trait HttpErr<T>
 where
     Self: Sized,
 {
@@ -280,3 +438,30 @@ impl<'a> HttpErr<LocalBoxFuture<'a, Result<awc::ConnectResponse, Error>>> for Se
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{byte_ranges, content_range};
+
+    #[test]
+    fn test_content_range() {
+        assert_eq!(content_range("bytes 0-99/1000"), Some((0, 1000)));
+        assert_eq!(content_range("bytes 100-199/200"), Some((100, 200)));
+        assert_eq!(content_range("bytes 0-99/*"), None);
+        assert_eq!(content_range("bytes */1000"), None);
+    }
+
+    #[test]
+    fn test_byte_ranges() {
+        assert_eq!(
+            byte_ranges(0, 10, 4).collect::<Vec<_>>(),
+            vec![(0, 3), (4, 7), (8, 9)]
+        );
+        assert_eq!(
+            byte_ranges(5, 13, 4).collect::<Vec<_>>(),
+            vec![(5, 8), (9, 12)]
+        );
+        assert_eq!(byte_ranges(0, 1, 4).collect::<Vec<_>>(), vec![(0, 0)]);
+        assert_eq!(byte_ranges(10, 10, 4).count(), 0);
+    }
+}
@@ -1,5 +1,6 @@
 mod archive;
 pub mod cache;
+mod checkpoint;
 mod container;
 pub mod error;
 mod file;
@@ -9,6 +10,7 @@ mod http;
 mod location;
 mod progress;
 mod retry;
+mod s3;
 pub mod transfer;
 mod traverse;
 
@@ -28,6 +30,7 @@ use url::Url;
 use crate::error::Error;
 
 pub use crate::archive::{archive, extract, ArchiveFormat};
+pub use crate::checkpoint::{Checkpoint, CHECKPOINT_INTERVAL};
 pub use crate::container::ContainerTransferProvider;
 pub use crate::file::{DirTransferProvider, FileTransferProvider};
 pub use crate::gftp::GftpTransferProvider;
@@ -35,7 +38,8 @@ pub use crate::http::HttpTransferProvider;
 pub use crate::location::{TransferUrl, UrlExt};
 pub use crate::progress::{wrap_sink_with_progress_reporting, wrap_stream_with_progress_reporting};
 pub use crate::retry::Retry;
-pub use crate::traverse::PathTraverse;
+pub use crate::s3::{S3Config, S3Credentials, S3TransferProvider};
+pub use crate::traverse::{GlobFilter, PathTraverse};
 
 use crate::hash::with_hash_stream;
 use ya_client_model::activity::TransferArgs;
@@ -348,6 +352,13 @@ impl TransferState {
         r.size = r.size.max(size);
     }
 
+    /// Discards the progress, so that the transfer starts over from the beginning
+    pub fn restart(&self) {
+        let mut r = self.inner.borrow_mut();
+        r.offset = 0;
+        r.size = None;
+    }
+
     pub fn retry(&self, count: i32) {
         self.retry_with(Retry::new(count));
     }
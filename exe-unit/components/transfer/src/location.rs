@@ -8,6 +8,8 @@ use crate::error::Error;
 
 pub trait UrlExt {
     fn path_decoded(&self) -> String;
+    /// Copy of the URL with credentials removed from the userinfo, safe to log
+    fn redacted(&self) -> Url;
 }
 
 impl UrlExt for url::Url {
@@ -16,6 +18,15 @@ impl UrlExt for url::Url {
             .decode_utf8_lossy()
             .to_string()
     }
+
+    fn redacted(&self) -> Url {
+        let mut url = self.clone();
+        if !url.username().is_empty() || url.password().is_some() {
+            let _ = url.set_username("***");
+            let _ = url.set_password(None);
+        }
+        url
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -98,7 +109,7 @@ fn parse_hash(url: &str) -> Result<(Option<TransferHash>, &str), Error> {
 
 #[cfg(test)]
 mod test {
-    use super::TransferUrl;
+    use super::{TransferUrl, UrlExt};
 
     macro_rules! should_fail {
         ($str:expr) => {
@@ -171,6 +182,15 @@ mod test {
         should_succeed!("http:location.com");
     }
 
+    #[test]
+    fn redacted() {
+        let url = url::Url::parse("s3://access:secret@bucket/key").unwrap();
+        assert_eq!(url.redacted().as_str(), "s3://***@bucket/key");
+
+        let url = url::Url::parse("http://location.com/file").unwrap();
+        assert_eq!(url.redacted(), url);
+    }
+
     #[test]
     #[cfg(windows)]
     fn fallback_to_file_on_windows_path() {
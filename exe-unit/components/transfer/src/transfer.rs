@@ -7,12 +7,13 @@ use actix::prelude::*;
 use futures::future::Abortable;
 use url::Url;
 
-use crate::cache::{Cache, CachePath};
+use crate::cache::{Cache, CacheLease, CachePath};
 use crate::error::Error;
 use crate::error::Error as TransferError;
 use crate::{
-    transfer_with, ContainerTransferProvider, FileTransferProvider, GftpTransferProvider,
-    HttpTransferProvider, Retry, TransferContext, TransferData, TransferProvider, TransferUrl,
+    transfer_with, Checkpoint, ContainerTransferProvider, FileTransferProvider,
+    GftpTransferProvider, HttpTransferProvider, Retry, S3TransferProvider, TransferContext,
+    TransferData, TransferProvider, TransferUrl, UrlExt,
 };
 
 use ya_client_model::activity::TransferArgs;
@@ -78,6 +79,8 @@ pub struct TransferServiceContext {
 
     pub deploy_retry: Option<Retry>,
     pub transfer_retry: Option<Retry>,
+    /// Maximum size of the image cache in bytes. Unlimited when not set.
+    pub cache_budget: Option<u64>,
 }
 
 /// Handles resources transfers.
@@ -91,18 +94,21 @@ pub struct TransferService {
     transfer_retry: Retry,
 
     abort_handles: Rc<RefCell<HashSet<Abort>>>,
+    /// Keeps deployed images in cache for the lifetime of the service
+    leases: Rc<RefCell<Vec<CacheLease>>>,
 }
 
 impl TransferService {
     pub fn new(ctx: TransferServiceContext) -> TransferService {
         TransferService {
             providers: Self::default_providers(),
-            cache: Cache::new(ctx.cache_dir),
+            cache: Cache::new(ctx.cache_dir, ctx.cache_budget),
             work_dir: ctx.work_dir,
             task_package: ctx.task_package,
             deploy_retry: ctx.deploy_retry.unwrap_or_default(),
             transfer_retry: ctx.transfer_retry.unwrap_or_default(),
             abort_handles: Default::default(),
+            leases: Default::default(),
         }
     }
 
@@ -133,6 +139,7 @@ impl TransferService {
         let provider_vec: Vec<Rc<dyn TransferProvider<TransferData, TransferError>>> = vec![
             Rc::new(GftpTransferProvider::default()),
             Rc::new(HttpTransferProvider::default()),
+            Rc::new(S3TransferProvider::default()),
         ];
         for provider in provider_vec {
             for scheme in provider.schemes() {
@@ -194,12 +201,12 @@ impl TransferService {
         let ctx = TransferContext::default();
         ctx.state.retry_with(self.deploy_retry.clone());
 
-        // Using partially downloaded image from previous executions could speed up deploy
-        // process, but it comes with the cost: If image under URL changed, Requestor will get
-        // error on the end. This can result with Provider being perceived as unreliable.
+        // Temporary path is derived from both the URL and the expected hash, so a checkpointed
+        // partial image left by a crashed execution is resumed. If the image under the URL has
+        // changed, hash verification fails and the partial file is removed.
         //
-        // For this reason it is better to use only fully downloaded images that are already in cache.
-        if path_tmp.exists() {
+        // Partial files without a checkpoint are of unknown state and are always discarded.
+        if path_tmp.exists() && !Checkpoint::path(&path_tmp).exists() {
             log::info!(
                 "Removing temporary file: {} from previous executions",
                 path_tmp.display()
@@ -207,10 +214,16 @@ impl TransferService {
             std::fs::remove_file(&path_tmp).ok();
         }
 
+        let lease = actor_try!(self.cache.lease(&src_name));
+        let leases = self.leases.clone();
+        let cache = self.cache.clone();
+
         let handles = self.abort_handles.clone();
         let fut = async move {
             if path.exists() {
                 log::info!("Deploying cached image: {:?}", path);
+                cache.touch(&src_name)?;
+                leases.borrow_mut().push(lease);
                 return Ok(Some(path));
             }
 
@@ -226,6 +239,7 @@ impl TransferService {
                         .map_err(|err| {
                             if let TransferError::InvalidHashError { .. } = err {
                                 let _ = std::fs::remove_file(&path_tmp);
+                                let _ = std::fs::remove_file(Checkpoint::path(&path_tmp));
                             }
                             err
                         })?,
@@ -233,7 +247,11 @@ impl TransferService {
             }?;
 
             move_file(&path_tmp, &path).await?;
-            log::info!("Deployment from {:?} finished", src_url.url);
+            leases.borrow_mut().push(lease);
+            if let Err(e) = cache.insert(&src_name) {
+                log::warn!("Unable to update the image cache index: {}", e);
+            }
+            log::info!("Deployment from {:?} finished", src_url.url.redacted());
 
             Ok(Some(path))
         };
@@ -267,7 +285,7 @@ impl Handler<DeployImage> for TransferService {
         let src_name = actor_try!(Cache::name(&src_url));
         let path = self.cache.to_final_path(&src_name).to_path_buf();
 
-        log::info!("Deploying from {:?} to {:?}", src_url.url, path);
+        log::info!("Deploying from {:?} to {:?}", src_url.url.redacted(), path);
 
         #[cfg(not(feature = "sgx"))]
         return self.deploy_no_sgx(src_url, src_name, path);
@@ -286,14 +304,18 @@ impl Handler<TransferResource> for TransferService {
         let src = actor_try!(self.provider(&src_url));
         let dst = actor_try!(self.provider(&dst_url));
 
-        let ctx = TransferContext::default();
+        let ctx = TransferContext::from(msg.args);
         ctx.state.retry_with(self.transfer_retry.clone());
 
         let (abort, reg) = Abort::new_pair();
 
         let handles = self.abort_handles.clone();
         let fut = async move {
-            log::info!("Transferring {:?} to {:?}", src_url.url, dst_url.url);
+            log::info!(
+                "Transferring {:?} to {:?}",
+                src_url.url.redacted(),
+                dst_url.url.redacted()
+            );
             {
                 let retry = transfer_with(src, &src_url, dst, &dst_url, &ctx);
 
@@ -304,8 +326,8 @@ impl Handler<TransferResource> for TransferService {
             }
             log::info!(
                 "Transfer of {:?} to {:?} finished",
-                src_url.url,
-                dst_url.url
+                src_url.url.redacted(),
+                dst_url.url.redacted()
             );
             Ok(())
         };
@@ -60,10 +60,7 @@ where
         R: AsRef<Path>,
     {
         let path = normalize_path(path)?;
-        let (incl, excl) = match pattern_builder {
-            Some(b) => b.build()?,
-            _ => FileSet::Pattern(SetEntry::Single("**/*".into())).build()?,
-        };
+        let filter = GlobFilter::new(pattern_builder)?;
 
         let it = self
             .map(move |p| {
@@ -71,13 +68,35 @@ where
                 Ok::<_, StripPrefixError>((p, relative))
             })
             .filter_map(Result::ok)
-            .filter(move |(_, r)| excl.is_match(r).not())
-            .filter(move |(_, r)| incl.is_match(r))
+            .filter(move |(_, r)| filter.is_match(r))
             .map(|(p, _)| p);
         Ok(Box::new(it))
     }
 }
 
+/// Include and exclude glob patterns, matched against relative paths
+#[derive(Clone, Debug)]
+pub struct GlobFilter {
+    incl: GlobSet,
+    excl: GlobSet,
+}
+
+impl GlobFilter {
+    /// Builds the filter from patterns, including all paths when none are provided
+    pub fn new<B: PatternBuilder>(pattern_builder: Option<&B>) -> Result<Self, Error> {
+        let (incl, excl) = match pattern_builder {
+            Some(b) => b.build()?,
+            _ => FileSet::Pattern(SetEntry::Single("**/*".into())).build()?,
+        };
+        Ok(GlobFilter { incl, excl })
+    }
+
+    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        self.excl.is_match(path).not() && self.incl.is_match(path)
+    }
+}
+
 pub trait PatternBuilder {
     fn add_patterns<'l>(&'l self, incl: &mut Vec<&'l str>, excl: &mut Vec<&'l str>);
 
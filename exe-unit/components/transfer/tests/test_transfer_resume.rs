@@ -17,7 +17,7 @@ use ya_framework_basic::temp_dir;
 use ya_runtime_api::deploy::ContainerVolume;
 use ya_transfer::error::{Error, HttpError};
 use ya_transfer::transfer::{
-    AddVolumes, TransferResource, TransferService, TransferServiceContext,
+    AddVolumes, Shutdown, TransferResource, TransferService, TransferServiceContext,
 };
 use ya_transfer::*;
 
@@ -30,8 +30,12 @@ struct UnreliableHttpProvider {
 
 impl UnreliableHttpProvider {
     pub fn new(interval: u64) -> Self {
+        Self::with_inner(Default::default(), interval)
+    }
+
+    pub fn with_inner(inner: HttpTransferProvider, interval: u64) -> Self {
         Self {
-            inner: Default::default(),
+            inner,
             last_failure: Arc::new(Mutex::new(Instant::now())),
             interval: Duration::from_millis(interval),
             packets_slowdown: Duration::from_millis(10),
@@ -100,6 +104,51 @@ impl TransferProvider<TransferData, Error> for UnreliableHttpProvider {
     }
 }
 
+/// Counts the delivered bytes and fails permanently after `limit` of them,
+/// like an ExeUnit killed in the middle of a transfer
+struct InterruptedHttpProvider {
+    inner: HttpTransferProvider,
+    delivered: Arc<Mutex<u64>>,
+    limit: Option<u64>,
+}
+
+impl TransferProvider<TransferData, Error> for InterruptedHttpProvider {
+    fn schemes(&self) -> Vec<&'static str> {
+        self.inner.schemes()
+    }
+
+    fn source(&self, url: &Url, ctx: &TransferContext) -> TransferStream<TransferData, Error> {
+        let mut src = self.inner.source(url, ctx);
+        let delivered = self.delivered.clone();
+        let limit = self.limit;
+
+        src.map_inner(move |r| match r {
+            Ok(v) => {
+                let mut delivered = delivered.lock().unwrap();
+                if limit.map(|limit| *delivered >= limit).unwrap_or(false) {
+                    return Err(HttpError::Client("transfer killed".into()).into());
+                }
+                *delivered += v.as_ref().len() as u64;
+                Ok(v)
+            }
+            Err(e) => Err(e),
+        });
+        src
+    }
+
+    fn destination(&self, url: &Url, ctx: &TransferContext) -> TransferSink<TransferData, Error> {
+        self.inner.destination(url, ctx)
+    }
+
+    fn prepare_source<'a>(
+        &self,
+        url: &Url,
+        ctx: &TransferContext,
+    ) -> LocalBoxFuture<'a, Result<(), Error>> {
+        self.inner.prepare_source(url, ctx)
+    }
+}
+
 async fn transfer_with_args(
     addr: &Addr<TransferService>,
     from: &str,
@@ -178,3 +227,136 @@ async fn test_transfer_resume(ctx: &mut DroppableTestContext) -> anyhow::Result<
 
     Ok(())
 }
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_transfer_parallel_resume(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("transfer-parallel-resume")?;
+    let temp_dir = dir.path();
+
+    log::debug!("Creating directories in: {}", temp_dir.display());
+    let work_dir = temp_dir.join("work_dir");
+
+    log::debug!("Starting TransferService");
+    let mut retry = Retry::new(i32::MAX);
+    retry.backoff(1., 1.);
+    let exe_ctx = TransferServiceContext {
+        work_dir: work_dir.clone(),
+        cache_dir: temp_dir.join("cache_dir"),
+        transfer_retry: Some(retry),
+        ..TransferServiceContext::default()
+    };
+
+    let interval: u64 = 1000;
+    let inner = HttpTransferProvider::default().with_ranges(4, 64 * 1024);
+    let mut service = TransferService::new(exe_ctx);
+    service.register_provider(UnreliableHttpProvider::with_inner(inner, interval));
+
+    let addr = service.start();
+
+    let volumes = vec![ContainerVolume {
+        name: "vol-1".into(),
+        path: "/input".into(),
+    }];
+    addr.send(AddVolumes::new(volumes)).await??;
+
+    let hash = generate_file_with_hash(temp_dir, "rnd", 4096_usize, 3 * 1024);
+
+    log::debug!("Starting HTTP servers");
+    start_http(ctx, temp_dir.to_path_buf())
+        .await
+        .expect("unable to start http servers");
+
+    log::warn!("[>>] Transfer HTTP (parallel ranges) -> container");
+    tokio::time::timeout(
+        Duration::from_secs(20),
+        transfer(&addr, "http://127.0.0.1:8001/rnd", "container:/input/rnd-1"),
+    )
+    .await??;
+    verify_hash(&hash, work_dir.join("vol-1"), "rnd-1");
+    log::warn!("Checksum verified");
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_transfer_restart(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("transfer-restart")?;
+    let temp_dir = dir.path();
+
+    log::debug!("Creating directories in: {}", temp_dir.display());
+    let work_dir = temp_dir.join("work_dir");
+    let partial = work_dir.join("vol-1").join("rnd");
+
+    let total = 4096 * 3 * 1024;
+    let hash = generate_file_with_hash(temp_dir, "rnd", 4096_usize, 3 * 1024);
+
+    log::debug!("Starting HTTP servers");
+    start_http(ctx, temp_dir.to_path_buf())
+        .await
+        .expect("unable to start http servers");
+
+    let volumes = vec![ContainerVolume {
+        name: "vol-1".into(),
+        path: "/input".into(),
+    }];
+    let start_service = |limit: Option<u64>, delivered: Arc<Mutex<u64>>| {
+        let exe_ctx = TransferServiceContext {
+            work_dir: work_dir.clone(),
+            cache_dir: temp_dir.join("cache_dir"),
+            transfer_retry: Some(Retry::new(0)),
+            ..TransferServiceContext::default()
+        };
+        let mut service = TransferService::new(exe_ctx);
+        service.register_provider(InterruptedHttpProvider {
+            inner: HttpTransferProvider::default().with_ranges(4, 64 * 1024),
+            delivered,
+            limit,
+        });
+        service.start()
+    };
+
+    log::warn!("[>>] Transfer HTTP -> container (killed)");
+    let addr = start_service(Some(total * 3 / 4), Default::default());
+    addr.send(AddVolumes::new(volumes.clone())).await??;
+    assert!(
+        transfer(&addr, "http://127.0.0.1:8001/rnd", "container:/input/rnd")
+            .await
+            .is_err()
+    );
+    addr.send(Shutdown::default()).await??;
+    // Let the sink flush the last checkpoint
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let checkpoint = std::fs::read(Checkpoint::path(&partial))?;
+    let checkpoint: Checkpoint = serde_json::from_slice(&checkpoint)?;
+    assert_eq!(checkpoint.size, Some(total));
+    assert!(checkpoint.resume_offset() >= CHECKPOINT_INTERVAL);
+
+    log::warn!("[>>] Transfer HTTP -> container (restarted)");
+    let delivered = Arc::new(Mutex::new(0));
+    let addr = start_service(None, delivered.clone());
+    addr.send(AddVolumes::new(volumes)).await??;
+    tokio::time::timeout(
+        Duration::from_secs(20),
+        transfer(&addr, "http://127.0.0.1:8001/rnd", "container:/input/rnd"),
+    )
+    .await??;
+    verify_hash(&hash, work_dir.join("vol-1"), "rnd");
+    log::warn!("Checksum verified");
+
+    assert_eq!(
+        *delivered.lock().unwrap(),
+        total - checkpoint.resume_offset()
+    );
+    assert!(!Checkpoint::path(&partial).exists());
+
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use actix::{Actor, Addr};
+use futures::future::LocalBoxFuture;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use test_context::test_context;
+use url::Url;
+
+use ya_client_model::activity::TransferArgs;
+use ya_exe_unit::error::Error;
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::file::generate_file_with_hash;
+use ya_framework_basic::hash::verify_hash;
+use ya_framework_basic::log::enable_logs;
+use ya_framework_basic::server_external::{start_http, start_s3};
+use ya_framework_basic::temp_dir;
+use ya_runtime_api::deploy::ContainerVolume;
+use ya_transfer::error::{Error as TransferError, HttpError};
+use ya_transfer::transfer::{
+    AddVolumes, TransferResource, TransferService, TransferServiceContext,
+};
+use ya_transfer::{
+    Retry, S3Config, S3Credentials, S3TransferProvider, TransferContext, TransferData,
+    TransferProvider, TransferSink, TransferStream,
+};
+
+/// Interrupts the source stream periodically, forcing the transfer to resume with range reads
+struct UnreliableS3Provider {
+    inner: S3TransferProvider,
+    last_failure: Arc<Mutex<Instant>>,
+    interval: Duration,
+}
+
+impl TransferProvider<TransferData, TransferError> for UnreliableS3Provider {
+    fn schemes(&self) -> Vec<&'static str> {
+        self.inner.schemes()
+    }
+
+    fn source(
+        &self,
+        url: &Url,
+        ctx: &TransferContext,
+    ) -> TransferStream<TransferData, TransferError> {
+        let mut src = self.inner.source(url, ctx);
+        let interval = self.interval;
+        let failure = self.last_failure.clone();
+
+        src.map_inner_async(move |item| {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                item
+            })
+        });
+        src.map_inner(move |r| {
+            let mut last = failure.lock().unwrap();
+            match r {
+                Ok(_) if Instant::now() - *last >= interval => {
+                    log::info!("Triggering failure");
+                    *last = Instant::now();
+                    Err(HttpError::Io(io::Error::from(io::ErrorKind::Interrupted)).into())
+                }
+                r => r,
+            }
+        });
+        src
+    }
+
+    fn destination(
+        &self,
+        url: &Url,
+        ctx: &TransferContext,
+    ) -> TransferSink<TransferData, TransferError> {
+        self.inner.destination(url, ctx)
+    }
+
+    fn prepare_source<'a>(
+        &self,
+        url: &Url,
+        ctx: &TransferContext,
+    ) -> LocalBoxFuture<'a, Result<(), TransferError>> {
+        self.inner.prepare_source(url, ctx)
+    }
+}
+
+fn s3_config() -> anyhow::Result<S3Config> {
+    Ok(S3Config {
+        endpoint: Some(Url::parse("http://127.0.0.1:8003")?),
+        credentials: Some(S3Credentials::new("minioadmin", "minioadmin")),
+        ..S3Config::default()
+    })
+}
+
+async fn transfer(addr: &Addr<TransferService>, from: &str, to: &str) -> Result<(), Error> {
+    log::info!("Triggering transfer from {} to {}", from, to);
+
+    addr.send(TransferResource {
+        from: from.to_owned(),
+        to: to.to_owned(),
+        args: TransferArgs::default(),
+    })
+    .await??;
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_transfer_s3(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("transfer-s3")?;
+    let temp_dir = dir.path();
+
+    log::debug!("Creating directories in: {}", temp_dir.display());
+    let work_dir = temp_dir.join("work_dir");
+    let s3_dir = temp_dir.join("s3");
+    std::fs::create_dir_all(s3_dir.join("bucket"))?;
+
+    // Single part and multipart uploads
+    let small = generate_file_with_hash(temp_dir, "rnd-small", 4096_usize, 256_usize);
+    let large = generate_file_with_hash(temp_dir, "rnd-large", 1024 * 1024_usize, 12_usize);
+
+    log::debug!("Starting HTTP and S3 servers");
+    start_http(ctx, temp_dir.to_path_buf())
+        .await
+        .expect("unable to start http servers");
+    start_s3(ctx, s3_dir.clone())
+        .await
+        .expect("unable to start s3 server");
+
+    log::debug!("Starting TransferService");
+    let exe_ctx = TransferServiceContext {
+        work_dir: work_dir.clone(),
+        cache_dir: temp_dir.join("cache_dir"),
+        ..TransferServiceContext::default()
+    };
+    let mut service = TransferService::new(exe_ctx);
+    service.register_provider(S3TransferProvider::new(s3_config()?));
+    let addr = service.start();
+
+    let volumes = vec![ContainerVolume {
+        name: "vol-1".into(),
+        path: "/input".into(),
+    }];
+    addr.send(AddVolumes::new(volumes)).await??;
+
+    for (name, hash) in [("rnd-small", &small), ("rnd-large", &large)] {
+        log::warn!("[>>] Transfer HTTP -> S3 ({})", name);
+        transfer(
+            &addr,
+            &format!("http://127.0.0.1:8001/{name}"),
+            &format!("s3://bucket/data/{name}"),
+        )
+        .await?;
+        verify_hash(hash, s3_dir.join("bucket").join("data"), name);
+        log::warn!("Checksum verified");
+
+        log::warn!("[>>] Transfer S3 -> container ({})", name);
+        transfer(
+            &addr,
+            &format!("s3://bucket/data/{name}"),
+            &format!("container:/input/{name}"),
+        )
+        .await?;
+        verify_hash(hash, work_dir.join("vol-1"), name);
+        log::warn!("Checksum verified");
+    }
+
+    log::warn!("[>>] Transfer S3 -> container (missing object)");
+    assert!(
+        transfer(&addr, "s3://bucket/missing", "container:/input/missing")
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_transfer_s3_resume(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("transfer-s3-resume")?;
+    let temp_dir = dir.path();
+
+    log::debug!("Creating directories in: {}", temp_dir.display());
+    let work_dir = temp_dir.join("work_dir");
+    let s3_dir = temp_dir.join("s3");
+    let data_dir = s3_dir.join("bucket").join("data");
+    std::fs::create_dir_all(&data_dir)?;
+
+    let hash = generate_file_with_hash(&data_dir, "rnd", 4096_usize, 3 * 1024);
+
+    log::debug!("Starting S3 server");
+    start_s3(ctx, s3_dir.clone())
+        .await
+        .expect("unable to start s3 server");
+
+    log::debug!("Starting TransferService");
+    let mut retry = Retry::new(i32::MAX);
+    retry.backoff(1., 1.);
+    let exe_ctx = TransferServiceContext {
+        work_dir: work_dir.clone(),
+        cache_dir: temp_dir.join("cache_dir"),
+        transfer_retry: Some(retry),
+        ..TransferServiceContext::default()
+    };
+    let mut service = TransferService::new(exe_ctx);
+    service.register_provider(UnreliableS3Provider {
+        inner: S3TransferProvider::new(s3_config()?),
+        last_failure: Arc::new(Mutex::new(Instant::now())),
+        interval: Duration::from_millis(2000),
+    });
+    let addr = service.start();
+
+    let volumes = vec![ContainerVolume {
+        name: "vol-1".into(),
+        path: "/input".into(),
+    }];
+    addr.send(AddVolumes::new(volumes)).await??;
+
+    log::warn!("[>>] Transfer S3 -> container (interrupted)");
+    tokio::time::timeout(
+        Duration::from_secs(20),
+        transfer(&addr, "s3://bucket/data/rnd", "container:/input/rnd"),
+    )
+    .await??;
+    verify_hash(&hash, work_dir.join("vol-1"), "rnd");
+    log::warn!("Checksum verified");
+
+    Ok(())
+}
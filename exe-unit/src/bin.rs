@@ -10,6 +10,7 @@ use ya_service_bus::RpcEnvelope;
 
 use ya_core_model::activity;
 use ya_exe_unit::agreement::Agreement;
+use ya_exe_unit::dns::DnsConfig;
 use ya_exe_unit::logger::*;
 use ya_exe_unit::manifest::ManifestContext;
 use ya_exe_unit::message::{GetState, GetStateResponse, Register};
@@ -17,7 +18,7 @@ use ya_exe_unit::runtime::process::RuntimeProcess;
 use ya_exe_unit::service::counters;
 use ya_exe_unit::service::signal::SignalMonitor;
 use ya_exe_unit::state::Supervision;
-use ya_exe_unit::{ExeUnit, ExeUnitContext};
+use ya_exe_unit::{ExeUnit, ExeUnitContext, InetLimits};
 use ya_transfer::transfer::TransferService;
 use ya_utils_path::normalize_path;
 
@@ -121,6 +122,14 @@ struct RunArgs {
     /// Common cache directory
     #[structopt(long, short)]
     cache_dir: PathBuf,
+    /// Maximum size of the common cache directory in bytes
+    #[structopt(long, env = "EXE_UNIT_CACHE_BUDGET")]
+    cache_budget: Option<u64>,
+    /// DNS resolver configuration file path
+    #[structopt(long, env = "EXE_UNIT_DNS_CONFIG")]
+    dns_config: Option<PathBuf>,
+    #[structopt(flatten)]
+    inet_limits: InetLimits,
 }
 
 fn create_path(path: &PathBuf) -> anyhow::Result<PathBuf> {
@@ -288,8 +297,13 @@ async fn run() -> anyhow::Result<()> {
 
     log::info!("Attempting to read app manifest ..");
 
-    let manifest_ctx =
-        ManifestContext::try_new(&agreement.inner).context("Invalid app manifest")?;
+    let dns_config = match args.dns_config {
+        Some(ref path) => DnsConfig::from_file(path)?,
+        None => Default::default(),
+    };
+    let manifest_ctx = ManifestContext::try_new(&agreement.inner)
+        .context("Invalid app manifest")?
+        .with_dns_config(dns_config);
     agreement.task_package = manifest_ctx
         .payload()
         .or_else(|| agreement.task_package.take());
@@ -308,6 +322,8 @@ async fn run() -> anyhow::Result<()> {
         agreement,
         work_dir,
         cache_dir,
+        cache_budget: args.cache_budget,
+        inet_limits: args.inet_limits.clone(),
         runtime_args: cli.runtime_arg.clone(),
         acl: Default::default(),
         credentials: None,
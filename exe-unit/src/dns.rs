@@ -1,17 +1,110 @@
-use std::collections::HashSet;
-use std::net::IpAddr;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
 use std::str::FromStr;
-
-#[cfg(test)]
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
 use trust_dns_resolver::config;
+use trust_dns_resolver::error::ResolveErrorKind;
+use trust_dns_resolver::proto::op::{Message, MessageType, OpCode, ResponseCode};
+use trust_dns_resolver::proto::rr::{RData, Record, RecordType};
 use trust_dns_resolver::TokioAsyncResolver;
 
+use ya_counters::{Counter, CounterData};
+
+/// Resolver configuration, read from a JSON file provided by the Provider agent.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DnsConfig {
+    /// Upstream name servers. `stable-dns.dev.golem.network` is used when empty.
+    pub servers: Vec<NameServer>,
+    /// Host name to IP addresses mapping, consulted before upstream servers.
+    /// Applies to the manifest's URLs and to runtime's DNS queries.
+    pub hosts: HashMap<String, Vec<IpAddr>>,
+    /// Maximum number of cached responses.
+    pub cache_size: Option<usize>,
+    /// Lower bound of cached record TTLs, in seconds.
+    pub min_ttl: Option<u64>,
+    /// Upper bound of cached record TTLs, in seconds.
+    pub max_ttl: Option<u64>,
+}
+
+impl DnsConfig {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read DNS config {}", path.display()))?;
+        let config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("invalid DNS config {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        for server in &self.servers {
+            if server.protocol.is_encrypted() && server.tls_name.is_none() {
+                anyhow::bail!("name server {} requires tlsName", server.address);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameServer {
+    pub address: SocketAddr,
+    #[serde(default)]
+    pub protocol: DnsProtocol,
+    /// Name verified against server's TLS certificate, required for `tls` and `https`.
+    pub tls_name: Option<String>,
+}
+
+impl From<&NameServer> for config::NameServerConfig {
+    fn from(server: &NameServer) -> Self {
+        let protocol = match server.protocol {
+            DnsProtocol::Udp => config::Protocol::Udp,
+            DnsProtocol::Tcp => config::Protocol::Tcp,
+            DnsProtocol::Tls => config::Protocol::Tls,
+            DnsProtocol::Https => config::Protocol::Https,
+        };
+        let mut config = config::NameServerConfig::new(server.address, protocol);
+        config.tls_dns_name = server.tls_name.clone();
+        config
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsProtocol {
+    #[default]
+    Udp,
+    Tcp,
+    /// DNS-over-TLS
+    Tls,
+    /// DNS-over-HTTPS
+    Https,
+}
+
+impl DnsProtocol {
+    fn is_encrypted(&self) -> bool {
+        matches!(self, Self::Tls | Self::Https)
+    }
+}
+
+/// TTL of answers built from the static hosts map, in seconds.
+const STATIC_HOSTS_TTL: u32 = 60;
+/// Largest UDP response accepted by clients not advertising a buffer size.
+const UDP_PAYLOAD_SIZE: u16 = 512;
+
 #[derive(Clone)]
 pub struct StableResolver {
-    stable_dns: IpAddr,
+    hosts: Arc<HashMap<String, HashSet<IpAddr>>>,
     resolver: TokioAsyncResolver,
+    stats: Arc<ResolverStats>,
 }
 
 impl StableResolver {
@@ -19,15 +112,87 @@ impl StableResolver {
         if let Ok(ip_addr) = IpAddr::from_str(host_name) {
             return Ok(HashSet::from([ip_addr]));
         }
+        if let Some(ips) = self.hosts.get(&normalize_host(host_name)) {
+            self.stats.static_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(ips.clone());
+        }
         log::debug!("Resolving IP addresses of '{}'", host_name);
 
-        let response = self.resolver.lookup_ip(host_name).await?;
+        self.stats.lookups.fetch_add(1, Ordering::Relaxed);
+        let response = self.resolver.lookup_ip(host_name).await.map_err(|e| {
+            self.stats.failures.fetch_add(1, Ordering::Relaxed);
+            e
+        })?;
 
         Ok(response.into_iter().collect())
     }
 
-    pub fn stable_dns(&self) -> IpAddr {
-        self.stable_dns
+    /// Answers a plain DNS query of the runtime the same way host names of the
+    /// manifest are resolved: with static hosts first, then with upstream servers.
+    pub async fn answer(&self, query: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let request = Message::from_vec(query)?;
+        let mut response = Message::new();
+        response
+            .set_id(request.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(request.op_code())
+            .set_recursion_desired(request.recursion_desired())
+            .set_recursion_available(true);
+
+        let query = match request.queries() {
+            [query] if request.op_code() == OpCode::Query => query.clone(),
+            _ => {
+                response.set_response_code(ResponseCode::NotImp);
+                return Ok(response.to_vec()?);
+            }
+        };
+        response.add_query(query.clone());
+
+        if let Some(ips) = self.hosts.get(&normalize_host(&query.name().to_utf8())) {
+            self.stats.static_hits.fetch_add(1, Ordering::Relaxed);
+            let answers = ips.iter().filter_map(|ip| {
+                let rdata = match (ip, query.query_type()) {
+                    (IpAddr::V4(ip), RecordType::A) => RData::A(*ip),
+                    (IpAddr::V6(ip), RecordType::AAAA) => RData::AAAA(*ip),
+                    _ => return None,
+                };
+                Some(Record::from_rdata(
+                    query.name().clone(),
+                    STATIC_HOSTS_TTL,
+                    rdata,
+                ))
+            });
+            response.add_answers(answers);
+        } else {
+            log::debug!("Resolving runtime's DNS query {}", query);
+
+            self.stats.lookups.fetch_add(1, Ordering::Relaxed);
+            match self
+                .resolver
+                .lookup(query.name().clone(), query.query_type())
+                .await
+            {
+                Ok(lookup) => {
+                    response.add_answers(lookup.records().iter().cloned());
+                }
+                Err(e) => {
+                    self.stats.failures.fetch_add(1, Ordering::Relaxed);
+                    let code = match e.kind() {
+                        ResolveErrorKind::NoRecordsFound { response_code, .. } => *response_code,
+                        _ => ResponseCode::ServFail,
+                    };
+                    response.set_response_code(code);
+                }
+            }
+        }
+
+        let bytes = response.to_vec()?;
+        if bytes.len() > request.max_payload().max(UDP_PAYLOAD_SIZE) as usize {
+            response.take_answers();
+            response.set_truncated(true);
+            return Ok(response.to_vec()?);
+        }
+        Ok(bytes)
     }
 
     #[cfg(test)]
@@ -36,6 +201,69 @@ impl StableResolver {
     }
 }
 
+/// Resolution statistics of the activity's resolvers.
+#[derive(Debug, Default)]
+pub struct ResolverStats {
+    lookups: AtomicU64,
+    static_hits: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl ResolverStats {
+    pub fn is_empty(&self) -> bool {
+        self.lookups.load(Ordering::Relaxed) == 0 && self.static_hits.load(Ordering::Relaxed) == 0
+    }
+}
+
+impl std::fmt::Display for ResolverStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "lookups: {}, static hosts: {}, failures: {}",
+            self.lookups.load(Ordering::Relaxed),
+            self.static_hits.load(Ordering::Relaxed),
+            self.failures.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Reports one of activity's [`ResolverStats`] values as a usage counter.
+pub struct DnsCounter {
+    stats: Arc<ResolverStats>,
+    value: fn(&ResolverStats) -> &AtomicU64,
+}
+
+impl DnsCounter {
+    /// Host names resolved with upstream servers
+    pub const LOOKUPS_ID: &'static str = "golem.usage.dns_lookups";
+    /// Failed upstream resolutions
+    pub const FAILURES_ID: &'static str = "golem.usage.dns_failures";
+
+    pub fn lookups(stats: Arc<ResolverStats>) -> Self {
+        DnsCounter {
+            stats,
+            value: |stats| &stats.lookups,
+        }
+    }
+
+    pub fn failures(stats: Arc<ResolverStats>) -> Self {
+        DnsCounter {
+            stats,
+            value: |stats| &stats.failures,
+        }
+    }
+}
+
+impl Counter for DnsCounter {
+    fn frame(&mut self) -> ya_counters::Result<CounterData> {
+        Ok((self.value)(&self.stats).load(Ordering::Relaxed) as CounterData)
+    }
+
+    fn peak(&mut self) -> ya_counters::Result<CounterData> {
+        self.frame()
+    }
+}
+
 #[cfg(test)]
 async fn google_resolver() -> anyhow::Result<StableResolver> {
     let mut options: config::ResolverOpts = Default::default();
@@ -43,32 +271,93 @@ async fn google_resolver() -> anyhow::Result<StableResolver> {
     options.cache_size = 0;
     let config = config::ResolverConfig::default();
     let resolver = TokioAsyncResolver::tokio(config, options)?;
-    let stable_dns = config::GOOGLE_IPS[0];
-
     Ok(StableResolver {
-        stable_dns,
+        hosts: Default::default(),
         resolver,
+        stats: Default::default(),
     })
 }
 
-pub async fn resolver() -> anyhow::Result<StableResolver> {
-    let default_resolver = TokioAsyncResolver::tokio(Default::default(), Default::default())?;
-    let response = default_resolver
-        .lookup_ip("stable-dns.dev.golem.network")
-        .await?;
-    let stable_dns = response.into_iter().next().unwrap_or(config::GOOGLE_IPS[0]);
+/// Creates a resolver, which records resolutions in activity's `stats`.
+pub async fn resolver(
+    dns_config: &DnsConfig,
+    stats: Arc<ResolverStats>,
+) -> anyhow::Result<StableResolver> {
+    let mut options = config::ResolverOpts::default();
+    if let Some(cache_size) = dns_config.cache_size {
+        options.cache_size = cache_size;
+    }
+    options.positive_min_ttl = dns_config.min_ttl.map(Duration::from_secs);
+    options.positive_max_ttl = dns_config.max_ttl.map(Duration::from_secs);
+
     let mut config = config::ResolverConfig::new();
-    config.add_name_server(config::NameServerConfig::new(
-        (stable_dns, 53).into(),
-        trust_dns_resolver::config::Protocol::Udp,
-    ));
-    let resolver = TokioAsyncResolver::tokio(config, Default::default())?;
+    if dns_config.servers.is_empty() {
+        let default_resolver = TokioAsyncResolver::tokio(Default::default(), Default::default())?;
+        let response = default_resolver
+            .lookup_ip("stable-dns.dev.golem.network")
+            .await?;
+        let stable_dns = response.into_iter().next().unwrap_or(config::GOOGLE_IPS[0]);
+        config.add_name_server(config::NameServerConfig::new(
+            (stable_dns, DNS_PORT).into(),
+            trust_dns_resolver::config::Protocol::Udp,
+        ));
+    } else {
+        dns_config
+            .servers
+            .iter()
+            .for_each(|server| config.add_name_server(server.into()));
+    }
+
+    let hosts = dns_config
+        .hosts
+        .iter()
+        .map(|(host, ips)| (normalize_host(host), ips.iter().cloned().collect()))
+        .collect();
+
+    let resolver = TokioAsyncResolver::tokio(config, options)?;
     Ok(StableResolver {
-        stable_dns,
+        hosts: Arc::new(hosts),
         resolver,
+        stats,
     })
 }
 
+/// Serves runtime's plain DNS queries on a local UDP socket, so that they are
+/// answered with the activity's resolver regardless of its upstream protocols.
+pub async fn spawn_forwarder(resolver: Arc<StableResolver>) -> anyhow::Result<SocketAddr> {
+    let socket = Arc::new(tokio::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await?);
+    let addr = socket.local_addr()?;
+    log::debug!("DNS forwarder listening on {}", addr);
+
+    tokio::task::spawn_local(async move {
+        let mut buf = vec![0u8; u16::MAX as usize];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    log::warn!("DNS forwarder stopped: {}", e);
+                    break;
+                }
+            };
+            let query = buf[..len].to_vec();
+            let (socket, resolver) = (socket.clone(), resolver.clone());
+            tokio::task::spawn_local(async move {
+                match resolver.answer(&query).await {
+                    Ok(response) => {
+                        let _ = socket.send_to(&response, peer).await;
+                    }
+                    Err(e) => log::debug!("Invalid DNS query from {}: {}", peer, e),
+                }
+            });
+        }
+    });
+    Ok(addr)
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_end_matches('.').to_lowercase()
+}
+
 pub const DNS_PORT: u16 = 53;
 
 pub fn dns_servers() -> impl Iterator<Item = IpAddr> {
@@ -87,7 +376,9 @@ pub fn dns_servers() -> impl Iterator<Item = IpAddr> {
 #[actix_rt::test]
 async fn test_resolver() {
     let name = "accounts.google.com";
-    let r = resolver().await.unwrap();
+    let r = resolver(&Default::default(), Default::default())
+        .await
+        .unwrap();
     let ac = r.ips(name).await.unwrap();
     for _i in 1..5 {
         actix_rt::time::sleep(Duration::from_secs(30)).await;
@@ -112,3 +403,81 @@ async fn test_fail_resolver() {
         assert_eq!(ac, ac2);
     }
 }
+
+#[cfg(test)]
+#[actix_rt::test]
+async fn test_static_hosts() {
+    let config: DnsConfig = serde_json::from_str(
+        r#"{
+            "servers": [
+                { "address": "127.0.0.1:53" },
+                { "address": "1.1.1.1:853", "protocol": "tls", "tlsName": "cloudflare-dns.com" }
+            ],
+            "hosts": { "Repo.Example.COM.": ["10.0.0.1", "10.0.0.2"] }
+        }"#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+
+    let stats = Arc::new(ResolverStats::default());
+    let r = resolver(&config, stats.clone()).await.unwrap();
+
+    let ips = r.ips("repo.example.com").await.unwrap();
+    assert_eq!(
+        ips,
+        HashSet::from([IpAddr::from([10, 0, 0, 1]), IpAddr::from([10, 0, 0, 2])])
+    );
+
+    // runtime's queries use static hosts as well
+    let mut query = Message::new();
+    query
+        .set_id(7)
+        .add_query(trust_dns_resolver::proto::op::Query::query(
+            "repo.example.com.".parse().unwrap(),
+            RecordType::A,
+        ));
+    let response = Message::from_vec(&r.answer(&query.to_vec().unwrap()).await.unwrap()).unwrap();
+    assert_eq!(response.id(), 7);
+    assert_eq!(response.message_type(), MessageType::Response);
+    let answers = response
+        .answers()
+        .iter()
+        .filter_map(|record| record.data().and_then(RData::to_ip_addr))
+        .collect::<HashSet<_>>();
+    assert_eq!(answers, ips);
+
+    // static hosts are not reported as lookups
+    assert_eq!(stats.static_hits.load(Ordering::Relaxed), 2);
+    assert_eq!(DnsCounter::lookups(stats.clone()).frame().unwrap(), 0.);
+    assert_eq!(DnsCounter::failures(stats).frame().unwrap(), 0.);
+}
+
+#[cfg(test)]
+#[actix_rt::test]
+async fn test_stats_per_activity() {
+    let config: DnsConfig =
+        serde_json::from_str(r#"{ "servers": [{ "address": "127.0.0.1:53" }] }"#).unwrap();
+    let (stats, other) = (Arc::new(ResolverStats::default()), Arc::default());
+    let r = resolver(&config, stats.clone()).await.unwrap();
+    let _ = resolver(&config, Arc::clone(&other)).await.unwrap();
+
+    // literal addresses are not resolved at all
+    r.ips("10.0.0.1").await.unwrap();
+    assert!(stats.is_empty());
+
+    stats.lookups.fetch_add(2, Ordering::Relaxed);
+    let mut counter = DnsCounter::lookups(stats.clone());
+    assert_eq!(counter.frame().unwrap(), 2.);
+    assert!(!stats.is_empty());
+    assert!(other.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_tls_name_required() {
+    let config: DnsConfig = serde_json::from_str(
+        r#"{ "servers": [{ "address": "1.1.1.1:443", "protocol": "https" }] }"#,
+    )
+    .unwrap();
+    assert!(config.validate().is_err());
+}
@@ -48,7 +48,8 @@ pub mod runtime;
 pub mod service;
 pub mod state;
 
-mod dns;
+pub mod dns;
+pub use network::shaping::InetLimits;
 pub type Result<T> = std::result::Result<T, Error>;
 
 lazy_static::lazy_static! {
@@ -449,6 +450,10 @@ impl<R: Runtime> Actor for ExeUnit<R> {
     }
 
     fn stopped(&mut self, _: &mut Self::Context) {
+        let dns_stats = &self.ctx.supervise.manifest.dns_stats;
+        if !dns_stats.is_empty() {
+            log::info!("DNS resolution stats: {}", dns_stats);
+        }
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(Ok(()));
         }
@@ -464,6 +469,8 @@ pub struct ExeUnitContext {
     pub agreement: Agreement,
     pub work_dir: PathBuf,
     pub cache_dir: PathBuf,
+    pub cache_budget: Option<u64>,
+    pub inet_limits: InetLimits,
     pub runtime_args: Vec<String>,
     pub acl: Acl,
     pub credentials: Option<Credentials>,
@@ -495,6 +502,7 @@ impl From<&ExeUnitContext> for TransferServiceContext {
             cache_dir: val.cache_dir.clone(),
             work_dir: val.work_dir.clone(),
             transfer_retry: None,
+            cache_budget: val.cache_budget,
         }
     }
 }
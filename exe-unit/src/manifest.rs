@@ -1,6 +1,6 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::ops::Not;
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
@@ -11,7 +11,7 @@ use serde_json::{Map, Value};
 use structopt::StructOpt;
 use url::Url;
 
-use crate::dns::{StableResolver, DNS_PORT};
+use crate::dns::{DnsConfig, DnsProtocol, ResolverStats, StableResolver, DNS_PORT};
 use ya_agreement_utils::AgreementView;
 use ya_client_model::activity::ExeScriptCommand;
 use ya_manifest_utils::{read_manifest, AppManifest, ArgMatch, Command, Feature, Script};
@@ -32,6 +32,9 @@ pub enum ValidationError {
 pub struct ManifestContext {
     pub manifest: Arc<Option<AppManifest>>,
     pub policy: Arc<PolicyConfig>,
+    pub dns: Arc<DnsConfig>,
+    /// Resolutions made on behalf of the activity, reported as usage counters
+    pub dns_stats: Arc<ResolverStats>,
     features: HashSet<Feature>,
     validators: Arc<RwLock<ValidatorMap>>,
 }
@@ -51,11 +54,18 @@ impl ManifestContext {
         Ok(Self {
             manifest: Arc::new(manifest),
             policy: Arc::new(policy),
+            dns: Default::default(),
+            dns_stats: Default::default(),
             features,
             validators: Arc::new(RwLock::new(Default::default())),
         })
     }
 
+    pub fn with_dns_config(mut self, dns: DnsConfig) -> Self {
+        self.dns = Arc::new(dns);
+        self
+    }
+
     pub fn features(&self) -> &HashSet<Feature> {
         &self.features
     }
@@ -78,17 +88,17 @@ impl ManifestContext {
         }
 
         let manifest = (*self.manifest).clone().unwrap();
-        let policy = self.policy.clone();
+        let ctx = self.clone();
 
         async move {
             let mut validators = ValidatorMap::default();
 
-            if let Some(validator) = ScriptValidator::build(&manifest, &policy).await? {
+            if let Some(validator) = ScriptValidator::build(&manifest, &ctx).await? {
                 let validator: Box<dyn Any> = Box::new(validator);
                 validators.insert(Validator::Script, validator);
             }
 
-            if let Some(validator) = UrlValidator::build(&manifest, &policy).await? {
+            if let Some(validator) = UrlValidator::build(&manifest, &ctx).await? {
                 let validator: Box<dyn Any> = Box::new(validator);
                 validators.insert(Validator::Url, validator);
             }
@@ -149,7 +159,7 @@ pub trait ManifestValidator: Clone + Sized {
 
     fn build<'a>(
         manifest: &AppManifest,
-        policy: &PolicyConfig,
+        ctx: &ManifestContext,
     ) -> future::LocalBoxFuture<'a, anyhow::Result<Option<Self>>>;
 }
 
@@ -194,9 +204,10 @@ impl ManifestValidator for ScriptValidator {
 
     fn build<'a>(
         manifest: &AppManifest,
-        policy: &PolicyConfig,
+        ctx: &ManifestContext,
     ) -> future::LocalBoxFuture<'a, anyhow::Result<Option<Self>>> {
-        if policy
+        if ctx
+            .policy
             .policy_set()
             .contains(&Policy::ManifestScriptCompliance)
             .not()
@@ -388,7 +399,7 @@ impl FromStr for ScriptValidator {
 #[derive(Clone)]
 pub struct UrlValidator {
     inner: Arc<AllowedAccess>,
-    resolver: Option<Arc<StableResolver>>,
+    dns_forwarder: Option<SocketAddr>,
 }
 
 enum AllowedAccess {
@@ -401,9 +412,10 @@ impl ManifestValidator for UrlValidator {
 
     fn build<'a>(
         manifest: &AppManifest,
-        policy: &PolicyConfig,
+        ctx: &ManifestContext,
     ) -> future::LocalBoxFuture<'a, anyhow::Result<Option<Self>>> {
-        if policy
+        if ctx
+            .policy
             .policy_set()
             .contains(&Policy::ManifestInetUrlCompliance)
             .not()
@@ -412,30 +424,41 @@ impl ManifestValidator for UrlValidator {
         }
 
         let access = manifest.get_outbound_access();
+        let dns = ctx.dns.clone();
+        let dns_stats = ctx.dns_stats.clone();
 
         async move {
             if let Some(access) = access {
                 match access {
                     ya_manifest_utils::OutboundAccess::Urls(urls) => {
-                        let resolver = crate::dns::resolver().await?;
+                        let resolver = crate::dns::resolver(&dns, dns_stats).await?;
 
-                        // by default we whitelist well known dns servers.
+                        // by default we whitelist well known and configured plain dns servers.
                         let mut set = crate::dns::dns_servers()
                             .map(|ip| (Protocol::Udp, ip, DNS_PORT))
                             .collect::<HashSet<_, _>>();
+                        set.extend(dns.servers.iter().filter_map(|server| {
+                            let protocol = match server.protocol {
+                                DnsProtocol::Udp => Protocol::Udp,
+                                DnsProtocol::Tcp => Protocol::Tcp,
+                                _ => return None,
+                            };
+                            Some((protocol, server.address.ip(), server.address.port()))
+                        }));
 
                         let ips = resolve_ips(&resolver, urls.iter()).await?;
 
                         set.extend(ips);
 
+                        let dns_forwarder = crate::dns::spawn_forwarder(Arc::new(resolver)).await?;
                         Ok(Some(Self {
                             inner: Arc::new(AllowedAccess::Urls(set)),
-                            resolver: Some(Arc::new(resolver)),
+                            dns_forwarder: Some(dns_forwarder),
                         }))
                     }
                     ya_manifest_utils::OutboundAccess::Unrestricted => Ok(Some(Self {
                         inner: Arc::new(AllowedAccess::Unrestricted),
-                        resolver: None,
+                        dns_forwarder: None,
                     })),
                 }
             } else {
@@ -462,8 +485,9 @@ impl UrlValidator {
         }
     }
 
-    pub fn stable_dns(&self) -> Option<IpAddr> {
-        self.resolver.as_ref().map(|r| r.stable_dns())
+    /// Local DNS server, which runtime's queries to well-known DNS servers are redirected to.
+    pub fn dns_forwarder(&self) -> Option<SocketAddr> {
+        self.dns_forwarder
     }
 }
 
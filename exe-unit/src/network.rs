@@ -16,6 +16,7 @@ use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpSocket, UdpSocket};
 use tokio::sync::mpsc;
 
+use ya_counters::NetworkCounter;
 use ya_runtime_api::deploy::ContainerEndpoint;
 use ya_runtime_api::server::Network;
 use ya_service_bus::{typed, typed::Endpoint as GsbEndpoint};
@@ -27,6 +28,7 @@ use crate::state::DeploymentNetwork;
 use crate::Result;
 
 pub(crate) mod inet;
+pub(crate) mod shaping;
 pub(crate) mod vpn;
 
 type SocketChannel = (
@@ -296,7 +298,10 @@ impl RemoteEndpoint {
             futures::stream::unfold((read, buffer), |(r, mut b)| async move {
                 match r.recv_from(&mut b).await.map(|t| t.0) {
                     Ok(0) => None,
-                    Ok(n) => Some((Ok::<_, Error>(Vec::from(&b[..n])), (r, b))),
+                    Ok(n) => {
+                        NetworkCounter::record(n);
+                        Some((Ok::<_, Error>(Vec::from(&b[..n])), (r, b)))
+                    }
                     Err(e) => Some((Err(e.into()), (r, b))),
                 }
             })
@@ -313,6 +318,7 @@ impl RemoteEndpoint {
                             log::error!("error writing to VM endpoint: {e}");
                             break;
                         }
+                        NetworkCounter::record(data.len());
                     }
                     Some(Err(e)) => {
                         log::error!("VM endpoint error: {e}");
@@ -366,7 +372,10 @@ where
         futures::stream::unfold((read, buffer), |(mut r, mut b)| async move {
             match r.read(&mut b).await {
                 Ok(0) => None,
-                Ok(n) => Some((Ok::<_, io::Error>(BytesMut::from(&b[..n])), (r, b))),
+                Ok(n) => {
+                    NetworkCounter::record(n);
+                    Some((Ok::<_, io::Error>(BytesMut::from(&b[..n])), (r, b)))
+                }
                 Err(e) => Some((Err(e), (r, b))),
             }
         })
@@ -394,6 +403,7 @@ where
                         log::error!("VM endpoint write error: {e}");
                         break;
                     }
+                    NetworkCounter::record(data.len());
                 }
                 Some(Err(e)) => {
                     log::error!("VM endpoint error: {e}");
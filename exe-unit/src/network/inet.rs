@@ -46,6 +46,7 @@ use ya_utils_networking::vpn::{
 use crate::dns::DNS_PORT;
 use crate::manifest::UrlValidator;
 use crate::message::Shutdown;
+use crate::network::shaping::{Direction, InetLimits, Shaper};
 use crate::network::Endpoint;
 use crate::{dns, Error, Result};
 
@@ -74,6 +75,7 @@ pub(crate) async fn start_inet<R: RuntimeService>(
     mut endpoint: Endpoint,
     service: &R,
     filter: Option<UrlValidator>,
+    limits: &InetLimits,
 ) -> Result<Addr<Inet>> {
     use ya_runtime_api::server::Network;
 
@@ -125,7 +127,7 @@ pub(crate) async fn start_inet<R: RuntimeService>(
         }
     };
 
-    Ok(Inet::new(endpoint, filter).start())
+    Ok(Inet::new(endpoint, filter, Shaper::new(limits)).start())
 }
 
 pub(crate) struct Inet {
@@ -135,9 +137,9 @@ pub(crate) struct Inet {
 }
 
 impl Inet {
-    pub fn new(endpoint: Endpoint, filter: Option<UrlValidator>) -> Self {
+    pub fn new(endpoint: Endpoint, filter: Option<UrlValidator>, shaper: Shaper) -> Self {
         let network = Self::create_network();
-        let proxy = Proxy::new(network.clone(), filter);
+        let proxy = Proxy::new(network.clone(), filter, shaper);
         Self {
             network,
             endpoint,
@@ -221,7 +223,11 @@ impl Actor for Inet {
 
     fn stopping(&mut self, _ctx: &mut Self::Context) -> Running {
         self.network = Self::create_network();
-        self.proxy = Proxy::new(self.network.clone(), self.proxy.filter.clone());
+        self.proxy = Proxy::new(
+            self.network.clone(),
+            self.proxy.filter.clone(),
+            self.proxy.shaper.clone(),
+        );
 
         log::info!("[inet] stopping service");
         Running::Stop
@@ -472,6 +478,7 @@ fn ip_packet_to_socket_desc(ip_packet: &IpPacket) -> Result<SocketDesc> {
 struct Proxy {
     state: Arc<RwLock<ProxyState>>,
     filter: Option<UrlValidator>,
+    shaper: Shaper,
 }
 
 struct ConnectionState {
@@ -485,7 +492,7 @@ struct ProxyState {
 }
 
 impl Proxy {
-    fn new(network: net::Network, filter: Option<UrlValidator>) -> Self {
+    fn new(network: net::Network, filter: Option<UrlValidator>, shaper: Shaper) -> Self {
         let state = ProxyState {
             network,
             remotes: Default::default(),
@@ -493,6 +500,7 @@ impl Proxy {
         Self {
             state: Arc::new(RwLock::new(state)),
             filter,
+            shaper,
         }
     }
 
@@ -607,7 +615,7 @@ impl Proxy {
 
         log::debug!("[inet] connect to {desc:?}, using handle: {handle}");
 
-        let (mut ip, port) = (
+        let (mut ip, mut port) = (
             conv_ip_addr(meta.local.addr).map_err(|e| ProxyingError::routeable(conn, e))?,
             meta.local.port,
         );
@@ -616,6 +624,9 @@ impl Proxy {
                 .validate(meta.protocol, ip, port)
                 .map_err(|e| ProxyingError::routeable(conn, e.into()))?;
         }
+        if let Some(e) = self.shaper.quota_exceeded() {
+            return Err(ProxyingError::routeable(conn, Error::Other(e.to_string())));
+        }
 
         if meta.protocol == Protocol::Udp {
             self.close_lru_udp_connections(200).await;
@@ -632,13 +643,15 @@ impl Proxy {
 
         let proxy2 = proxy.clone();
         let network2 = network.clone();
+        let shaper = self.shaper.clone();
 
-        if let Some(stable_dns) = self.filter.as_ref().and_then(|f| f.stable_dns()) {
+        if let Some(forwarder) = self.filter.as_ref().and_then(|f| f.dns_forwarder()) {
             if port == DNS_PORT
                 && meta.protocol == Protocol::Udp
                 && dns::dns_servers().any(|dns_ip| ip == dns_ip)
             {
-                ip = stable_dns;
+                ip = forwarder.ip();
+                port = forwarder.port();
             }
         }
 
@@ -653,14 +666,32 @@ impl Proxy {
 
             match maybe_tx_rx {
                 Ok((mut tcp_tx, mut tcp_rx)) => {
+                    let egress_shaper = shaper.clone();
                     tokio::task::spawn_local(async move {
                         while let Some(data) = proxy_rx.next().await {
+                            if let Err(e) =
+                                egress_shaper.transfer(Direction::Egress, data.len()).await
+                            {
+                                log::warn!("[inet] closing connection to {ip}:{port}: {e}");
+                                let handle = get_handle(&network2, &meta).unwrap_or(handle);
+                                let _ = proxy2.disconnect(handle).await;
+                                break;
+                            }
                             tcp_tx.send(data).await.log_err().unwrap();
                         }
                     });
 
                     tokio::task::spawn_local(async move {
                         while let Some(data) = tcp_rx.next().await {
+                            if let Ok(ref bytes) = data {
+                                if let Err(e) =
+                                    shaper.transfer(Direction::Ingress, bytes.len()).await
+                                {
+                                    // dropping the sender closes the connection
+                                    log::warn!("[inet] closing connection to {ip}:{port}: {e}");
+                                    break;
+                                }
+                            }
                             proxy_tx
                                 .send(data.map(Into::<Bytes>::into))
                                 .await
@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use structopt::StructOpt;
+
+use ya_counters::{InetEgressCounter, InetIngressCounter};
+
+/// Outbound network limits, shared by all connections of an activity.
+#[derive(StructOpt, Clone, Debug, Default)]
+pub struct InetLimits {
+    /// Maximum rate of data sent to the internet, in bytes per second
+    #[structopt(long, env = "EXE_UNIT_INET_EGRESS_RATE")]
+    pub inet_egress_rate: Option<u64>,
+    /// Maximum rate of data received from the internet, in bytes per second
+    #[structopt(long, env = "EXE_UNIT_INET_INGRESS_RATE")]
+    pub inet_ingress_rate: Option<u64>,
+    /// Maximum number of bytes sent to the internet
+    #[structopt(long, env = "EXE_UNIT_INET_EGRESS_QUOTA")]
+    pub inet_egress_quota: Option<u64>,
+    /// Maximum number of bytes received from the internet
+    #[structopt(long, env = "EXE_UNIT_INET_INGRESS_QUOTA")]
+    pub inet_ingress_quota: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Direction {
+    Egress,
+    Ingress,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("outbound network {0:?} quota of {1} B exceeded")]
+pub(crate) struct QuotaExceeded(Direction, u64);
+
+/// Accounts and limits traffic of the outbound network proxy.
+#[derive(Clone)]
+pub(crate) struct Shaper {
+    egress: Arc<Limiter>,
+    ingress: Arc<Limiter>,
+}
+
+impl Shaper {
+    pub fn new(limits: &InetLimits) -> Self {
+        Self {
+            egress: Arc::new(Limiter::new(
+                Direction::Egress,
+                limits.inet_egress_rate,
+                limits.inet_egress_quota,
+            )),
+            ingress: Arc::new(Limiter::new(
+                Direction::Ingress,
+                limits.inet_ingress_rate,
+                limits.inet_ingress_quota,
+            )),
+        }
+    }
+
+    pub fn quota_exceeded(&self) -> Option<QuotaExceeded> {
+        self.egress
+            .check_quota()
+            .and(self.ingress.check_quota())
+            .err()
+    }
+
+    /// Accounts `bytes` transferred in given direction. Waits until the transfer
+    /// fits within the rate limit, fails when the quota is exhausted.
+    pub async fn transfer(&self, direction: Direction, bytes: usize) -> Result<(), QuotaExceeded> {
+        match direction {
+            Direction::Egress => {
+                self.egress.consume(bytes)?;
+                InetEgressCounter::record(bytes);
+                self.egress.throttle(bytes).await;
+            }
+            Direction::Ingress => {
+                self.ingress.consume(bytes)?;
+                InetIngressCounter::record(bytes);
+                self.ingress.throttle(bytes).await;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Limiter {
+    direction: Direction,
+    bucket: Option<Mutex<TokenBucket>>,
+    quota: Option<u64>,
+    used: AtomicU64,
+}
+
+impl Limiter {
+    fn new(direction: Direction, rate: Option<u64>, quota: Option<u64>) -> Self {
+        Self {
+            direction,
+            bucket: rate.map(|rate| Mutex::new(TokenBucket::new(rate))),
+            quota,
+            used: Default::default(),
+        }
+    }
+
+    fn check_quota(&self) -> Result<(), QuotaExceeded> {
+        match self.quota {
+            Some(quota) if self.used.load(Ordering::Relaxed) >= quota => {
+                Err(QuotaExceeded(self.direction, quota))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn consume(&self, bytes: usize) -> Result<(), QuotaExceeded> {
+        let used = self.used.fetch_add(bytes as u64, Ordering::Relaxed) + bytes as u64;
+        match self.quota {
+            Some(quota) if used > quota => Err(QuotaExceeded(self.direction, quota)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn throttle(&self, bytes: usize) {
+        let delay = match self.bucket {
+            Some(ref bucket) => bucket.lock().unwrap().take(bytes),
+            None => return,
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Token bucket with a burst size of 1 second worth of traffic.
+/// Transfers exceeding available tokens are delayed, accumulating debt.
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        let rate = rate.max(1) as f64;
+        Self {
+            rate,
+            tokens: rate,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Returns the time to wait before transferring `bytes`.
+    fn take(&mut self, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate) - bytes as f64;
+        self.updated = now;
+
+        match self.tokens < 0. {
+            true => Duration::from_secs_f64(-self.tokens / self.rate),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_delays_over_rate() {
+        let mut bucket = TokenBucket::new(1000);
+        assert_eq!(bucket.take(1000), Duration::ZERO);
+
+        let delay = bucket.take(500);
+        assert!(delay > Duration::from_millis(450) && delay <= Duration::from_millis(500));
+    }
+
+    #[actix_rt::test]
+    async fn quota_exceeded() {
+        let shaper = Shaper::new(&InetLimits {
+            inet_egress_quota: Some(1000),
+            ..Default::default()
+        });
+
+        shaper.transfer(Direction::Egress, 600).await.unwrap();
+        shaper.transfer(Direction::Ingress, 2000).await.unwrap();
+        assert!(shaper.quota_exceeded().is_none());
+
+        assert!(shaper.transfer(Direction::Egress, 600).await.is_err());
+        assert!(shaper.quota_exceeded().is_some());
+    }
+}
@@ -31,7 +31,7 @@ use crate::output::forward_output;
 use crate::runtime::event::EventMonitor;
 use crate::runtime::{Runtime, RuntimeMode};
 use crate::state::Deployment;
-use crate::ExeUnitContext;
+use crate::{ExeUnitContext, InetLimits};
 
 const PROCESS_KILL_TIMEOUT_SECONDS_ENV_VAR: &str = "PROCESS_KILL_TIMEOUT_SECONDS";
 const DEFAULT_PROCESS_KILL_TIMEOUT_SECONDS: i64 = 5;
@@ -355,6 +355,7 @@ impl RuntimeProcess {
                         endpoint,
                         &service_,
                         rt_ctx.manifest.validator::<UrlValidator>(),
+                        &rt_ctx.inet_limits,
                     )
                     .await?;
                     address.send(SetInetService(inet)).await?;
@@ -572,6 +573,7 @@ struct RuntimeProcessContext {
     supervise_hardware: bool,
     infrastructure: HashMap<String, f64>,
     manifest: ManifestContext,
+    inet_limits: InetLimits,
 }
 
 impl<'a> From<&'a ExeUnitContext> for RuntimeProcessContext {
@@ -583,6 +585,7 @@ impl<'a> From<&'a ExeUnitContext> for RuntimeProcessContext {
             supervise_hardware: ctx.supervise.hardware,
             infrastructure: ctx.agreement.infrastructure.clone(),
             manifest: ctx.supervise.manifest.clone(),
+            inet_limits: ctx.inet_limits.clone(),
         }
     }
 }
@@ -1,10 +1,16 @@
+#[cfg(not(feature = "sgx"))]
+use crate::dns::DnsCounter;
 #[allow(unused_imports)]
 use crate::ExeUnitContext;
 
 use ya_counters::service::{CountersService, CountersServiceBuilder};
+#[cfg(all(not(feature = "sgx"), target_os = "linux"))]
+use ya_counters::{Cgroup, DiskIoCounter};
 use ya_counters::{Counter, TimeCounter};
 #[cfg(not(feature = "sgx"))]
-use ya_counters::{CpuCounter, MemCounter, StorageCounter};
+use ya_counters::{
+    CpuCounter, InetEgressCounter, InetIngressCounter, MemCounter, NetworkCounter, StorageCounter,
+};
 
 use std::collections::HashMap;
 
@@ -27,6 +33,11 @@ pub fn build(
         builder.with_counter(&counter_id, counter);
     }
 
+    #[cfg(all(not(feature = "sgx"), target_os = "linux"))]
+    if let Some(cgroup) = ctx.activity_id.as_deref().and_then(Cgroup::for_activity) {
+        builder.with_cgroup_counters(cgroup);
+    }
+
     builder.build()
 }
 
@@ -47,19 +58,30 @@ fn counters(_ctx: &ExeUnitContext) -> HashMap<String, Box<dyn Counter>> {
 
 #[cfg(not(feature = "sgx"))]
 pub fn usage_vector() -> Vec<String> {
-    vec![
+    #[allow(unused_mut)]
+    let mut usage_vector = vec![
         TimeCounter::ID.to_string(),
         CpuCounter::ID.to_string(),
         MemCounter::ID.to_string(),
         StorageCounter::ID.to_string(),
-    ]
-    .into_iter()
-    .collect()
+        NetworkCounter::ID.to_string(),
+        InetEgressCounter::ID.to_string(),
+        InetIngressCounter::ID.to_string(),
+        DnsCounter::LOOKUPS_ID.to_string(),
+        DnsCounter::FAILURES_ID.to_string(),
+    ];
+
+    #[cfg(target_os = "linux")]
+    if Cgroup::activities_parent().map_or(false, |cgroup| cgroup.children_support_io()) {
+        usage_vector.push(DiskIoCounter::ID.to_string());
+    }
+
+    usage_vector
 }
 
 #[cfg(not(feature = "sgx"))]
 fn counters(ctx: &ExeUnitContext) -> HashMap<String, Box<dyn Counter>> {
-    vec![
+    let mut counters: HashMap<String, Box<dyn Counter>> = vec![
         (
             CpuCounter::ID.to_string(),
             Box::<CpuCounter>::default() as Box<dyn Counter>,
@@ -81,5 +103,41 @@ fn counters(ctx: &ExeUnitContext) -> HashMap<String, Box<dyn Counter>> {
         ),
     ]
     .into_iter()
-    .collect()
+    .collect();
+
+    // Network and internet traffic and DNS resolutions are reported only when the offer prices them
+    let agreed = |counter_id: &str| ctx.agreement.usage_vector.iter().any(|id| id == counter_id);
+    if agreed(NetworkCounter::ID) {
+        counters.insert(
+            NetworkCounter::ID.to_string(),
+            Box::<NetworkCounter>::default(),
+        );
+    }
+    if agreed(InetEgressCounter::ID) {
+        counters.insert(
+            InetEgressCounter::ID.to_string(),
+            Box::<InetEgressCounter>::default(),
+        );
+    }
+    if agreed(InetIngressCounter::ID) {
+        counters.insert(
+            InetIngressCounter::ID.to_string(),
+            Box::<InetIngressCounter>::default(),
+        );
+    }
+
+    let dns_stats = &ctx.supervise.manifest.dns_stats;
+    if agreed(DnsCounter::LOOKUPS_ID) {
+        counters.insert(
+            DnsCounter::LOOKUPS_ID.to_string(),
+            Box::new(DnsCounter::lookups(dns_stats.clone())),
+        );
+    }
+    if agreed(DnsCounter::FAILURES_ID) {
+        counters.insert(
+            DnsCounter::FAILURES_ID.to_string(),
+            Box::new(DnsCounter::failures(dns_stats.clone())),
+        );
+    }
+    counters
 }
@@ -78,6 +78,14 @@ lazy_static! {
                 token: "GLM",
             },
         );
+        erc20.insert(
+            NetworkName::Dev.into(),
+            PaymentPlatform {
+                platform: "erc20-dev-tglm",
+                driver: "erc20",
+                token: "tGLM",
+            },
+        );
 
         PaymentDriver {
             platforms: erc20,
@@ -61,3 +61,153 @@ pub async fn start_http(ctx: &mut DroppableTestContext, path: PathBuf) -> anyhow
 
     Ok(())
 }
+
+/// Minimal S3-compatible object store serving `{path}/{bucket}/{key}` with path-style addressing.
+/// Supports GET (with ranges), HEAD, PUT and multipart uploads. Request signatures are not verified.
+pub async fn start_s3(ctx: &mut DroppableTestContext, path: PathBuf) -> anyhow::Result<()> {
+    let inner = path.clone();
+    let srv = HttpServer::new(move || {
+        App::new()
+            .wrap(middleware::Logger::default())
+            .app_data(Data::new(inner.clone()))
+            .service(
+                web::resource("/{bucket}/{key:.*}")
+                    .route(web::get().to(s3::get_object))
+                    .route(web::head().to(s3::get_object))
+                    .route(web::put().to(s3::put_object))
+                    .route(web::post().to(s3::post_object))
+                    .route(web::delete().to(s3::abort_upload)),
+            )
+    })
+    .bind("127.0.0.1:8003")?
+    .run();
+
+    ctx.register(srv.handle());
+    tokio::task::spawn_local(async move { anyhow::Ok(srv.await?) });
+
+    Ok(())
+}
+
+mod s3 {
+    use actix_files::NamedFile;
+    use actix_web::{web, HttpResponse};
+    use futures::StreamExt;
+    use rand::Rng;
+    use regex::Regex;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use tokio::io::AsyncWriteExt;
+
+    type Query = web::Query<HashMap<String, String>>;
+
+    fn object_path(root: &Path, object: &(String, String)) -> PathBuf {
+        root.join(&object.0).join(&object.1)
+    }
+
+    fn upload_dir(root: &Path, upload_id: &str) -> PathBuf {
+        root.join(".s3-uploads").join(upload_id)
+    }
+
+    async fn write_payload(dst_path: &Path, mut payload: web::Payload) -> std::io::Result<()> {
+        if let Some(parent) = dst_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut dst = tokio::fs::File::create(dst_path).await?;
+        while let Some(chunk) = payload.next().await {
+            let data =
+                chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+            dst.write_all(&data).await?;
+        }
+        dst.flush().await
+    }
+
+    pub async fn get_object(
+        root: web::Data<PathBuf>,
+        object: web::Path<(String, String)>,
+    ) -> actix_web::Result<NamedFile> {
+        Ok(NamedFile::open_async(object_path(&root, &object)).await?)
+    }
+
+    pub async fn put_object(
+        root: web::Data<PathBuf>,
+        object: web::Path<(String, String)>,
+        query: Query,
+        payload: web::Payload,
+    ) -> actix_web::Result<HttpResponse> {
+        match (query.get("uploadId"), query.get("partNumber")) {
+            (Some(upload_id), Some(part_number)) => {
+                let part_number: u32 = part_number
+                    .parse()
+                    .map_err(actix_web::error::ErrorBadRequest)?;
+                let dir = upload_dir(&root, upload_id);
+                write_payload(&dir.join(part_number.to_string()), payload).await?;
+                Ok(HttpResponse::Ok()
+                    .insert_header(("ETag", format!("\"{upload_id}-{part_number}\"")))
+                    .finish())
+            }
+            _ => {
+                write_payload(&object_path(&root, &object), payload).await?;
+                Ok(HttpResponse::Ok().finish())
+            }
+        }
+    }
+
+    pub async fn post_object(
+        root: web::Data<PathBuf>,
+        object: web::Path<(String, String)>,
+        query: Query,
+        body: web::Bytes,
+    ) -> actix_web::Result<HttpResponse> {
+        if query.contains_key("uploads") {
+            let upload_id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+            tokio::fs::create_dir_all(upload_dir(&root, &upload_id)).await?;
+
+            return Ok(HttpResponse::Ok().body(format!(
+                "<InitiateMultipartUploadResult>\
+                <Bucket>{}</Bucket><Key>{}</Key><UploadId>{upload_id}</UploadId>\
+                </InitiateMultipartUploadResult>",
+                object.0, object.1
+            )));
+        }
+
+        let upload_id = match query.get("uploadId") {
+            Some(upload_id) => upload_id,
+            None => return Ok(HttpResponse::BadRequest().finish()),
+        };
+
+        lazy_static::lazy_static! {
+            static ref RE: Regex = Regex::new(r"<PartNumber>(\d+)</PartNumber>").unwrap();
+        }
+        let body = String::from_utf8_lossy(&body);
+        let dir = upload_dir(&root, upload_id);
+        let dst_path = object_path(&root, &object);
+        if let Some(parent) = dst_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut dst = tokio::fs::File::create(&dst_path).await?;
+        for captures in RE.captures_iter(&body) {
+            let part = tokio::fs::read(dir.join(&captures[1])).await?;
+            dst.write_all(&part).await?;
+        }
+        dst.flush().await?;
+        tokio::fs::remove_dir_all(dir).await?;
+
+        Ok(HttpResponse::Ok().body(format!(
+            "<CompleteMultipartUploadResult>\
+            <Bucket>{}</Bucket><Key>{}</Key>\
+            </CompleteMultipartUploadResult>",
+            object.0, object.1
+        )))
+    }
+
+    pub async fn abort_upload(
+        root: web::Data<PathBuf>,
+        query: Query,
+    ) -> actix_web::Result<HttpResponse> {
+        if let Some(upload_id) = query.get("uploadId") {
+            tokio::fs::remove_dir_all(upload_dir(&root, upload_id)).await?;
+        }
+        Ok(HttpResponse::NoContent().finish())
+    }
+}